@@ -13,6 +13,16 @@
  * * query(account_id) -> balance
  * * transfer(to, amount, fee)
  *
+ * ERC-20 style delegated transfer:
+ *
+ * * approve(spender, amount, fee)
+ * * allowance(owner, spender) -> amount
+ * * transfer_from(from, to, amount, fee)
+ *
+ * Pay to multiple receivers with one fee:
+ *
+ * * batch_transfer([(to, amount)], fee)
+ *
  * # Mint & Burn
  *
  * To join a Rollup, users deposit SUDT assets on layer1;
@@ -25,6 +35,13 @@
  * send the layer1 SUDT assets to users.
  *
  * The aggregators operate Mint & Burn by directly modify the state tree.
+ *
+ * A layer2 native token has an issuer registry address in the script args,
+ * which is fixed when the account is created, see `sudt_utils.h`.
+ * Only the issuer can mint and burn a layer2 native token:
+ *
+ * * mint(to, amount, fee)
+ * * burn(amount, fee)
  */
 
 #include "ckb_syscalls.h"
@@ -35,6 +52,64 @@
 /* MSG_TYPE */
 #define MSG_QUERY 0
 #define MSG_TRANSFER 1
+#define MSG_APPROVE 2
+#define MSG_ALLOWANCE 3
+#define MSG_TRANSFER_FROM 4
+#define MSG_BATCH_TRANSFER 5
+#define MSG_MINT 6
+#define MSG_BURN 7
+
+/* load the sender's registry address of the registry_id of fee */
+int load_sender_addr(gw_context_t *ctx, mol_seg_t *fee_seg,
+                     gw_reg_addr_t *sender_addr) {
+  mol_seg_t fee_reg_seg = MolReader_Fee_get_registry_id(fee_seg);
+  uint32_t reg_id = 0;
+  _gw_fast_memcpy((uint8_t *)(&reg_id), fee_reg_seg.ptr, sizeof(uint32_t));
+
+  uint32_t from_id = ctx->transaction_context.from_id;
+  uint8_t from_script_hash[32] = {0};
+  int ret =
+      ctx->sys_get_script_hash_by_account_id(ctx, from_id, from_script_hash);
+  if (ret != 0) {
+    return ret;
+  }
+  return ctx->sys_get_registry_address_by_script_hash(ctx, from_script_hash,
+                                                      reg_id, sender_addr);
+}
+
+/* parse the receiver and the amount of a batch transfer item */
+int parse_transfer_item(mol_seg_t *transfers_seg, uint32_t index,
+                        gw_reg_addr_t *to_addr, uint256_t *amount) {
+  mol_seg_res_t item_res =
+      MolReader_SUDTTransferItemVec_get(transfers_seg, index);
+  if (item_res.errno != MOL_OK) {
+    printf("batch transfer: invalid transfer item");
+    return GW_FATAL_INVALID_DATA;
+  }
+  mol_seg_t to_seg = MolReader_SUDTTransferItem_get_to_address(&item_res.seg);
+  mol_seg_t raw_to_seg = MolReader_Bytes_raw_bytes(&to_seg);
+  int ret = _gw_parse_addr(raw_to_seg.ptr, raw_to_seg.size, to_addr);
+  if (ret != 0) {
+    return ret;
+  }
+  mol_seg_t amount_seg = MolReader_SUDTTransferItem_get_amount(&item_res.seg);
+  _gw_fast_memcpy((uint8_t *)amount, (uint8_t *)amount_seg.ptr,
+                  sizeof(uint256_t));
+  return 0;
+}
+
+/* pay fee in CKB */
+int pay_fee(gw_context_t *ctx, mol_seg_t *fee_seg, gw_reg_addr_t payer_addr) {
+  mol_seg_t fee_amount_seg = MolReader_Fee_get_amount(fee_seg);
+  uint256_t fee_amount = {0};
+  _gw_fast_memcpy((uint8_t *)(&fee_amount), (uint8_t *)fee_amount_seg.ptr,
+                  sizeof(uint128_t));
+  int ret = sudt_pay_fee(ctx, CKB_SUDT_ACCOUNT_ID, payer_addr, fee_amount);
+  if (ret != 0) {
+    printf("pay fee failed");
+  }
+  return ret;
+}
 
 int main() {
   /* initialize context */
@@ -129,6 +204,228 @@ int main() {
       printf("transfer token failed");
       return ret;
     }
+  } else if (msg.item_id == MSG_APPROVE) {
+    /* Approve */
+    mol_seg_t spender_seg = MolReader_SUDTApprove_get_spender_address(&msg.seg);
+    mol_seg_t raw_spender_seg = MolReader_Bytes_raw_bytes(&spender_seg);
+    mol_seg_t amount_seg = MolReader_SUDTApprove_get_amount(&msg.seg);
+    mol_seg_t fee_seg = MolReader_SUDTApprove_get_fee(&msg.seg);
+
+    gw_reg_addr_t owner_addr;
+    ret = load_sender_addr(&ctx, &fee_seg, &owner_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    gw_reg_addr_t spender_addr;
+    ret = _gw_parse_addr(raw_spender_seg.ptr, raw_spender_seg.size,
+                         &spender_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    uint256_t amount = {0};
+    _gw_fast_memcpy((uint8_t *)(&amount), (uint8_t *)amount_seg.ptr,
+                    sizeof(uint256_t));
+
+    ret = pay_fee(&ctx, &fee_seg, owner_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = sudt_approve(&ctx, sudt_id, owner_addr, spender_addr, amount);
+    if (ret != 0) {
+      printf("approve failed");
+      return ret;
+    }
+  } else if (msg.item_id == MSG_ALLOWANCE) {
+    /* Allowance */
+    mol_seg_t owner_seg = MolReader_SUDTAllowance_get_owner_address(&msg.seg);
+    mol_seg_t raw_owner_seg = MolReader_Bytes_raw_bytes(&owner_seg);
+    mol_seg_t spender_seg =
+        MolReader_SUDTAllowance_get_spender_address(&msg.seg);
+    mol_seg_t raw_spender_seg = MolReader_Bytes_raw_bytes(&spender_seg);
+
+    gw_reg_addr_t owner_addr;
+    ret = _gw_parse_addr(raw_owner_seg.ptr, raw_owner_seg.size, &owner_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    gw_reg_addr_t spender_addr;
+    ret = _gw_parse_addr(raw_spender_seg.ptr, raw_spender_seg.size,
+                         &spender_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    uint256_t allowance = {0};
+    ret = sudt_get_allowance(&ctx, sudt_id, owner_addr, spender_addr,
+                             &allowance);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = ctx.sys_set_program_return_data(&ctx, (uint8_t *)&allowance,
+                                          sizeof(uint256_t));
+    if (ret != 0) {
+      return ret;
+    }
+  } else if (msg.item_id == MSG_TRANSFER_FROM) {
+    /* Transfer from */
+    mol_seg_t from_seg = MolReader_SUDTTransferFrom_get_from_address(&msg.seg);
+    mol_seg_t raw_from_seg = MolReader_Bytes_raw_bytes(&from_seg);
+    mol_seg_t to_seg = MolReader_SUDTTransferFrom_get_to_address(&msg.seg);
+    mol_seg_t raw_to_seg = MolReader_Bytes_raw_bytes(&to_seg);
+    mol_seg_t amount_seg = MolReader_SUDTTransferFrom_get_amount(&msg.seg);
+    mol_seg_t fee_seg = MolReader_SUDTTransferFrom_get_fee(&msg.seg);
+
+    /* the sender is the spender */
+    gw_reg_addr_t spender_addr;
+    ret = load_sender_addr(&ctx, &fee_seg, &spender_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    gw_reg_addr_t from_addr;
+    ret = _gw_parse_addr(raw_from_seg.ptr, raw_from_seg.size, &from_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    gw_reg_addr_t to_addr;
+    ret = _gw_parse_addr(raw_to_seg.ptr, raw_to_seg.size, &to_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    uint256_t amount = {0};
+    _gw_fast_memcpy((uint8_t *)(&amount), (uint8_t *)amount_seg.ptr,
+                    sizeof(uint256_t));
+
+    ret = pay_fee(&ctx, &fee_seg, spender_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = sudt_transfer_from(&ctx, sudt_id, spender_addr, from_addr, to_addr,
+                             amount);
+    if (ret != 0) {
+      printf("transfer from failed");
+      return ret;
+    }
+  } else if (msg.item_id == MSG_BATCH_TRANSFER) {
+    /* Batch transfer */
+    mol_seg_t transfers_seg =
+        MolReader_SUDTBatchTransfer_get_transfers(&msg.seg);
+    mol_seg_t fee_seg = MolReader_SUDTBatchTransfer_get_fee(&msg.seg);
+
+    gw_reg_addr_t from_addr;
+    ret = load_sender_addr(&ctx, &fee_seg, &from_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    ret = pay_fee(&ctx, &fee_seg, from_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    /* Check all items before updating balances, so the batch either
+     * succeeds or fails as a whole. We do not rely on sys_snapshot and
+     * sys_revert, which are unimplemented in the validator. */
+    uint32_t transfers_len =
+        MolReader_SUDTTransferItemVec_length(&transfers_seg);
+    uint256_t total_amount = {0};
+    for (uint32_t i = 0; i < transfers_len; i++) {
+      gw_reg_addr_t to_addr;
+      uint256_t amount = {0};
+      ret = parse_transfer_item(&transfers_seg, i, &to_addr, &amount);
+      if (ret != 0) {
+        return ret;
+      }
+      int overflow =
+          gw_uint256_overflow_add(total_amount, amount, &total_amount);
+      if (overflow) {
+        printf("batch transfer: total amount overflow");
+        return GW_SUDT_ERROR_AMOUNT_OVERFLOW;
+      }
+    }
+    uint256_t from_balance = {0};
+    ret = sudt_get_balance(&ctx, sudt_id, from_addr, &from_balance);
+    if (ret != 0) {
+      return ret;
+    }
+    if (gw_uint256_cmp(from_balance, total_amount) == GW_UINT256_SMALLER) {
+      printf("batch transfer: insufficient balance");
+      return GW_SUDT_ERROR_INSUFFICIENT_BALANCE;
+    }
+
+    /* transfer, one log per receiver */
+    for (uint32_t i = 0; i < transfers_len; i++) {
+      gw_reg_addr_t to_addr;
+      uint256_t amount = {0};
+      ret = parse_transfer_item(&transfers_seg, i, &to_addr, &amount);
+      if (ret != 0) {
+        return ret;
+      }
+      ret = sudt_transfer(&ctx, sudt_id, from_addr, to_addr, amount);
+      if (ret != 0) {
+        printf("batch transfer: transfer token failed");
+        return ret;
+      }
+    }
+  } else if (msg.item_id == MSG_MINT) {
+    /* Mint */
+    mol_seg_t to_seg = MolReader_SUDTMint_get_to_address(&msg.seg);
+    mol_seg_t raw_to_seg = MolReader_Bytes_raw_bytes(&to_seg);
+    mol_seg_t amount_seg = MolReader_SUDTMint_get_amount(&msg.seg);
+    mol_seg_t fee_seg = MolReader_SUDTMint_get_fee(&msg.seg);
+
+    gw_reg_addr_t issuer_addr;
+    ret = load_sender_addr(&ctx, &fee_seg, &issuer_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    gw_reg_addr_t to_addr;
+    ret = _gw_parse_addr(raw_to_seg.ptr, raw_to_seg.size, &to_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    uint256_t amount = {0};
+    _gw_fast_memcpy((uint8_t *)(&amount), (uint8_t *)amount_seg.ptr,
+                    sizeof(uint256_t));
+
+    ret = pay_fee(&ctx, &fee_seg, issuer_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = sudt_mint(&ctx, sudt_id, issuer_addr, to_addr, amount);
+    if (ret != 0) {
+      printf("mint failed");
+      return ret;
+    }
+  } else if (msg.item_id == MSG_BURN) {
+    /* Burn */
+    mol_seg_t amount_seg = MolReader_SUDTBurn_get_amount(&msg.seg);
+    mol_seg_t fee_seg = MolReader_SUDTBurn_get_fee(&msg.seg);
+
+    gw_reg_addr_t issuer_addr;
+    ret = load_sender_addr(&ctx, &fee_seg, &issuer_addr);
+    if (ret != 0) {
+      return ret;
+    }
+
+    uint256_t amount = {0};
+    _gw_fast_memcpy((uint8_t *)(&amount), (uint8_t *)amount_seg.ptr,
+                    sizeof(uint256_t));
+
+    ret = pay_fee(&ctx, &fee_seg, issuer_addr);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = sudt_burn(&ctx, sudt_id, issuer_addr, amount);
+    if (ret != 0) {
+      printf("burn failed");
+      return ret;
+    }
   } else {
     return GW_FATAL_UNKNOWN_ARGS;
   }
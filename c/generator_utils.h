@@ -438,7 +438,8 @@ int _sys_load_rollup_config(uint8_t *addr, uint64_t *len) {
   mol_seg_t config_seg;
   config_seg.ptr = addr;
   config_seg.size = *len;
-  if (MolReader_RollupConfig_verify(&config_seg, false) != MOL_OK) {
+  /* accept extra fields appended to RollupConfig */
+  if (MolReader_RollupConfig_verify(&config_seg, true) != MOL_OK) {
     printf("rollup config cell data is not RollupConfig format");
     return GW_FATAL_INVALID_DATA;
   }
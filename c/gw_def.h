@@ -49,10 +49,17 @@ typedef unsigned __int128 uint128_t;
 #define GW_LOG_SUDT_PAY_FEE 0x1
 #define GW_LOG_POLYJUICE_SYSTEM 0x2
 #define GW_LOG_POLYJUICE_USER 0x3
+#define GW_LOG_SUDT_APPROVE 0x4
+#define GW_LOG_SUDT_MINT 0x5
+#define GW_LOG_SUDT_BURN 0x6
 
 #define GW_ALLOWED_EOA_UNKNOWN 0
 #define GW_ALLOWED_EOA_ETH 1
 #define GW_ALLOWED_EOA_TRON 2
+#define GW_ALLOWED_EOA_BITCOIN 3
+#define GW_ALLOWED_EOA_ED25519 4
+#define GW_ALLOWED_EOA_WEBAUTHN 5
+#define GW_ALLOWED_EOA_MULTISIG 6
 
 #define GW_ALLOWED_CONTRACT_UNKNOWN 0
 #define GW_ALLOWED_CONTRACT_META 1
@@ -48,6 +48,8 @@
 #define GW_SUDT_ERROR_AMOUNT_OVERFLOW 93
 #define GW_SUDT_ERROR_TO_ADDR 94
 #define GW_SUDT_ERROR_ACCOUNT_NOT_EXISTS 95
+#define GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE 96
+#define GW_SUDT_ERROR_NOT_ISSUER 97
 
 /* Registry error */
 #define GW_REGISTRY_ERROR_DUPLICATE_MAPPING 101
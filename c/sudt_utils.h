@@ -26,9 +26,25 @@
  * The SMT key of Simple UDT total supply is:
  * 0xffffffffffffffff(32 bytes)
  *
+ * The SMT key of the amount a spender can transfer from an owner is:
+ * blake2b(ALLOWANCE_FLAG(value: 2, take 4 bytes) | owner_address |
+ * spender_address)
+ *
  * To support transfer with backend engine native addresses(such as Ethereum
  * address), we introduce registry address format:
  * `registry_id(4 bytes) | address len (4 bytes) | address(n bytes)`
+ *
+ * ## Layer2 native tokens
+ *
+ * The script args of a Simple UDT account bridged from layer1 is:
+ * `rollup_script_hash(32 bytes) | l1_sudt_script_hash(32 bytes)`
+ *
+ * A layer2 native token appends the issuer's registry address:
+ * `rollup_script_hash(32 bytes) | token_id(32 bytes) | issuer_address`
+ *
+ * Only the issuer can mint and burn a layer2 native token. Deposits and
+ * withdrawals always locate the bridged account by the 64 bytes args, so a
+ * layer2 native token can never be withdrawn to layer1 as custodian assets.
  */
 
 #include "godwoken.h"
@@ -39,6 +55,9 @@
 
 #define CKB_SUDT_ACCOUNT_ID 1
 #define SUDT_KEY_FLAG_BALANCE 1
+#define SUDT_KEY_FLAG_ALLOWANCE 2
+/* rollup_script_hash(32 bytes) | l1_sudt_script_hash(32 bytes) */
+#define SUDT_BRIDGED_SCRIPT_ARGS_LEN 64
 
 const uint8_t SUDT_TOTAL_SUPPLY_KEY[] = {
     0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
@@ -61,6 +80,26 @@ int _sudt_build_key(uint32_t key_flag, gw_reg_addr_t registry_address,
   return 0;
 }
 
+/* format:
+ * key_flag(4 bytes) | owner_address | spender_address
+ */
+int _sudt_build_allowance_key(gw_reg_addr_t owner_addr,
+                              gw_reg_addr_t spender_addr, uint8_t *key,
+                              uint32_t *key_len) {
+  uint32_t owner_addr_size = GW_REG_ADDR_SIZE(owner_addr);
+  uint32_t spender_addr_size = GW_REG_ADDR_SIZE(spender_addr);
+  if (*key_len < (4 + owner_addr_size + spender_addr_size)) {
+    printf("_sudt_build_allowance_key: addr is large than buffer");
+    return GW_FATAL_BUFFER_OVERFLOW;
+  }
+  *key_len = 4 + owner_addr_size + spender_addr_size;
+  uint32_t key_flag = SUDT_KEY_FLAG_ALLOWANCE;
+  _gw_fast_memcpy(key, (uint8_t *)(&key_flag), 4);
+  _gw_cpy_addr(key + 4, owner_addr);
+  _gw_cpy_addr(key + 4 + owner_addr_size, spender_addr);
+  return 0;
+}
+
 /* format:
  * from_addr | to_addr | amount(32 bytes)
  */
@@ -140,6 +179,14 @@ int _sudt_get_total_supply(gw_context_t *ctx, const uint32_t sudt_id,
   return 0;
 }
 
+int _sudt_set_total_supply(gw_context_t *ctx, const uint32_t sudt_id,
+                           uint256_t total_supply) {
+  uint8_t value[32] = {0};
+  _gw_fast_memcpy((uint8_t *)&value, (uint8_t *)(&total_supply),
+                  sizeof(uint256_t));
+  return ctx->sys_store(ctx, sudt_id, SUDT_TOTAL_SUPPLY_KEY, 32, value);
+}
+
 int sudt_get_total_supply(gw_context_t *ctx, const uint32_t sudt_id,
                           uint256_t *total_supply) {
   int ret = gw_verify_sudt_account(ctx, sudt_id);
@@ -243,3 +290,261 @@ int sudt_pay_fee(gw_context_t *ctx, const uint32_t sudt_id,
   }
   return ret;
 }
+
+int _sudt_get_allowance(gw_context_t *ctx, const uint32_t sudt_id,
+                        gw_reg_addr_t owner_addr, gw_reg_addr_t spender_addr,
+                        uint256_t *allowance) {
+  uint8_t key[64] = {0};
+  uint32_t key_len = 64;
+  int ret = _sudt_build_allowance_key(owner_addr, spender_addr, key, &key_len);
+  if (ret != 0) {
+    return ret;
+  }
+  uint8_t value[32] = {0};
+  ret = ctx->sys_load(ctx, sudt_id, key, key_len, value);
+  if (ret != 0) {
+    return ret;
+  }
+  _gw_fast_memcpy((uint8_t *)allowance, (uint8_t *)(&value), 32);
+  return 0;
+}
+
+int _sudt_set_allowance(gw_context_t *ctx, const uint32_t sudt_id,
+                        gw_reg_addr_t owner_addr, gw_reg_addr_t spender_addr,
+                        uint256_t allowance) {
+  uint8_t key[64] = {0};
+  uint32_t key_len = 64;
+  int ret = _sudt_build_allowance_key(owner_addr, spender_addr, key, &key_len);
+  if (ret != 0) {
+    return ret;
+  }
+
+  uint8_t value[32] = {0};
+  _gw_fast_memcpy((uint8_t *)&value, (uint8_t *)(&allowance),
+                  sizeof(uint256_t));
+  return ctx->sys_store(ctx, sudt_id, key, key_len, value);
+}
+
+int sudt_get_allowance(gw_context_t *ctx, const uint32_t sudt_id,
+                       gw_reg_addr_t owner_addr, gw_reg_addr_t spender_addr,
+                       uint256_t *allowance) {
+  int ret = gw_verify_sudt_account(ctx, sudt_id);
+  if (ret != 0) {
+    return ret;
+  }
+  return _sudt_get_allowance(ctx, sudt_id, owner_addr, spender_addr,
+                             allowance);
+}
+
+/* Set the amount the spender can transfer from the owner,
+ * the previous allowance is overwritten */
+int sudt_approve(gw_context_t *ctx, const uint32_t sudt_id,
+                 gw_reg_addr_t owner_addr, gw_reg_addr_t spender_addr,
+                 const uint256_t amount) {
+  int ret = gw_verify_sudt_account(ctx, sudt_id);
+  if (ret != 0) {
+    printf("approve: invalid sudt_id");
+    return ret;
+  }
+
+  ret = _sudt_set_allowance(ctx, sudt_id, owner_addr, spender_addr, amount);
+  if (ret != 0) {
+    printf("approve: update allowance failed");
+    return ret;
+  }
+
+  /* emit log */
+  ret = _sudt_emit_log(ctx, sudt_id, owner_addr, spender_addr, amount,
+                       GW_LOG_SUDT_APPROVE);
+  if (ret != 0) {
+    printf("approve: emit log failed");
+  }
+  return ret;
+}
+
+/* Transfer from the owner by the spender, the allowance is decreased */
+int sudt_transfer_from(gw_context_t *ctx, const uint32_t sudt_id,
+                       gw_reg_addr_t spender_addr, gw_reg_addr_t from_addr,
+                       gw_reg_addr_t to_addr, const uint256_t amount) {
+  uint256_t allowance = {0};
+  int ret =
+      sudt_get_allowance(ctx, sudt_id, from_addr, spender_addr, &allowance);
+  if (ret != 0) {
+    printf("transfer from: can't get spender's allowance");
+    return ret;
+  }
+  if (gw_uint256_cmp(allowance, amount) == GW_UINT256_SMALLER) {
+    printf("transfer from: insufficient allowance");
+    return GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE;
+  }
+
+  uint256_t new_allowance = {0};
+  gw_uint256_underflow_sub(allowance, amount, &new_allowance);
+  ret = _sudt_set_allowance(ctx, sudt_id, from_addr, spender_addr,
+                            new_allowance);
+  if (ret != 0) {
+    printf("transfer from: update allowance failed");
+    return ret;
+  }
+
+  return _sudt_transfer(ctx, sudt_id, from_addr, to_addr, amount,
+                        GW_LOG_SUDT_TRANSFER);
+}
+
+/* Load the issuer of a layer2 native token from the account script args,
+ * returns GW_SUDT_ERROR_NOT_ISSUER if the token is bridged from layer1 */
+int sudt_get_issuer(gw_context_t *ctx, const uint32_t sudt_id,
+                    gw_reg_addr_t *issuer_addr) {
+  int ret = gw_verify_sudt_account(ctx, sudt_id);
+  if (ret != 0) {
+    return ret;
+  }
+  uint8_t script_buffer[GW_MAX_SCRIPT_SIZE];
+  uint64_t script_len = GW_MAX_SCRIPT_SIZE;
+  ret = ctx->sys_get_account_script(ctx, sudt_id, &script_len, 0,
+                                    script_buffer);
+  if (ret != 0) {
+    return ret;
+  }
+  if (script_len > GW_MAX_SCRIPT_SIZE) {
+    return GW_FATAL_INVALID_SUDT_SCRIPT;
+  }
+  mol_seg_t script_seg;
+  script_seg.ptr = script_buffer;
+  script_seg.size = script_len;
+  mol_seg_t args_seg = MolReader_Script_get_args(&script_seg);
+  mol_seg_t raw_args_seg = MolReader_Bytes_raw_bytes(&args_seg);
+  if (raw_args_seg.size <= SUDT_BRIDGED_SCRIPT_ARGS_LEN) {
+    printf("get issuer: the token is bridged from layer1");
+    return GW_SUDT_ERROR_NOT_ISSUER;
+  }
+  uint32_t issuer_len = raw_args_seg.size - SUDT_BRIDGED_SCRIPT_ARGS_LEN;
+  ret = _gw_parse_addr(raw_args_seg.ptr + SUDT_BRIDGED_SCRIPT_ARGS_LEN,
+                       issuer_len, issuer_addr);
+  if (ret != 0) {
+    return ret;
+  }
+  if (GW_REG_ADDR_SIZE((*issuer_addr)) != issuer_len) {
+    printf("get issuer: invalid issuer address");
+    return GW_FATAL_INVALID_SUDT_SCRIPT;
+  }
+  return 0;
+}
+
+int _sudt_check_issuer(gw_context_t *ctx, const uint32_t sudt_id,
+                       gw_reg_addr_t addr) {
+  gw_reg_addr_t issuer_addr;
+  int ret = sudt_get_issuer(ctx, sudt_id, &issuer_addr);
+  if (ret != 0) {
+    return ret;
+  }
+  if (_gw_cmp_addr(issuer_addr, addr) != 0) {
+    printf("check issuer: the sender is not the issuer");
+    return GW_SUDT_ERROR_NOT_ISSUER;
+  }
+  return 0;
+}
+
+/* Mint layer2 native tokens by the issuer, the total supply is increased */
+int sudt_mint(gw_context_t *ctx, const uint32_t sudt_id,
+              gw_reg_addr_t issuer_addr, gw_reg_addr_t to_addr,
+              const uint256_t amount) {
+  int ret = _sudt_check_issuer(ctx, sudt_id, issuer_addr);
+  if (ret != 0) {
+    return ret;
+  }
+
+  /* update total supply */
+  uint256_t total_supply = {0};
+  ret = _sudt_get_total_supply(ctx, sudt_id, &total_supply);
+  if (ret != 0) {
+    return ret;
+  }
+  uint256_t new_total_supply = {0};
+  int overflow =
+      gw_uint256_overflow_add(total_supply, amount, &new_total_supply);
+  if (overflow) {
+    printf("mint: total supply overflow");
+    return GW_SUDT_ERROR_AMOUNT_OVERFLOW;
+  }
+  ret = _sudt_set_total_supply(ctx, sudt_id, new_total_supply);
+  if (ret != 0) {
+    printf("mint: update total supply failed");
+    return ret;
+  }
+
+  /* update receiver balance, it can't overflow since the total supply
+   * doesn't */
+  uint256_t to_balance = {0};
+  ret = _sudt_get_balance(ctx, sudt_id, to_addr, &to_balance);
+  if (ret != 0) {
+    printf("mint: can't get receiver's balance");
+    return ret;
+  }
+  uint256_t new_to_balance = {0};
+  gw_uint256_overflow_add(to_balance, amount, &new_to_balance);
+  ret = _sudt_set_balance(ctx, sudt_id, to_addr, new_to_balance);
+  if (ret != 0) {
+    printf("mint: update receiver's balance failed");
+    return ret;
+  }
+
+  /* emit log */
+  ret = _sudt_emit_log(ctx, sudt_id, issuer_addr, to_addr, amount,
+                       GW_LOG_SUDT_MINT);
+  if (ret != 0) {
+    printf("mint: emit log failed");
+  }
+  return ret;
+}
+
+/* Burn layer2 native tokens from the issuer's balance, the total supply is
+ * decreased */
+int sudt_burn(gw_context_t *ctx, const uint32_t sudt_id,
+              gw_reg_addr_t issuer_addr, const uint256_t amount) {
+  int ret = _sudt_check_issuer(ctx, sudt_id, issuer_addr);
+  if (ret != 0) {
+    return ret;
+  }
+
+  /* update issuer balance */
+  uint256_t balance = {0};
+  ret = _sudt_get_balance(ctx, sudt_id, issuer_addr, &balance);
+  if (ret != 0) {
+    printf("burn: can't get issuer's balance");
+    return ret;
+  }
+  if (gw_uint256_cmp(balance, amount) == GW_UINT256_SMALLER) {
+    printf("burn: insufficient balance");
+    return GW_SUDT_ERROR_INSUFFICIENT_BALANCE;
+  }
+  uint256_t new_balance = {0};
+  gw_uint256_underflow_sub(balance, amount, &new_balance);
+  ret = _sudt_set_balance(ctx, sudt_id, issuer_addr, new_balance);
+  if (ret != 0) {
+    printf("burn: update issuer's balance failed");
+    return ret;
+  }
+
+  /* update total supply, it can't underflow since it's the sum of balances */
+  uint256_t total_supply = {0};
+  ret = _sudt_get_total_supply(ctx, sudt_id, &total_supply);
+  if (ret != 0) {
+    return ret;
+  }
+  uint256_t new_total_supply = {0};
+  gw_uint256_underflow_sub(total_supply, amount, &new_total_supply);
+  ret = _sudt_set_total_supply(ctx, sudt_id, new_total_supply);
+  if (ret != 0) {
+    printf("burn: update total supply failed");
+    return ret;
+  }
+
+  /* emit log */
+  ret = _sudt_emit_log(ctx, sudt_id, issuer_addr, issuer_addr, amount,
+                       GW_LOG_SUDT_BURN);
+  if (ret != 0) {
+    printf("burn: emit log failed");
+  }
+  return ret;
+}
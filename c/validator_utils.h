@@ -11,6 +11,7 @@
 #define SCRIPT_HASH_TYPE_DATA 0
 #define SCRIPT_HASH_TYPE_TYPE 1
 #define TARGET_TYPE_TRANSACTION 0
+#define TARGET_TYPE_WITHDRAWAL 2
 
 /* buffer size */
 #define GW_MAX_KV_PROOF_SIZE 32768
@@ -87,7 +88,10 @@ typedef struct gw_context_t {
   gw_account_merkle_state_t prev_account; /* RawL2Block.prev_account */
   gw_account_merkle_state_t post_account; /* RawL2Block.post_account */
 
-  /* challenged tx index */
+  /* challenged tx index, or the withdrawal index if the target type is
+   * TARGET_TYPE_WITHDRAWAL, in which case the sender contract verifies an
+   * authorization call of the withdrawal and the state must be unchanged */
+  uint8_t target_type;
   uint32_t tx_index;
 
   /* sender's original nonce */
@@ -793,7 +797,8 @@ int _load_rollup_config(uint8_t config_cell_data_hash[32],
   mol_seg_t config_seg;
   config_seg.ptr = rollup_config_buf;
   config_seg.size = *rollup_config_size;
-  if (MolReader_RollupConfig_verify(&config_seg, false) != MOL_OK) {
+  /* accept extra fields appended to RollupConfig */
+  if (MolReader_RollupConfig_verify(&config_seg, true) != MOL_OK) {
     printf("rollup config cell data is not RollupConfig format");
     return GW_FATAL_INVALID_DATA;
   }
@@ -860,7 +865,8 @@ int _load_verification_context(
     uint8_t rollup_script_hash[32], uint64_t rollup_cell_index,
     uint64_t rollup_cell_source, uint64_t *challenge_cell_index,
     uint8_t challenged_block_hash[32], uint8_t block_merkle_root[32],
-    uint32_t *tx_index, uint8_t rollup_config[GW_MAX_ROLLUP_CONFIG_SIZE],
+    uint8_t *target_type, uint32_t *tx_index,
+    uint8_t rollup_config[GW_MAX_ROLLUP_CONFIG_SIZE],
     uint64_t *rollup_config_size) {
   /* load global state from rollup cell */
   uint8_t global_state_buf[sizeof(MolDefault_GlobalState)] = {0};
@@ -954,12 +960,13 @@ int _load_verification_context(
   /* check challenge type */
   mol_seg_t target_type_seg =
       MolReader_ChallengeTarget_get_target_type(&target_seg);
-  uint8_t target_type = *(uint8_t *)target_type_seg.ptr;
-  if (target_type != TARGET_TYPE_TRANSACTION) {
+  *target_type = *(uint8_t *)target_type_seg.ptr;
+  if (*target_type != TARGET_TYPE_TRANSACTION &&
+      *target_type != TARGET_TYPE_WITHDRAWAL) {
     printf("challenge target type is invalid");
     return GW_FATAL_INVALID_DATA;
   }
-  /* get challenged transaction index */
+  /* get challenged transaction or withdrawal index */
   mol_seg_t tx_index_seg =
       MolReader_ChallengeTarget_get_target_index(&target_seg);
   _gw_fast_memcpy(tx_index, tx_index_seg.ptr, sizeof(uint32_t));
@@ -1093,12 +1100,63 @@ int _load_tx_checkpoint(mol_seg_t *raw_l2block_seg, uint32_t tx_index,
   return 0;
 }
 
+/*
+ * Load the state checkpoint before the withdrawal, the authorization call of
+ * a withdrawal is executed on it
+ */
+int _load_withdrawal_checkpoint(mol_seg_t *raw_l2block_seg,
+                                uint32_t withdrawal_index,
+                                uint8_t checkpoint[32]) {
+  mol_seg_t submit_withdrawals_seg =
+      MolReader_RawL2Block_get_submit_withdrawals(raw_l2block_seg);
+  mol_seg_t withdrawals_count_seg =
+      MolReader_SubmitWithdrawals_get_withdrawal_count(&submit_withdrawals_seg);
+  uint32_t withdrawals_count = 0;
+  _gw_fast_memcpy((uint8_t *)(&withdrawals_count), withdrawals_count_seg.ptr,
+                  sizeof(uint32_t));
+  if (withdrawal_index >= withdrawals_count) {
+    printf("invalid withdrawal index");
+    return GW_FATAL_INVALID_DATA;
+  }
+
+  if (0 == withdrawal_index) {
+    /* checkpoint of RawL2Block.prev_account */
+    mol_seg_t prev_account_seg =
+        MolReader_RawL2Block_get_prev_account(raw_l2block_seg);
+    mol_seg_t merkle_root_seg =
+        MolReader_AccountMerkleState_get_merkle_root(&prev_account_seg);
+    mol_seg_t count_seg =
+        MolReader_AccountMerkleState_get_count(&prev_account_seg);
+    blake2b_state blake2b_ctx;
+    blake2b_init(&blake2b_ctx, 32);
+    blake2b_update(&blake2b_ctx, merkle_root_seg.ptr, 32);
+    blake2b_update(&blake2b_ctx, count_seg.ptr, sizeof(uint32_t));
+    blake2b_final(&blake2b_ctx, checkpoint, 32);
+    return 0;
+  }
+
+  mol_seg_t checkpoint_list_seg =
+      MolReader_RawL2Block_get_state_checkpoint_list(raw_l2block_seg);
+  mol_seg_res_t checkpoint_res =
+      MolReader_Byte32Vec_get(&checkpoint_list_seg, withdrawal_index - 1);
+  if (MOL_OK != checkpoint_res.errno || 32 != checkpoint_res.seg.size) {
+    printf("invalid prev withdrawal checkpoint");
+    return GW_FATAL_INVALID_DATA;
+  }
+  _gw_fast_memcpy(checkpoint, checkpoint_res.seg.ptr, 32);
+  return 0;
+}
+
 /* Load verify transaction witness
+ *
+ * For TARGET_TYPE_WITHDRAWAL the CCTransactionWitness of the authorization
+ * call is read from witness_args.input_type, witness_args.lock is the
+ * CCWithdrawalWitness verified by the challenge lock.
  */
 int _load_verify_transaction_witness(uint8_t rollup_script_hash[32],
                                      uint64_t challenge_cell_index,
                                      uint8_t challenged_block_hash[32],
-                                     uint32_t tx_index,
+                                     uint8_t target_type, uint32_t tx_index,
                                      uint8_t block_merkle_root[32],
                                      gw_context_t *ctx) {
   /* load witness from challenge cell */
@@ -1119,8 +1177,13 @@ int _load_verify_transaction_witness(uint8_t rollup_script_hash[32],
     return GW_FATAL_INVALID_DATA;
   }
 
-  /* read VerifyTransactionWitness from witness_args.lock */
-  mol_seg_t content_seg = MolReader_WitnessArgs_get_lock(&witness_seg);
+  /* read VerifyTransactionWitness from witness_args.lock or input_type */
+  mol_seg_t content_seg;
+  if (TARGET_TYPE_WITHDRAWAL == target_type) {
+    content_seg = MolReader_WitnessArgs_get_input_type(&witness_seg);
+  } else {
+    content_seg = MolReader_WitnessArgs_get_lock(&witness_seg);
+  }
   if (MolReader_BytesOpt_is_none(&content_seg)) {
     printf("WitnessArgs has no input field");
     return GW_FATAL_INVALID_DATA;
@@ -1152,19 +1215,21 @@ int _load_verify_transaction_witness(uint8_t rollup_script_hash[32],
       MolReader_CCTransactionWitness_get_l2tx(&cc_tx_witness_seg);
   mol_seg_t raw_l2tx_seg = MolReader_L2Transaction_get_raw(&l2tx_seg);
 
-  /* verify tx merkle proof */
-  mol_seg_t submit_txs_seg =
-      MolReader_RawL2Block_get_submit_transactions(&raw_l2block_seg);
-  mol_seg_t tx_witness_root_seg =
-      MolReader_SubmitTransactions_get_tx_witness_root(&submit_txs_seg);
-  mol_seg_t tx_proof_seg =
-      MolReader_CCTransactionWitness_get_tx_proof(&cc_tx_witness_seg);
-
-  ret = _gw_verify_cbmt_tx_proof(&tx_proof_seg, &tx_witness_root_seg, tx_index,
-                                 &l2tx_seg);
-  if (ret != 0) {
-    printf("failed to verify tx witness root ret %d", ret);
-    return GW_FATAL_SMT_VERIFY;
+  /* verify tx merkle proof, an authorization call isn't in the block */
+  if (TARGET_TYPE_TRANSACTION == target_type) {
+    mol_seg_t submit_txs_seg =
+        MolReader_RawL2Block_get_submit_transactions(&raw_l2block_seg);
+    mol_seg_t tx_witness_root_seg =
+        MolReader_SubmitTransactions_get_tx_witness_root(&submit_txs_seg);
+    mol_seg_t tx_proof_seg =
+        MolReader_CCTransactionWitness_get_tx_proof(&cc_tx_witness_seg);
+
+    ret = _gw_verify_cbmt_tx_proof(&tx_proof_seg, &tx_witness_root_seg,
+                                   tx_index, &l2tx_seg);
+    if (ret != 0) {
+      printf("failed to verify tx witness root ret %d", ret);
+      return GW_FATAL_SMT_VERIFY;
+    }
   }
 
   /* load transaction context */
@@ -1290,8 +1355,15 @@ int _load_verify_transaction_witness(uint8_t rollup_script_hash[32],
   ctx->kv_state_proof_size = kv_state_proof_bytes_seg.size;
 
   /* load tx checkpoint */
-  ret = _load_tx_checkpoint(&raw_l2block_seg, tx_index, ctx->prev_tx_checkpoint,
-                            ctx->post_tx_checkpoint);
+  if (TARGET_TYPE_WITHDRAWAL == target_type) {
+    /* the authorization call must not change the state */
+    ret = _load_withdrawal_checkpoint(&raw_l2block_seg, tx_index,
+                                      ctx->prev_tx_checkpoint);
+    _gw_fast_memcpy(ctx->post_tx_checkpoint, ctx->prev_tx_checkpoint, 32);
+  } else {
+    ret = _load_tx_checkpoint(&raw_l2block_seg, tx_index,
+                              ctx->prev_tx_checkpoint, ctx->post_tx_checkpoint);
+  }
   if (ret != 0) {
     return ret;
   }
@@ -1667,7 +1739,8 @@ int gw_context_init(gw_context_t *ctx) {
   ret = _load_verification_context(
       rollup_script_hash, rollup_cell_index, CKB_SOURCE_INPUT,
       &challenge_cell_index, challenged_block_hash, block_merkle_root,
-      &ctx->tx_index, ctx->rollup_config, &ctx->rollup_config_size);
+      &ctx->target_type, &ctx->tx_index, ctx->rollup_config,
+      &ctx->rollup_config_size);
   if (ret != 0) {
     printf("failed to load verification context");
     return ret;
@@ -1676,7 +1749,7 @@ int gw_context_init(gw_context_t *ctx) {
   /* load context fields */
   ret = _load_verify_transaction_witness(
       rollup_script_hash, challenge_cell_index, challenged_block_hash,
-      ctx->tx_index, block_merkle_root, ctx);
+      ctx->target_type, ctx->tx_index, block_merkle_root, ctx);
   if (ret != 0) {
     printf("failed to load verify transaction witness");
     return ret;
@@ -1703,11 +1776,14 @@ int gw_context_init(gw_context_t *ctx) {
 }
 
 int gw_finalize(gw_context_t *ctx) {
-  /* update sender nonce */
-  int ret = _increase_sender_nonce(ctx);
-  if (ret != 0) {
-    printf("failed to update original sender nonce");
-    return ret;
+  /* update sender nonce, an authorization call isn't a transaction */
+  int ret;
+  if (TARGET_TYPE_TRANSACTION == ctx->target_type) {
+    ret = _increase_sender_nonce(ctx);
+    if (ret != 0) {
+      printf("failed to update original sender nonce");
+      return ret;
+    }
   }
 
   uint8_t return_data_hash[32] = {0};
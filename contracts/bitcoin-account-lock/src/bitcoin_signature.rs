@@ -0,0 +1,118 @@
+//! Secp256k1 Bitcoin implementation
+//!
+//! Signatures are in the BIP-137 format: header(1 byte) | r(32 bytes) | s(32 bytes),
+//! the header encodes the recovery id and the address type.
+//! The pubkey hash is `ripemd160(sha256(pubkey))`, P2SH-P2WPKH addresses hash the
+//! redeem script `0x00 | 0x14 | pubkey_hash` instead.
+//!
+//! Wallets sign text, so `SigningType::WithPrefix` signs the 64 chars lowercase hex
+//! of the message: double_sha256(magic | varint(64) | hex(message)).
+
+use alloc::vec::Vec;
+
+use gw_account_lock::{
+    hex_message,
+    secp256k1::{to_digest, SIGNATURE_SIZE},
+    AccountLockScheme, HEX_MESSAGE_LEN,
+};
+use gw_utils::{ckb_std::debug, error::Error, gw_common::H256};
+use ripemd160::Ripemd160;
+use secp256k1_utils::{compress_pubkey, recover_uncompressed_key};
+use sha2::{Digest, Sha256};
+
+/// "Bitcoin Signed Message:\n" prefixed by its length
+const MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+enum AddressType {
+    /// P2PKH with an uncompressed pubkey, header 27-30
+    P2PKHUncompressed,
+    /// P2PKH with a compressed pubkey, header 31-34
+    P2PKHCompressed,
+    /// Segwit P2SH-P2WPKH, header 35-38
+    P2SHP2WPKH,
+    /// Segwit bech32 P2WPKH, header 39-42
+    P2WPKH,
+}
+
+fn hash160(data: &[u8]) -> [u8; 20] {
+    let hash = Ripemd160::digest(&Sha256::digest(data));
+    let mut buf = [0u8; 20];
+    buf.copy_from_slice(&hash);
+    buf
+}
+
+fn double_sha256(data: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for item in data {
+        hasher.update(item);
+    }
+    let hash = Sha256::digest(&hasher.finalize());
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(&hash);
+    buf
+}
+
+/// Split the header into the recovery id and the address type
+fn parse_header(header: u8) -> Result<(u8, AddressType), Error> {
+    match header {
+        27..=30 => Ok((header - 27, AddressType::P2PKHUncompressed)),
+        31..=34 => Ok((header - 31, AddressType::P2PKHCompressed)),
+        35..=38 => Ok((header - 35, AddressType::P2SHP2WPKH)),
+        39..=42 => Ok((header - 39, AddressType::P2WPKH)),
+        _ => {
+            debug!("Invalid signature header: {}", header);
+            Err(Error::WrongSignature)
+        }
+    }
+}
+
+pub struct Secp256k1Bitcoin;
+
+impl AccountLockScheme for Secp256k1Bitcoin {
+    const ADDRESS_LEN: usize = 20;
+
+    /// The message is signed as its hex string, the length fits in a single byte varint
+    fn prefix_message(message: &H256) -> Vec<u8> {
+        double_sha256(&[
+            MESSAGE_MAGIC,
+            &[HEX_MESSAGE_LEN as u8],
+            &hex_message(message),
+        ])
+        .to_vec()
+    }
+
+    fn recover_address(signature: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
+        if signature.len() != SIGNATURE_SIZE {
+            debug!(
+                "signature len: {}, expected len: {}",
+                signature.len(),
+                SIGNATURE_SIZE
+            );
+            return Err(Error::WrongSignature);
+        }
+        let (rec_id, address_type) = parse_header(signature[0])?;
+        // move the recovery id to the end: r | s | rec_id
+        let mut recoverable_signature = [0u8; SIGNATURE_SIZE];
+        recoverable_signature[..64].copy_from_slice(&signature[1..]);
+        recoverable_signature[64] = rec_id;
+        let pubkey = recover_uncompressed_key(to_digest(message)?, recoverable_signature).map_err(
+            |err| {
+                debug!("failed to recover secp256k1 pubkey, error number: {}", err);
+                Error::WrongSignature
+            },
+        )?;
+        let pubkey_hash = match address_type {
+            AddressType::P2PKHUncompressed => hash160(&pubkey),
+            AddressType::P2PKHCompressed | AddressType::P2WPKH => {
+                hash160(&compress_pubkey(&pubkey))
+            }
+            AddressType::P2SHP2WPKH => {
+                let mut redeem_script = [0u8; 22];
+                redeem_script[..2].copy_from_slice(&[0x00, 0x14]);
+                redeem_script[2..].copy_from_slice(&hash160(&compress_pubkey(&pubkey)));
+                hash160(&redeem_script)
+            }
+        };
+        Ok(pubkey_hash.to_vec())
+    }
+}
@@ -0,0 +1,13 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use crate::bitcoin_signature::Secp256k1Bitcoin;
+use gw_account_lock::verify_account_lock;
+use gw_utils::error::Error;
+
+/// Bitcoin account lock
+/// script args: rollup_script_hash(32 bytes) | pubkey_hash(20 bytes)
+/// data: onetime_owner_lock_hash(32 bytes) | signing type (1 byte) | message(32 bytes)
+pub fn main() -> Result<(), Error> {
+    verify_account_lock::<Secp256k1Bitcoin>()
+}
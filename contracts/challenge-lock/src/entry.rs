@@ -17,6 +17,7 @@ use gw_utils::{
     error::Error,
 };
 
+use crate::verifications::args_sub_code;
 use gw_types::{
     core::ChallengeTargetType,
     packed::{ChallengeLockArgs, ChallengeLockArgsReader},
@@ -30,7 +31,7 @@ fn parse_lock_args() -> Result<([u8; 32], ChallengeLockArgs), Error> {
 
     let mut rollup_type_hash: [u8; 32] = [0u8; 32];
     if args.len() < rollup_type_hash.len() {
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(args_sub_code::ARGS_TOO_SHORT));
     }
     rollup_type_hash.copy_from_slice(&args[..32]);
     match ChallengeLockArgsReader::verify(&args.slice(32..), false) {
@@ -38,7 +39,7 @@ fn parse_lock_args() -> Result<([u8; 32], ChallengeLockArgs), Error> {
             rollup_type_hash,
             ChallengeLockArgs::new_unchecked(args.slice(32..)),
         )),
-        Err(_) => Err(Error::InvalidArgs),
+        Err(_) => Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_LOCK_ARGS)),
     }
 }
 
@@ -69,7 +70,7 @@ pub fn main() -> Result<(), Error> {
         RollupActionUnionReader::RollupCancelChallenge(_) => {}
         _ => {
             debug!("unsupport action {:?}", action.to_enum());
-            return Err(Error::InvalidArgs);
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::UNSUPPORTED_ROLLUP_ACTION));
         }
     }
 
@@ -84,7 +85,9 @@ pub fn main() -> Result<(), Error> {
     let challenge_target = lock_args.target();
     let target_type: ChallengeTargetType = {
         let target_type: u8 = challenge_target.target_type().into();
-        target_type.try_into().map_err(|_| Error::InvalidArgs)?
+        target_type
+            .try_into()
+            .map_err(|_| Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_TARGET_TYPE))?
     };
 
     match target_type {
@@ -31,7 +31,31 @@ pub struct TxContext {
     pub receiver_script_hash: H256,
     pub sender: Script,
     pub receiver: Script,
-    pub sender_address: RegistryAddress,
+    /// Type of the sender in `RollupConfig.allowed_eoa_type_hashes`
+    pub sender_eoa_type: u8,
+    /// Type of the receiver in `RollupConfig.allowed_contract_type_hashes`
+    pub receiver_contract_type: u8,
+    /// ETH registry address of the sender, EOAs of other types may not have one
+    pub sender_address: Option<RegistryAddress>,
+}
+
+/// Type of the External Owned Account in `RollupConfig.allowed_eoa_type_hashes`
+pub fn get_eoa_type(rollup_config: &RollupConfig, script: &Script) -> Result<u8, Error> {
+    if script.hash_type() != ScriptHashType::Type.into() {
+        debug!("EOA script has invalid script hash type: Data");
+        return Err(Error::UnknownEOAScript);
+    }
+    match rollup_config
+        .allowed_eoa_type_hashes()
+        .into_iter()
+        .find(|type_hash| type_hash.hash() == script.code_hash())
+    {
+        Some(type_hash) => Ok(type_hash.type_().into()),
+        None => {
+            debug!("EOA script has unknown code_hash: {}", script.code_hash());
+            Err(Error::UnknownEOAScript)
+        }
+    }
 }
 
 pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
@@ -82,38 +106,27 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
         .ok_or(Error::ScriptNotFound)?;
 
     // sender must be a valid External Owned Account
-    if sender_script.hash_type() != ScriptHashType::Type.into() {
-        debug!("sender script has invalid script hash type: Data");
-        return Err(Error::UnknownEOAScript);
-    }
-    if !rollup_config
-        .allowed_eoa_type_hashes()
-        .into_iter()
-        .any(|type_hash| type_hash.hash() == sender_script.code_hash())
-    {
-        debug!(
-            "sender script has unknown code_hash: {}",
-            sender_script.code_hash()
-        );
-        return Err(Error::UnknownEOAScript);
-    }
+    let sender_eoa_type = get_eoa_type(rollup_config, &sender_script)?;
 
     // receiver must be a valid contract account
     if receiver_script.hash_type() != ScriptHashType::Type.into() {
         debug!("receiver script has invalid script hash type: Data");
         return Err(Error::UnknownContractScript);
     }
-    if !rollup_config
+    let receiver_contract_type: u8 = match rollup_config
         .allowed_contract_type_hashes()
         .into_iter()
-        .any(|type_hash| type_hash.hash() == receiver_script.code_hash())
+        .find(|type_hash| type_hash.hash() == receiver_script.code_hash())
     {
-        debug!(
-            "receiver script has unknown code_hash: {}",
-            receiver_script.code_hash()
-        );
-        return Err(Error::UnknownContractScript);
-    }
+        Some(type_hash) => type_hash.type_().into(),
+        None => {
+            debug!(
+                "receiver script has unknown code_hash: {}",
+                receiver_script.code_hash()
+            );
+            return Err(Error::UnknownContractScript);
+        }
+    };
 
     // verify block hash
     if raw_block.hash() != target.block_hash().as_slice() {
@@ -168,14 +181,15 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
     }
 
     let sender_address = kv_state
-        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
-        .ok_or(Error::RegistryAddressNotFound)?;
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?;
 
     let tx_ctx = TxContext {
         sender_script_hash,
         receiver_script_hash,
         sender: sender_script,
         receiver: receiver_script,
+        sender_eoa_type,
+        receiver_contract_type,
         sender_address,
     };
     Ok(tx_ctx)
@@ -7,11 +7,19 @@ use alloc::{
 use gw_utils::{
     ckb_std::debug,
     error::Error,
-    gw_types::{core::ScriptHashType, packed::RawWithdrawalRequest, prelude::Unpack},
+    gw_types::{
+        core::ScriptHashType,
+        packed::{
+            BatchCreateEthAccountsReader, CreateAccountReader, FeeReader, RawL2Transaction,
+            RawWithdrawalRequest, SUDTTransferReader, SetMappingReader,
+        },
+        prelude::{Reader, Unpack},
+    },
 };
 use sha3::{Digest, Keccak256};
 
 use super::traits::EIP712Encode;
+use crate::verifications::args_sub_code;
 
 pub struct Script {
     code_hash: [u8; 32],
@@ -19,6 +27,23 @@ pub struct Script {
     args: Vec<u8>,
 }
 
+impl Script {
+    fn from_script(script: &gw_utils::gw_types::packed::Script) -> Result<Self, Error> {
+        let hash_type = match ScriptHashType::try_from(script.hash_type()).map_err(|hash_type| {
+            debug!("Invalid hash type: {}", hash_type);
+            Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_SCRIPT_HASH_TYPE)
+        })? {
+            ScriptHashType::Data => "data",
+            ScriptHashType::Type => "type",
+        };
+        Ok(Script {
+            code_hash: script.code_hash().unpack(),
+            hash_type: hash_type.to_string(),
+            args: script.args().unpack(),
+        })
+    }
+}
+
 impl EIP712Encode for Script {
     fn type_name() -> &'static str {
         "Script"
@@ -86,7 +111,7 @@ impl AddressRegistry {
             gw_utils::gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID => Ok(Self::ETH),
             _ => {
                 debug!("Unsupported registry id : {}", registry_id);
-                Err(Error::InvalidArgs)
+                Err(Error::InvalidArgs.with_sub_code(args_sub_code::UNSUPPORTED_REGISTRY))
             }
         }
     }
@@ -99,22 +124,41 @@ pub struct RegistryAddress {
 }
 
 impl RegistryAddress {
-    fn from_address(
-        address: gw_utils::gw_common::registry_address::RegistryAddress,
-    ) -> Result<Self, Error> {
-        let registry = AddressRegistry::from_registry_id(address.registry_id)?;
-        if address.address.len() != 20 {
+    fn new(registry_id: u32, address: &[u8]) -> Result<Self, Error> {
+        let registry = AddressRegistry::from_registry_id(registry_id)?;
+        if address.len() != 20 {
             debug!(
                 "Invalid ETH address len, expected 20, got {}",
-                address.address.len()
+                address.len()
             );
-            return Err(Error::InvalidArgs);
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_REGISTRY_ADDRESS));
         }
         Ok(RegistryAddress {
             registry,
-            address: address.address.try_into().expect("eth address"),
+            address: address.try_into().expect("eth address"),
         })
     }
+
+    fn from_address(
+        address: gw_utils::gw_common::registry_address::RegistryAddress,
+    ) -> Result<Self, Error> {
+        Self::new(address.registry_id, &address.address)
+    }
+
+    /// Parse registry_id(4 bytes) | address len(4 bytes) | address
+    fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < 8 {
+            debug!("Invalid registry address len: {}", data.len());
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_REGISTRY_ADDRESS));
+        }
+        let registry_id = u32::from_le_bytes(data[..4].try_into().expect("registry id"));
+        let address_len = u32::from_le_bytes(data[4..8].try_into().expect("address len"));
+        if data.len() != 8 + address_len as usize {
+            debug!("Invalid registry address len: {}", data.len());
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_REGISTRY_ADDRESS));
+        }
+        Self::new(registry_id, &data[8..])
+    }
 }
 
 impl EIP712Encode for RegistryAddress {
@@ -138,6 +182,43 @@ impl EIP712Encode for RegistryAddress {
     }
 }
 
+/// Fee of the builtin contracts
+pub struct Fee {
+    registry: AddressRegistry,
+    // CKB amount
+    amount: u128,
+}
+
+impl EIP712Encode for Fee {
+    fn type_name() -> &'static str {
+        "Fee"
+    }
+
+    fn encode_type(&self, buf: &mut Vec<u8>) {
+        buf.extend(b"Fee(string registry,uint256 amount)");
+    }
+
+    fn encode_data(&self, buf: &mut Vec<u8>) {
+        use ethabi::Token;
+        let registry: [u8; 32] = {
+            let mut hasher = Keccak256::new();
+            hasher.update(self.registry.to_string().as_bytes());
+            hasher.finalize().into()
+        };
+        buf.extend(ethabi::encode(&[Token::Uint(registry.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.amount.into())]));
+    }
+}
+
+impl Fee {
+    fn from_reader(fee: FeeReader) -> Result<Self, Error> {
+        Ok(Fee {
+            registry: AddressRegistry::from_registry_id(fee.registry_id().unpack())?,
+            amount: fee.amount().unpack(),
+        })
+    }
+}
+
 /// L2Transaction
 #[derive(Debug)]
 pub struct L2Transaction {
@@ -241,14 +322,6 @@ impl Withdrawal {
         owner_lock: gw_utils::gw_types::packed::Script,
         address: gw_utils::gw_common::registry_address::RegistryAddress,
     ) -> Result<Self, Error> {
-        let hash_type =
-            match ScriptHashType::try_from(owner_lock.hash_type()).map_err(|hash_type| {
-                debug!("Invalid hash type: {}", hash_type);
-                Error::InvalidArgs
-            })? {
-                ScriptHashType::Data => "data",
-                ScriptHashType::Type => "type",
-            };
         let address = RegistryAddress::from_address(address)?;
         let withdrawal = Withdrawal {
             nonce: data.nonce().unpack(),
@@ -258,11 +331,7 @@ impl Withdrawal {
                 udt_amount: data.amount().unpack(),
                 udt_script_hash: data.sudt_script_hash().unpack(),
             },
-            layer1_owner_lock: Script {
-                code_hash: owner_lock.code_hash().unpack(),
-                hash_type: hash_type.to_string(),
-                args: owner_lock.args().unpack(),
-            },
+            layer1_owner_lock: Script::from_script(&owner_lock)?,
             fee: data.fee().unpack(),
             chain_id: data.chain_id().unpack(),
         };
@@ -270,6 +339,232 @@ impl Withdrawal {
     }
 }
 
+/// SUDTTransfer of the layer2 sUDT, `sudt` is the receiver of the L2Transaction
+pub struct SUDTTransfer {
+    chain_id: u64,
+    nonce: u32,
+    from: RegistryAddress,
+    sudt: [u8; 32],
+    to: RegistryAddress,
+    amount: ethabi::Uint,
+    fee: Fee,
+}
+
+impl EIP712Encode for SUDTTransfer {
+    fn type_name() -> &'static str {
+        "SUDTTransfer"
+    }
+
+    fn encode_type(&self, buf: &mut Vec<u8>) {
+        buf.extend(b"SUDTTransfer(uint256 chainId,uint256 nonce,RegistryAddress from,bytes32 sudt,RegistryAddress to,uint256 amount,Fee fee)");
+        self.fee.encode_type(buf);
+        self.from.encode_type(buf);
+    }
+
+    fn encode_data(&self, buf: &mut Vec<u8>) {
+        use ethabi::Token;
+        buf.extend(ethabi::encode(&[Token::Uint(self.chain_id.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.nonce.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.from.hash_struct().into(),
+        )]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.sudt.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.to.hash_struct().into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.amount)]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.fee.hash_struct().into(),
+        )]));
+    }
+}
+
+impl SUDTTransfer {
+    pub fn from_raw(
+        data: &RawL2Transaction,
+        sender_address: gw_utils::gw_common::registry_address::RegistryAddress,
+        sudt_script_hash: gw_utils::gw_common::H256,
+        transfer: SUDTTransferReader,
+    ) -> Result<Self, Error> {
+        let to = RegistryAddress::from_slice(transfer.to_address().raw_data())?;
+        Ok(SUDTTransfer {
+            chain_id: data.chain_id().unpack(),
+            nonce: data.nonce().unpack(),
+            from: RegistryAddress::from_address(sender_address)?,
+            sudt: sudt_script_hash.into(),
+            to,
+            amount: ethabi::Uint::from_little_endian(transfer.amount().as_slice()),
+            fee: Fee::from_reader(transfer.fee())?,
+        })
+    }
+}
+
+/// CreateAccount of the meta contract
+pub struct CreateAccount {
+    chain_id: u64,
+    nonce: u32,
+    from: RegistryAddress,
+    script: Script,
+    fee: Fee,
+}
+
+impl EIP712Encode for CreateAccount {
+    fn type_name() -> &'static str {
+        "CreateAccount"
+    }
+
+    fn encode_type(&self, buf: &mut Vec<u8>) {
+        buf.extend(b"CreateAccount(uint256 chainId,uint256 nonce,RegistryAddress from,Script script,Fee fee)");
+        self.fee.encode_type(buf);
+        self.from.encode_type(buf);
+        self.script.encode_type(buf);
+    }
+
+    fn encode_data(&self, buf: &mut Vec<u8>) {
+        use ethabi::Token;
+        buf.extend(ethabi::encode(&[Token::Uint(self.chain_id.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.nonce.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.from.hash_struct().into(),
+        )]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.script.hash_struct().into(),
+        )]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.fee.hash_struct().into(),
+        )]));
+    }
+}
+
+impl CreateAccount {
+    pub fn from_raw(
+        data: &RawL2Transaction,
+        sender_address: gw_utils::gw_common::registry_address::RegistryAddress,
+        create: CreateAccountReader,
+    ) -> Result<Self, Error> {
+        Ok(CreateAccount {
+            chain_id: data.chain_id().unpack(),
+            nonce: data.nonce().unpack(),
+            from: RegistryAddress::from_address(sender_address)?,
+            script: Script::from_script(&create.script().to_entity())?,
+            fee: Fee::from_reader(create.fee())?,
+        })
+    }
+}
+
+/// BatchCreateEthAccounts of the meta contract
+pub struct BatchCreateEthAccounts {
+    chain_id: u64,
+    nonce: u32,
+    from: RegistryAddress,
+    scripts: Vec<Script>,
+    fee: Fee,
+}
+
+impl EIP712Encode for BatchCreateEthAccounts {
+    fn type_name() -> &'static str {
+        "BatchCreateEthAccounts"
+    }
+
+    fn encode_type(&self, buf: &mut Vec<u8>) {
+        buf.extend(b"BatchCreateEthAccounts(uint256 chainId,uint256 nonce,RegistryAddress from,Script[] scripts,Fee fee)");
+        self.fee.encode_type(buf);
+        self.from.encode_type(buf);
+        // the type of Script is the same for all scripts
+        buf.extend(b"Script(bytes32 codeHash,string hashType,bytes args)");
+    }
+
+    fn encode_data(&self, buf: &mut Vec<u8>) {
+        use ethabi::Token;
+        buf.extend(ethabi::encode(&[Token::Uint(self.chain_id.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.nonce.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.from.hash_struct().into(),
+        )]));
+        // an array is encoded as the keccak256 of the concatenated hash_struct of items
+        let scripts: [u8; 32] = {
+            let mut hasher = Keccak256::new();
+            for script in &self.scripts {
+                hasher.update(&script.hash_struct());
+            }
+            hasher.finalize().into()
+        };
+        buf.extend(ethabi::encode(&[Token::Uint(scripts.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.fee.hash_struct().into(),
+        )]));
+    }
+}
+
+impl BatchCreateEthAccounts {
+    pub fn from_raw(
+        data: &RawL2Transaction,
+        sender_address: gw_utils::gw_common::registry_address::RegistryAddress,
+        batch: BatchCreateEthAccountsReader,
+    ) -> Result<Self, Error> {
+        let scripts = batch
+            .scripts()
+            .iter()
+            .map(|script| Script::from_script(&script.to_entity()))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(BatchCreateEthAccounts {
+            chain_id: data.chain_id().unpack(),
+            nonce: data.nonce().unpack(),
+            from: RegistryAddress::from_address(sender_address)?,
+            scripts,
+            fee: Fee::from_reader(batch.fee())?,
+        })
+    }
+}
+
+/// SetMapping of the ETH address registry
+pub struct SetMapping {
+    chain_id: u64,
+    nonce: u32,
+    from: RegistryAddress,
+    gw_script_hash: [u8; 32],
+    fee: Fee,
+}
+
+impl EIP712Encode for SetMapping {
+    fn type_name() -> &'static str {
+        "SetMapping"
+    }
+
+    fn encode_type(&self, buf: &mut Vec<u8>) {
+        buf.extend(b"SetMapping(uint256 chainId,uint256 nonce,RegistryAddress from,bytes32 gwScriptHash,Fee fee)");
+        self.fee.encode_type(buf);
+        self.from.encode_type(buf);
+    }
+
+    fn encode_data(&self, buf: &mut Vec<u8>) {
+        use ethabi::Token;
+        buf.extend(ethabi::encode(&[Token::Uint(self.chain_id.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.nonce.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.from.hash_struct().into(),
+        )]));
+        buf.extend(ethabi::encode(&[Token::Uint(self.gw_script_hash.into())]));
+        buf.extend(ethabi::encode(&[Token::Uint(
+            self.fee.hash_struct().into(),
+        )]));
+    }
+}
+
+impl SetMapping {
+    pub fn from_raw(
+        data: &RawL2Transaction,
+        sender_address: gw_utils::gw_common::registry_address::RegistryAddress,
+        mapping: SetMappingReader,
+    ) -> Result<Self, Error> {
+        Ok(SetMapping {
+            chain_id: data.chain_id().unpack(),
+            nonce: data.nonce().unpack(),
+            from: RegistryAddress::from_address(sender_address)?,
+            gw_script_hash: mapping.gw_script_hash().unpack(),
+            fee: Fee::from_reader(mapping.fee())?,
+        })
+    }
+}
+
 pub struct EIP712Domain {
     pub name: String,
     pub version: String,
@@ -288,6 +583,37 @@ impl EIP712Domain {
             salt: None,
         }
     }
+
+    /// Domain of the rollup selected by `eip712_domain_version` of RollupConfig
+    ///
+    /// * 0: `chainId` only, rollups sharing a chain id accept each other's signatures
+    /// * 1: version "2", `verifyingContract` is the last 20 bytes of the rollup type hash
+    ///   and `salt` is the rollup type hash
+    pub fn domain_of_rollup(
+        chain_id: u64,
+        rollup_script_hash: &[u8; 32],
+        domain_version: u8,
+    ) -> Result<EIP712Domain, Error> {
+        match domain_version {
+            0 => Ok(Self::domain_with_chain_id(chain_id)),
+            1 => Ok(EIP712Domain {
+                name: "Godwoken".to_string(),
+                chain_id,
+                version: "2".to_string(),
+                verifying_contract: Some(
+                    rollup_script_hash[12..]
+                        .try_into()
+                        .expect("verifying contract"),
+                ),
+                salt: Some(*rollup_script_hash),
+            }),
+            _ => {
+                debug!("Unsupported EIP-712 domain version: {}", domain_version);
+                Err(Error::InvalidArgs
+                    .with_sub_code(args_sub_code::UNSUPPORTED_EIP712_DOMAIN_VERSION))
+            }
+        }
+    }
 }
 
 impl EIP712Encode for EIP712Domain {
@@ -3,3 +3,23 @@ mod eip712;
 pub mod tx_execution;
 pub mod tx_signature;
 pub mod withdrawal;
+
+/// sub codes of `Error::InvalidArgs`
+pub mod args_sub_code {
+    pub const ARGS_TOO_SHORT: u16 = 1;
+    pub const INVALID_LOCK_ARGS: u16 = 2;
+    pub const UNSUPPORTED_ROLLUP_ACTION: u16 = 3;
+    pub const INVALID_TARGET_TYPE: u16 = 4;
+    pub const MISSING_UNLOCK_WITNESS: u16 = 5;
+    pub const INVALID_UNLOCK_WITNESS: u16 = 6;
+    pub const SENDER_SCRIPT_MISMATCH: u16 = 7;
+    pub const OWNER_LOCK_MISMATCH: u16 = 8;
+    pub const MISSING_AUTHORIZATION_WITNESS: u16 = 9;
+    pub const INVALID_AUTHORIZATION_WITNESS: u16 = 10;
+    pub const AUTHORIZATION_RECEIVER_MISMATCH: u16 = 11;
+    pub const AUTHORIZATION_CALL_MISMATCH: u16 = 12;
+    pub const INVALID_SCRIPT_HASH_TYPE: u16 = 13;
+    pub const UNSUPPORTED_REGISTRY: u16 = 14;
+    pub const INVALID_REGISTRY_ADDRESS: u16 = 15;
+    pub const UNSUPPORTED_EIP712_DOMAIN_VERSION: u16 = 16;
+}
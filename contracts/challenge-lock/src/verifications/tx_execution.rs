@@ -1,3 +1,4 @@
+use crate::verifications::args_sub_code::{INVALID_UNLOCK_WITNESS, MISSING_UNLOCK_WITNESS};
 use crate::verifications::context::{verify_tx_context, TxContext, TxContextInput};
 use core::result::Result;
 use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS, kv_state::KVState};
@@ -28,12 +29,15 @@ pub fn verify_tx_execution(
             Error::Encoding
         })?;
 
-        reader.lock().to_opt().ok_or(Error::InvalidArgs)?
+        reader
+            .lock()
+            .to_opt()
+            .ok_or_else(|| Error::InvalidArgs.with_sub_code(MISSING_UNLOCK_WITNESS))?
     };
 
     let unlock_args = match CCTransactionWitnessReader::verify(witness_args.raw_data(), false) {
         Ok(_) => CCTransactionWitnessReader::new_unchecked(witness_args.raw_data()),
-        Err(_) => return Err(Error::InvalidArgs),
+        Err(_) => return Err(Error::InvalidArgs.with_sub_code(INVALID_UNLOCK_WITNESS)),
     };
 
     let tx = unlock_args.l2tx().to_entity();
@@ -1,5 +1,12 @@
+use crate::verifications::args_sub_code::{INVALID_UNLOCK_WITNESS, MISSING_UNLOCK_WITNESS};
 use crate::verifications::context::{verify_tx_context, TxContext, TxContextInput};
-use crate::verifications::eip712::{traits::EIP712Encode, types::EIP712Domain};
+use crate::verifications::eip712::{
+    traits::EIP712Encode,
+    types::{
+        BatchCreateEthAccounts, CreateAccount, EIP712Domain, L2Transaction, SUDTTransfer,
+        SetMapping,
+    },
+};
 use alloc::vec;
 use core::result::Result;
 use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS, kv_state::KVState};
@@ -8,6 +15,7 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::{
+    cells::rollup::load_eip712_domain_version,
     ckb_std::{
         ckb_constants::Source,
         ckb_types::{bytes::Bytes, prelude::Unpack as CKBUnpack},
@@ -17,33 +25,53 @@ use gw_utils::{
     gw_types::{
         core::SigningType,
         packed::{
-            CCTransactionSignatureWitness, CCTransactionSignatureWitnessReader, Script, ScriptVec,
+            CCTransactionSignatureWitness, CCTransactionSignatureWitnessReader,
+            ETHAddrRegArgsReader, ETHAddrRegArgsUnionReader, MetaContractArgsReader,
+            MetaContractArgsUnionReader, SUDTArgsReader, SUDTArgsUnionReader, Script, ScriptVec,
         },
     },
-    signature::check_l2_account_signature_cell,
+    signature::{
+        check_l2_account_signature_cell, ALLOWED_CONTRACT_ETH_ADDR_REG, ALLOWED_CONTRACT_META,
+        ALLOWED_CONTRACT_SUDT, ALLOWED_EOA_ED25519, ALLOWED_EOA_MULTISIG, ALLOWED_EOA_WEBAUTHN,
+        DEPOSIT_CALL_SIGNATURE,
+    },
 };
 use gw_utils::{
-    gw_common::H256,
+    gw_common::{blake2b::new_blake2b, registry_address::RegistryAddress, H256},
     gw_types::{self, packed::RawL2Transaction},
 };
 use sha3::{Digest, Keccak256};
 
+/// The first EIP-712 domain version signing the typed messages of builtin contract calls,
+/// rollups of older versions sign all of them as the generic `L2Transaction`
+const TYPED_TX_MESSAGE_DOMAIN_VERSION: u8 = 1;
+
 /// Verify tx signature
+///
+/// The signing message depends on the sender's EOA type:
+/// * ETH style EOAs sign the RLP encoded Polyjuice tx or an EIP-712 typed message,
+///   see `calc_typed_tx_message`
+/// * Ed25519, WebAuthn and multisig EOAs have no ETH address, they sign
+///   blake2b(rollup_script_hash | sender_script_hash | receiver_script_hash | raw_tx)
+///
+/// A deposit call is signed by its layer1 deposit cell, which is checked on block submission,
+/// it must be the first transaction of the account created by the deposit
 pub fn verify_tx_signature(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
     let witness_args: Bytes = load_witness_args(0, Source::GroupInput)?
         .lock()
         .to_opt()
-        .ok_or(Error::InvalidArgs)?
+        .ok_or_else(|| Error::InvalidArgs.with_sub_code(MISSING_UNLOCK_WITNESS))?
         .unpack();
     let unlock_args = match CCTransactionSignatureWitnessReader::verify(&witness_args, false) {
         Ok(_) => CCTransactionSignatureWitness::new_unchecked(witness_args),
-        Err(_) => return Err(Error::InvalidArgs),
+        Err(_) => return Err(Error::InvalidArgs.with_sub_code(INVALID_UNLOCK_WITNESS)),
     };
     let tx = unlock_args.l2tx();
+    let is_deposit_call = tx.signature().raw_data().as_ref() == DEPOSIT_CALL_SIGNATURE;
 
     // check rollup chain id
     let expected_rollup_chain_id: u64 = rollup_config.chain_id().unpack();
@@ -89,9 +117,34 @@ pub fn verify_tx_signature(
         receiver_script_hash,
         receiver,
         sender: _,
+        sender_eoa_type,
+        receiver_contract_type,
         sender_address,
     } = verify_tx_context(input)?;
 
+    if is_deposit_call {
+        // the sender nonce is checked by the tx context
+        let nonce: u32 = raw_tx.nonce().unpack();
+        if nonce != 0 {
+            crate::ckb_std::debug!("Deposit call isn't the first tx of the created account");
+            return Err(Error::WrongSignature);
+        }
+        return Ok(());
+    }
+
+    if matches!(
+        sender_eoa_type,
+        ALLOWED_EOA_ED25519 | ALLOWED_EOA_WEBAUTHN | ALLOWED_EOA_MULTISIG
+    ) {
+        let message = calc_raw_tx_message(
+            rollup_script_hash,
+            &sender_script_hash,
+            &receiver_script_hash,
+            &raw_tx,
+        );
+        return check_l2_account_signature_cell(&sender_script_hash, SigningType::Raw, message);
+    }
+
     let (message, signing_type) = match try_assemble_polyjuice_args(&raw_tx, receiver) {
         Some(rlp_data) => {
             let mut hasher = Keccak256::new();
@@ -102,14 +155,19 @@ pub fn verify_tx_signature(
             (H256::from(signing_message), SigningType::Raw)
         }
         None => {
-            let chain_id = raw_tx.chain_id().unpack();
-            let typed_tx = crate::verifications::eip712::types::L2Transaction::from_raw(
+            let sender_address = sender_address.ok_or(Error::RegistryAddressNotFound)?;
+            let domain_version = load_eip712_domain_version(rollup_config)?;
+            let domain_separator =
+                EIP712Domain::domain_of_rollup(chain_id, rollup_script_hash, domain_version)?
+                    .hash_struct();
+            let message = calc_typed_tx_message(
                 raw_tx,
+                domain_version,
+                domain_separator,
+                receiver_contract_type,
                 sender_address,
                 receiver_script_hash,
             )?;
-            let message =
-                typed_tx.eip712_message(EIP712Domain::domain_with_chain_id(chain_id).hash_struct());
             (message.into(), SigningType::Raw)
         }
     };
@@ -119,6 +177,87 @@ pub fn verify_tx_signature(
     Ok(())
 }
 
+/// EIP-712 message of the tx
+///
+/// Since `TYPED_TX_MESSAGE_DOMAIN_VERSION` the message is typed by the receiver's backend:
+/// * sUDT: `SUDTTransfer`
+/// * meta contract: `CreateAccount` and `BatchCreateEthAccounts`
+/// * ETH address registry: `SetMapping`
+///
+/// Other messages, and all messages of older domain versions,
+/// are signed as the generic `L2Transaction`
+fn calc_typed_tx_message(
+    raw_tx: RawL2Transaction,
+    domain_version: u8,
+    domain_separator: [u8; 32],
+    receiver_contract_type: u8,
+    sender_address: RegistryAddress,
+    receiver_script_hash: H256,
+) -> Result<[u8; 32], Error> {
+    let args: Bytes = raw_tx.args().unpack();
+    if domain_version < TYPED_TX_MESSAGE_DOMAIN_VERSION {
+        let typed_tx = L2Transaction::from_raw(raw_tx, sender_address, receiver_script_hash)?;
+        return Ok(typed_tx.eip712_message(domain_separator));
+    }
+    match receiver_contract_type {
+        ALLOWED_CONTRACT_SUDT => {
+            if let Ok(SUDTArgsUnionReader::SUDTTransfer(transfer)) =
+                SUDTArgsReader::from_slice(&args).map(|args| args.to_enum())
+            {
+                let typed_tx = SUDTTransfer::from_raw(
+                    &raw_tx,
+                    sender_address,
+                    receiver_script_hash,
+                    transfer,
+                )?;
+                return Ok(typed_tx.eip712_message(domain_separator));
+            }
+        }
+        ALLOWED_CONTRACT_META => {
+            match MetaContractArgsReader::from_slice(&args).map(|args| args.to_enum()) {
+                Ok(MetaContractArgsUnionReader::CreateAccount(create)) => {
+                    let typed_tx = CreateAccount::from_raw(&raw_tx, sender_address, create)?;
+                    return Ok(typed_tx.eip712_message(domain_separator));
+                }
+                Ok(MetaContractArgsUnionReader::BatchCreateEthAccounts(batch)) => {
+                    let typed_tx =
+                        BatchCreateEthAccounts::from_raw(&raw_tx, sender_address, batch)?;
+                    return Ok(typed_tx.eip712_message(domain_separator));
+                }
+                _ => {}
+            }
+        }
+        ALLOWED_CONTRACT_ETH_ADDR_REG => {
+            if let Ok(ETHAddrRegArgsUnionReader::SetMapping(mapping)) =
+                ETHAddrRegArgsReader::from_slice(&args).map(|args| args.to_enum())
+            {
+                let typed_tx = SetMapping::from_raw(&raw_tx, sender_address, mapping)?;
+                return Ok(typed_tx.eip712_message(domain_separator));
+            }
+        }
+        _ => {}
+    }
+    let typed_tx = L2Transaction::from_raw(raw_tx, sender_address, receiver_script_hash)?;
+    Ok(typed_tx.eip712_message(domain_separator))
+}
+
+/// blake2b(rollup_script_hash | sender_script_hash | receiver_script_hash | raw_tx)
+fn calc_raw_tx_message(
+    rollup_script_hash: &[u8; 32],
+    sender_script_hash: &H256,
+    receiver_script_hash: &H256,
+    raw_tx: &RawL2Transaction,
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_script_hash);
+    hasher.update(sender_script_hash.as_slice());
+    hasher.update(receiver_script_hash.as_slice());
+    hasher.update(raw_tx.as_slice());
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message.into()
+}
+
 fn try_assemble_polyjuice_args(
     raw_tx: &RawL2Transaction,
     receiver_script: Script,
@@ -1,4 +1,9 @@
-use crate::verifications::eip712::traits::EIP712Encode;
+use crate::verifications::args_sub_code::{
+    AUTHORIZATION_CALL_MISMATCH, AUTHORIZATION_RECEIVER_MISMATCH, INVALID_AUTHORIZATION_WITNESS,
+    INVALID_UNLOCK_WITNESS, MISSING_AUTHORIZATION_WITNESS, MISSING_UNLOCK_WITNESS,
+    OWNER_LOCK_MISMATCH, SENDER_SCRIPT_MISMATCH,
+};
+use crate::verifications::{context::get_eoa_type, eip712::traits::EIP712Encode};
 use core::result::Result;
 use gw_common::{
     builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State, H256,
@@ -8,10 +13,14 @@ use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS};
 use gw_types::packed::ChallengeLockArgs;
 use gw_utils::gw_types::{
     self,
-    packed::{RollupConfig, Script, WithdrawalRequest},
+    core::{ScriptHashType, SigningType},
+    packed::{
+        CCTransactionWitnessReader, RawWithdrawalRequest, RollupConfig, Script, WithdrawalRequest,
+    },
     prelude::*,
 };
 use gw_utils::{
+    cells::{rollup::load_eip712_domain_version, utils::search_lock_hash},
     ckb_std::{
         ckb_constants::Source,
         ckb_types::{bytes::Bytes, prelude::Unpack as CKBUnpack},
@@ -19,22 +28,33 @@ use gw_utils::{
         high_level::load_witness_args,
     },
     error::Error,
-    signature::check_l2_account_signature_cell,
+    signature::{
+        check_l2_account_signature_cell, ALLOWED_EOA_BITCOIN, ALLOWED_EOA_ETH, ALLOWED_EOA_TRON,
+        ALLOWED_EOA_UNKNOWN,
+    },
 };
 use gw_utils::{
     gw_common::{
         self,
-        merkle_utils::{ckb_merkle_leaf_hash, CBMTMerkleProof},
+        blake2b::new_blake2b,
+        merkle_utils::{calculate_state_checkpoint, ckb_merkle_leaf_hash, CBMTMerkleProof},
     },
     gw_types::packed::{CCWithdrawalWitness, CCWithdrawalWitnessReader},
 };
 
 use super::eip712::types::EIP712Domain;
 
+/// `isValidSignature(bytes32,bytes)` selector of EIP-1271, also the magic value returned on success
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+/// Polyjuice args: header(52 bytes) | input data
+const POLYJUICE_ARGS_HEADER_LEN: usize = 52;
+
 struct WithdrawalContext {
     withdrawal: WithdrawalRequest,
     sender_script_hash: H256,
-    withdrawal_address: RegistryAddress,
+    sender: Script,
+    /// ETH registry address of the sender, EOAs of other types may not have one
+    withdrawal_address: Option<RegistryAddress>,
     owner_lock: Script,
 }
 
@@ -42,11 +62,11 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
     let witness_args: Bytes = load_witness_args(0, Source::GroupInput)?
         .lock()
         .to_opt()
-        .ok_or(Error::InvalidArgs)?
+        .ok_or_else(|| Error::InvalidArgs.with_sub_code(MISSING_UNLOCK_WITNESS))?
         .unpack();
     let unlock_args = match CCWithdrawalWitnessReader::verify(&witness_args, false) {
         Ok(_) => CCWithdrawalWitness::new_unchecked(witness_args),
-        Err(_) => return Err(Error::InvalidArgs),
+        Err(_) => return Err(Error::InvalidArgs.with_sub_code(INVALID_UNLOCK_WITNESS)),
     };
 
     let withdrawal = unlock_args.withdrawal();
@@ -57,12 +77,12 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
 
     if H256::from(sender.hash()) != sender_script_hash {
         debug!("Mismatch sender script hash");
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(SENDER_SCRIPT_MISMATCH));
     }
 
     if H256::from(owner_lock.hash()) != raw_withdrawal.owner_lock_hash().unpack() {
         debug!("Mismatch owner lock hash");
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(OWNER_LOCK_MISMATCH));
     }
 
     // verify block hash
@@ -107,13 +127,13 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
     )?;
 
     let withdrawal_address = kv_state
-        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
-        .ok_or(Error::RegistryAddressNotFound)?;
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?;
 
     let context = WithdrawalContext {
         withdrawal,
         withdrawal_address,
         sender_script_hash,
+        sender,
         owner_lock,
     };
 
@@ -121,14 +141,26 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
 }
 
 /// Verify withdrawal signature
+///
+/// The signing message depends on the sender's EOA type:
+/// * ETH EOAs sign the EIP-712 typed Withdrawal, the domain follows
+///   `eip712_domain_version` of RollupConfig
+/// * Tron and Bitcoin wallets only sign prefixed messages, they sign
+///   blake2b(rollup_script_hash | raw_withdrawal) with `SigningType::WithPrefix`
+/// * Other EOAs, including locks added later, sign
+///   blake2b(rollup_script_hash | raw_withdrawal) with `SigningType::Raw`
+///
+/// A contract account sender authorizes blake2b(rollup_script_hash | raw_withdrawal)
+/// like EIP-1271, see `verify_contract_authorization`.
 pub fn verify_withdrawal(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
     let WithdrawalContext {
         withdrawal,
         sender_script_hash,
+        sender,
         withdrawal_address,
         owner_lock,
     } = verify_withdrawal_proof(lock_args)?;
@@ -142,20 +174,173 @@ pub fn verify_withdrawal(
         return Err(Error::WrongSignature);
     }
 
-    // calculate EIP-712 message
-    let typed_message = crate::verifications::eip712::types::Withdrawal::from_raw(
-        withdrawal.raw(),
-        owner_lock,
-        withdrawal_address,
-    )?;
-    let message = typed_message.eip712_message(
-        EIP712Domain::domain_with_chain_id(raw_withdrawal.chain_id().unpack()).hash_struct(),
-    );
+    if is_contract_account(rollup_config, &sender) {
+        let message = calc_raw_withdrawal_message(rollup_script_hash, &raw_withdrawal);
+        return verify_contract_authorization(&sender_script_hash, lock_args, message);
+    }
+
+    let (message, signing_type) = match get_eoa_type(rollup_config, &sender)? {
+        // EOAs registered before the EOA types are ETH ones
+        ALLOWED_EOA_ETH | ALLOWED_EOA_UNKNOWN => {
+            let withdrawal_address = withdrawal_address.ok_or(Error::RegistryAddressNotFound)?;
+            let typed_message = crate::verifications::eip712::types::Withdrawal::from_raw(
+                raw_withdrawal,
+                owner_lock,
+                withdrawal_address,
+            )?;
+            let domain_version = load_eip712_domain_version(rollup_config)?;
+            let domain =
+                EIP712Domain::domain_of_rollup(chain_id, rollup_script_hash, domain_version)?;
+            let message = typed_message.eip712_message(domain.hash_struct());
+            (message.into(), SigningType::Raw)
+        }
+        ALLOWED_EOA_TRON | ALLOWED_EOA_BITCOIN => (
+            calc_raw_withdrawal_message(rollup_script_hash, &raw_withdrawal),
+            SigningType::WithPrefix,
+        ),
+        _ => (
+            calc_raw_withdrawal_message(rollup_script_hash, &raw_withdrawal),
+            SigningType::Raw,
+        ),
+    };
     // verify sender's script is in the input
-    check_l2_account_signature_cell(
-        &sender_script_hash,
-        gw_types::core::SigningType::Raw,
-        message.into(),
+    check_l2_account_signature_cell(&sender_script_hash, signing_type, message)
+}
+
+/// blake2b(rollup_script_hash | raw_withdrawal)
+fn calc_raw_withdrawal_message(
+    rollup_script_hash: &[u8; 32],
+    raw_withdrawal: &RawWithdrawalRequest,
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_script_hash);
+    hasher.update(raw_withdrawal.as_slice());
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message.into()
+}
+
+fn is_contract_account(rollup_config: &RollupConfig, script: &Script) -> bool {
+    script.hash_type() == ScriptHashType::Type.into()
+        && rollup_config
+            .allowed_contract_type_hashes()
+            .into_iter()
+            .any(|type_hash| type_hash.hash() == script.code_hash())
+}
+
+/// Verify the contract account authorizes the withdrawal
+///
+/// `witness_args.input_type` is the `CCTransactionWitness` of an authorization call,
+/// which calls `isValidSignature(message, signature)` of the sender contract
+/// and returns the EIP-1271 magic value.
+/// The kv state of the witness must be the state before the withdrawal, the backend of
+/// the sender must be in the inputs, it executes the call in validator mode on the state
+/// and requires the state is unchanged, see `c/validator_utils.h`.
+fn verify_contract_authorization(
+    sender_script_hash: &H256,
+    lock_args: &ChallengeLockArgs,
+    message: H256,
+) -> Result<(), Error> {
+    let witness: Bytes = load_witness_args(0, Source::GroupInput)?
+        .input_type()
+        .to_opt()
+        .ok_or_else(|| Error::InvalidArgs.with_sub_code(MISSING_AUTHORIZATION_WITNESS))?
+        .unpack();
+    let auth_witness = match CCTransactionWitnessReader::verify(&witness, false) {
+        Ok(_) => CCTransactionWitnessReader::new_unchecked(&witness),
+        Err(_) => return Err(Error::InvalidArgs.with_sub_code(INVALID_AUTHORIZATION_WITNESS)),
+    };
+
+    // the call is executed on the challenged block
+    let raw_block = auth_witness.raw_l2block().to_entity();
+    if raw_block.hash() != lock_args.target().block_hash().as_slice() {
+        debug!("authorization call isn't executed on the challenged block");
+        return Err(Error::InvalidBlock);
+    }
+
+    // the call is to the sender contract
+    let raw_tx = auth_witness.l2tx().raw();
+    let mut tree_buffer = [Pair::default(); GW_MAX_KV_PAIRS];
+    let kv_state_proof: Bytes = auth_witness.kv_state_proof().unpack();
+    let kv_state = KVState::build(
+        &mut tree_buffer,
+        auth_witness.kv_state(),
+        &kv_state_proof,
+        auth_witness.account_count().unpack(),
+        None,
     )?;
+
+    // verify kv-state merkle proof (the state before the withdrawal)
+    let withdrawal_index: u32 = lock_args.target().target_index().unpack();
+    let prev_state_checkpoint: H256 = match withdrawal_index.checked_sub(1) {
+        Some(prev_withdrawal_index) => raw_block
+            .state_checkpoint_list()
+            .get(prev_withdrawal_index as usize)
+            .ok_or(Error::InvalidStateCheckpoint)?
+            .unpack(),
+        None => {
+            let prev_account = raw_block.prev_account();
+            calculate_state_checkpoint(
+                &prev_account.merkle_root().unpack(),
+                prev_account.count().unpack(),
+            )
+        }
+    };
+    let state_root = kv_state.calculate_root().map_err(|_err| {
+        debug!(
+            "verify contract authorization, calculate merkle root error: {:?}",
+            _err
+        );
+        Error::MerkleProof
+    })?;
+    let account_count = kv_state.get_account_count()?;
+    let calculated_state_checkpoint: H256 = calculate_state_checkpoint(&state_root, account_count);
+    if prev_state_checkpoint != calculated_state_checkpoint {
+        debug!(
+            "authorization call mismatch prev_state_checkpoint: {:?}, calculated_state_checkpoint: {:?}",
+            prev_state_checkpoint, calculated_state_checkpoint
+        );
+        return Err(Error::MerkleProof);
+    }
+
+    let to_script_hash = kv_state.get_script_hash(raw_tx.to_id().unpack())?;
+    if &to_script_hash != sender_script_hash {
+        debug!("authorization call isn't to the sender contract");
+        return Err(Error::InvalidArgs.with_sub_code(AUTHORIZATION_RECEIVER_MISMATCH));
+    }
+
+    // isValidSignature(message, signature)
+    let args = raw_tx.args().raw_data();
+    let input_offset = POLYJUICE_ARGS_HEADER_LEN;
+    if args.len() < input_offset + EIP1271_MAGIC_VALUE.len() + 32
+        || args[0..7] != b"\xFF\xFF\xFFPOLY"[..]
+        || args[input_offset..input_offset + 4] != EIP1271_MAGIC_VALUE
+        || args[input_offset + 4..input_offset + 36] != message.as_slice()[..]
+    {
+        debug!("authorization call isn't isValidSignature of the withdrawal");
+        return Err(Error::InvalidArgs.with_sub_code(AUTHORIZATION_CALL_MISMATCH));
+    }
+
+    // the call returns the magic value, ABI encoded as bytes32
+    let mut expected_return_data = [0u8; 32];
+    expected_return_data[..4].copy_from_slice(&EIP1271_MAGIC_VALUE);
+    let mut expected_return_data_hash = [0u8; 32];
+    let mut hasher = new_blake2b();
+    hasher.update(&expected_return_data);
+    hasher.finalize(&mut expected_return_data_hash);
+    if auth_witness.return_data_hash().as_slice() != &expected_return_data_hash[..] {
+        debug!("authorization call doesn't return the magic value");
+        return Err(Error::WrongSignature);
+    }
+
+    // verify the sender's backend is in the input,
+    // the backend will verify the execution of the call
+    if search_lock_hash(&(*sender_script_hash).into(), Source::Input).is_none() {
+        debug!(
+            "verify contract authorization, can't find sender_script_hash from the input: {:?}",
+            sender_script_hash
+        );
+        return Err(Error::AccountScriptCellNotFound);
+    }
     Ok(())
 }
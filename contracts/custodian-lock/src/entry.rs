@@ -5,7 +5,7 @@ use gw_utils::{
     cells::{
         rollup::{
             load_rollup_config, parse_rollup_action, search_rollup_cell, search_rollup_state,
-            MAX_ROLLUP_WITNESS_SIZE,
+            MAX_ROLLUP_WITNESS_SIZE, ROLLUP_STATUS_EMERGENCY,
         },
         utils::search_lock_hash,
     },
@@ -41,7 +41,8 @@ fn parse_lock_args() -> Result<([u8; 32], CustodianLockArgs), Error> {
         return Err(Error::InvalidArgs);
     }
     rollup_type_hash.copy_from_slice(&args[..32]);
-    match CustodianLockArgsReader::verify(&args.slice(32..), false) {
+    // accept extra fields of the deposit lock args, e.g. `recipient`
+    match CustodianLockArgsReader::verify(&args.slice(32..), true) {
         Ok(()) => Ok((
             rollup_type_hash,
             CustodianLockArgs::new_unchecked(args.slice(32..)),
@@ -97,7 +98,7 @@ pub fn main() -> Result<(), Error> {
             return Err(Error::InvalidArgs);
         }
 
-        match DepositLockArgsReader::verify(&args.slice(32..), false) {
+        match DepositLockArgsReader::verify(&args.slice(32..), true) {
             Ok(_) => DepositLockArgs::new_unchecked(args.slice(32..)),
             Err(_) => return Err(Error::InvalidOutput),
         }
@@ -109,6 +110,12 @@ pub fn main() -> Result<(), Error> {
         return Err(Error::InvalidOutput);
     }
 
+    // the unfinalized state is dropped in emergency mode, every unfinalized deposit is reverted
+    let status: u8 = global_state.status().into();
+    if status == ROLLUP_STATUS_EMERGENCY {
+        return Ok(());
+    }
+
     // check deposit block is reverted
     let deposit_block_hash = lock_args.deposit_block_hash();
     let mut rollup_action_witness = [0u8; MAX_ROLLUP_WITNESS_SIZE];
@@ -3,6 +3,10 @@
 //! The cell can be unlocked by the rollup cell which match the rollup_type_hash,
 //! or can be unlocked by user.
 //!
+//! A cell without type script which carries a signed WithdrawalRequest in the data
+//! is a forced withdrawal request, it shares the unlock paths of a deposit request,
+//! the state-validator checks the request is included or halts the rollup after timeout.
+//!
 //! Args: DepositLockArgs
 
 // Import from `core` instead of from `std` since we are in no-std mode
@@ -40,7 +44,8 @@ fn parse_lock_args() -> Result<([u8; 32], DepositLockArgs), Error> {
         return Err(Error::InvalidArgs);
     }
     rollup_type_hash.copy_from_slice(&args[..32]);
-    match DepositLockArgsReader::verify(&args.slice(32..), false) {
+    // accept extra fields, e.g. `recipient`
+    match DepositLockArgsReader::verify(&args.slice(32..), true) {
         Ok(()) => Ok((
             rollup_type_hash,
             DepositLockArgs::new_unchecked(args.slice(32..)),
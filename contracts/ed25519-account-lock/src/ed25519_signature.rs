@@ -0,0 +1,56 @@
+//! Ed25519 implementation
+//!
+//! The pubkey is the address, a signature can't recover the signer so it is verified directly.
+//! `SigningType::WithPrefix` signs the 64 chars lowercase hex of the message in Solana's
+//! off-chain message format v0, wallets show the hex string to the user:
+//! signing_domain(16 bytes) | header_version(1 byte) | message_format(1 byte) | message_len(2 bytes) | message
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use ed25519_dalek::{PublicKey, Signature};
+use gw_account_lock::{hex_message, AccountLockScheme, HEX_MESSAGE_LEN};
+use gw_utils::{ckb_std::debug, error::Error, gw_common::H256};
+
+const SIGNING_DOMAIN: &[u8] = b"\xffsolana offchain";
+const HEADER_VERSION: u8 = 0;
+/// Printable ASCII messages, a hex string is one of them
+const MESSAGE_FORMAT_RESTRICTED_ASCII: u8 = 0;
+pub const SIGNATURE_SIZE: usize = 64;
+
+pub struct Ed25519;
+
+impl AccountLockScheme for Ed25519 {
+    const ADDRESS_LEN: usize = 32;
+
+    fn prefix_message(message: &H256) -> Vec<u8> {
+        let mut signing_message = Vec::with_capacity(SIGNING_DOMAIN.len() + 4 + HEX_MESSAGE_LEN);
+        signing_message.extend_from_slice(SIGNING_DOMAIN);
+        signing_message.push(HEADER_VERSION);
+        signing_message.push(MESSAGE_FORMAT_RESTRICTED_ASCII);
+        signing_message.extend_from_slice(&(HEX_MESSAGE_LEN as u16).to_le_bytes());
+        signing_message.extend_from_slice(&hex_message(message));
+        signing_message
+    }
+
+    fn verify_signature(address: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, Error> {
+        if signature.len() != SIGNATURE_SIZE {
+            debug!(
+                "signature len: {}, expected len: {}",
+                signature.len(),
+                SIGNATURE_SIZE
+            );
+            return Err(Error::WrongSignature);
+        }
+        let pubkey = PublicKey::from_bytes(address).map_err(|_| {
+            debug!("invalid ed25519 pubkey");
+            Error::InvalidArgs
+        })?;
+        let signature = Signature::try_from(signature).map_err(|_| {
+            debug!("invalid ed25519 signature");
+            Error::WrongSignature
+        })?;
+        // reject malleable signatures and weak keys
+        Ok(pubkey.verify_strict(message, &signature).is_ok())
+    }
+}
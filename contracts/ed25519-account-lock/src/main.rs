@@ -0,0 +1,35 @@
+//! Generated by capsule
+//!
+//! `main.rs` is used to define rust lang items and modules.
+//! See `entry.rs` for the `main` function.
+//! See `error.rs` for the `Error` type.
+
+#![no_std]
+#![no_main]
+#![feature(lang_items)]
+#![feature(alloc_error_handler)]
+#![feature(panic_info_message)]
+#![feature(asm)]
+
+// define modules
+mod ed25519_signature;
+mod entry;
+
+pub use gw_utils::ckb_std;
+
+use ckb_std::default_alloc;
+
+ckb_std::entry!(program_entry);
+default_alloc!();
+
+/// program entry
+fn program_entry() -> i8 {
+    // Call main function and return error code
+    match entry::main() {
+        Ok(_) => 0,
+        Err(err) => {
+            err.report();
+            err.exit_code()
+        }
+    }
+}
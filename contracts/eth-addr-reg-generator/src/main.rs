@@ -0,0 +1,27 @@
+//! Generator of the ETH address registry contract, built from the Rust port in `gw-context`
+//!
+//! It's a drop-in replacement of `c/contracts/eth_addr_reg.c` built with `GW_GENERATOR`.
+
+#![no_std]
+#![no_main]
+#![feature(lang_items)]
+#![feature(alloc_error_handler)]
+#![feature(panic_info_message)]
+#![feature(asm)]
+
+pub use gw_context::gw_utils::ckb_std;
+
+use ckb_std::default_alloc;
+use gw_context::{contracts::eth_addr_reg, generator::GeneratorContext};
+
+ckb_std::entry!(program_entry);
+default_alloc!();
+
+/// program entry
+fn program_entry() -> i8 {
+    // Call main function and return error code
+    match GeneratorContext::init().and_then(|mut ctx| eth_addr_reg::main(&mut ctx)) {
+        Ok(_) => 0,
+        Err(err) => err as i8,
+    }
+}
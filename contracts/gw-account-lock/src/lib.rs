@@ -0,0 +1,21 @@
+//! Layer2 account locks
+//!
+//! An account lock cell proves the owner of a layer2 account signed a message,
+//! the cell is searched by `gw_utils::signature::check_l2_account_signature_cell`.
+//!
+//! script args: rollup_script_hash(32 bytes) | address
+//! data: onetime_owner_lock_hash(32 bytes) | signing type (1 byte) | message(32 bytes)
+//! witness lock: signature
+//!
+//! A new account lock implements `AccountLockScheme` and calls `verify_account_lock` in its entry.
+
+#![no_std]
+
+extern crate alloc;
+
+mod lock;
+mod scheme;
+pub mod secp256k1;
+
+pub use lock::{load_lock_args, load_signature_from_witness, verify_account_lock};
+pub use scheme::{hex_message, AccountLockScheme, HEX_MESSAGE_LEN};
@@ -0,0 +1,75 @@
+use core::result::Result;
+
+use gw_utils::{
+    cells::utils::search_lock_hash,
+    ckb_std::{
+        ckb_constants::Source,
+        ckb_types::{bytes::Bytes, prelude::Unpack as CKBUnpack},
+        debug,
+        high_level::{load_script, load_witness_args},
+    },
+    error::Error,
+    gw_common::H256,
+    gw_types::core::SigningType,
+    signature::L2AccountSignatureCellData,
+};
+
+use crate::scheme::AccountLockScheme;
+
+/// Verify the account lock cell with the scheme
+pub fn verify_account_lock<S: AccountLockScheme>() -> Result<(), Error> {
+    // parse args
+    let (_rollup_script_hash, address) = load_lock_args(S::ADDRESS_LEN)?;
+    debug!("address {:?}", &address);
+
+    // parse data
+    let data = L2AccountSignatureCellData::load(0, Source::GroupInput)?;
+
+    // check owner lock hash cell
+    // to prevent others unlock this cell
+    if search_lock_hash(&data.onetime_owner_lock_hash, Source::Input).is_none() {
+        return Err(Error::OwnerCellNotFound);
+    }
+
+    // verify signature
+    debug!("Verify message signature {:?}", &data.message);
+    let signature = load_signature_from_witness()?;
+    let message = match data.signing_type {
+        SigningType::WithPrefix => S::prefix_message(&data.message),
+        SigningType::Raw => data.message.as_slice().to_vec(),
+    };
+    if !S::verify_signature(&address, &signature, &message)? {
+        debug!("Wrong signature, message: {:?}", message);
+        return Err(Error::WrongSignature);
+    }
+
+    Ok(())
+}
+
+/// Load the script args
+/// return (rollup_script_hash, address)
+pub fn load_lock_args(address_len: usize) -> Result<(H256, Bytes), Error> {
+    let script = load_script()?;
+    let args: Bytes = CKBUnpack::unpack(&script.args());
+    if args.len() != 32 + address_len {
+        debug!("Invalid lock args len: {}", args.len());
+        return Err(Error::InvalidArgs);
+    }
+    let rollup_script_hash = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&args[..32]);
+        buf.into()
+    };
+    Ok((rollup_script_hash, args.slice(32..)))
+}
+
+/// Load the signature from the witness lock
+pub fn load_signature_from_witness() -> Result<Bytes, Error> {
+    let witness_args = load_witness_args(0, Source::GroupInput)?;
+    let signature: Bytes = witness_args
+        .lock()
+        .to_opt()
+        .ok_or(Error::WrongSignature)?
+        .unpack();
+    Ok(signature)
+}
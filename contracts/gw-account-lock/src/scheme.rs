@@ -0,0 +1,40 @@
+use alloc::vec::Vec;
+
+use gw_utils::{ckb_std::debug, error::Error, gw_common::H256};
+
+/// Length of the lowercase hex encoding of a message
+pub const HEX_MESSAGE_LEN: usize = 64;
+
+/// Lowercase hex encoding of the message, wallets which sign text show it to the user
+pub fn hex_message(message: &H256) -> [u8; HEX_MESSAGE_LEN] {
+    const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = [0u8; HEX_MESSAGE_LEN];
+    for (i, byte) in message.as_slice().iter().enumerate() {
+        hex[i * 2] = HEX_CHARS[(byte >> 4) as usize];
+        hex[i * 2 + 1] = HEX_CHARS[(byte & 0xf) as usize];
+    }
+    hex
+}
+
+/// The signature scheme of a layer2 account lock
+pub trait AccountLockScheme {
+    /// Length of the address following the rollup_script_hash in the script args
+    const ADDRESS_LEN: usize;
+
+    /// The message signed by the wallet for `SigningType::WithPrefix`,
+    /// secp256k1 schemes return the 32 bytes digest of the prefixed message
+    fn prefix_message(message: &H256) -> Vec<u8>;
+
+    /// Recover the signer's address from the signature
+    fn recover_address(_signature: &[u8], _message: &[u8]) -> Result<Vec<u8>, Error> {
+        debug!("the scheme can't recover the signer");
+        Err(Error::WrongSignature)
+    }
+
+    /// Check the message is signed by the address,
+    /// schemes can't recover the signer should override this
+    fn verify_signature(address: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, Error> {
+        let recovered_address = Self::recover_address(signature, message)?;
+        Ok(recovered_address.as_slice() == address)
+    }
+}
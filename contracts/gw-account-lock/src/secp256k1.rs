@@ -0,0 +1,56 @@
+//! Secp256k1 utils for Ethereum style addresses
+
+use alloc::vec::Vec;
+
+use gw_utils::{ckb_std::debug, error::Error, gw_common::H256};
+use secp256k1_utils::recover_uncompressed_key;
+use sha3::{Digest, Keccak256};
+
+pub const SIGNATURE_SIZE: usize = 65;
+
+pub fn keccak256(data: &[&[u8]]) -> H256 {
+    let mut hasher = Keccak256::new();
+    for item in data {
+        hasher.update(item);
+    }
+    let buf = hasher.finalize();
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&buf[..]);
+    hash.into()
+}
+
+/// Check the message is a 32 bytes digest
+pub fn to_digest(message: &[u8]) -> Result<[u8; 32], Error> {
+    if message.len() != 32 {
+        debug!("message len: {}, expected len: 32", message.len());
+        return Err(Error::WrongSignature);
+    }
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(message);
+    Ok(buf)
+}
+
+/// Recover the 20 bytes address: keccak256(uncompressed_pubkey[1..])[12..]
+pub fn recover_keccak_address(signature: [u8; 65], message: &[u8]) -> Result<Vec<u8>, Error> {
+    let pubkey = recover_uncompressed_key(to_digest(message)?, signature).map_err(|err| {
+        debug!("failed to recover secp256k1 pubkey, error number: {}", err);
+        Error::WrongSignature
+    })?;
+    let pubkey_hash = keccak256(&[&pubkey[1..]]);
+    Ok(pubkey_hash.as_slice()[12..].to_vec())
+}
+
+/// Check the signature is a 65 bytes recoverable signature
+pub fn to_recoverable_signature(signature: &[u8]) -> Result<[u8; 65], Error> {
+    if signature.len() != SIGNATURE_SIZE {
+        debug!(
+            "signature len: {}, expected len: {}",
+            signature.len(),
+            SIGNATURE_SIZE
+        );
+        return Err(Error::WrongSignature);
+    }
+    let mut buf = [0u8; SIGNATURE_SIZE];
+    buf.copy_from_slice(signature);
+    Ok(buf)
+}
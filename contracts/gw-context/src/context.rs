@@ -0,0 +1,230 @@
+//! The layer2 syscalls, see `gw_context_t` in `c/gw_def.h`
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{
+        bytes::Bytes,
+        packed::{BlockInfoReader, RawL2TransactionReader, RollupConfig},
+        prelude::*,
+    },
+};
+
+use crate::{
+    error::GwError,
+    keys::{
+        build_account_field_key, build_account_key, build_data_hash_key,
+        build_registry_address_to_script_hash_key, build_script_hash_to_account_id_key,
+        build_script_hash_to_registry_address_key, ACCOUNT_NONCE, ACCOUNT_SCRIPT_HASH,
+    },
+    registry_address::RegistryAddress,
+    u256::U256,
+    MAX_L2TX_ARGS_SIZE,
+};
+
+/// The executing layer2 transaction
+#[derive(Debug, Clone)]
+pub struct TransactionContext {
+    pub from_id: u32,
+    pub to_id: u32,
+    pub args: Bytes,
+}
+
+impl TransactionContext {
+    pub fn from_slice(raw_tx: &[u8]) -> Result<Self, GwError> {
+        let raw_tx =
+            RawL2TransactionReader::from_slice(raw_tx).map_err(|_| GwError::InvalidData)?;
+        let args = raw_tx.args().raw_data();
+        if args.len() > MAX_L2TX_ARGS_SIZE {
+            return Err(GwError::InvalidData);
+        }
+        Ok(TransactionContext {
+            from_id: raw_tx.from_id().unpack(),
+            to_id: raw_tx.to_id().unpack(),
+            args: Bytes::copy_from_slice(args),
+        })
+    }
+}
+
+/// The block which contains the executing transaction
+#[derive(Debug, Clone)]
+pub struct BlockInfo {
+    pub number: u64,
+    pub timestamp: u64,
+    pub block_producer: RegistryAddress,
+}
+
+impl BlockInfo {
+    pub fn from_slice(block_info: &[u8]) -> Result<Self, GwError> {
+        let block_info =
+            BlockInfoReader::from_slice(block_info).map_err(|_| GwError::InvalidData)?;
+        let block_producer = RegistryAddress::from_slice(block_info.block_producer().raw_data())
+            .map_err(|err| {
+                debug!("failed to parse block producer addr");
+                err
+            })?;
+        Ok(BlockInfo {
+            number: block_info.number().unpack(),
+            timestamp: block_info.timestamp().unpack(),
+            block_producer,
+        })
+    }
+}
+
+/// Layer2 syscalls
+///
+/// Implementors provide the raw state access and the syscalls whose behavior
+/// differs between generator and validator, the account and registry helpers
+/// are built on top of the raw state.
+pub trait GwContext {
+    fn transaction_context(&self) -> &TransactionContext;
+
+    fn block_info(&self) -> &BlockInfo;
+
+    fn rollup_config(&self) -> &RollupConfig;
+
+    /// Load a value from the state tree by a raw key
+    fn load_raw(&self, raw_key: &[u8; 32]) -> Result<[u8; 32], GwError>;
+
+    /// Store a value into the state tree by a raw key
+    fn store_raw(&mut self, raw_key: &[u8; 32], value: &[u8; 32]) -> Result<(), GwError>;
+
+    fn sys_set_program_return_data(&mut self, data: &[u8]) -> Result<(), GwError>;
+
+    /// Create an account, returns the new account id
+    fn sys_create(&mut self, script: &[u8]) -> Result<u32, GwError>;
+
+    fn sys_get_account_script(&self, account_id: u32) -> Result<Bytes, GwError>;
+
+    fn sys_store_data(&mut self, data: &[u8]) -> Result<(), GwError>;
+
+    fn sys_load_data(&self, data_hash: &[u8; 32]) -> Result<Bytes, GwError>;
+
+    fn sys_get_block_hash(&self, number: u64) -> Result<[u8; 32], GwError>;
+
+    fn sys_log(&mut self, account_id: u32, service_flag: u8, data: &[u8]) -> Result<(), GwError>;
+
+    /// Emit the pay fee event, the fee itself is transferred by the caller
+    fn sys_pay_fee(
+        &mut self,
+        payer: &RegistryAddress,
+        sudt_id: u32,
+        amount: U256,
+    ) -> Result<(), GwError>;
+
+    fn sys_snapshot(&mut self) -> Result<u32, GwError>;
+
+    fn sys_revert(&mut self, snapshot: u32) -> Result<(), GwError>;
+
+    /// The sender's nonce when the context is initialized
+    fn original_sender_nonce(&self) -> u32;
+
+    /// Must be called once the contract succeeds
+    fn finalize(&mut self) -> Result<(), GwError>;
+
+    fn sys_load(&self, account_id: u32, key: &[u8]) -> Result<[u8; 32], GwError> {
+        self.ensure_account_exists(account_id)?;
+        self.load_raw(&build_account_key(account_id, key))
+    }
+
+    fn sys_store(&mut self, account_id: u32, key: &[u8], value: &[u8; 32]) -> Result<(), GwError> {
+        self.ensure_account_exists(account_id)?;
+        self.store_raw(&build_account_key(account_id, key), value)
+    }
+
+    fn sys_get_account_id_by_script_hash(&self, script_hash: &[u8; 32]) -> Result<u32, GwError> {
+        let value = self.load_raw(&build_script_hash_to_account_id_key(script_hash))?;
+        // id(4 bytes) | exists flag(1 byte)
+        if value[4] != 1 {
+            return Err(GwError::AccountNotExists);
+        }
+        let mut id = [0u8; 4];
+        id.copy_from_slice(&value[..4]);
+        Ok(u32::from_le_bytes(id))
+    }
+
+    fn sys_get_script_hash_by_account_id(&self, account_id: u32) -> Result<[u8; 32], GwError> {
+        self.ensure_account_exists(account_id)?;
+        self.load_raw(&build_account_field_key(account_id, ACCOUNT_SCRIPT_HASH))
+    }
+
+    fn sys_get_account_nonce(&self, account_id: u32) -> Result<u32, GwError> {
+        self.ensure_account_exists(account_id)?;
+        let value = self.load_raw(&build_account_field_key(account_id, ACCOUNT_NONCE))?;
+        let mut nonce = [0u8; 4];
+        nonce.copy_from_slice(&value[..4]);
+        Ok(u32::from_le_bytes(nonce))
+    }
+
+    fn sys_get_registry_address_by_script_hash(
+        &self,
+        script_hash: &[u8; 32],
+        reg_id: u32,
+    ) -> Result<RegistryAddress, GwError> {
+        let key = build_script_hash_to_registry_address_key(script_hash);
+        let value = self.sys_load(reg_id, &key)?;
+        if value == [0u8; 32] {
+            debug!("failed to get registry address by script hash");
+            return Err(GwError::NotFound);
+        }
+        RegistryAddress::from_slice(&value)
+    }
+
+    fn sys_get_script_hash_by_registry_address(
+        &self,
+        addr: &RegistryAddress,
+    ) -> Result<[u8; 32], GwError> {
+        let key = build_registry_address_to_script_hash_key(addr)?;
+        let script_hash = self.sys_load(addr.reg_id, &key)?;
+        if script_hash == [0u8; 32] {
+            debug!("failed to get script hash by registry address");
+            return Err(GwError::NotFound);
+        }
+        Ok(script_hash)
+    }
+
+    fn ensure_account_exists(&self, account_id: u32) -> Result<(), GwError> {
+        let script_hash =
+            self.load_raw(&build_account_field_key(account_id, ACCOUNT_SCRIPT_HASH))?;
+        if script_hash == [0u8; 32] {
+            return Err(GwError::AccountNotExists);
+        }
+        Ok(())
+    }
+
+    fn account_exists_by_script_hash(&self, script_hash: &[u8; 32]) -> Result<bool, GwError> {
+        let value = self.load_raw(&build_script_hash_to_account_id_key(script_hash))?;
+        Ok(value[4] == 1)
+    }
+
+    fn data_hash_exists(&self, data_hash: &[u8; 32]) -> Result<bool, GwError> {
+        let value = self.load_raw(&build_data_hash_key(data_hash))?;
+        Ok(value != [0u8; 32])
+    }
+}
+
+/// Load the sender's nonce directly from the state,
+/// the sender may not exist when the context is initialized.
+pub fn load_sender_nonce<C: GwContext + ?Sized>(ctx: &C) -> Result<u32, GwError> {
+    let from_id = ctx.transaction_context().from_id;
+    let value = ctx.load_raw(&build_account_field_key(from_id, ACCOUNT_NONCE))?;
+    let mut nonce = [0u8; 4];
+    nonce.copy_from_slice(&value[..4]);
+    Ok(u32::from_le_bytes(nonce))
+}
+
+/// Increase the sender's nonce unless the contract already increased it
+pub fn increase_sender_nonce<C: GwContext + ?Sized>(ctx: &mut C) -> Result<(), GwError> {
+    let original_nonce = ctx.original_sender_nonce();
+    let nonce = load_sender_nonce(ctx)?;
+    if nonce < original_nonce {
+        debug!("sender's new nonce is less than original nonce");
+        return Err(GwError::InvalidContext);
+    }
+    if nonce == original_nonce {
+        let from_id = ctx.transaction_context().from_id;
+        let mut value = [0u8; 32];
+        value[..4].copy_from_slice(&nonce.wrapping_add(1).to_le_bytes());
+        ctx.store_raw(&build_account_field_key(from_id, ACCOUNT_NONCE), &value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! ETH address registry contract, see `c/contracts/eth_addr_reg.c`
+//!
+//! * EthToGw(eth_address) -> script hash
+//! * GwToEth(script_hash) -> eth address
+//! * SetMapping(script_hash, fee)
+//! * BatchSetMapping(script_hashes, fee)
+
+use gw_utils::gw_types::{
+    packed::{ETHAddrRegArgsReader, ETHAddrRegArgsUnionReader},
+    prelude::*,
+};
+
+use super::handle_fee;
+use crate::{
+    context::GwContext,
+    error::GwError,
+    eth_addr_reg::{register_eth_address, ETH_ADDRESS_LEN},
+    registry_address::RegistryAddress,
+};
+
+pub fn main<C: GwContext>(ctx: &mut C) -> Result<(), GwError> {
+    let args = ctx.transaction_context().args.clone();
+    let args = ETHAddrRegArgsReader::from_slice(&args).map_err(|_| GwError::InvalidData)?;
+    let registry_id = ctx.transaction_context().to_id;
+
+    match args.to_enum() {
+        ETHAddrRegArgsUnionReader::EthToGw(query) => {
+            let addr = RegistryAddress::new(registry_id, query.eth_address().as_slice().to_vec());
+            let script_hash = ctx.sys_get_script_hash_by_registry_address(&addr)?;
+            ctx.sys_set_program_return_data(&script_hash)?;
+        }
+        ETHAddrRegArgsUnionReader::GwToEth(query) => {
+            let script_hash: [u8; 32] = query.gw_script_hash().unpack();
+            let addr = ctx.sys_get_registry_address_by_script_hash(&script_hash, registry_id)?;
+            if addr.address.len() != ETH_ADDRESS_LEN {
+                return Err(GwError::InvalidData);
+            }
+            ctx.sys_set_program_return_data(&addr.address)?;
+        }
+        ETHAddrRegArgsUnionReader::SetMapping(mapping) => {
+            register_eth_address(ctx, &mapping.gw_script_hash().unpack())?;
+            handle_fee(ctx, mapping.fee())?;
+        }
+        ETHAddrRegArgsUnionReader::BatchSetMapping(mapping) => {
+            for script_hash in mapping.gw_script_hashes().iter() {
+                register_eth_address(ctx, &script_hash.unpack())?;
+            }
+            handle_fee(ctx, mapping.fee())?;
+        }
+    }
+
+    ctx.finalize()
+}
@@ -0,0 +1,65 @@
+//! Meta contract, see `c/contracts/meta_contract.c`
+//!
+//! The meta contract is built into the rollup with account id zero,
+//! it creates contract accounts.
+//!
+//! * CreateAccount(script, fee) -> account id
+//! * BatchCreateEthAccounts(scripts, fee) -> the last created account id
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{
+        packed::{MetaContractArgsReader, MetaContractArgsUnionReader},
+        prelude::*,
+    },
+};
+
+use super::handle_fee;
+use crate::{
+    context::GwContext, error::GwError, eth_addr_reg::register_eth_address,
+    META_CONTRACT_ACCOUNT_ID,
+};
+
+pub fn main<C: GwContext>(ctx: &mut C) -> Result<(), GwError> {
+    if ctx.transaction_context().to_id != META_CONTRACT_ACCOUNT_ID {
+        return Err(GwError::InvalidContext);
+    }
+
+    let args = ctx.transaction_context().args.clone();
+    let args = MetaContractArgsReader::from_slice(&args).map_err(|_| GwError::InvalidData)?;
+
+    match args.to_enum() {
+        MetaContractArgsUnionReader::CreateAccount(create) => {
+            handle_fee(ctx, create.fee()).map_err(|err| {
+                debug!("failed to handle fee");
+                err
+            })?;
+            let account_id = ctx.sys_create(create.script().as_slice()).map_err(|err| {
+                debug!("failed to create account");
+                err
+            })?;
+            ctx.sys_set_program_return_data(&account_id.to_le_bytes())?;
+        }
+        MetaContractArgsUnionReader::BatchCreateEthAccounts(batch) => {
+            handle_fee(ctx, batch.fee()).map_err(|err| {
+                debug!("failed to handle fee");
+                err
+            })?;
+            let mut account_id = 0u32;
+            for script in batch.scripts().iter() {
+                account_id = ctx.sys_create(script.as_slice()).map_err(|err| {
+                    debug!("failed to create eth account");
+                    err
+                })?;
+                let script_hash = ctx.sys_get_script_hash_by_account_id(account_id)?;
+                register_eth_address(ctx, &script_hash).map_err(|err| {
+                    debug!("failed to register eth address");
+                    err
+                })?;
+            }
+            ctx.sys_set_program_return_data(&account_id.to_le_bytes())?;
+        }
+    }
+
+    ctx.finalize()
+}
@@ -0,0 +1,24 @@
+//! Rust ports of the built-in layer2 contracts in `c/contracts`
+//!
+//! Each `main` is generic over the context, so the same code is built as
+//! the generator and the validator of the contract.
+
+use gw_utils::gw_types::{packed::FeeReader, prelude::*};
+
+use crate::{context::GwContext, error::GwError, sudt, u256::U256, CKB_SUDT_ACCOUNT_ID};
+
+pub mod eth_addr_reg;
+pub mod meta_contract;
+pub mod sudt_contract;
+
+/// Charge the fee in CKB from the sender, the sender's address is the one in the fee's registry
+pub(crate) fn handle_fee<C: GwContext>(ctx: &mut C, fee: FeeReader) -> Result<(), GwError> {
+    let registry_id: u32 = fee.registry_id().unpack();
+    let amount: u128 = fee.amount().unpack();
+
+    let from_id = ctx.transaction_context().from_id;
+    let payer_script_hash = ctx.sys_get_script_hash_by_account_id(from_id)?;
+    let payer_addr =
+        ctx.sys_get_registry_address_by_script_hash(&payer_script_hash, registry_id)?;
+    sudt::pay_fee(ctx, CKB_SUDT_ACCOUNT_ID, &payer_addr, U256::from(amount))
+}
@@ -0,0 +1,259 @@
+//! Layer2 simple UDT contract, see `c/contracts/sudt.c`
+//!
+//! One layer2 simple UDT account maps to one layer1 simple UDT, tokens are
+//! minted on deposit and burned on withdrawal by the rollup directly.
+//!
+//! * SUDTQuery(address) -> balance
+//! * SUDTTransfer(to_address, amount, fee)
+//! * SUDTApprove(spender_address, amount, fee)
+//! * SUDTAllowance(owner_address, spender_address) -> amount
+//! * SUDTTransferFrom(from_address, to_address, amount, fee)
+//! * SUDTBatchTransfer([(to_address, amount)], fee)
+//! * SUDTMint(to_address, amount, fee), only by the issuer of a layer2 native token
+//! * SUDTBurn(amount, fee), only by the issuer of a layer2 native token
+
+use alloc::vec::Vec;
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{
+        packed::{BytesReader, FeeReader, Uint256Reader},
+        prelude::*,
+    },
+    mol_ext::parse_dynvec,
+};
+
+use crate::{
+    context::GwContext, error::GwError, registry_address::RegistryAddress, sudt, u256::U256,
+    CKB_SUDT_ACCOUNT_ID,
+};
+
+/// Items of `SUDTArgs`
+const MSG_QUERY: u32 = 0;
+const MSG_TRANSFER: u32 = 1;
+const MSG_APPROVE: u32 = 2;
+const MSG_ALLOWANCE: u32 = 3;
+const MSG_TRANSFER_FROM: u32 = 4;
+const MSG_BATCH_TRANSFER: u32 = 5;
+const MSG_MINT: u32 = 6;
+const MSG_BURN: u32 = 7;
+
+/// Field types of the `SUDTArgs` items
+#[derive(Clone, Copy)]
+enum Field {
+    Bytes,
+    Uint256,
+    Fee,
+    TransferItemVec,
+}
+
+fn item_fields(item_id: u32) -> Option<&'static [Field]> {
+    use Field::*;
+    let fields: &'static [Field] = match item_id {
+        MSG_QUERY => &[Bytes],
+        MSG_TRANSFER => &[Bytes, Uint256, Fee],
+        MSG_APPROVE => &[Bytes, Uint256, Fee],
+        MSG_ALLOWANCE => &[Bytes, Bytes],
+        MSG_TRANSFER_FROM => &[Bytes, Bytes, Uint256, Fee],
+        MSG_BATCH_TRANSFER => &[TransferItemVec, Fee],
+        MSG_MINT => &[Bytes, Uint256, Fee],
+        MSG_BURN => &[Uint256, Fee],
+        _ => return None,
+    };
+    Some(fields)
+}
+
+/// Parse a table with exactly the given fields, the same as the strict verification of molecule
+fn parse_table<'a>(data: &'a [u8], fields: &[Field]) -> Option<Vec<&'a [u8]>> {
+    let values = parse_dynvec(data).ok()?;
+    if values.len() != fields.len() {
+        return None;
+    }
+    let valid = values
+        .iter()
+        .zip(fields.iter())
+        .all(|(value, field)| match field {
+            Field::Bytes => BytesReader::verify(value, false).is_ok(),
+            Field::Uint256 => Uint256Reader::verify(value, false).is_ok(),
+            Field::Fee => FeeReader::verify(value, false).is_ok(),
+            Field::TransferItemVec => parse_dynvec(value).map_or(false, |items| {
+                items
+                    .iter()
+                    .all(|item| parse_table(item, &[Field::Bytes, Field::Uint256]).is_some())
+            }),
+        });
+    if valid {
+        Some(values)
+    } else {
+        None
+    }
+}
+
+/// Parse `SUDTArgs`, returns the item id and the fields of the item,
+/// gw-types only knows `SUDTQuery` and `SUDTTransfer`, so the union is parsed here.
+fn parse_args(args: &[u8]) -> Result<(u32, Vec<&[u8]>), GwError> {
+    if args.len() < 4 {
+        return Err(GwError::InvalidData);
+    }
+    let mut item_id = [0u8; 4];
+    item_id.copy_from_slice(&args[..4]);
+    let item_id = u32::from_le_bytes(item_id);
+    let fields = item_fields(item_id).ok_or(GwError::InvalidData)?;
+    let values = parse_table(&args[4..], fields).ok_or(GwError::InvalidData)?;
+    Ok((item_id, values))
+}
+
+fn parse_address(bytes: &[u8]) -> Result<RegistryAddress, GwError> {
+    RegistryAddress::from_slice(BytesReader::new_unchecked(bytes).raw_data())
+}
+
+fn parse_amount(uint256: &[u8]) -> U256 {
+    let mut amount = [0u8; 32];
+    amount.copy_from_slice(uint256);
+    U256::from_le_bytes(amount)
+}
+
+/// Parse the receiver and the amount of a batch transfer item
+fn parse_transfer_item(item: &[u8]) -> Result<(RegistryAddress, U256), GwError> {
+    let fields = parse_dynvec(item).map_err(|_| GwError::InvalidData)?;
+    let to_addr = parse_address(fields[0])?;
+    Ok((to_addr, parse_amount(fields[1])))
+}
+
+/// Load the sender's registry address of the registry id of fee
+fn load_sender_addr<C: GwContext>(ctx: &C, fee: FeeReader) -> Result<RegistryAddress, GwError> {
+    let registry_id: u32 = fee.registry_id().unpack();
+    let from_id = ctx.transaction_context().from_id;
+    let from_script_hash = ctx.sys_get_script_hash_by_account_id(from_id)?;
+    ctx.sys_get_registry_address_by_script_hash(&from_script_hash, registry_id)
+}
+
+/// Pay fee in CKB
+fn pay_fee<C: GwContext>(
+    ctx: &mut C,
+    fee: FeeReader,
+    payer_addr: &RegistryAddress,
+) -> Result<(), GwError> {
+    let fee_amount: u128 = fee.amount().unpack();
+    sudt::pay_fee(ctx, CKB_SUDT_ACCOUNT_ID, payer_addr, U256::from(fee_amount)).map_err(|err| {
+        debug!("pay fee failed");
+        err
+    })
+}
+
+pub fn main<C: GwContext>(ctx: &mut C) -> Result<(), GwError> {
+    let args = ctx.transaction_context().args.clone();
+    let (item_id, fields) = parse_args(&args)?;
+    let sudt_id = ctx.transaction_context().to_id;
+
+    match item_id {
+        MSG_QUERY => {
+            let addr = parse_address(fields[0])?;
+            let balance = sudt::get_balance(ctx, sudt_id, &addr)?;
+            ctx.sys_set_program_return_data(&balance.to_le_bytes())?;
+        }
+        MSG_TRANSFER => {
+            let fee = FeeReader::new_unchecked(fields[2]);
+            let from_addr = load_sender_addr(ctx, fee)?;
+            let to_addr = parse_address(fields[0])?;
+            let amount = parse_amount(fields[1]);
+
+            pay_fee(ctx, fee, &from_addr)?;
+            sudt::transfer(ctx, sudt_id, &from_addr, &to_addr, amount).map_err(|err| {
+                debug!("transfer token failed");
+                err
+            })?;
+        }
+        MSG_APPROVE => {
+            let fee = FeeReader::new_unchecked(fields[2]);
+            let owner_addr = load_sender_addr(ctx, fee)?;
+            let spender_addr = parse_address(fields[0])?;
+            let amount = parse_amount(fields[1]);
+
+            pay_fee(ctx, fee, &owner_addr)?;
+            sudt::approve(ctx, sudt_id, &owner_addr, &spender_addr, amount).map_err(|err| {
+                debug!("approve failed");
+                err
+            })?;
+        }
+        MSG_ALLOWANCE => {
+            let owner_addr = parse_address(fields[0])?;
+            let spender_addr = parse_address(fields[1])?;
+            let allowance = sudt::get_allowance(ctx, sudt_id, &owner_addr, &spender_addr)?;
+            ctx.sys_set_program_return_data(&allowance.to_le_bytes())?;
+        }
+        MSG_TRANSFER_FROM => {
+            // the sender is the spender
+            let fee = FeeReader::new_unchecked(fields[3]);
+            let spender_addr = load_sender_addr(ctx, fee)?;
+            let from_addr = parse_address(fields[0])?;
+            let to_addr = parse_address(fields[1])?;
+            let amount = parse_amount(fields[2]);
+
+            pay_fee(ctx, fee, &spender_addr)?;
+            sudt::transfer_from(ctx, sudt_id, &spender_addr, &from_addr, &to_addr, amount)
+                .map_err(|err| {
+                    debug!("transfer from failed");
+                    err
+                })?;
+        }
+        MSG_BATCH_TRANSFER => {
+            let fee = FeeReader::new_unchecked(fields[1]);
+            let from_addr = load_sender_addr(ctx, fee)?;
+            pay_fee(ctx, fee, &from_addr)?;
+
+            // check all items before updating balances, so the batch either
+            // succeeds or fails as a whole
+            let items = parse_dynvec(fields[0]).map_err(|_| GwError::InvalidData)?;
+            let mut transfers = Vec::with_capacity(items.len());
+            let mut total_amount = U256::zero();
+            for item in items {
+                let (to_addr, amount) = parse_transfer_item(item)?;
+                total_amount = total_amount.checked_add(amount).ok_or_else(|| {
+                    debug!("batch transfer: total amount overflow");
+                    GwError::SUDTAmountOverflow
+                })?;
+                transfers.push((to_addr, amount));
+            }
+            let from_balance = sudt::get_balance(ctx, sudt_id, &from_addr)?;
+            if from_balance < total_amount {
+                debug!("batch transfer: insufficient balance");
+                return Err(GwError::SUDTInsufficientBalance);
+            }
+
+            // transfer, one log per receiver
+            for (to_addr, amount) in transfers {
+                sudt::transfer(ctx, sudt_id, &from_addr, &to_addr, amount).map_err(|err| {
+                    debug!("batch transfer: transfer token failed");
+                    err
+                })?;
+            }
+        }
+        MSG_MINT => {
+            let fee = FeeReader::new_unchecked(fields[2]);
+            let issuer_addr = load_sender_addr(ctx, fee)?;
+            let to_addr = parse_address(fields[0])?;
+            let amount = parse_amount(fields[1]);
+
+            pay_fee(ctx, fee, &issuer_addr)?;
+            sudt::mint(ctx, sudt_id, &issuer_addr, &to_addr, amount).map_err(|err| {
+                debug!("mint failed");
+                err
+            })?;
+        }
+        MSG_BURN => {
+            let fee = FeeReader::new_unchecked(fields[1]);
+            let issuer_addr = load_sender_addr(ctx, fee)?;
+            let amount = parse_amount(fields[0]);
+
+            pay_fee(ctx, fee, &issuer_addr)?;
+            sudt::burn(ctx, sudt_id, &issuer_addr, amount).map_err(|err| {
+                debug!("burn failed");
+                err
+            })?;
+        }
+        _ => return Err(GwError::UnknownArgs),
+    }
+
+    ctx.finalize()
+}
@@ -0,0 +1,89 @@
+//! Layer2 contract errors
+//!
+//! The exit codes are the same as `c/gw_errors.h`,
+//! fatals are errors that can't be recovered by user programs,
+//! errors are the syscall errors caused by user inputs.
+
+use gw_utils::ckb_std::{debug, error::SysError};
+
+/// Error
+///
+/// NOTICE: exit codes are shared with the C contracts, do not change existing codes.
+#[repr(i8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GwError {
+    // data fatals
+    BufferOverflow = 50,
+    InvalidContext = 51,
+    InvalidData = 52,
+    MismatchReturnData = 53,
+    UnknownArgs = 54,
+    InvalidSUDTScript = 55,
+    InvalidCheckPoint = 56,
+    // not found fatals
+    DataCellNotFound = 60,
+    StateKeyNotFound = 61,
+    SignatureCellNotFound = 62,
+    ScriptNotFound = 63,
+    // merkle fatals
+    SMTVerify = 70,
+    SMTFetch = 71,
+    SMTStore = 72,
+    SMTCalculateRoot = 73,
+    // syscall errors
+    DuplicatedScriptHash = 80,
+    UnknownScriptCodeHash = 81,
+    InvalidAccountScript = 82,
+    NotFound = 83,
+    Recover = 84,
+    AccountNotExists = 85,
+    Unimplemented = 86,
+    // sUDT errors
+    SUDTInsufficientBalance = 92,
+    SUDTAmountOverflow = 93,
+    SUDTToAddr = 94,
+    SUDTAccountNotExists = 95,
+    SUDTInsufficientAllowance = 96,
+    SUDTNotIssuer = 97,
+    // registry errors
+    RegistryDuplicateMapping = 101,
+}
+
+impl GwError {
+    /// Convert the return code of a godwoken syscall,
+    /// the generator returns the codes defined above.
+    pub fn from_syscall_code(code: u64) -> Self {
+        use GwError::*;
+        match code {
+            50 => BufferOverflow,
+            52 => InvalidData,
+            54 => UnknownArgs,
+            55 => InvalidSUDTScript,
+            61 => StateKeyNotFound,
+            63 => ScriptNotFound,
+            71 => SMTFetch,
+            72 => SMTStore,
+            80 => DuplicatedScriptHash,
+            81 => UnknownScriptCodeHash,
+            82 => InvalidAccountScript,
+            83 => NotFound,
+            84 => Recover,
+            85 => AccountNotExists,
+            86 => Unimplemented,
+            _ => {
+                debug!("unknown syscall return code: {}", code);
+                InvalidContext
+            }
+        }
+    }
+}
+
+/// Failures of layer1 syscalls mean the context is incomplete
+impl From<SysError> for GwError {
+    fn from(err: SysError) -> Self {
+        match err {
+            SysError::Encoding => Self::InvalidData,
+            _ => Self::InvalidContext,
+        }
+    }
+}
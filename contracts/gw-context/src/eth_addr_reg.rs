@@ -0,0 +1,120 @@
+//! ETH address registry utils, see `c/gw_eth_addr_reg.h`
+//!
+//! The registry maps an ETH address to a godwoken account script hash and vice versa.
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{packed::ScriptReader, prelude::*},
+};
+
+use crate::{
+    context::GwContext,
+    error::GwError,
+    keys::{build_registry_address_to_script_hash_key, build_script_hash_to_registry_address_key},
+    registry_address::RegistryAddress,
+    ALLOWED_CONTRACT_POLYJUICE, ALLOWED_EOA_ETH, ETH_REGISTRY_ACCOUNT_ID,
+};
+
+pub const ETH_ADDRESS_LEN: usize = 20;
+/// rollup_script_hash(32 bytes) | eth_address(20 bytes)
+pub const EOA_SCRIPT_ARGS_LEN: usize = 52;
+/// rollup_script_hash(32 bytes) | creator_account_id(4 bytes) | eth_address(20 bytes)
+pub const CONTRACT_ACCOUNT_SCRIPT_ARGS_LEN: usize = 56;
+
+/// Map `eth_address` to `script_hash` and vice versa,
+/// the old mapping of the address is cleared if `overwrite` is set.
+pub fn update_eth_address_register<C: GwContext>(
+    ctx: &mut C,
+    eth_address: &[u8; ETH_ADDRESS_LEN],
+    script_hash: &[u8; 32],
+    overwrite: bool,
+) -> Result<(), GwError> {
+    if script_hash == &[0u8; 32] {
+        debug!("update eth address register: script hash is zero");
+        return Err(GwError::InvalidData);
+    }
+
+    let addr = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, eth_address.to_vec());
+
+    // check if the address has been registered
+    if let Ok(old_script_hash) = ctx.sys_get_script_hash_by_registry_address(&addr) {
+        if !overwrite {
+            return Err(GwError::RegistryDuplicateMapping);
+        }
+        // clear the old mapping
+        let key = build_script_hash_to_registry_address_key(&old_script_hash);
+        ctx.sys_store(ETH_REGISTRY_ACCOUNT_ID, &key, &[0u8; 32])?;
+    }
+
+    // eth_address -> script_hash
+    let key = build_registry_address_to_script_hash_key(&addr)?;
+    ctx.sys_store(ETH_REGISTRY_ACCOUNT_ID, &key, script_hash)?;
+
+    // script_hash -> eth_address
+    let key = build_script_hash_to_registry_address_key(script_hash);
+    let mut value = [0u8; 32];
+    let addr = addr.to_bytes();
+    value[..addr.len()].copy_from_slice(&addr);
+    ctx.sys_store(ETH_REGISTRY_ACCOUNT_ID, &key, &value)
+}
+
+/// Register an ETH EOA or a Polyjuice contract account by its script hash
+///
+/// The address is read from the account script args,
+/// an account can only be registered once.
+pub fn register_eth_address<C: GwContext>(
+    ctx: &mut C,
+    script_hash: &[u8; 32],
+) -> Result<(), GwError> {
+    let account_id = ctx
+        .sys_get_account_id_by_script_hash(script_hash)
+        .map_err(|_| GwError::AccountNotExists)?;
+    let script = ctx.sys_get_account_script(account_id)?;
+    let script = ScriptReader::from_slice(&script).map_err(|_| GwError::InvalidAccountScript)?;
+    let code_hash = script.code_hash();
+    let args = script.args().raw_data();
+
+    let mut eth_address = [0u8; ETH_ADDRESS_LEN];
+    let config = ctx.rollup_config().clone();
+
+    // ETH EOA
+    let is_eth_eoa = config.allowed_eoa_type_hashes().into_iter().any(|allowed| {
+        let type_: u8 = allowed.type_().into();
+        type_ == ALLOWED_EOA_ETH && allowed.hash().as_slice() == code_hash.as_slice()
+    });
+    if is_eth_eoa {
+        debug!("register eth address: an ETH externally owned account");
+        if args.len() != EOA_SCRIPT_ARGS_LEN {
+            debug!("register eth address: not eth account lock args");
+            return Err(GwError::UnknownArgs);
+        }
+        eth_address.copy_from_slice(&args[32..]);
+        return update_eth_address_register(ctx, &eth_address, script_hash, false);
+    }
+
+    // Polyjuice contract account
+    //
+    // NOTICE: `c/gw_eth_addr_reg.h` iterates `allowed_contract_type_hashes` but reads
+    // the entries from `allowed_eoa_type_hashes`, this port does the same so the
+    // validator agrees with the C generator in challenges.
+    let allowed_eoa_type_hashes = config.allowed_eoa_type_hashes();
+    for i in 0..config.allowed_contract_type_hashes().len() {
+        let allowed = allowed_eoa_type_hashes.get(i).ok_or_else(|| {
+            debug!("register eth address: failed to get Polyjuice code hash");
+            GwError::InvalidData
+        })?;
+        let type_: u8 = allowed.type_().into();
+        if type_ == ALLOWED_CONTRACT_POLYJUICE && allowed.hash().as_slice() == code_hash.as_slice()
+        {
+            debug!("register eth address: a Polyjuice contract account");
+            if args.len() != CONTRACT_ACCOUNT_SCRIPT_ARGS_LEN {
+                debug!("register eth address: not Polyjuice contract script args");
+                return Err(GwError::UnknownArgs);
+            }
+            eth_address.copy_from_slice(&args[36..]);
+            return update_eth_address_register(ctx, &eth_address, script_hash, false);
+        }
+    }
+
+    Err(GwError::UnknownScriptCodeHash)
+}
@@ -0,0 +1,373 @@
+//! Generator context, see `c/generator_utils.h`
+//!
+//! The state is served by the godwoken generator through syscalls.
+
+use alloc::vec;
+use core::arch::asm;
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{
+        bytes::Bytes,
+        packed::{RollupConfig, RollupConfigReader},
+        prelude::*,
+    },
+};
+
+use crate::{
+    context::{increase_sender_nonce, load_sender_nonce, BlockInfo, GwContext, TransactionContext},
+    error::GwError,
+    keys::blake2b_hash,
+    registry_address::RegistryAddress,
+    u256::U256,
+    MAX_BLOCK_INFO_SIZE, MAX_DATA_SIZE, MAX_L2TX_SIZE, MAX_RETURN_DATA_SIZE,
+    MAX_ROLLUP_CONFIG_SIZE, MAX_SCRIPT_SIZE,
+};
+
+/* syscalls */
+const SYS_CREATE: u64 = 3100;
+const SYS_STORE: u64 = 3101;
+const SYS_LOAD: u64 = 3102;
+const SYS_LOAD_ACCOUNT_SCRIPT: u64 = 3105;
+const SYS_SET_RETURN_DATA: u64 = 3201;
+const SYS_STORE_DATA: u64 = 3301;
+const SYS_LOAD_DATA: u64 = 3302;
+const SYS_LOAD_ROLLUP_CONFIG: u64 = 3401;
+const SYS_LOAD_TRANSACTION: u64 = 3402;
+const SYS_LOAD_BLOCKINFO: u64 = 3403;
+const SYS_GET_BLOCK_HASH: u64 = 3404;
+const SYS_PAY_FEE: u64 = 3501;
+const SYS_LOG: u64 = 3502;
+const SYS_SNAPSHOT: u64 = 3701;
+const SYS_REVERT: u64 = 3702;
+
+unsafe fn syscall(a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64, a7: u64) -> u64 {
+    let ret: u64;
+    asm!(
+        "ecall",
+        inlateout("a0") a0 => ret,
+        in("a1") a1,
+        in("a2") a2,
+        in("a3") a3,
+        in("a4") a4,
+        in("a5") a5,
+        in("a7") a7,
+    );
+    ret
+}
+
+fn check_ret(ret: u64) -> Result<(), GwError> {
+    match ret {
+        0 => Ok(()),
+        code => Err(GwError::from_syscall_code(code)),
+    }
+}
+
+/// Load a variable length object into `buf`, returns the full length of the object
+fn load_to_buf(syscall_id: u64, buf: &mut [u8], a2: u64, a3: u64) -> Result<usize, GwError> {
+    let mut len = buf.len() as u64;
+    let ret = unsafe {
+        syscall(
+            buf.as_mut_ptr() as u64,
+            &mut len as *mut u64 as u64,
+            a2,
+            a3,
+            0,
+            0,
+            syscall_id,
+        )
+    };
+    check_ret(ret)?;
+    Ok(len as usize)
+}
+
+pub struct GeneratorContext {
+    transaction_context: TransactionContext,
+    block_info: BlockInfo,
+    rollup_config: RollupConfig,
+    original_sender_nonce: u32,
+}
+
+impl GeneratorContext {
+    pub fn init() -> Result<Self, GwError> {
+        let mut tx_buf = vec![0u8; MAX_L2TX_SIZE];
+        let len = load_to_buf(SYS_LOAD_TRANSACTION, &mut tx_buf, 0, 0)?;
+        if len > MAX_L2TX_SIZE {
+            return Err(GwError::InvalidData);
+        }
+        let transaction_context = TransactionContext::from_slice(&tx_buf[..len])?;
+
+        let mut block_info_buf = [0u8; MAX_BLOCK_INFO_SIZE];
+        let len = load_to_buf(SYS_LOAD_BLOCKINFO, &mut block_info_buf, 0, 0)?;
+        if len > MAX_BLOCK_INFO_SIZE {
+            return Err(GwError::InvalidData);
+        }
+        let block_info = BlockInfo::from_slice(&block_info_buf[..len])?;
+
+        let mut config_buf = vec![0u8; MAX_ROLLUP_CONFIG_SIZE];
+        let len = load_to_buf(SYS_LOAD_ROLLUP_CONFIG, &mut config_buf, 0, 0)?;
+        if len > MAX_ROLLUP_CONFIG_SIZE {
+            debug!("length too long");
+            return Err(GwError::InvalidData);
+        }
+        // accept extra fields appended to RollupConfig
+        if RollupConfigReader::verify(&config_buf[..len], true).is_err() {
+            debug!("rollup config cell data is not RollupConfig format");
+            return Err(GwError::InvalidData);
+        }
+        config_buf.truncate(len);
+        let rollup_config = RollupConfig::new_unchecked(Bytes::from(config_buf));
+
+        let mut ctx = GeneratorContext {
+            transaction_context,
+            block_info,
+            rollup_config,
+            original_sender_nonce: 0,
+        };
+        ctx.original_sender_nonce = load_sender_nonce(&ctx).map_err(|err| {
+            debug!("failed to init original sender nonce");
+            err
+        })?;
+        Ok(ctx)
+    }
+}
+
+impl GwContext for GeneratorContext {
+    fn transaction_context(&self) -> &TransactionContext {
+        &self.transaction_context
+    }
+
+    fn block_info(&self) -> &BlockInfo {
+        &self.block_info
+    }
+
+    fn rollup_config(&self) -> &RollupConfig {
+        &self.rollup_config
+    }
+
+    fn load_raw(&self, raw_key: &[u8; 32]) -> Result<[u8; 32], GwError> {
+        let mut value = [0u8; 32];
+        let ret = unsafe {
+            syscall(
+                raw_key.as_ptr() as u64,
+                value.as_mut_ptr() as u64,
+                0,
+                0,
+                0,
+                0,
+                SYS_LOAD,
+            )
+        };
+        if ret != 0 {
+            debug!("failed to load raw, syscall ret: {}", ret);
+            // Even we load via syscall, the data structure in the bottom is a SMT
+            return Err(GwError::SMTFetch);
+        }
+        Ok(value)
+    }
+
+    fn store_raw(&mut self, raw_key: &[u8; 32], value: &[u8; 32]) -> Result<(), GwError> {
+        let ret = unsafe {
+            syscall(
+                raw_key.as_ptr() as u64,
+                value.as_ptr() as u64,
+                0,
+                0,
+                0,
+                0,
+                SYS_STORE,
+            )
+        };
+        if ret != 0 {
+            debug!("failed to store raw, syscall ret: {}", ret);
+            return Err(GwError::SMTStore);
+        }
+        Ok(())
+    }
+
+    fn sys_set_program_return_data(&mut self, data: &[u8]) -> Result<(), GwError> {
+        if data.len() > MAX_RETURN_DATA_SIZE {
+            debug!("Exceeded max return data size");
+            return Err(GwError::BufferOverflow);
+        }
+        let ret = unsafe {
+            syscall(
+                data.as_ptr() as u64,
+                data.len() as u64,
+                0,
+                0,
+                0,
+                0,
+                SYS_SET_RETURN_DATA,
+            )
+        };
+        check_ret(ret)
+    }
+
+    fn sys_create(&mut self, script: &[u8]) -> Result<u32, GwError> {
+        if script.len() > MAX_SCRIPT_SIZE {
+            return Err(GwError::InvalidAccountScript);
+        }
+        let script_hash = blake2b_hash(script);
+        if self.account_exists_by_script_hash(&script_hash)? {
+            return Err(GwError::DuplicatedScriptHash);
+        }
+        let mut account_id = 0u32;
+        let ret = unsafe {
+            syscall(
+                script.as_ptr() as u64,
+                script.len() as u64,
+                &mut account_id as *mut u32 as u64,
+                0,
+                0,
+                0,
+                SYS_CREATE,
+            )
+        };
+        check_ret(ret)?;
+        Ok(account_id)
+    }
+
+    fn sys_get_account_script(&self, account_id: u32) -> Result<Bytes, GwError> {
+        let script_hash = self.sys_get_script_hash_by_account_id(account_id)?;
+        if script_hash == [0u8; 32] {
+            return Err(GwError::NotFound);
+        }
+        let mut script = [0u8; MAX_SCRIPT_SIZE];
+        let len = load_to_buf(SYS_LOAD_ACCOUNT_SCRIPT, &mut script, 0, account_id as u64)?;
+        if len > MAX_SCRIPT_SIZE {
+            return Err(GwError::InvalidAccountScript);
+        }
+        Ok(Bytes::copy_from_slice(&script[..len]))
+    }
+
+    fn sys_store_data(&mut self, data: &[u8]) -> Result<(), GwError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if data.len() > MAX_DATA_SIZE {
+            debug!("Exceeded max store data size");
+            return Err(GwError::InvalidData);
+        }
+        let ret = unsafe {
+            syscall(
+                data.len() as u64,
+                data.as_ptr() as u64,
+                0,
+                0,
+                0,
+                0,
+                SYS_STORE_DATA,
+            )
+        };
+        check_ret(ret)
+    }
+
+    fn sys_load_data(&self, data_hash: &[u8; 32]) -> Result<Bytes, GwError> {
+        // return not found if data isn't exist in the state tree
+        if !self.data_hash_exists(data_hash)? {
+            return Err(GwError::NotFound);
+        }
+        let mut data = vec![0u8; MAX_DATA_SIZE];
+        let len = load_to_buf(SYS_LOAD_DATA, &mut data, 0, data_hash.as_ptr() as u64)?;
+        if len > MAX_DATA_SIZE {
+            return Err(GwError::BufferOverflow);
+        }
+        data.truncate(len);
+        Ok(Bytes::from(data))
+    }
+
+    fn sys_get_block_hash(&self, number: u64) -> Result<[u8; 32], GwError> {
+        let mut block_hash = [0u8; 32];
+        let ret = unsafe {
+            syscall(
+                block_hash.as_mut_ptr() as u64,
+                number,
+                0,
+                0,
+                0,
+                0,
+                SYS_GET_BLOCK_HASH,
+            )
+        };
+        check_ret(ret)?;
+        Ok(block_hash)
+    }
+
+    fn sys_log(&mut self, account_id: u32, service_flag: u8, data: &[u8]) -> Result<(), GwError> {
+        self.ensure_account_exists(account_id)?;
+        let ret = unsafe {
+            syscall(
+                account_id as u64,
+                service_flag as u64,
+                data.len() as u64,
+                data.as_ptr() as u64,
+                0,
+                0,
+                SYS_LOG,
+            )
+        };
+        check_ret(ret)
+    }
+
+    fn sys_pay_fee(
+        &mut self,
+        payer: &RegistryAddress,
+        sudt_id: u32,
+        amount: U256,
+    ) -> Result<(), GwError> {
+        self.ensure_account_exists(sudt_id)?;
+        let addr = payer.to_bytes();
+        if addr.len() > 32 {
+            debug!("sys_pay_fee: invalid addr len, expect <= 20");
+            return Err(GwError::BufferOverflow);
+        }
+        let amount = amount.to_le_bytes();
+        let ret = unsafe {
+            syscall(
+                addr.as_ptr() as u64,
+                addr.len() as u64,
+                sudt_id as u64,
+                amount.as_ptr() as u64,
+                0,
+                0,
+                SYS_PAY_FEE,
+            )
+        };
+        check_ret(ret)
+    }
+
+    fn sys_snapshot(&mut self) -> Result<u32, GwError> {
+        let mut snapshot = 0u32;
+        let ret = unsafe {
+            syscall(
+                &mut snapshot as *mut u32 as u64,
+                0,
+                0,
+                0,
+                0,
+                0,
+                SYS_SNAPSHOT,
+            )
+        };
+        check_ret(ret)?;
+        Ok(snapshot)
+    }
+
+    fn sys_revert(&mut self, snapshot: u32) -> Result<(), GwError> {
+        let ret = unsafe { syscall(snapshot as u64, 0, 0, 0, 0, 0, SYS_REVERT) };
+        check_ret(ret)
+    }
+
+    fn original_sender_nonce(&self) -> u32 {
+        self.original_sender_nonce
+    }
+
+    fn finalize(&mut self) -> Result<(), GwError> {
+        // update sender nonce
+        increase_sender_nonce(self).map_err(|err| {
+            debug!("failed to update original sender nonce");
+            err
+        })
+    }
+}
@@ -0,0 +1,77 @@
+//! State tree keys, see `c/common.h`
+
+use gw_utils::gw_common::blake2b::new_blake2b;
+
+use crate::{error::GwError, registry_address::RegistryAddress};
+
+pub const ACCOUNT_KV: u8 = 0;
+pub const ACCOUNT_NONCE: u8 = 1;
+pub const ACCOUNT_SCRIPT_HASH: u8 = 2;
+pub const ACCOUNT_SCRIPT_HASH_TO_ID: u8 = 3;
+pub const DATA_HASH_PREFIX: u8 = 4;
+
+pub const REGISTRY_KEY_FLAG_SCRIPT_HASH_TO_NATIVE: u8 = 1;
+pub const REGISTRY_KEY_FLAG_NATIVE_TO_SCRIPT_HASH: u8 = 2;
+
+pub fn blake2b_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+fn build_prefixed_key(id: u32, type_: u8, key: &[u8]) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(&id.to_le_bytes());
+    hasher.update(&[type_]);
+    hasher.update(key);
+    let mut raw_key = [0u8; 32];
+    hasher.finalize(&mut raw_key);
+    raw_key
+}
+
+/// raw key of an account's storage: blake2b(id | ACCOUNT_KV | key)
+pub fn build_account_key(id: u32, key: &[u8]) -> [u8; 32] {
+    build_prefixed_key(id, ACCOUNT_KV, key)
+}
+
+/// raw key of an account's field: id | field_type | zeros
+pub fn build_account_field_key(id: u32, field_type: u8) -> [u8; 32] {
+    let mut raw_key = [0u8; 32];
+    raw_key[..4].copy_from_slice(&id.to_le_bytes());
+    raw_key[4] = field_type;
+    raw_key
+}
+
+pub fn build_script_hash_to_account_id_key(script_hash: &[u8; 32]) -> [u8; 32] {
+    build_prefixed_key(0, ACCOUNT_SCRIPT_HASH_TO_ID, script_hash)
+}
+
+pub fn build_data_hash_key(data_hash: &[u8; 32]) -> [u8; 32] {
+    build_prefixed_key(0, DATA_HASH_PREFIX, data_hash)
+}
+
+/// "reg" | flag(1 byte) | script_hash(32 bytes)
+pub fn build_script_hash_to_registry_address_key(script_hash: &[u8; 32]) -> [u8; 36] {
+    let mut key = [0u8; 36];
+    key[..3].copy_from_slice(b"reg");
+    key[3] = REGISTRY_KEY_FLAG_SCRIPT_HASH_TO_NATIVE;
+    key[4..].copy_from_slice(script_hash);
+    key
+}
+
+/// "reg" | flag(1 byte) | registry_address(28 bytes)
+pub fn build_registry_address_to_script_hash_key(
+    addr: &RegistryAddress,
+) -> Result<[u8; 32], GwError> {
+    let addr = addr.to_bytes();
+    if addr.len() != 28 {
+        return Err(GwError::BufferOverflow);
+    }
+    let mut key = [0u8; 32];
+    key[..3].copy_from_slice(b"reg");
+    key[3] = REGISTRY_KEY_FLAG_NATIVE_TO_SCRIPT_HASH;
+    key[4..].copy_from_slice(&addr);
+    Ok(key)
+}
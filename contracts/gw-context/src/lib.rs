@@ -0,0 +1,61 @@
+//! Godwoken layer2 contract context
+//!
+//! A Rust equivalent of `gw_context_t` in `c/generator_utils.h` and `c/validator_utils.h`.
+//! Layer2 contracts are written against the `GwContext` trait and built twice:
+//!
+//! * with the `generator` feature, syscalls are served by the godwoken generator
+//! * with the `validator` feature, syscalls are verified against the challenge context on layer1
+//!
+//! The state layout and the exit codes are the same as the C headers,
+//! so a Rust contract is a drop-in replacement of the C one.
+
+#![no_std]
+#![allow(clippy::upper_case_acronyms)]
+
+extern crate alloc;
+
+pub use gw_utils;
+
+pub mod context;
+pub mod contracts;
+pub mod error;
+pub mod eth_addr_reg;
+pub mod keys;
+pub mod registry_address;
+pub mod sudt;
+pub mod u256;
+
+#[cfg(feature = "generator")]
+pub mod generator;
+#[cfg(feature = "validator")]
+pub mod validator;
+
+/// Account id of the meta contract
+pub const META_CONTRACT_ACCOUNT_ID: u32 = 0;
+/// Account id of the CKB simple UDT
+pub const CKB_SUDT_ACCOUNT_ID: u32 = 1;
+/// Account id of the ETH address registry
+pub const ETH_REGISTRY_ACCOUNT_ID: u32 = 2;
+
+/// Limits, see `c/gw_def.h`
+pub const MAX_DATA_SIZE: usize = 25 * 1024;
+pub const MAX_RETURN_DATA_SIZE: usize = 128 * 1024;
+pub const MAX_L2TX_ARGS_SIZE: usize = 128 * 1024;
+pub const MAX_L2TX_SIZE: usize = 132 * 1024;
+pub const MAX_SCRIPT_SIZE: usize = 256;
+pub const MAX_SCRIPT_ENTRIES_SIZE: usize = 100;
+pub const MAX_LOAD_DATA_ENTRIES_SIZE: usize = 100;
+pub const MAX_ROLLUP_CONFIG_SIZE: usize = 4 * 1024;
+pub const MAX_BLOCK_INFO_SIZE: usize = 256;
+
+/// Log service flags
+pub const LOG_SUDT_TRANSFER: u8 = 0x0;
+pub const LOG_SUDT_PAY_FEE: u8 = 0x1;
+pub const LOG_SUDT_APPROVE: u8 = 0x4;
+pub const LOG_SUDT_MINT: u8 = 0x5;
+pub const LOG_SUDT_BURN: u8 = 0x6;
+
+/// Types of `RollupConfig.allowed_eoa_type_hashes`
+pub const ALLOWED_EOA_ETH: u8 = 1;
+/// Types of `RollupConfig.allowed_contract_type_hashes`
+pub const ALLOWED_CONTRACT_POLYJUICE: u8 = 3;
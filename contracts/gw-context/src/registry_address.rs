@@ -0,0 +1,56 @@
+//! Registry address, see `c/gw_registry_addr.h`
+//!
+//! format: registry_id(4 bytes) | address len(4 bytes) | address(n bytes)
+
+use alloc::vec::Vec;
+
+use crate::error::GwError;
+
+/// Only addresses no longer than an ETH address are supported for now
+pub const MAX_ADDRESS_LEN: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryAddress {
+    pub reg_id: u32,
+    pub address: Vec<u8>,
+}
+
+impl RegistryAddress {
+    pub fn new(reg_id: u32, address: Vec<u8>) -> Self {
+        RegistryAddress { reg_id, address }
+    }
+
+    /// Parse a registry address, trailing bytes are ignored
+    pub fn from_slice(buf: &[u8]) -> Result<Self, GwError> {
+        if buf.len() < 8 {
+            return Err(GwError::InvalidData);
+        }
+        let mut reg_id = [0u8; 4];
+        reg_id.copy_from_slice(&buf[..4]);
+        let mut addr_len = [0u8; 4];
+        addr_len.copy_from_slice(&buf[4..8]);
+        let addr_len = u32::from_le_bytes(addr_len) as usize;
+        if addr_len > MAX_ADDRESS_LEN {
+            return Err(GwError::BufferOverflow);
+        }
+        if addr_len + 8 > buf.len() {
+            return Err(GwError::InvalidData);
+        }
+        Ok(RegistryAddress {
+            reg_id: u32::from_le_bytes(reg_id),
+            address: buf[8..8 + addr_len].to_vec(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        8 + self.address.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.size());
+        buf.extend_from_slice(&self.reg_id.to_le_bytes());
+        buf.extend_from_slice(&(self.address.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.address);
+        buf
+    }
+}
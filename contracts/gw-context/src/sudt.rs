@@ -0,0 +1,360 @@
+//! Simple UDT utils, see `c/sudt_utils.h`
+//!
+//! The balance key of a registry address is `key_flag(4 bytes) | registry_address`,
+//! the allowance key is `key_flag(4 bytes) | owner_address | spender_address`,
+//! the total supply key is `0xff` * 32.
+//!
+//! A layer2 native token appends the issuer's registry address to the script args:
+//! `rollup_script_hash(32 bytes) | token_id(32 bytes) | issuer_address`,
+//! only the issuer can mint and burn it.
+//! Layer2 contracts should always operate simple UDT through the functions here.
+
+use alloc::vec::Vec;
+
+use gw_utils::{
+    ckb_std::debug,
+    gw_types::{packed::ScriptReader, prelude::*},
+};
+
+use crate::{
+    context::GwContext, error::GwError, registry_address::RegistryAddress, u256::U256,
+    LOG_SUDT_APPROVE, LOG_SUDT_BURN, LOG_SUDT_MINT, LOG_SUDT_PAY_FEE, LOG_SUDT_TRANSFER,
+    MAX_SCRIPT_SIZE,
+};
+
+pub const SUDT_KEY_FLAG_BALANCE: u32 = 1;
+pub const SUDT_KEY_FLAG_ALLOWANCE: u32 = 2;
+pub const SUDT_TOTAL_SUPPLY_KEY: [u8; 32] = [0xff; 32];
+/// rollup_script_hash(32 bytes) | l1_sudt_script_hash(32 bytes)
+pub const SUDT_BRIDGED_SCRIPT_ARGS_LEN: usize = 64;
+
+/// key_flag(4 bytes) | registry_address
+pub fn build_key(key_flag: u32, addr: &RegistryAddress) -> Vec<u8> {
+    let mut key = Vec::with_capacity(4 + addr.size());
+    key.extend_from_slice(&key_flag.to_le_bytes());
+    key.extend_from_slice(&addr.to_bytes());
+    key
+}
+
+/// key_flag(4 bytes) | owner_address | spender_address
+pub fn build_allowance_key(
+    owner_addr: &RegistryAddress,
+    spender_addr: &RegistryAddress,
+) -> Vec<u8> {
+    let mut key = Vec::with_capacity(4 + owner_addr.size() + spender_addr.size());
+    key.extend_from_slice(&SUDT_KEY_FLAG_ALLOWANCE.to_le_bytes());
+    key.extend_from_slice(&owner_addr.to_bytes());
+    key.extend_from_slice(&spender_addr.to_bytes());
+    key
+}
+
+/// from_addr | to_addr | amount(32 bytes)
+fn emit_log<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    from_addr: &RegistryAddress,
+    to_addr: &RegistryAddress,
+    amount: U256,
+    service_flag: u8,
+) -> Result<(), GwError> {
+    let mut data = Vec::with_capacity(from_addr.size() + to_addr.size() + 32);
+    data.extend_from_slice(&from_addr.to_bytes());
+    data.extend_from_slice(&to_addr.to_bytes());
+    data.extend_from_slice(&amount.to_le_bytes());
+    ctx.sys_log(sudt_id, service_flag, &data)
+}
+
+/// Check the account is a layer2 simple UDT
+pub fn verify_sudt_account<C: GwContext>(ctx: &C, sudt_id: u32) -> Result<(), GwError> {
+    let script = ctx.sys_get_account_script(sudt_id)?;
+    let script = ScriptReader::from_slice(&script).map_err(|_| {
+        debug!("load account script: invalid script");
+        GwError::InvalidSUDTScript
+    })?;
+    let l2_sudt_validator_script_type_hash =
+        ctx.rollup_config().l2_sudt_validator_script_type_hash();
+    if script.code_hash().as_slice() != l2_sudt_validator_script_type_hash.as_slice() {
+        return Err(GwError::InvalidSUDTScript);
+    }
+    let hash_type: u8 = script.hash_type().to_entity().into();
+    if hash_type != 1 {
+        return Err(GwError::InvalidSUDTScript);
+    }
+    Ok(())
+}
+
+fn load_balance<C: GwContext>(
+    ctx: &C,
+    sudt_id: u32,
+    addr: &RegistryAddress,
+) -> Result<U256, GwError> {
+    let value = ctx.sys_load(sudt_id, &build_key(SUDT_KEY_FLAG_BALANCE, addr))?;
+    Ok(U256::from_le_bytes(value))
+}
+
+fn store_balance<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    addr: &RegistryAddress,
+    balance: U256,
+) -> Result<(), GwError> {
+    ctx.sys_store(
+        sudt_id,
+        &build_key(SUDT_KEY_FLAG_BALANCE, addr),
+        &balance.to_le_bytes(),
+    )
+}
+
+pub fn get_balance<C: GwContext>(
+    ctx: &C,
+    sudt_id: u32,
+    addr: &RegistryAddress,
+) -> Result<U256, GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+    load_balance(ctx, sudt_id, addr)
+}
+
+fn load_total_supply<C: GwContext>(ctx: &C, sudt_id: u32) -> Result<U256, GwError> {
+    let value = ctx.sys_load(sudt_id, &SUDT_TOTAL_SUPPLY_KEY)?;
+    Ok(U256::from_le_bytes(value))
+}
+
+fn store_total_supply<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    total_supply: U256,
+) -> Result<(), GwError> {
+    ctx.sys_store(sudt_id, &SUDT_TOTAL_SUPPLY_KEY, &total_supply.to_le_bytes())
+}
+
+pub fn get_total_supply<C: GwContext>(ctx: &C, sudt_id: u32) -> Result<U256, GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+    load_total_supply(ctx, sudt_id)
+}
+
+fn transfer_with_flag<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    from_addr: &RegistryAddress,
+    to_addr: &RegistryAddress,
+    amount: U256,
+    service_flag: u8,
+) -> Result<(), GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+
+    // update sender balance
+    let from_balance = load_balance(ctx, sudt_id, from_addr)?;
+    let new_from_balance = from_balance.checked_sub(amount).ok_or_else(|| {
+        debug!("transfer: insufficient balance");
+        GwError::SUDTInsufficientBalance
+    })?;
+    if from_addr == to_addr {
+        debug!("transfer: [warning] transfer to self");
+    }
+    store_balance(ctx, sudt_id, from_addr, new_from_balance)?;
+
+    // update receiver balance
+    let to_balance = load_balance(ctx, sudt_id, to_addr)?;
+    let new_to_balance = to_balance.checked_add(amount).ok_or_else(|| {
+        debug!("transfer: balance overflow");
+        GwError::SUDTAmountOverflow
+    })?;
+    store_balance(ctx, sudt_id, to_addr, new_to_balance)?;
+
+    emit_log(ctx, sudt_id, from_addr, to_addr, amount, service_flag)
+}
+
+pub fn transfer<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    from_addr: &RegistryAddress,
+    to_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    transfer_with_flag(ctx, sudt_id, from_addr, to_addr, amount, LOG_SUDT_TRANSFER)
+}
+
+/// Transfer the fee to the block producer
+pub fn pay_fee<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    from_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    let block_producer = ctx.block_info().block_producer.clone();
+    transfer_with_flag(
+        ctx,
+        sudt_id,
+        from_addr,
+        &block_producer,
+        amount,
+        LOG_SUDT_PAY_FEE,
+    )?;
+    // the syscall emits the event to the runtime, the fee is already paid
+    ctx.sys_pay_fee(from_addr, sudt_id, amount)
+}
+
+fn load_allowance<C: GwContext>(
+    ctx: &C,
+    sudt_id: u32,
+    owner_addr: &RegistryAddress,
+    spender_addr: &RegistryAddress,
+) -> Result<U256, GwError> {
+    let value = ctx.sys_load(sudt_id, &build_allowance_key(owner_addr, spender_addr))?;
+    Ok(U256::from_le_bytes(value))
+}
+
+fn store_allowance<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    owner_addr: &RegistryAddress,
+    spender_addr: &RegistryAddress,
+    allowance: U256,
+) -> Result<(), GwError> {
+    ctx.sys_store(
+        sudt_id,
+        &build_allowance_key(owner_addr, spender_addr),
+        &allowance.to_le_bytes(),
+    )
+}
+
+pub fn get_allowance<C: GwContext>(
+    ctx: &C,
+    sudt_id: u32,
+    owner_addr: &RegistryAddress,
+    spender_addr: &RegistryAddress,
+) -> Result<U256, GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+    load_allowance(ctx, sudt_id, owner_addr, spender_addr)
+}
+
+/// Set the amount the spender can transfer from the owner,
+/// the previous allowance is overwritten
+pub fn approve<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    owner_addr: &RegistryAddress,
+    spender_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+    store_allowance(ctx, sudt_id, owner_addr, spender_addr, amount)?;
+    emit_log(
+        ctx,
+        sudt_id,
+        owner_addr,
+        spender_addr,
+        amount,
+        LOG_SUDT_APPROVE,
+    )
+}
+
+/// Transfer from the owner by the spender, the allowance is decreased
+pub fn transfer_from<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    spender_addr: &RegistryAddress,
+    from_addr: &RegistryAddress,
+    to_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    let allowance = get_allowance(ctx, sudt_id, from_addr, spender_addr)?;
+    let new_allowance = allowance.checked_sub(amount).ok_or_else(|| {
+        debug!("transfer from: insufficient allowance");
+        GwError::SUDTInsufficientAllowance
+    })?;
+    store_allowance(ctx, sudt_id, from_addr, spender_addr, new_allowance)?;
+    transfer_with_flag(ctx, sudt_id, from_addr, to_addr, amount, LOG_SUDT_TRANSFER)
+}
+
+/// Load the issuer of a layer2 native token from the account script args,
+/// a token bridged from layer1 has no issuer.
+pub fn get_issuer<C: GwContext>(ctx: &C, sudt_id: u32) -> Result<RegistryAddress, GwError> {
+    verify_sudt_account(ctx, sudt_id)?;
+    let script = ctx.sys_get_account_script(sudt_id)?;
+    if script.len() > MAX_SCRIPT_SIZE {
+        return Err(GwError::InvalidSUDTScript);
+    }
+    let script = ScriptReader::from_slice(&script).map_err(|_| GwError::InvalidSUDTScript)?;
+    let args = script.args().raw_data();
+    if args.len() <= SUDT_BRIDGED_SCRIPT_ARGS_LEN {
+        debug!("get issuer: the token is bridged from layer1");
+        return Err(GwError::SUDTNotIssuer);
+    }
+    let issuer_addr = RegistryAddress::from_slice(&args[SUDT_BRIDGED_SCRIPT_ARGS_LEN..])?;
+    if issuer_addr.size() != args.len() - SUDT_BRIDGED_SCRIPT_ARGS_LEN {
+        debug!("get issuer: invalid issuer address");
+        return Err(GwError::InvalidSUDTScript);
+    }
+    Ok(issuer_addr)
+}
+
+fn check_issuer<C: GwContext>(
+    ctx: &C,
+    sudt_id: u32,
+    addr: &RegistryAddress,
+) -> Result<(), GwError> {
+    if &get_issuer(ctx, sudt_id)? != addr {
+        debug!("check issuer: the sender is not the issuer");
+        return Err(GwError::SUDTNotIssuer);
+    }
+    Ok(())
+}
+
+/// Mint layer2 native tokens by the issuer, the total supply is increased
+pub fn mint<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    issuer_addr: &RegistryAddress,
+    to_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    check_issuer(ctx, sudt_id, issuer_addr)?;
+
+    // update total supply
+    let total_supply = load_total_supply(ctx, sudt_id)?;
+    let new_total_supply = total_supply.checked_add(amount).ok_or_else(|| {
+        debug!("mint: total supply overflow");
+        GwError::SUDTAmountOverflow
+    })?;
+    store_total_supply(ctx, sudt_id, new_total_supply)?;
+
+    // update receiver balance, it can't overflow since the total supply doesn't
+    let to_balance = load_balance(ctx, sudt_id, to_addr)?;
+    let (new_to_balance, _) = to_balance.overflowing_add(amount);
+    store_balance(ctx, sudt_id, to_addr, new_to_balance)?;
+
+    emit_log(ctx, sudt_id, issuer_addr, to_addr, amount, LOG_SUDT_MINT)
+}
+
+/// Burn layer2 native tokens from the issuer's balance, the total supply is decreased
+pub fn burn<C: GwContext>(
+    ctx: &mut C,
+    sudt_id: u32,
+    issuer_addr: &RegistryAddress,
+    amount: U256,
+) -> Result<(), GwError> {
+    check_issuer(ctx, sudt_id, issuer_addr)?;
+
+    // update issuer balance
+    let balance = load_balance(ctx, sudt_id, issuer_addr)?;
+    let new_balance = balance.checked_sub(amount).ok_or_else(|| {
+        debug!("burn: insufficient balance");
+        GwError::SUDTInsufficientBalance
+    })?;
+    store_balance(ctx, sudt_id, issuer_addr, new_balance)?;
+
+    // update total supply, it can't underflow since it's the sum of balances
+    let total_supply = load_total_supply(ctx, sudt_id)?;
+    let (new_total_supply, _) = total_supply.overflowing_sub(amount);
+    store_total_supply(ctx, sudt_id, new_total_supply)?;
+
+    emit_log(
+        ctx,
+        sudt_id,
+        issuer_addr,
+        issuer_addr,
+        amount,
+        LOG_SUDT_BURN,
+    )
+}
@@ -0,0 +1,89 @@
+//! Little endian 256 bits unsigned integer, the same layout as `c/uint256.h`
+
+use core::cmp::Ordering;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const fn zero() -> Self {
+        U256([0u64; 4])
+    }
+
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(buf);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Returns the sum and whether an overflow happened, the sum wraps around on overflow
+    pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let mut limbs = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, overflow_a) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, overflow_b) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = overflow_a || overflow_b;
+        }
+        (U256(limbs), carry)
+    }
+
+    /// Returns the difference and whether an underflow happened,
+    /// the difference wraps around on underflow
+    pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let mut limbs = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, overflow_a) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, overflow_b) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = overflow_a || overflow_b;
+        }
+        (U256(limbs), borrow)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+            (diff, false) => Some(diff),
+            (_, true) => None,
+        }
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,711 @@
+//! Validator context, see `c/validator_utils.h`
+//!
+//! The contract is executed on layer1 to verify a challenged transaction,
+//! the state is loaded from the `CCTransactionWitness` of the challenge cell
+//! and is verified against the checkpoints of the challenged block.
+//!
+//! A withdrawal target whose sender is a contract account is cancelled by an
+//! authorization call of the withdrawal, the `CCTransactionWitness` of the call
+//! is in `witness_args.input_type`, it is executed on the state before the
+//! withdrawal and must not change it.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use gw_state::{
+    ckb_smt::smt::{Pair, Tree},
+    constants::GW_MAX_KV_PAIRS,
+    kv_state::KVState,
+};
+use gw_utils::{
+    cells::{
+        lock_cells::find_challenge_cell,
+        rollup::{load_rollup_config, search_rollup_cell, search_rollup_state},
+    },
+    ckb_std::{
+        ckb_constants::Source,
+        ckb_types::prelude::Unpack as CKBUnpack,
+        debug,
+        high_level::{
+            load_cell_data, load_cell_data_hash, load_cell_lock_hash, load_script,
+            load_witness_args, QueryIter,
+        },
+    },
+    gw_common::{
+        merkle_utils::{calculate_state_checkpoint, ckb_merkle_leaf_hash, CBMTMerkleProof},
+        state::State,
+        H256,
+    },
+    gw_types::{
+        bytes::Bytes,
+        core::ScriptHashType,
+        packed::{
+            CCTransactionWitness, CCTransactionWitnessReader, ChallengeTarget, RawL2Block,
+            RollupConfig, ScriptReader,
+        },
+        prelude::*,
+    },
+};
+
+use crate::{
+    context::{increase_sender_nonce, load_sender_nonce, BlockInfo, GwContext, TransactionContext},
+    error::GwError,
+    keys::{
+        blake2b_hash, build_account_field_key, build_data_hash_key,
+        build_script_hash_to_account_id_key, ACCOUNT_NONCE, ACCOUNT_SCRIPT_HASH,
+    },
+    registry_address::RegistryAddress,
+    u256::U256,
+    MAX_DATA_SIZE, MAX_LOAD_DATA_ENTRIES_SIZE, MAX_RETURN_DATA_SIZE, MAX_SCRIPT_ENTRIES_SIZE,
+    MAX_SCRIPT_SIZE,
+};
+
+const TARGET_TYPE_TRANSACTION: u8 = 0;
+const TARGET_TYPE_WITHDRAWAL: u8 = 2;
+const MAX_KV_PROOF_SIZE: usize = 32768;
+const MAX_GET_BLOCK_HASH_DEPTH: u64 = 256;
+
+/// An owner_lock_hash(32 bytes) is put in the current cell's data to prevent
+/// others consume the cell, checks an input cell is unlocked by the owner.
+fn check_owner_lock_hash() -> Result<(), GwError> {
+    let data = load_cell_data(0, Source::GroupInput).map_err(|err| {
+        debug!(
+            "check owner lock hash failed, can't load cell data, err: {:?}",
+            err
+        );
+        GwError::InvalidContext
+    })?;
+    if data.len() != 32 {
+        debug!(
+            "check owner lock hash failed, invalid data len: {}",
+            data.len()
+        );
+        return Err(GwError::InvalidData);
+    }
+    if QueryIter::new(load_cell_lock_hash, Source::Input).any(|lock_hash| lock_hash[..] == data[..])
+    {
+        return Ok(());
+    }
+    debug!("check owner lock hash failed: owner cell not found");
+    Err(GwError::InvalidContext)
+}
+
+fn load_rollup_script_hash() -> Result<[u8; 32], GwError> {
+    let script = load_script().map_err(|err| {
+        debug!(
+            "load rollup script hash: failed to load script, err: {:?}",
+            err
+        );
+        GwError::InvalidContext
+    })?;
+    let args: Bytes = script.args().unpack();
+    if args.len() < 32 {
+        debug!("current script is less than 32 bytes");
+        return Err(GwError::InvalidData);
+    }
+    let mut rollup_script_hash = [0u8; 32];
+    rollup_script_hash.copy_from_slice(&args[..32]);
+    Ok(rollup_script_hash)
+}
+
+/// Check that an account script is allowed to be created
+fn check_account_script_is_allowed(
+    rollup_script_hash: &[u8; 32],
+    script: &[u8],
+    config: &RollupConfig,
+) -> Result<(), GwError> {
+    let script = ScriptReader::from_slice(script).map_err(|_| {
+        debug!("[check account script] script invalid format");
+        GwError::InvalidAccountScript
+    })?;
+    if script.as_slice().len() > MAX_SCRIPT_SIZE {
+        debug!("[check account script] script size is too large");
+        return Err(GwError::InvalidAccountScript);
+    }
+    if script.hash_type().to_entity() != ScriptHashType::Type.into() {
+        debug!("[check account script] hash type is not 'type'");
+        return Err(GwError::UnknownScriptCodeHash);
+    }
+    let args = script.args().raw_data();
+    if args.len() < 32 {
+        debug!("[check account script] script args is less than 32 bytes");
+        return Err(GwError::InvalidAccountScript);
+    }
+    if &args[..32] != rollup_script_hash {
+        debug!("[check account script] args is not start with rollup_script_hash");
+        return Err(GwError::InvalidAccountScript);
+    }
+
+    let code_hash = script.code_hash();
+    let is_allowed = config
+        .allowed_eoa_type_hashes()
+        .into_iter()
+        .chain(config.allowed_contract_type_hashes().into_iter())
+        .any(|allowed| allowed.hash().as_slice() == code_hash.as_slice());
+    if !is_allowed {
+        debug!("[check account script] unknown code_hash");
+        return Err(GwError::UnknownScriptCodeHash);
+    }
+    Ok(())
+}
+
+/// The challenge context loaded from layer1 cells
+pub struct ValidatorWitness {
+    rollup_script_hash: [u8; 32],
+    rollup_config: RollupConfig,
+    block_merkle_root: [u8; 32],
+    target: ChallengeTarget,
+    target_type: u8,
+    witness: CCTransactionWitness,
+    kv_state_proof: Bytes,
+}
+
+impl ValidatorWitness {
+    /// Returns None if the rollup cell isn't in the inputs,
+    /// which means we are not in a challenge, the cell can be unlocked without execution.
+    pub fn load() -> Result<Option<Self>, GwError> {
+        check_owner_lock_hash().map_err(|err| {
+            debug!("gw_context_init: not found owner lock");
+            err
+        })?;
+
+        let rollup_script_hash = load_rollup_script_hash()?;
+        if search_rollup_cell(&rollup_script_hash, Source::Input).is_none() {
+            debug!(
+                "gw_context_init: can't found rollup cell from inputs, we are not in a challenge"
+            );
+            return Ok(None);
+        }
+        let global_state = search_rollup_state(&rollup_script_hash, Source::Input)?
+            .ok_or(GwError::InvalidContext)?;
+        let block_merkle_root: [u8; 32] = global_state.block().merkle_root().unpack();
+        let rollup_config_hash: [u8; 32] = global_state.rollup_config_hash().unpack();
+        let rollup_config = load_rollup_config(&rollup_config_hash).map_err(|err| {
+            debug!("failed to load rollup config, err: {:?}", err);
+            GwError::InvalidData
+        })?;
+
+        // load challenge cell
+        let challenge_cell =
+            find_challenge_cell(&rollup_script_hash.into(), &rollup_config, Source::Input)
+                .map_err(|err| {
+                    debug!("failed to load challenge lock args, err: {:?}", err);
+                    GwError::InvalidData
+                })?
+                .ok_or(GwError::InvalidContext)?;
+        let target = challenge_cell.args.target();
+        let target_type: u8 = target.target_type().into();
+        if target_type != TARGET_TYPE_TRANSACTION && target_type != TARGET_TYPE_WITHDRAWAL {
+            debug!("challenge target type is invalid");
+            return Err(GwError::InvalidData);
+        }
+
+        // read CCTransactionWitness from witness_args.lock,
+        // or witness_args.input_type for an authorization call of a withdrawal
+        let witness_args = load_witness_args(challenge_cell.index, Source::Input)?;
+        let witness = if target_type == TARGET_TYPE_WITHDRAWAL {
+            witness_args.input_type()
+        } else {
+            witness_args.lock()
+        };
+        let witness: Bytes = witness
+            .to_opt()
+            .ok_or_else(|| {
+                debug!("WitnessArgs has no CCTransactionWitness");
+                GwError::InvalidData
+            })?
+            .unpack();
+        if CCTransactionWitnessReader::verify(&witness, false).is_err() {
+            debug!("witness is not CCTransactionWitness");
+            return Err(GwError::InvalidData);
+        }
+        let witness = CCTransactionWitness::new_unchecked(witness);
+        let kv_state_proof: Bytes = witness.kv_state_proof().unpack();
+        if kv_state_proof.len() > MAX_KV_PROOF_SIZE {
+            debug!("kv state proof is too long");
+            return Err(GwError::BufferOverflow);
+        }
+
+        Ok(Some(ValidatorWitness {
+            rollup_script_hash,
+            rollup_config,
+            block_merkle_root,
+            target,
+            target_type,
+            witness,
+            kv_state_proof,
+        }))
+    }
+}
+
+/// Load the state checkpoints before and after the challenged transaction
+fn load_tx_checkpoints(
+    raw_block: &RawL2Block,
+    tx_index: u32,
+) -> Result<([u8; 32], [u8; 32]), GwError> {
+    let withdrawal_count: u32 = raw_block.submit_withdrawals().withdrawal_count().unpack();
+    let checkpoint_list = raw_block.state_checkpoint_list();
+    let prev_tx_checkpoint = match tx_index.checked_sub(1) {
+        Some(prev_index) => checkpoint_list
+            .get((withdrawal_count + prev_index) as usize)
+            .ok_or_else(|| {
+                debug!("invalid prev tx checkpoint");
+                GwError::InvalidData
+            })?
+            .unpack(),
+        None => raw_block
+            .submit_transactions()
+            .prev_state_checkpoint()
+            .unpack(),
+    };
+    let post_tx_checkpoint = checkpoint_list
+        .get((withdrawal_count + tx_index) as usize)
+        .ok_or_else(|| {
+            debug!("invalid post tx checkpoint");
+            GwError::InvalidData
+        })?
+        .unpack();
+    Ok((prev_tx_checkpoint, post_tx_checkpoint))
+}
+
+/// Load the state checkpoint before the withdrawal
+fn load_withdrawal_checkpoint(
+    raw_block: &RawL2Block,
+    withdrawal_index: u32,
+) -> Result<[u8; 32], GwError> {
+    let withdrawal_count: u32 = raw_block.submit_withdrawals().withdrawal_count().unpack();
+    if withdrawal_index >= withdrawal_count {
+        debug!("invalid withdrawal index");
+        return Err(GwError::InvalidData);
+    }
+    match withdrawal_index.checked_sub(1) {
+        Some(prev_index) => Ok(raw_block
+            .state_checkpoint_list()
+            .get(prev_index as usize)
+            .ok_or_else(|| {
+                debug!("invalid prev withdrawal checkpoint");
+                GwError::InvalidData
+            })?
+            .unpack()),
+        None => {
+            let prev_account = raw_block.prev_account();
+            Ok(calculate_state_checkpoint(
+                &prev_account.merkle_root().unpack(),
+                prev_account.count().unpack(),
+            )
+            .into())
+        }
+    }
+}
+
+/// Load block hashes of the recent blocks and verify them against the block merkle root
+fn load_block_hashes(
+    witness: &ValidatorWitness,
+    challenged_block_number: u64,
+) -> Result<BTreeMap<u64, [u8; 32]>, GwError> {
+    let max_block_number = challenged_block_number.saturating_sub(1);
+    let min_block_number = challenged_block_number.saturating_sub(MAX_GET_BLOCK_HASH_DEPTH);
+
+    let mut block_hashes = BTreeMap::new();
+    let mut buf = [Pair::default(); MAX_GET_BLOCK_HASH_DEPTH as usize];
+    let mut tree = Tree::new(&mut buf);
+    for entry in witness.witness.block_hashes().into_iter() {
+        let number: u64 = entry.number().unpack();
+        if number < min_block_number || number > max_block_number {
+            debug!("invalid number in block hashes");
+            return Err(GwError::InvalidData);
+        }
+        let hash: [u8; 32] = entry.hash().unpack();
+        tree.update(&RawL2Block::compute_smt_key(number), &hash)
+            .map_err(|err| {
+                debug!("failed to insert into smt, err: {}", err);
+                GwError::SMTStore
+            })?;
+        block_hashes.insert(number, hash);
+    }
+    if !block_hashes.is_empty() {
+        let proof: Bytes = witness.witness.block_hashes_proof().unpack();
+        tree.normalize();
+        tree.verify(&witness.block_merkle_root, &proof)
+            .map_err(|err| {
+                debug!(
+                    "failed to verify block merkle root and block hashes, err: {}",
+                    err
+                );
+                GwError::SMTVerify
+            })?;
+    }
+    Ok(block_hashes)
+}
+
+pub struct ValidatorContext<'a> {
+    witness: &'a ValidatorWitness,
+    transaction_context: TransactionContext,
+    block_info: BlockInfo,
+    kv_state: KVState<'a>,
+    block_hashes: BTreeMap<u64, [u8; 32]>,
+    scripts: Vec<([u8; 32], Bytes)>,
+    load_data: BTreeMap<[u8; 32], Bytes>,
+    return_data: Bytes,
+    prev_tx_checkpoint: [u8; 32],
+    post_tx_checkpoint: [u8; 32],
+    original_sender_nonce: u32,
+}
+
+impl<'a> ValidatorContext<'a> {
+    pub fn new(
+        witness: &'a ValidatorWitness,
+        tree_buffer: &'a mut [Pair],
+    ) -> Result<Self, GwError> {
+        let cc_witness = &witness.witness;
+        let raw_block = cc_witness.raw_l2block();
+
+        // verify challenged block
+        if raw_block.hash() != witness.target.block_hash().as_slice() {
+            debug!("block hash mismatched with challenged block hash");
+            return Err(GwError::InvalidData);
+        }
+
+        // verify tx merkle proof, an authorization call isn't in the block
+        let tx_index: u32 = witness.target.target_index().unpack();
+        let l2tx = cc_witness.l2tx();
+        if witness.target_type == TARGET_TYPE_TRANSACTION {
+            let tx_proof = cc_witness.tx_proof();
+            if tx_proof.indices().len() != 1 {
+                debug!(
+                    "[verify tx proof] more than one leaf, len {}",
+                    tx_proof.indices().len()
+                );
+                return Err(GwError::InvalidData);
+            }
+            let tx_witness_root: H256 = raw_block.submit_transactions().tx_witness_root().unpack();
+            let proof =
+                CBMTMerkleProof::new(tx_proof.indices().unpack(), tx_proof.lemmas().unpack());
+            let leaf = ckb_merkle_leaf_hash(tx_index, &l2tx.witness_hash().into());
+            if !proof.verify(&tx_witness_root, &[leaf]) {
+                debug!("failed to verify tx witness root");
+                return Err(GwError::SMTVerify);
+            }
+        }
+
+        let transaction_context =
+            TransactionContext::from_slice(l2tx.raw().as_slice()).map_err(|err| {
+                debug!("parse l2 transaction failed");
+                err
+            })?;
+        let block_info = BlockInfo {
+            number: raw_block.number().unpack(),
+            timestamp: raw_block.timestamp().unpack(),
+            block_producer: RegistryAddress::from_slice(&raw_block.block_producer().raw_data())?,
+        };
+        let block_hashes = load_block_hashes(witness, block_info.number)?;
+
+        // load kv state
+        let kv_state = cc_witness.kv_state();
+        if kv_state.len() > GW_MAX_KV_PAIRS {
+            debug!("too many key/value pair");
+            return Err(GwError::InvalidData);
+        }
+        let account_count: u32 = cc_witness.account_count().unpack();
+        let kv_state = KVState::build(
+            tree_buffer,
+            kv_state.as_reader(),
+            &witness.kv_state_proof,
+            account_count,
+            None,
+        )
+        .map_err(|err| {
+            debug!("failed to insert smt kv pair, err: {:?}", err);
+            GwError::SMTStore
+        })?;
+
+        let (prev_tx_checkpoint, post_tx_checkpoint) =
+            if witness.target_type == TARGET_TYPE_WITHDRAWAL {
+                // the authorization call must not change the state
+                let checkpoint = load_withdrawal_checkpoint(&raw_block, tx_index)?;
+                (checkpoint, checkpoint)
+            } else {
+                load_tx_checkpoints(&raw_block, tx_index)?
+            };
+
+        // load scripts
+        let scripts = cc_witness.scripts();
+        if scripts.len() > MAX_SCRIPT_ENTRIES_SIZE {
+            debug!("script size is exceeded maximum");
+            return Err(GwError::BufferOverflow);
+        }
+        let scripts = scripts
+            .into_iter()
+            .map(|script| {
+                if script.as_slice().len() > MAX_SCRIPT_SIZE {
+                    debug!("invalid script entry format");
+                    return Err(GwError::InvalidData);
+                }
+                let script = script.as_bytes();
+                Ok((blake2b_hash(&script), script))
+            })
+            .collect::<Result<Vec<_>, GwError>>()?;
+
+        // load data
+        let load_data = cc_witness.load_data();
+        if load_data.len() > MAX_LOAD_DATA_ENTRIES_SIZE {
+            debug!("load data size is exceeded maximum");
+            return Err(GwError::BufferOverflow);
+        }
+        let load_data = load_data
+            .into_iter()
+            .map(|data| {
+                let data: Bytes = data.unpack();
+                if data.len() > MAX_DATA_SIZE {
+                    debug!("load data too long");
+                    return Err(GwError::InvalidData);
+                }
+                Ok((blake2b_hash(&data), data))
+            })
+            .collect::<Result<BTreeMap<_, _>, GwError>>()?;
+
+        let mut ctx = ValidatorContext {
+            witness,
+            transaction_context,
+            block_info,
+            kv_state,
+            block_hashes,
+            scripts,
+            load_data,
+            return_data: Bytes::new(),
+            prev_tx_checkpoint,
+            post_tx_checkpoint,
+            original_sender_nonce: 0,
+        };
+
+        // verify kv_state merkle proof
+        ctx.verify_checkpoint(&ctx.prev_tx_checkpoint)
+            .map_err(|err| {
+                debug!("failed to merkle verify prev tx checkpoint");
+                err
+            })?;
+
+        ctx.original_sender_nonce = load_sender_nonce(&ctx).map_err(|err| {
+            debug!("failed to init original sender nonce");
+            err
+        })?;
+        Ok(ctx)
+    }
+
+    fn verify_checkpoint(&self, checkpoint: &[u8; 32]) -> Result<(), GwError> {
+        let root = self.kv_state.calculate_root().map_err(|err| {
+            debug!("failed to calculate kv state root, err: {:?}", err);
+            GwError::SMTCalculateRoot
+        })?;
+        let account_count = self
+            .kv_state
+            .get_account_count()
+            .map_err(|_| GwError::SMTFetch)?;
+        let proof_checkpoint: [u8; 32] = calculate_state_checkpoint(&root, account_count).into();
+        if &proof_checkpoint != checkpoint {
+            return Err(GwError::InvalidCheckPoint);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> GwContext for ValidatorContext<'a> {
+    fn transaction_context(&self) -> &TransactionContext {
+        &self.transaction_context
+    }
+
+    fn block_info(&self) -> &BlockInfo {
+        &self.block_info
+    }
+
+    fn rollup_config(&self) -> &RollupConfig {
+        &self.witness.rollup_config
+    }
+
+    fn load_raw(&self, raw_key: &[u8; 32]) -> Result<[u8; 32], GwError> {
+        let value = self.kv_state.get_raw(&(*raw_key).into()).map_err(|err| {
+            debug!("failed to load raw, err: {:?}", err);
+            GwError::SMTFetch
+        })?;
+        Ok(value.into())
+    }
+
+    fn store_raw(&mut self, raw_key: &[u8; 32], value: &[u8; 32]) -> Result<(), GwError> {
+        self.kv_state
+            .update_raw((*raw_key).into(), (*value).into())
+            .map_err(|err| {
+                debug!("failed to store raw, err: {:?}", err);
+                GwError::SMTStore
+            })
+    }
+
+    fn sys_set_program_return_data(&mut self, data: &[u8]) -> Result<(), GwError> {
+        if data.len() > MAX_RETURN_DATA_SIZE {
+            debug!("Exceeded max return data size");
+            return Err(GwError::BufferOverflow);
+        }
+        self.return_data = Bytes::copy_from_slice(data);
+        Ok(())
+    }
+
+    fn sys_create(&mut self, script: &[u8]) -> Result<u32, GwError> {
+        // return failure if scripts slots is full
+        if self.scripts.len() >= MAX_SCRIPT_ENTRIES_SIZE {
+            debug!("[sys_create] script slots is full");
+            return Err(GwError::BufferOverflow);
+        }
+        check_account_script_is_allowed(
+            &self.witness.rollup_script_hash,
+            script,
+            &self.witness.rollup_config,
+        )
+        .map_err(|err| {
+            debug!("[sys_create] reject invalid account script");
+            err
+        })?;
+
+        let script_hash = blake2b_hash(script);
+        if self.account_exists_by_script_hash(&script_hash)? {
+            return Err(GwError::DuplicatedScriptHash);
+        }
+
+        let id = self
+            .kv_state
+            .get_account_count()
+            .map_err(|_| GwError::SMTFetch)?;
+        // init account nonce
+        self.store_raw(&build_account_field_key(id, ACCOUNT_NONCE), &[0u8; 32])?;
+        // init account script hash
+        self.store_raw(
+            &build_account_field_key(id, ACCOUNT_SCRIPT_HASH),
+            &script_hash,
+        )?;
+        // init script hash -> account_id, id(4 bytes) | exists flag(1 byte)
+        let mut value = [0u8; 32];
+        value[..4].copy_from_slice(&id.to_le_bytes());
+        value[4] = 1;
+        self.store_raw(&build_script_hash_to_account_id_key(&script_hash), &value)?;
+
+        self.scripts
+            .push((script_hash, Bytes::copy_from_slice(script)));
+        self.kv_state
+            .set_account_count(id + 1)
+            .map_err(|_| GwError::SMTStore)?;
+        Ok(id)
+    }
+
+    fn sys_get_account_script(&self, account_id: u32) -> Result<Bytes, GwError> {
+        let script_hash = self.sys_get_script_hash_by_account_id(account_id)?;
+        if script_hash == [0u8; 32] {
+            debug!("account script_hash is zero, which means account isn't exist");
+            return Err(GwError::AccountNotExists);
+        }
+        self.scripts
+            .iter()
+            .find(|(hash, _script)| hash == &script_hash)
+            .map(|(_hash, script)| script.clone())
+            .ok_or_else(|| {
+                debug!(
+                    "account script_hash exist, but we can't found, we miss the necessary context"
+                );
+                GwError::ScriptNotFound
+            })
+    }
+
+    fn sys_store_data(&mut self, data: &[u8]) -> Result<(), GwError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        if data.len() > MAX_DATA_SIZE {
+            debug!("Exceeded max store data size");
+            return Err(GwError::InvalidData);
+        }
+        // In validator, we do not need to actually store data.
+        // We only need to update the data_hash in the state tree
+        let data_hash = blake2b_hash(data);
+        let mut value = [0u8; 32];
+        value[..4].copy_from_slice(&1u32.to_le_bytes());
+        self.store_raw(&build_data_hash_key(&data_hash), &value)
+    }
+
+    fn sys_load_data(&self, data_hash: &[u8; 32]) -> Result<Bytes, GwError> {
+        // return not found if data isn't exist in the state tree
+        if !self.data_hash_exists(data_hash)? {
+            return Err(GwError::NotFound);
+        }
+        // try load data from witness
+        if let Some(data) = self.load_data.get(data_hash) {
+            return Ok(data.clone());
+        }
+        // then from the cell deps
+        let index = QueryIter::new(load_cell_data_hash, Source::CellDep)
+            .position(|hash| &hash == data_hash)
+            .ok_or_else(|| {
+                debug!("not found cell data by data hash");
+                GwError::DataCellNotFound
+            })?;
+        let data = load_cell_data(index, Source::CellDep).map_err(|_| {
+            debug!("load cell data failed");
+            GwError::DataCellNotFound
+        })?;
+        Ok(Bytes::from(data))
+    }
+
+    fn sys_get_block_hash(&self, number: u64) -> Result<[u8; 32], GwError> {
+        self.block_hashes.get(&number).cloned().ok_or_else(|| {
+            debug!(
+                "sys_get_block_hash: block hash of {} isn't in the context",
+                number
+            );
+            GwError::SMTFetch
+        })
+    }
+
+    fn sys_log(&mut self, account_id: u32, _service_flag: u8, _data: &[u8]) -> Result<(), GwError> {
+        // do nothing
+        self.ensure_account_exists(account_id)
+    }
+
+    fn sys_pay_fee(
+        &mut self,
+        _payer: &RegistryAddress,
+        sudt_id: u32,
+        _amount: U256,
+    ) -> Result<(), GwError> {
+        // do nothing
+        self.ensure_account_exists(sudt_id)
+    }
+
+    fn sys_snapshot(&mut self) -> Result<u32, GwError> {
+        Err(GwError::Unimplemented)
+    }
+
+    fn sys_revert(&mut self, _snapshot: u32) -> Result<(), GwError> {
+        Err(GwError::Unimplemented)
+    }
+
+    fn original_sender_nonce(&self) -> u32 {
+        self.original_sender_nonce
+    }
+
+    fn finalize(&mut self) -> Result<(), GwError> {
+        // update sender nonce, an authorization call isn't a transaction
+        if self.witness.target_type == TARGET_TYPE_TRANSACTION {
+            increase_sender_nonce(self).map_err(|err| {
+                debug!("failed to update original sender nonce");
+                err
+            })?;
+        }
+
+        let return_data_hash: [u8; 32] = self.witness.witness.return_data_hash().unpack();
+        if blake2b_hash(&self.return_data) != return_data_hash {
+            debug!("return data hash not match");
+            return Err(GwError::MismatchReturnData);
+        }
+
+        self.verify_checkpoint(&self.post_tx_checkpoint)
+            .map_err(|err| {
+                debug!("failed to merkle verify post tx checkpoint");
+                err
+            })
+    }
+}
@@ -1,10 +1,11 @@
 //! Lock cells
 
+use super::rollup::load_allowed_stake_assets;
 use super::types::{
-    BurnCell, CellValue, ChallengeCell, CustodianCell, DepositRequestCell, StakeCell,
-    WithdrawalCell,
+    BurnCell, CellValue, ChallengeCell, CustodianCell, DepositRecipient, DepositRequestCell,
+    ForcedWithdrawalCell, StakeCell, WithdrawalCell,
 };
-use crate::error::Error;
+use crate::{error::Error, mol_ext::table_extra_field};
 use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
@@ -15,11 +16,15 @@ use ckb_std::{
         load_cell_type_hash, QueryIter,
     },
 };
-use gw_common::{CKB_SUDT_SCRIPT_ARGS, H256};
+use gw_common::{registry_address::RegistryAddress, CKB_SUDT_SCRIPT_ARGS, H256};
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
-    packed::{Byte32, Byte32Reader, DepositLockArgs, RollupConfig, StakeLockArgs},
+    packed::{
+        Byte32, Byte32Reader, BytesReader, DepositLockArgs, RawL2Transaction,
+        RawL2TransactionReader, RollupConfig, StakeLockArgs, WithdrawalRequest,
+        WithdrawalRequestReader,
+    },
     prelude::*,
 };
 
@@ -41,12 +46,12 @@ fn fetch_sudt_script_hash(
     }
 }
 
-/// used in filter_map
-fn extract_args_from_lock<ArgsType: Entity>(
+/// return the lock_args after the rollup_type_hash, or none if the lock isn't the lock script
+fn extract_raw_args_from_lock(
     lock: &crate::ckb_std::ckb_types::packed::Script,
     rollup_type_hash: &H256,
     lock_script_type_hash: &Byte32,
-) -> Option<Result<ArgsType, Error>> {
+) -> Option<Bytes> {
     let lock_args: Bytes = lock.args().unpack();
     let is_lock = lock_args.len() > 32
         && &lock_args[..32] == rollup_type_hash.as_slice()
@@ -57,15 +62,41 @@ fn extract_args_from_lock<ArgsType: Entity>(
     if !is_lock {
         return None;
     }
+    Some(lock_args.slice(32..))
+}
+
+/// used in filter_map
+fn extract_args_from_lock<ArgsType: Entity>(
+    lock: &crate::ckb_std::ckb_types::packed::Script,
+    rollup_type_hash: &H256,
+    lock_script_type_hash: &Byte32,
+) -> Option<Result<ArgsType, Error>> {
+    let raw_args = extract_raw_args_from_lock(lock, rollup_type_hash, lock_script_type_hash)?;
 
     // parse the remaining lock_args
-    let raw_args = lock_args[32..].to_vec();
     Some(ArgsType::from_slice(&raw_args).map_err(|_err| {
         debug!("Fail to extract args, lock args parsing err");
         Error::Encoding
     }))
 }
 
+/// used in filter_map, accept extra fields, e.g. `recipient` of DepositLockArgs
+///
+/// Only deposit and custodian locks carry extra fields.
+fn extract_compatible_args_from_lock<ArgsType: Entity>(
+    lock: &crate::ckb_std::ckb_types::packed::Script,
+    rollup_type_hash: &H256,
+    lock_script_type_hash: &Byte32,
+) -> Option<Result<ArgsType, Error>> {
+    let raw_args = extract_raw_args_from_lock(lock, rollup_type_hash, lock_script_type_hash)?;
+
+    // parse the remaining lock_args
+    Some(ArgsType::from_compatible_slice(&raw_args).map_err(|_err| {
+        debug!("Fail to extract args, lock args parsing err");
+        Error::Encoding
+    }))
+}
+
 /// fetch capacity and SUDT value of a cell
 pub fn fetch_capacity_and_sudt_value(
     config: &RollupConfig,
@@ -99,6 +130,7 @@ pub fn collect_stake_cells(
     config: &RollupConfig,
     source: Source,
 ) -> Result<Vec<StakeCell>, Error> {
+    let allowed_stake_assets = load_allowed_stake_assets(config)?;
     let iter = QueryIter::new(load_cell_lock, source)
         .enumerate()
         .filter_map(|(index, lock)| -> Option<Result<StakeCell, _>> {
@@ -115,16 +147,16 @@ pub fn collect_stake_cells(
                 Ok(value) => value,
                 Err(err) => return Some(Err(err)),
             };
-            // we only accept CKB as staking assets for now
-            if value.sudt_script_hash != CKB_SUDT_SCRIPT_ARGS.into() || value.amount != 0 {
-                debug!("found a stake cell with simple UDT");
+            // accept CKB and the simple UDT assets allowed by the rollup config
+            let is_allowed_asset = value.sudt_script_hash == CKB_SUDT_SCRIPT_ARGS.into()
+                || allowed_stake_assets
+                    .iter()
+                    .any(|asset| asset.sudt_script_hash == value.sudt_script_hash);
+            if !is_allowed_asset {
+                debug!("found a stake cell with unsupported simple UDT");
                 return Some(Err(Error::InvalidStakeCell));
             }
-            let cell = StakeCell {
-                index,
-                args,
-                capacity: value.capacity,
-            };
+            let cell = StakeCell { index, args, value };
             Some(Ok(cell))
         });
     // reject if found multiple stake cells
@@ -241,7 +273,7 @@ pub fn collect_custodian_locks(
     QueryIter::new(load_cell_lock, source)
         .enumerate()
         .filter_map(|(index, lock)| {
-            let args = match extract_args_from_lock(
+            let args = match extract_compatible_args_from_lock(
                 &lock,
                 rollup_type_hash,
                 &config.custodian_script_type_hash(),
@@ -260,6 +292,93 @@ pub fn collect_custodian_locks(
         .collect::<Result<_, Error>>()
 }
 
+/// A deposit lock cell without type script carries a forced withdrawal request in the data
+fn load_forced_withdrawal_request(
+    index: usize,
+    source: Source,
+) -> Result<Option<WithdrawalRequest>, Error> {
+    if load_cell_type(index, source)?.is_some() {
+        return Ok(None);
+    }
+    let data = load_cell_data(index, source)?;
+    if data.is_empty() || WithdrawalRequestReader::verify(&data, false).is_err() {
+        return Ok(None);
+    }
+    Ok(Some(WithdrawalRequest::new_unchecked(data.into())))
+}
+
+/// Extra fields appended to DepositLockArgs after `registry_id`
+mod deposit_lock_args_extra_field {
+    /// recipient: DepositRecipientOpt
+    pub const RECIPIENT: usize = 0;
+    /// call: RawL2Transaction
+    pub const CALL: usize = 1;
+}
+
+/// union DepositRecipient {
+///     Uint32, // account id
+///     Bytes,  // registry_id | address len | address
+/// }
+fn parse_deposit_recipient(args: &DepositLockArgs) -> Result<Option<DepositRecipient>, Error> {
+    const ACCOUNT_ID: u32 = 0;
+    const REGISTRY_ADDRESS: u32 = 1;
+
+    let data = match table_extra_field(
+        args.as_slice(),
+        DepositLockArgs::FIELD_COUNT,
+        deposit_lock_args_extra_field::RECIPIENT,
+    ) {
+        // an empty option, e.g. a deposit only carries the `call` field
+        Some(data) if data.is_empty() => return Ok(None),
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    if data.len() < 4 {
+        debug!("Invalid encoding of deposit recipient");
+        return Err(Error::Encoding);
+    }
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&data[..4]);
+    let item = &data[4..];
+    match u32::from_le_bytes(buf) {
+        ACCOUNT_ID if item.len() == 4 => {
+            buf.copy_from_slice(item);
+            Ok(Some(DepositRecipient::AccountId(u32::from_le_bytes(buf))))
+        }
+        REGISTRY_ADDRESS => {
+            let address = match BytesReader::from_slice(item) {
+                Ok(bytes) => RegistryAddress::from_slice(bytes.raw_data()),
+                Err(_) => None,
+            };
+            match address {
+                Some(address) => Ok(Some(DepositRecipient::RegistryAddress(address))),
+                None => {
+                    debug!("Invalid encoding of deposit recipient registry address");
+                    Err(Error::Encoding)
+                }
+            }
+        }
+        _ => {
+            debug!("Invalid encoding of deposit recipient");
+            Err(Error::Encoding)
+        }
+    }
+}
+
+/// A malformed call is dropped instead of failing the deposit, see `DepositRequestCell::call`
+fn parse_deposit_call(args: &DepositLockArgs) -> Option<RawL2Transaction> {
+    let data = table_extra_field(
+        args.as_slice(),
+        DepositLockArgs::FIELD_COUNT,
+        deposit_lock_args_extra_field::CALL,
+    )?;
+    if RawL2TransactionReader::verify(data, false).is_err() {
+        debug!("Invalid encoding of deposit call, drop it");
+        return None;
+    }
+    Some(RawL2Transaction::new_unchecked(Bytes::from(data.to_vec())))
+}
+
 pub fn collect_deposit_locks(
     rollup_type_hash: &H256,
     config: &RollupConfig,
@@ -268,7 +387,7 @@ pub fn collect_deposit_locks(
     QueryIter::new(load_cell_lock, source)
         .enumerate()
         .filter_map(|(index, lock)| {
-            let args: DepositLockArgs = match extract_args_from_lock(
+            let args: DepositLockArgs = match extract_compatible_args_from_lock(
                 &lock,
                 rollup_type_hash,
                 &config.deposit_script_type_hash(),
@@ -277,10 +396,21 @@ pub fn collect_deposit_locks(
                 Some(Err(err)) => return Some(Err(err)),
                 None => return None,
             };
+            // skip forced withdrawal cells
+            match load_forced_withdrawal_request(index, source) {
+                Ok(Some(_)) => return None,
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
             let value = match fetch_capacity_and_sudt_value(config, index, source) {
                 Ok(value) => value,
                 Err(err) => return Some(Err(err)),
             };
+            let recipient = match parse_deposit_recipient(&args) {
+                Ok(recipient) => recipient,
+                Err(err) => return Some(Err(err)),
+            };
+            let call = parse_deposit_call(&args);
             let account_script = args.layer2_lock();
             let account_script_hash = account_script.hash().into();
             let cell = DepositRequestCell {
@@ -289,6 +419,49 @@ pub fn collect_deposit_locks(
                 value,
                 account_script,
                 account_script_hash,
+                recipient,
+                call,
+            };
+            Some(Ok(cell))
+        })
+        .collect::<Result<_, Error>>()
+}
+
+pub fn collect_forced_withdrawal_cells(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+    source: Source,
+) -> Result<Vec<ForcedWithdrawalCell>, Error> {
+    QueryIter::new(load_cell_lock, source)
+        .enumerate()
+        .filter_map(|(index, lock)| {
+            let raw_args = extract_raw_args_from_lock(
+                &lock,
+                rollup_type_hash,
+                &config.deposit_script_type_hash(),
+            )?;
+            // skip deposit cells, their args may carry extra fields
+            let request = match load_forced_withdrawal_request(index, source) {
+                Ok(Some(request)) => request,
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            };
+            let args = match DepositLockArgs::from_slice(&raw_args) {
+                Ok(args) => args,
+                Err(_err) => {
+                    debug!("Fail to extract args, lock args parsing err");
+                    return Some(Err(Error::Encoding));
+                }
+            };
+            let value = match fetch_capacity_and_sudt_value(config, index, source) {
+                Ok(value) => value,
+                Err(err) => return Some(Err(err)),
+            };
+            let cell = ForcedWithdrawalCell {
+                index,
+                args,
+                value,
+                request,
             };
             Some(Ok(cell))
         })
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     debug,
@@ -5,14 +6,17 @@ use ckb_std::{
     syscalls::{load_witness, SysError},
 };
 use gw_types::{
+    bytes::Bytes,
     packed::{
-        GlobalState, GlobalStateReader, GlobalStateV0, GlobalStateV0Reader, RollupActionReader,
-        RollupConfig, RollupConfigReader, WitnessArgsReader,
+        Byte32VecReader, BytesReader, GlobalState, GlobalStateReader, GlobalStateV0,
+        GlobalStateV0Reader, L2BlockReader, RollupActionReader, RollupConfig, RollupConfigReader,
+        RollupSubmitBlockReader, WitnessArgsReader,
     },
     prelude::*,
 };
 
-use crate::error::Error;
+use super::types::StakeAsset;
+use crate::{error::Error, mol_ext::table_extra_field};
 
 /// 524_288 we choose this value because it is smaller than the MAX_BLOCK_BYTES which is 597K
 pub const MAX_ROLLUP_WITNESS_SIZE: usize = 1 << 19;
@@ -30,7 +34,8 @@ fn search_rollup_config_cell(rollup_config_hash: &[u8; 32]) -> Option<usize> {
 pub fn load_rollup_config(rollup_config_hash: &[u8; 32]) -> Result<RollupConfig, Error> {
     let index = search_rollup_config_cell(rollup_config_hash).ok_or(Error::RollupConfigNotFound)?;
     let data = load_cell_data(index, Source::CellDep)?;
-    match RollupConfigReader::verify(&data, false) {
+    // accept extra fields, see `config_extra_field`
+    match RollupConfigReader::verify(&data, true) {
         Ok(_) => Ok(RollupConfig::new_unchecked(data.into())),
         Err(_) => {
             debug!("Invalid encoding of RollupConfig");
@@ -39,6 +44,141 @@ pub fn load_rollup_config(rollup_config_hash: &[u8; 32]) -> Result<RollupConfig,
     }
 }
 
+/// Fields appended to RollupConfig after `allowed_contract_type_hashes`,
+/// gw-types doesn't know them yet, so they are read from the raw table.
+///
+/// The fields are positional, a config setting a field must also set the fields before it,
+/// so each field has an encoding meaning "not configured".
+pub mod config_extra_field {
+    /// allowed_stake_assets: StakeAssetVec, empty for CKB only
+    pub const ALLOWED_STAKE_ASSETS: usize = 0;
+    /// governance_lock_hash: Byte32, zero hash disables RollupUpdateConfig
+    pub const GOVERNANCE_LOCK_HASH: usize = 1;
+    /// forced_withdrawal_timeout: Uint64, zero disables forced withdrawals
+    pub const FORCED_WITHDRAWAL_TIMEOUT: usize = 2;
+    /// eip712_domain_version: byte
+    pub const EIP712_DOMAIN_VERSION: usize = 3;
+    /// emergency_exit_timeout: Uint64
+    pub const EMERGENCY_EXIT_TIMEOUT: usize = 4;
+}
+
+/// Returns the raw extra field of RollupConfig, or None if the config doesn't contain it
+pub fn rollup_config_extra_field(config: &RollupConfig, index: usize) -> Option<Bytes> {
+    table_extra_field(config.as_slice(), RollupConfig::FIELD_COUNT, index)
+        .map(Bytes::copy_from_slice)
+}
+
+/// Returns the lock hash which authorizes RollupUpdateConfig,
+/// or None if the rollup config can't be updated, i.e. the field is absent or a zero hash
+pub fn load_governance_lock_hash(config: &RollupConfig) -> Result<Option<[u8; 32]>, Error> {
+    let data = match rollup_config_extra_field(config, config_extra_field::GOVERNANCE_LOCK_HASH) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    if data.len() != 32 {
+        debug!("Invalid encoding of governance lock hash");
+        return Err(Error::Encoding);
+    }
+    let mut lock_hash = [0u8; 32];
+    lock_hash.copy_from_slice(&data);
+    if lock_hash == [0u8; 32] {
+        return Ok(None);
+    }
+    Ok(Some(lock_hash))
+}
+
+/// Returns the relative since after which a forced withdrawal cell can halt the rollup,
+/// or None if forced withdrawals are disabled, i.e. the field is absent or zero
+pub fn load_forced_withdrawal_timeout(config: &RollupConfig) -> Result<Option<u64>, Error> {
+    let data =
+        match rollup_config_extra_field(config, config_extra_field::FORCED_WITHDRAWAL_TIMEOUT) {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+    if data.len() != 8 {
+        debug!("Invalid encoding of forced withdrawal timeout");
+        return Err(Error::Encoding);
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data);
+    match u64::from_le_bytes(buf) {
+        0 => Ok(None),
+        timeout => Ok(Some(timeout)),
+    }
+}
+
+/// Returns the version of the EIP-712 domain signed by layer2 accounts,
+/// rollups without the field keep the version 0 domain
+pub fn load_eip712_domain_version(config: &RollupConfig) -> Result<u8, Error> {
+    let data = match rollup_config_extra_field(config, config_extra_field::EIP712_DOMAIN_VERSION) {
+        Some(data) => data,
+        None => return Ok(0),
+    };
+    if data.len() != 1 {
+        debug!("Invalid encoding of EIP-712 domain version");
+        return Err(Error::Encoding);
+    }
+    Ok(data[0])
+}
+
+/// Returns the milliseconds after the tip block timestamp, after which a halting rollup
+/// can enter emergency mode, or None if emergency exits are disabled
+pub fn load_emergency_exit_timeout(config: &RollupConfig) -> Result<Option<u64>, Error> {
+    let data = match rollup_config_extra_field(config, config_extra_field::EMERGENCY_EXIT_TIMEOUT) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    if data.len() != 8 {
+        debug!("Invalid encoding of emergency exit timeout");
+        return Err(Error::Encoding);
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data);
+    Ok(Some(u64::from_le_bytes(buf)))
+}
+
+/// struct StakeAsset {
+///     sudt_script_hash: Byte32,
+///     minimum_amount: Uint128,
+/// }
+/// vector StakeAssetVec <StakeAsset>;
+///
+/// Returns the sUDT assets accepted by stake cells besides CKB
+pub fn load_allowed_stake_assets(config: &RollupConfig) -> Result<Vec<StakeAsset>, Error> {
+    const STAKE_ASSET_SIZE: usize = 32 + 16;
+
+    let data = match rollup_config_extra_field(config, config_extra_field::ALLOWED_STAKE_ASSETS) {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+    if data.len() < 4 {
+        debug!("Invalid encoding of allowed stake assets");
+        return Err(Error::Encoding);
+    }
+    let mut count_buf = [0u8; 4];
+    count_buf.copy_from_slice(&data[..4]);
+    let count = u32::from_le_bytes(count_buf) as usize;
+    if data.len() != 4 + count * STAKE_ASSET_SIZE {
+        debug!("Invalid encoding of allowed stake assets");
+        return Err(Error::Encoding);
+    }
+
+    let assets = data[4..]
+        .chunks_exact(STAKE_ASSET_SIZE)
+        .map(|raw| {
+            let mut sudt_script_hash = [0u8; 32];
+            sudt_script_hash.copy_from_slice(&raw[..32]);
+            let mut amount_buf = [0u8; 16];
+            amount_buf.copy_from_slice(&raw[32..]);
+            StakeAsset {
+                sudt_script_hash: sudt_script_hash.into(),
+                minimum_amount: u128::from_le_bytes(amount_buf),
+            }
+        })
+        .collect();
+    Ok(assets)
+}
+
 pub fn search_rollup_state(
     rollup_type_hash: &[u8; 32],
     source: Source,
@@ -63,11 +203,11 @@ pub fn search_rollup_state(
     }
 }
 
-pub fn parse_rollup_action(
+fn load_rollup_action_data(
     buf: &mut [u8; MAX_ROLLUP_WITNESS_SIZE],
     index: usize,
     source: Source,
-) -> Result<RollupActionReader, Error> {
+) -> Result<&[u8], Error> {
     let loaded_len = load_witness(buf, 0, index, source)?;
     debug!("load rollup witness, loaded len: {}", loaded_len);
 
@@ -79,9 +219,130 @@ pub fn parse_rollup_action(
         debug!("WitnessArgs#output_type is none");
         Error::Encoding
     })?;
-    let action = RollupActionReader::from_slice(output.raw_data()).map_err(|_err| {
+    Ok(output.raw_data())
+}
+
+pub fn parse_rollup_action(
+    buf: &mut [u8; MAX_ROLLUP_WITNESS_SIZE],
+    index: usize,
+    source: Source,
+) -> Result<RollupActionReader, Error> {
+    let data = load_rollup_action_data(buf, index, source)?;
+    verify_rollup_action(data)
+}
+
+/// Extra fields of RollupSubmitBlock: following_blocks
+const ROLLUP_SUBMIT_BLOCK_EXTRA_FIELD_COUNT: usize = 1;
+
+/// Verify the RollupAction strictly, only RollupSubmitBlock may carry extra fields,
+/// e.g. RollupSubmitBlock#following_blocks, its known fields are still verified strictly.
+fn verify_rollup_action(data: &[u8]) -> Result<RollupActionReader, Error> {
+    if let Ok(action) = RollupActionReader::from_slice(data) {
+        return Ok(action);
+    }
+
+    // union header: item id
+    if data.len() < 4 || read_u32(data) != ROLLUP_SUBMIT_BLOCK_ITEM_ID {
         debug!("output is not a valid RollupActionReader");
+        return Err(Error::Encoding);
+    }
+    let args = RollupSubmitBlockReader::from_compatible_slice(&data[4..]).map_err(|_err| {
+        debug!("output is not a valid RollupSubmitBlock");
         Error::Encoding
     })?;
+    if args.field_count()
+        > RollupSubmitBlockReader::FIELD_COUNT + ROLLUP_SUBMIT_BLOCK_EXTRA_FIELD_COUNT
+        || L2BlockReader::verify(args.block().as_slice(), false).is_err()
+        || Byte32VecReader::verify(args.reverted_block_hashes().as_slice(), false).is_err()
+        || BytesReader::verify(args.reverted_block_proof().as_slice(), false).is_err()
+    {
+        debug!("output is not a valid RollupSubmitBlock");
+        return Err(Error::Encoding);
+    }
+    Ok(RollupActionReader::new_unchecked(data))
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&data[..4]);
+    u32::from_le_bytes(buf)
+}
+
+pub const ROLLUP_SUBMIT_BLOCK_ITEM_ID: u32 = 0;
+pub const ROLLUP_UPDATE_CONFIG_ITEM_ID: u32 = 4;
+pub const ROLLUP_FORCE_HALT_ITEM_ID: u32 = 5;
+pub const ROLLUP_REBALANCE_CUSTODIANS_ITEM_ID: u32 = 6;
+pub const ROLLUP_ENTER_EMERGENCY_ITEM_ID: u32 = 7;
+pub const ROLLUP_EMERGENCY_EXIT_ITEM_ID: u32 = 8;
+
+/// `GlobalState.status` after RollupEnterEmergency, gw-types only knows running and halting.
+/// There is no way back to running, only RollupEmergencyExit is accepted.
+pub const ROLLUP_STATUS_EMERGENCY: u8 = 2;
+
+pub enum RollupActionKind<'a> {
+    /// actions known by gw-types: RollupSubmitBlock, RollupEnterChallenge,
+    /// RollupCancelChallenge, RollupRevert
+    Action(RollupActionReader<'a>),
+    /// change the rollup config, the new config is referenced by the post global state
+    UpdateConfig,
+    /// halt the rollup, a forced withdrawal request isn't included in time,
+    /// the raw RollupForceHalt table
+    ForceHalt(&'a [u8]),
+    /// merge or split finalized custodian cells
+    RebalanceCustodians,
+    /// enter emergency mode, the raw RollupEnterEmergency table
+    EnterEmergency(&'a [u8]),
+    /// claim layer2 balances in emergency mode, the raw RollupEmergencyExit table
+    EmergencyExit(&'a [u8]),
+}
+
+/// union RollupAction {
+///     RollupSubmitBlock,
+///     RollupEnterChallenge,
+///     RollupCancelChallenge,
+///     RollupRevert,
+///     RollupUpdateConfig,
+///     RollupForceHalt,
+///     RollupRebalanceCustodians,
+///     RollupEnterEmergency,
+///     RollupEmergencyExit,
+/// }
+/// table RollupUpdateConfig {}
+/// table RollupRebalanceCustodians {}
+///
+/// Actions after RollupRevert are not part of gw-types yet, parse them manually.
+pub fn parse_rollup_action_kind(
+    buf: &mut [u8; MAX_ROLLUP_WITNESS_SIZE],
+    index: usize,
+    source: Source,
+) -> Result<RollupActionKind, Error> {
+    let data = load_rollup_action_data(buf, index, source)?;
+    if let Ok(action) = verify_rollup_action(data) {
+        return Ok(RollupActionKind::Action(action));
+    }
+
+    // union header: item id
+    if data.len() < 4 {
+        debug!("output is not a valid RollupAction");
+        return Err(Error::Encoding);
+    }
+    // table header: total size, accept extra fields
+    let table = &data[4..];
+    if table.len() < 4 || read_u32(table) as usize != table.len() {
+        debug!("output is not a valid RollupAction table");
+        return Err(Error::Encoding);
+    }
+
+    let action = match read_u32(data) {
+        ROLLUP_UPDATE_CONFIG_ITEM_ID => RollupActionKind::UpdateConfig,
+        ROLLUP_FORCE_HALT_ITEM_ID => RollupActionKind::ForceHalt(table),
+        ROLLUP_REBALANCE_CUSTODIANS_ITEM_ID => RollupActionKind::RebalanceCustodians,
+        ROLLUP_ENTER_EMERGENCY_ITEM_ID => RollupActionKind::EnterEmergency(table),
+        ROLLUP_EMERGENCY_EXIT_ITEM_ID => RollupActionKind::EmergencyExit(table),
+        _ => {
+            debug!("output is not a valid RollupAction");
+            return Err(Error::Encoding);
+        }
+    };
     Ok(action)
 }
@@ -1,9 +1,9 @@
 //! Cell types
 
-use crate::gw_common::sparse_merkle_tree::H256;
+use crate::gw_common::{registry_address::RegistryAddress, sparse_merkle_tree::H256};
 use crate::gw_types::packed::{
-    ChallengeLockArgs, CustodianLockArgs, DepositLockArgs, Script, StakeLockArgs,
-    WithdrawalLockArgs,
+    ChallengeLockArgs, CustodianLockArgs, DepositLockArgs, RawL2Transaction, Script, StakeLockArgs,
+    WithdrawalLockArgs, WithdrawalRequest,
 };
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -27,6 +27,27 @@ pub struct DepositRequestCell {
     pub value: CellValue,
     pub account_script: Script,
     pub account_script_hash: H256,
+    /// `recipient` extra field of the args
+    pub recipient: Option<DepositRecipient>,
+    /// `call` extra field of the args, executed from the account created by the deposit
+    /// right after the deposit mint
+    pub call: Option<RawL2Transaction>,
+}
+
+/// An existing layer2 account credited by a deposit instead of the `layer2_lock` account
+#[derive(Clone)]
+pub enum DepositRecipient {
+    AccountId(u32),
+    RegistryAddress(RegistryAddress),
+}
+
+/// A deposit lock cell carries a signed withdrawal request,
+/// the rollup must include the request in time.
+pub struct ForcedWithdrawalCell {
+    pub index: usize,
+    pub args: DepositLockArgs,
+    pub value: CellValue,
+    pub request: WithdrawalRequest,
 }
 
 #[derive(Debug)]
@@ -39,7 +60,14 @@ pub struct CustodianCell {
 pub struct StakeCell {
     pub index: usize,
     pub args: StakeLockArgs,
-    pub capacity: u64,
+    pub value: CellValue,
+}
+
+/// sUDT asset accepted by stake cells, configured in RollupConfig
+#[derive(Debug, Clone)]
+pub struct StakeAsset {
+    pub sudt_script_hash: H256,
+    pub minimum_amount: u128,
 }
 
 pub struct ChallengeCell {
@@ -28,6 +28,8 @@ pub fn search_lock_hash(owner_lock_hash: &[u8; 32], source: Source) -> Option<us
     QueryIter::new(load_cell_lock_hash, source).position(|lock_hash| &lock_hash == owner_lock_hash)
 }
 
+/// Layer2 native tokens append the issuer address to the args,
+/// so they are never resolved from a layer1 sUDT script hash
 pub fn build_l2_sudt_script(
     rollup_script_hash: &H256,
     config: &RollupConfig,
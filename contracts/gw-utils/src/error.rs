@@ -1,11 +1,32 @@
 //! godwoken validator errors
+//!
+//! Exit codes are limited to i8, so every error has a stable exit code and
+//! belongs to a namespace. A failing branch can attach a sub code to the
+//! error, the detailed code `namespace | exit code | sub code` is written to
+//! the debug syscall by `Error::report` when the script exits.
 
-use ckb_std::error::SysError;
+use ckb_std::{error::SysError, syscalls::debug};
 use gw_common::{error::Error as CommonError, smt::Error as SMTError};
 
-/// Error
-#[repr(i8)]
-pub enum Error {
+macro_rules! define_error_codes {
+    ($($(#[$attr:meta])* $name:ident = $code:expr,)*) => {
+        /// Exit codes
+        ///
+        /// NOTICE: exit codes are part of the protocol, do not change existing codes.
+        #[repr(i8)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrorCode {
+            $($(#[$attr])* $name = $code,)*
+        }
+
+        #[allow(non_upper_case_globals)]
+        impl Error {
+            $(pub const $name: Self = Self::new(ErrorCode::$name);)*
+        }
+    };
+}
+
+define_error_codes! {
     IndexOutOfBound = 1,
     ItemMissing = 2,
     LengthNotEnough = 3,
@@ -24,6 +45,7 @@ pub enum Error {
     InsufficientAmount = 15,
     InsufficientInputFinalizedAssets = 16,
     InsufficientOutputFinalizedAssets = 17,
+    /// SMT and SMT store errors, the sub code tells them apart, see `smt_sub_code`
     SMTKeyMissing = 18,
     InvalidStateCheckpoint = 19,
     InvalidBlock = 20,
@@ -52,6 +74,147 @@ pub enum Error {
     DuplicatedScriptHash = 42,
     RegistryAddressNotFound = 43,
     DuplicatedRegistryAddress = 44,
+    /// unknown syscall error, the sub code is the syscall return code
+    SysUnknown = 45,
+}
+
+/// Error
+///
+/// An exit code and the sub code of the failing branch, 0 if the branch doesn't attach one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error {
+    code: ErrorCode,
+    sub_code: u16,
+}
+
+/// Error namespace
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorNamespace {
+    Syscall = 1,
+    Encoding = 2,
+    SMT = 3,
+    Custodian = 4,
+    Stake = 5,
+    Challenge = 6,
+    Signature = 7,
+    Rollup = 8,
+    Account = 9,
+}
+
+impl ErrorNamespace {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Syscall => "syscall",
+            Self::Encoding => "encoding",
+            Self::SMT => "smt",
+            Self::Custodian => "custodian",
+            Self::Stake => "stake",
+            Self::Challenge => "challenge",
+            Self::Signature => "signature",
+            Self::Rollup => "rollup",
+            Self::Account => "account",
+        }
+    }
+}
+
+impl Error {
+    pub const fn new(code: ErrorCode) -> Self {
+        Error { code, sub_code: 0 }
+    }
+
+    pub fn namespace(&self) -> ErrorNamespace {
+        use ErrorCode::*;
+        match self.code {
+            IndexOutOfBound | ItemMissing | LengthNotEnough | SysUnknown => ErrorNamespace::Syscall,
+            Encoding | InvalidArgs | InvalidSince | InvalidOutput | ProofNotFound => {
+                ErrorNamespace::Encoding
+            }
+            MerkleProof | SMTKeyMissing | InvalidStateCheckpoint => ErrorNamespace::SMT,
+            AmountOverflow
+            | InsufficientAmount
+            | InsufficientInputFinalizedAssets
+            | InsufficientOutputFinalizedAssets
+            | InvalidDepositCell
+            | InvalidWithdrawalCell
+            | InvalidCustodianCell
+            | InvalidSUDTCell
+            | InvalidWithdrawalRequest => ErrorNamespace::Custodian,
+            InvalidStakeCellUnlock | InvalidStakeCell => ErrorNamespace::Stake,
+            InvalidChallengeCell
+            | InvalidRevertedBlocks
+            | InvalidChallengeReward
+            | InvalidChallengeTarget => ErrorNamespace::Challenge,
+            OwnerCellNotFound
+            | UnknownEOAScript
+            | UnknownContractScript
+            | AccountLockCellNotFound
+            | WrongSignature => ErrorNamespace::Signature,
+            RollupCellNotFound
+            | RollupConfigNotFound
+            | InvalidBlock
+            | InvalidStatus
+            | InvalidPostGlobalState
+            | InvalidTypeID => ErrorNamespace::Rollup,
+            AccountNotFound
+            | ScriptNotFound
+            | AccountScriptCellNotFound
+            | UnexpectedTxNonce
+            | DuplicatedScriptHash
+            | RegistryAddressNotFound
+            | DuplicatedRegistryAddress => ErrorNamespace::Account,
+        }
+    }
+
+    /// Attach a sub code to the error, the sub code distinguishes branches
+    /// that return the same exit code.
+    pub const fn with_sub_code(self, sub_code: u16) -> Self {
+        Error {
+            code: self.code,
+            sub_code,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// Returns the sub code attached to this error, or 0
+    pub fn sub_code(&self) -> u16 {
+        self.sub_code
+    }
+
+    /// The exit code of the script
+    pub fn exit_code(&self) -> i8 {
+        self.code as i8
+    }
+
+    /// detailed code: namespace(8 bits) | exit code(8 bits) | sub code(16 bits)
+    pub fn detailed_code(&self) -> u32 {
+        let namespace = self.namespace() as u8 as u32;
+        let code = self.exit_code() as u8 as u32;
+        namespace << 24 | code << 16 | self.sub_code as u32
+    }
+
+    /// Write the detailed error to the debug syscall,
+    /// the output is available without rebuilding scripts with debug features.
+    pub fn report(&self) {
+        debug(alloc::format!(
+            "[gw error] namespace: {}, code: {}, sub code: {}, detailed code: {:#010x}",
+            self.namespace().name(),
+            self.exit_code(),
+            self.sub_code,
+            self.detailed_code()
+        ));
+    }
+}
+
+/// sub codes of syscall errors
+pub mod sys_sub_code {
+    /// the sub code of LengthNotEnough is the actual length if it fits in u16
+    pub const LENGTH_OVERFLOW: u16 = u16::MAX;
+    /// the sub code of an unknown syscall error is the error code if it fits in u16
+    pub const SYS_CODE_OVERFLOW: u16 = u16::MAX;
 }
 
 impl From<SysError> for Error {
@@ -60,18 +223,40 @@ impl From<SysError> for Error {
         match err {
             IndexOutOfBound => Self::IndexOutOfBound,
             ItemMissing => Self::ItemMissing,
-            LengthNotEnough(_) => Self::LengthNotEnough,
+            LengthNotEnough(len) => {
+                let sub_code = if len > u16::MAX as usize {
+                    sys_sub_code::LENGTH_OVERFLOW
+                } else {
+                    len as u16
+                };
+                Self::LengthNotEnough.with_sub_code(sub_code)
+            }
             Encoding => Self::Encoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+            Unknown(err_code) => {
+                let sub_code = if err_code > u16::MAX as u64 {
+                    sys_sub_code::SYS_CODE_OVERFLOW
+                } else {
+                    err_code as u16
+                };
+                Self::SysUnknown.with_sub_code(sub_code)
+            }
         }
     }
 }
 
+/// sub codes of `Error::SMTKeyMissing`, a missing key has no sub code
+pub mod smt_sub_code {
+    pub const SMT_INTERNAL: u16 = 1;
+    pub const SMT_STORE: u16 = 2;
+}
+
 impl From<CommonError> for Error {
     fn from(err: CommonError) -> Self {
         use CommonError::*;
         match err {
-            SMT(_) | Store | MissingKey => Self::SMTKeyMissing,
+            SMT(_) => Self::SMTKeyMissing.with_sub_code(smt_sub_code::SMT_INTERNAL),
+            Store => Self::SMTKeyMissing.with_sub_code(smt_sub_code::SMT_STORE),
+            MissingKey => Self::SMTKeyMissing,
             MerkleProof => Self::MerkleProof,
             AmountOverflow => Self::AmountOverflow,
             DuplicatedScriptHash => Self::DuplicatedScriptHash,
@@ -84,6 +269,6 @@ impl From<CommonError> for Error {
 
 impl From<SMTError> for Error {
     fn from(_err: SMTError) -> Self {
-        Self::SMTKeyMissing
+        Self::SMTKeyMissing.with_sub_code(smt_sub_code::SMT_INTERNAL)
     }
 }
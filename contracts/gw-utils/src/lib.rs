@@ -11,6 +11,7 @@ pub use gw_types;
 
 pub mod cells;
 pub mod error;
+pub mod mol_ext;
 pub mod signature;
 pub mod type_id;
 pub mod withdrawal;
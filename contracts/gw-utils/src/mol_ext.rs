@@ -0,0 +1,73 @@
+//! Read molecule data unknown to gw-types, e.g. fields appended to a table.
+//!
+//! The data must be verified in compatible mode before calling these functions.
+
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+fn read_u32(data: &[u8], offset: usize) -> usize {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_le_bytes(buf) as usize
+}
+
+/// Returns the raw extra field of a table, `known_field_count` is the field count
+/// known by gw-types, `index` counts from the first extra field.
+pub fn table_extra_field(table: &[u8], known_field_count: usize, index: usize) -> Option<&[u8]> {
+    // table: total size | field offsets | fields
+    if table.len() <= 4 {
+        return None;
+    }
+    let field_count = read_u32(table, 4) / 4 - 1;
+    let field_index = known_field_count + index;
+    if field_index >= field_count {
+        return None;
+    }
+    let start = read_u32(table, 4 + field_index * 4);
+    let end = if field_index + 1 == field_count {
+        table.len()
+    } else {
+        read_u32(table, 8 + field_index * 4)
+    };
+    Some(&table[start..end])
+}
+
+/// Returns the raw items of a dynvec
+pub fn parse_dynvec(data: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    // dynvec: total size | item offsets | items
+    let total_size = data.len();
+    if total_size < 4 || read_u32(data, 0) != total_size {
+        return Err(Error::Encoding);
+    }
+    if total_size == 4 {
+        return Ok(Vec::new());
+    }
+    if total_size < 8 {
+        return Err(Error::Encoding);
+    }
+    let offset_first = read_u32(data, 4);
+    if offset_first % 4 != 0 || offset_first < 8 {
+        return Err(Error::Encoding);
+    }
+    let item_count = offset_first / 4 - 1;
+    let header_size = 4 + 4 * item_count;
+    if header_size > total_size {
+        return Err(Error::Encoding);
+    }
+
+    // same as the verification of molecule: the first offset is the header size,
+    // the offsets are in order and within the total size
+    let mut offsets: Vec<usize> = (0..item_count).map(|i| read_u32(data, 4 + i * 4)).collect();
+    if offsets[0] != header_size {
+        return Err(Error::Encoding);
+    }
+    offsets.push(total_size);
+    if offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(Error::Encoding);
+    }
+    Ok(offsets
+        .windows(2)
+        .map(|pair| &data[pair[0]..pair[1]])
+        .collect())
+}
@@ -5,6 +5,73 @@ use ckb_std::{ckb_constants::Source, debug, syscalls::load_cell_data};
 use gw_common::H256;
 use gw_types::core::SigningType;
 
+/// Types of `RollupConfig.allowed_eoa_type_hashes`, see `c/gw_def.h`
+pub const ALLOWED_EOA_UNKNOWN: u8 = 0;
+pub const ALLOWED_EOA_ETH: u8 = 1;
+pub const ALLOWED_EOA_TRON: u8 = 2;
+pub const ALLOWED_EOA_BITCOIN: u8 = 3;
+pub const ALLOWED_EOA_ED25519: u8 = 4;
+pub const ALLOWED_EOA_WEBAUTHN: u8 = 5;
+pub const ALLOWED_EOA_MULTISIG: u8 = 6;
+
+/// Types of `RollupConfig.allowed_contract_type_hashes`, see `c/gw_def.h`
+pub const ALLOWED_CONTRACT_UNKNOWN: u8 = 0;
+pub const ALLOWED_CONTRACT_META: u8 = 1;
+pub const ALLOWED_CONTRACT_SUDT: u8 = 2;
+pub const ALLOWED_CONTRACT_POLYJUICE: u8 = 3;
+pub const ALLOWED_CONTRACT_ETH_ADDR_REG: u8 = 4;
+
+/// Signature of a deposit call transaction, the call is authorized by its layer1 deposit cell,
+/// state-validator only accepts it at the position of the deposit call when a block is submitted
+pub const DEPOSIT_CALL_SIGNATURE: &[u8] = b"deposit call";
+
+/// onetime_owner_lock_hash(32 bytes) | signing type (1 byte) | message(32 bytes)
+pub const L2_ACCOUNT_SIGNATURE_CELL_DATA_LEN: usize = 65;
+
+/// The data of a layer2 account signature cell
+pub struct L2AccountSignatureCellData {
+    pub onetime_owner_lock_hash: [u8; 32],
+    pub signing_type: SigningType,
+    pub message: H256,
+}
+
+impl L2AccountSignatureCellData {
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != L2_ACCOUNT_SIGNATURE_CELL_DATA_LEN {
+            debug!("Invalid data size: {}", data.len());
+            return Err(Error::Encoding);
+        }
+
+        let mut onetime_owner_lock_hash = [0u8; 32];
+        onetime_owner_lock_hash.copy_from_slice(&data[..32]);
+
+        let signing_type = SigningType::try_from(data[32]).map_err(|err| {
+            debug!("Invalid signature message type {}", err);
+            Error::Encoding
+        })?;
+
+        let mut message = [0u8; 32];
+        message.copy_from_slice(&data[33..]);
+
+        Ok(L2AccountSignatureCellData {
+            onetime_owner_lock_hash,
+            signing_type,
+            message: message.into(),
+        })
+    }
+
+    /// Load the data of a layer2 account signature cell
+    pub fn load(index: usize, source: Source) -> Result<Self, Error> {
+        let mut data = [0u8; L2_ACCOUNT_SIGNATURE_CELL_DATA_LEN];
+        let loaded_size = load_cell_data(&mut data, 0, index, source)?;
+        if loaded_size != data.len() {
+            debug!("Invalid data size: {}", loaded_size);
+            return Err(Error::Encoding);
+        }
+        Self::from_slice(&data)
+    }
+}
+
 /// Check l2 account signature cell
 pub fn check_l2_account_signature_cell(
     script_hash: &H256,
@@ -14,25 +81,17 @@ pub fn check_l2_account_signature_cell(
     debug!("Check l2 account signature for message {:?}", message);
     // search layer2 account lock cell from inputs
     for index in search_lock_hashes(&(*script_hash).into(), Source::Input) {
-        // expected data is equals to onetime_lock_hash(32 bytes) | sign type (1 byte) | message(32 bytes)
-        let mut data = [0u8; 33];
-        let len = load_cell_data(&mut data, 32, index, Source::Input)?;
-
-        // skip if the data isn't 32 length
-        if len != data.len() {
-            continue;
-        }
-
-        let signing_type = match SigningType::try_from(data[0]) {
-            Ok(type_) => type_,
+        // skip if the data isn't a signature cell data
+        let data = match L2AccountSignatureCellData::load(index, Source::Input) {
+            Ok(data) => data,
             Err(_err) => continue,
         };
 
-        if signing_type != expected_signing_type {
+        if data.signing_type != expected_signing_type {
             continue;
         }
 
-        if &data[1..] == message.as_slice() {
+        if data.message == message {
             return Ok(());
         }
     }
@@ -1,12 +1,25 @@
 use ckb_std::debug;
 use gw_types::{
     bytes::Bytes,
-    packed::{Script, ScriptReader, WithdrawalLockArgs, WithdrawalLockArgsReader},
+    packed::{
+        Script, ScriptReader, UnlockWithdrawalWitness, UnlockWithdrawalWitnessReader,
+        WithdrawalLockArgs, WithdrawalLockArgsReader,
+    },
     prelude::{Entity, Reader, Unpack},
 };
 
 use crate::error::Error;
 
+/// sub codes of `Error::InvalidArgs` returned by `parse_lock_args`
+pub mod args_sub_code {
+    pub const ARGS_TOO_SHORT: u16 = 1;
+    pub const INVALID_LOCK_ARGS: u16 = 2;
+    pub const MISSING_OWNER_LOCK: u16 = 3;
+    pub const INVALID_OWNER_LOCK_LEN: u16 = 4;
+    pub const INVALID_OWNER_LOCK: u16 = 5;
+    pub const OWNER_LOCK_HASH_MISMATCH: u16 = 6;
+}
+
 pub struct WithdrawalLockArgsWithOwnerLock {
     pub lock_args: WithdrawalLockArgs,
     pub owner_lock: Script,
@@ -19,19 +32,21 @@ pub fn parse_lock_args(args: &Bytes) -> Result<WithdrawalLockArgsWithOwnerLock,
 
     let args_len = args.len();
     if args_len < lock_args_end {
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(args_sub_code::ARGS_TOO_SHORT));
     }
 
     let raw_args = args.slice(lock_args_start..lock_args_end);
     let lock_args = match WithdrawalLockArgsReader::verify(&raw_args, false) {
         Ok(()) => WithdrawalLockArgs::new_unchecked(raw_args),
-        Err(_) => return Err(Error::InvalidArgs),
+        Err(_) => {
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_LOCK_ARGS));
+        }
     };
 
     let owner_lock_start = lock_args_end + 4; // u32 length
     if args_len <= owner_lock_start {
         debug!("[parse withdrawal] missing owner lock");
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(args_sub_code::MISSING_OWNER_LOCK));
     }
 
     let mut owner_lock_len_buf = [0u8; 4];
@@ -40,19 +55,21 @@ pub fn parse_lock_args(args: &Bytes) -> Result<WithdrawalLockArgsWithOwnerLock,
     let owner_lock_len = u32::from_be_bytes(owner_lock_len_buf) as usize;
     let owner_lock_end = owner_lock_start + owner_lock_len;
     if owner_lock_end != args_len {
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_OWNER_LOCK_LEN));
     }
 
     let raw_script = args.slice(owner_lock_start..owner_lock_end);
     let owner_lock = match ScriptReader::verify(&raw_script, false) {
         Ok(()) => Script::new_unchecked(raw_script),
-        Err(_) => return Err(Error::InvalidArgs),
+        Err(_) => {
+            return Err(Error::InvalidArgs.with_sub_code(args_sub_code::INVALID_OWNER_LOCK));
+        }
     };
 
     let owner_lock_hash: [u8; 32] = lock_args.owner_lock_hash().unpack();
     if owner_lock.hash() != owner_lock_hash {
         debug!("[parse withdrawal] incorrect owner lock");
-        return Err(Error::InvalidArgs);
+        return Err(Error::InvalidArgs.with_sub_code(args_sub_code::OWNER_LOCK_HASH_MISMATCH));
     }
 
     Ok(WithdrawalLockArgsWithOwnerLock {
@@ -60,3 +77,54 @@ pub fn parse_lock_args(args: &Bytes) -> Result<WithdrawalLockArgsWithOwnerLock,
         owner_lock,
     })
 }
+
+/// item id of `UnlockWithdrawalViaTrade` in the `UnlockWithdrawalWitness` union
+pub const UNLOCK_WITHDRAWAL_VIA_TRADE_ITEM_ID: u32 = 2;
+
+pub enum UnlockWithdrawal {
+    /// variants known by gw-types: UnlockWithdrawalViaFinalize, UnlockWithdrawalViaRevert
+    Witness(UnlockWithdrawalWitness),
+    /// transfer an unfinalized withdrawal to a new owner
+    ViaTrade { owner_lock: Script },
+}
+
+/// union UnlockWithdrawalWitness {
+///     UnlockWithdrawalViaFinalize,
+///     UnlockWithdrawalViaRevert,
+///     UnlockWithdrawalViaTrade,
+/// }
+/// table UnlockWithdrawalViaTrade { owner_lock: Script }
+///
+/// UnlockWithdrawalViaTrade is not part of gw-types yet, parse it manually.
+pub fn parse_unlock_witness(data: Bytes) -> Result<UnlockWithdrawal, Error> {
+    if UnlockWithdrawalWitnessReader::verify(&data, false).is_ok() {
+        return Ok(UnlockWithdrawal::Witness(
+            UnlockWithdrawalWitness::new_unchecked(data),
+        ));
+    }
+
+    let read_u32 = |data: &[u8]| {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&data[..4]);
+        u32::from_le_bytes(buf)
+    };
+
+    // union header: item id
+    if data.len() < 4 || read_u32(&data) != UNLOCK_WITHDRAWAL_VIA_TRADE_ITEM_ID {
+        return Err(Error::ProofNotFound);
+    }
+
+    // table header: total size | offset of owner_lock
+    let table = data.slice(4..);
+    if table.len() < 8 || read_u32(&table) as usize != table.len() || read_u32(&table[4..]) != 8 {
+        return Err(Error::ProofNotFound);
+    }
+
+    let raw_script = table.slice(8..);
+    match ScriptReader::verify(&raw_script, false) {
+        Ok(()) => Ok(UnlockWithdrawal::ViaTrade {
+            owner_lock: Script::new_unchecked(raw_script),
+        }),
+        Err(_) => Err(Error::ProofNotFound),
+    }
+}
@@ -0,0 +1,14 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use crate::multisig_signature::Secp256k1Multisig;
+use gw_account_lock::verify_account_lock;
+use gw_utils::error::Error;
+
+/// Multisig account lock
+/// script args: rollup_script_hash(32 bytes) | blake160(multisig_config)(20 bytes)
+/// data: onetime_owner_lock_hash(32 bytes) | signing type (1 byte) | message(32 bytes)
+/// witness lock: multisig_config | signatures(65 bytes * threshold)
+pub fn main() -> Result<(), Error> {
+    verify_account_lock::<Secp256k1Multisig>()
+}
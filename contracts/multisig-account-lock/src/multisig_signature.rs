@@ -0,0 +1,138 @@
+//! Secp256k1 multisig implementation
+//!
+//! The multisig config follows the layout of CKB's secp256k1_blake160_multisig_all:
+//! reserved(1 byte) | require_first_n(1 byte) | threshold(1 byte) | pubkeys_cnt(1 byte) | pubkey_hashes(20 bytes * pubkeys_cnt)
+//!
+//! A pubkey hash is the Ethereum address of the key. The config is followed by `threshold` signatures,
+//! signers must appear in the order of `pubkey_hashes` and the first `require_first_n` keys must sign.
+//! The recovery id of a signature can be either raw (0 / 1) or Ethereum style (27 / 28).
+
+use alloc::vec::Vec;
+
+use gw_account_lock::{
+    secp256k1::{keccak256, recover_keccak_address, SIGNATURE_SIZE},
+    AccountLockScheme,
+};
+use gw_utils::{
+    ckb_std::debug,
+    error::Error,
+    gw_common::{blake2b::new_blake2b, H256},
+};
+
+const BLAKE160_SIZE: usize = 20;
+const CONFIG_HEADER_SIZE: usize = 4;
+const ETH_RECOVERY_ID_OFFSET: u8 = 27;
+
+pub struct Secp256k1Multisig;
+
+impl AccountLockScheme for Secp256k1Multisig {
+    /// blake160 of the multisig config
+    const ADDRESS_LEN: usize = BLAKE160_SIZE;
+
+    fn prefix_message(message: &H256) -> Vec<u8> {
+        keccak256(&[b"\x19Ethereum Signed Message:\n32", message.as_slice()])
+            .as_slice()
+            .to_vec()
+    }
+
+    fn verify_signature(address: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, Error> {
+        let config = MultisigConfig::from_slice(signature)?;
+        if blake160(config.raw) != address {
+            debug!("mismatch multisig config hash");
+            return Err(Error::InvalidArgs);
+        }
+
+        let signatures = &signature[config.raw.len()..];
+        if signatures.len() != config.threshold * SIGNATURE_SIZE {
+            debug!(
+                "signatures len: {}, expected len: {}",
+                signatures.len(),
+                config.threshold * SIGNATURE_SIZE
+            );
+            return Err(Error::WrongSignature);
+        }
+
+        // index of the next pubkey hash can be matched
+        let mut next_index = 0;
+        for (i, signature) in signatures.chunks(SIGNATURE_SIZE).enumerate() {
+            let mut buf = [0u8; SIGNATURE_SIZE];
+            buf.copy_from_slice(signature);
+            if buf[64] >= ETH_RECOVERY_ID_OFFSET {
+                buf[64] -= ETH_RECOVERY_ID_OFFSET;
+            }
+            let address = recover_keccak_address(buf, message)?;
+            let matched_index = match config.position(&address, next_index) {
+                Some(index) => index,
+                None => {
+                    debug!("signature {} isn't signed by the remaining pubkeys", i);
+                    return Ok(false);
+                }
+            };
+            if i < config.require_first_n && matched_index != i {
+                debug!("pubkey {} is required to sign", i);
+                return Ok(false);
+            }
+            next_index = matched_index + 1;
+        }
+        Ok(true)
+    }
+}
+
+struct MultisigConfig<'a> {
+    raw: &'a [u8],
+    require_first_n: usize,
+    threshold: usize,
+    pubkey_hashes: &'a [u8],
+}
+
+impl<'a> MultisigConfig<'a> {
+    /// Parse the config from the head of the witness lock
+    fn from_slice(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < CONFIG_HEADER_SIZE {
+            debug!("invalid multisig witness len: {}", data.len());
+            return Err(Error::WrongSignature);
+        }
+        let reserved = data[0];
+        let require_first_n = data[1] as usize;
+        let threshold = data[2] as usize;
+        let pubkeys_cnt = data[3] as usize;
+        if reserved != 0 || threshold == 0 || threshold > pubkeys_cnt || require_first_n > threshold
+        {
+            debug!(
+                "invalid multisig config, require_first_n: {}, threshold: {}, pubkeys_cnt: {}",
+                require_first_n, threshold, pubkeys_cnt
+            );
+            return Err(Error::InvalidArgs);
+        }
+        let config_size = CONFIG_HEADER_SIZE + pubkeys_cnt * BLAKE160_SIZE;
+        if data.len() < config_size {
+            debug!("invalid multisig witness len: {}", data.len());
+            return Err(Error::WrongSignature);
+        }
+        Ok(MultisigConfig {
+            raw: &data[..config_size],
+            require_first_n,
+            threshold,
+            pubkey_hashes: &data[CONFIG_HEADER_SIZE..config_size],
+        })
+    }
+
+    /// Find the pubkey hash at or after `from`
+    fn position(&self, pubkey_hash: &[u8], from: usize) -> Option<usize> {
+        self.pubkey_hashes
+            .chunks(BLAKE160_SIZE)
+            .skip(from)
+            .position(|hash| hash == pubkey_hash)
+            .map(|offset| from + offset)
+    }
+}
+
+fn blake160(data: &[u8]) -> [u8; BLAKE160_SIZE] {
+    let mut hasher = new_blake2b();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    let mut buf = [0u8; BLAKE160_SIZE];
+    buf.copy_from_slice(&hash[..BLAKE160_SIZE]);
+    buf
+}
@@ -22,3 +22,11 @@ pub fn recover_uncompressed_key(message: [u8; 32], signature: [u8; 65]) -> Resul
         Err(ret)
     }
 }
+
+/// Serialize an uncompressed public key in the compressed format
+pub fn compress_pubkey(uncompressed_pubkey: &[u8; 65]) -> [u8; 33] {
+    let mut pubkey = [0u8; 33];
+    pubkey[0] = 0x02 | (uncompressed_pubkey[64] & 1);
+    pubkey[1..].copy_from_slice(&uncompressed_pubkey[1..33]);
+    pubkey
+}
@@ -12,17 +12,22 @@ use core::result::Result;
 use crate::ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, prelude::Unpack as CKBTypeUnpack},
-    high_level::load_script,
+    debug,
+    high_level::{load_cell_lock_hash, load_cell_type_hash, load_script, QueryIter},
 };
 
 use gw_utils::cells::{
-    rollup::{search_rollup_cell, search_rollup_state},
+    lock_cells::fetch_capacity_and_sudt_value,
+    rollup::{
+        load_allowed_stake_assets, load_rollup_config, search_rollup_cell, search_rollup_state,
+    },
     utils::search_lock_hash,
 };
-use gw_utils::gw_types;
+use gw_utils::{gw_common, gw_types};
 
+use gw_common::CKB_SUDT_SCRIPT_ARGS;
 use gw_types::{
-    packed::{StakeLockArgs, StakeLockArgsReader},
+    packed::{GlobalState, StakeLockArgs, StakeLockArgsReader},
     prelude::*,
 };
 
@@ -46,20 +51,79 @@ fn parse_lock_args() -> Result<([u8; 32], StakeLockArgs), Error> {
     }
 }
 
+/// Withdraw the stake above the requirement of the rollup config.
+/// The stake cell is updated in place, it keeps the lock and the type, so it still covers
+/// unfinalized blocks until `stake_block_number` is finalized.
+/// The reduced stake must cover `required_staking_capacity`,
+/// or the minimum amount of the allowed stake asset.
+fn check_surplus_withdrawal(global_state: &GlobalState) -> Result<(), Error> {
+    if QueryIter::new(load_cell_lock_hash, Source::GroupInput).count() != 1 {
+        debug!("[stake lock] withdraw surplus from more than one stake cell");
+        return Err(Error::InvalidStakeCellUnlock);
+    }
+    let stake_lock_hash = load_cell_lock_hash(0, Source::GroupInput)?;
+    let output_index = {
+        let mut indexes = QueryIter::new(load_cell_lock_hash, Source::Output)
+            .enumerate()
+            .filter(|(_index, lock_hash)| lock_hash == &stake_lock_hash)
+            .map(|(index, _lock_hash)| index);
+        match (indexes.next(), indexes.next()) {
+            (Some(index), None) => index,
+            _ => {
+                debug!("[stake lock] expect one updated stake cell in the outputs");
+                return Err(Error::InvalidStakeCellUnlock);
+            }
+        }
+    };
+    if load_cell_type_hash(0, Source::GroupInput)?
+        != load_cell_type_hash(output_index, Source::Output)?
+    {
+        debug!("[stake lock] the stake asset is changed");
+        return Err(Error::InvalidStakeCellUnlock);
+    }
+
+    let config = load_rollup_config(&global_state.rollup_config_hash().unpack())?;
+    let output_stake_value = fetch_capacity_and_sudt_value(&config, output_index, Source::Output)?;
+    if output_stake_value.sudt_script_hash == CKB_SUDT_SCRIPT_ARGS.into() {
+        let required_staking_capacity: u64 = config.required_staking_capacity().unpack();
+        if output_stake_value.capacity < required_staking_capacity {
+            debug!(
+                "[stake lock] stake cell's capacity is insufficient {} {}",
+                output_stake_value.capacity, required_staking_capacity
+            );
+            return Err(Error::InvalidStakeCell);
+        }
+    } else {
+        let stake_asset = load_allowed_stake_assets(&config)?
+            .into_iter()
+            .find(|asset| asset.sudt_script_hash == output_stake_value.sudt_script_hash)
+            .ok_or(Error::InvalidStakeCell)?;
+        if output_stake_value.amount < stake_asset.minimum_amount {
+            debug!(
+                "[stake lock] stake cell's amount is insufficient {} {}",
+                output_stake_value.amount, stake_asset.minimum_amount
+            );
+            return Err(Error::InvalidStakeCell);
+        }
+    }
+    Ok(())
+}
+
 pub fn main() -> Result<(), Error> {
     let (rollup_type_hash, lock_args) = parse_lock_args()?;
+    // read global state from rollup cell in deps
+    let global_state = search_rollup_state(&rollup_type_hash, Source::CellDep)?;
+    let has_owner_cell =
+        search_lock_hash(&lock_args.owner_lock_hash().unpack(), Source::Input).is_some();
 
     // Unlock by User
-    // read global state from rollup cell in deps
-    if let Some(global_state) = search_rollup_state(&rollup_type_hash, Source::CellDep)? {
+    if let Some(global_state) = &global_state {
         let stake_block_number: u64 = lock_args.stake_block_number().unpack();
         let last_finalized_block_number: u64 = global_state.last_finalized_block_number().unpack();
 
         // 1. check if stake_block_number is finalized
         // 2. check if owner_lock_hash exists in input cells
-        if stake_block_number <= last_finalized_block_number
-            && search_lock_hash(&lock_args.owner_lock_hash().unpack(), Source::Input).is_some()
-        {
+        if stake_block_number <= last_finalized_block_number && has_owner_cell {
             return Ok(());
         }
     }
@@ -71,5 +135,12 @@ pub fn main() -> Result<(), Error> {
         return Ok(());
     }
 
+    // Withdraw surplus stake by User
+    if let Some(global_state) = global_state {
+        if has_owner_cell {
+            return check_surplus_withdrawal(&global_state);
+        }
+    }
+
     Err(Error::InvalidStakeCellUnlock)
 }
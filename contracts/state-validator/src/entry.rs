@@ -4,7 +4,9 @@ use core::result::Result;
 // Import heap related library from `alloc`
 // https://doc.rust-lang.org/alloc/index.html
 use gw_utils::{
-    cells::rollup::{load_rollup_config, parse_rollup_action, MAX_ROLLUP_WITNESS_SIZE},
+    cells::rollup::{
+        load_rollup_config, parse_rollup_action_kind, RollupActionKind, MAX_ROLLUP_WITNESS_SIZE,
+    },
     ckb_std::{
         ckb_types::prelude::Unpack as CKBUnpack,
         debug,
@@ -93,14 +95,65 @@ pub fn main() -> Result<(), Error> {
 
     // load rollup action
     let mut rollup_witness_buf = [0u8; MAX_ROLLUP_WITNESS_SIZE];
-    let action = parse_rollup_action(&mut rollup_witness_buf, 0, Source::GroupOutput)?;
+    let action = match parse_rollup_action_kind(&mut rollup_witness_buf, 0, Source::GroupOutput)? {
+        RollupActionKind::Action(action) => action,
+        RollupActionKind::UpdateConfig => {
+            // verify update config
+            return verifications::update_config::verify(
+                rollup_type_hash,
+                &rollup_config,
+                &prev_global_state,
+                &post_global_state,
+            );
+        }
+        RollupActionKind::ForceHalt(args) => {
+            // verify force halt
+            return verifications::forced_withdrawal::verify_force_halt(
+                rollup_type_hash,
+                &rollup_config,
+                args,
+                &prev_global_state,
+                &post_global_state,
+            );
+        }
+        RollupActionKind::RebalanceCustodians => {
+            // verify rebalance custodians
+            return verifications::custodian::verify_rebalance_custodians(
+                rollup_type_hash,
+                &rollup_config,
+                &prev_global_state,
+                &post_global_state,
+            );
+        }
+        RollupActionKind::EnterEmergency(args) => {
+            // verify enter emergency
+            return verifications::emergency::verify_enter_emergency(
+                rollup_type_hash,
+                &rollup_config,
+                args,
+                &prev_global_state,
+                &post_global_state,
+            );
+        }
+        RollupActionKind::EmergencyExit(args) => {
+            // verify emergency exit
+            return verifications::emergency::verify_emergency_exit(
+                rollup_type_hash,
+                &rollup_config,
+                args,
+                &prev_global_state,
+                &post_global_state,
+            );
+        }
+    };
     match action.to_enum() {
         RollupActionUnionReader::RollupSubmitBlock(args) => {
-            // verify submit block
+            // verify submit block, the action may carry following blocks of a batch
+            let blocks = verifications::submit_block::parse_submitted_blocks(&args)?;
             verifications::submit_block::verify(
                 rollup_type_hash,
                 &rollup_config,
-                &args.block(),
+                &blocks,
                 &prev_global_state,
                 &post_global_state,
             )?;
@@ -0,0 +1,108 @@
+//! Custodian maintenance
+//!
+//! Deposits leave one custodian cell each, withdrawals consume them as a whole.
+//! RollupRebalanceCustodians merges tiny finalized custodian cells or splits large ones,
+//! so withdrawal transactions stay small. The assets of each sUDT type hash are conserved.
+
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use gw_common::H256;
+use gw_types::{
+    packed::{GlobalState, RollupConfig},
+    prelude::*,
+};
+use gw_utils::{
+    cells::{
+        lock_cells::{
+            collect_custodian_locks, collect_deposit_locks, collect_forced_withdrawal_cells,
+            collect_withdrawal_locks, find_challenge_cell,
+        },
+        types::CustodianCell,
+    },
+    ckb_std::{ckb_constants::Source, debug},
+    error::Error,
+};
+use gw_utils::{gw_common, gw_types};
+
+use super::{check_stake_cells, submit_block::build_assets_map_from_cells};
+
+/// All custodian cells must be finalized by the global state
+fn check_finalized_custodian_cells(
+    cells: &[CustodianCell],
+    last_finalized_block_number: u64,
+) -> Result<(), Error> {
+    for cell in cells {
+        let deposit_block_number: u64 = cell.args.deposit_block_number().unpack();
+        if deposit_block_number > last_finalized_block_number {
+            debug!(
+                "[rebalance custodians] custodian cell {} isn't finalized",
+                cell.index
+            );
+            return Err(Error::InvalidCustodianCell);
+        }
+    }
+    Ok(())
+}
+
+/// Verify RollupRebalanceCustodians
+///
+/// Finalized input custodian cells are replaced by finalized output custodian cells,
+/// the total capacity and the sUDT amount of each type hash must be equal.
+/// The action is allowed in any status, the global state isn't changed.
+pub fn verify_rebalance_custodians(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    if prev_global_state != post_global_state {
+        debug!("[rebalance custodians] unexpected global state change");
+        return Err(Error::InvalidPostGlobalState);
+    }
+
+    // do not allow other rollup cells in the transaction
+    if !collect_deposit_locks(&rollup_type_hash, config, Source::Input)?.is_empty()
+        || !collect_deposit_locks(&rollup_type_hash, config, Source::Output)?.is_empty()
+        || !collect_forced_withdrawal_cells(&rollup_type_hash, config, Source::Input)?.is_empty()
+    {
+        return Err(Error::InvalidDepositCell);
+    }
+    if !collect_withdrawal_locks(&rollup_type_hash, config, Source::Input)?.is_empty()
+        || !collect_withdrawal_locks(&rollup_type_hash, config, Source::Output)?.is_empty()
+    {
+        return Err(Error::InvalidWithdrawalCell);
+    }
+    check_stake_cells(&rollup_type_hash, config)?;
+    if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
+        || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
+    {
+        return Err(Error::InvalidChallengeCell);
+    }
+
+    let last_finalized_block_number: u64 = prev_global_state.last_finalized_block_number().unpack();
+    let input_cells = collect_custodian_locks(&rollup_type_hash, config, Source::Input)?;
+    if input_cells.is_empty() {
+        debug!("[rebalance custodians] no input custodian cells");
+        return Err(Error::InvalidCustodianCell);
+    }
+    check_finalized_custodian_cells(&input_cells, last_finalized_block_number)?;
+    let output_cells = collect_custodian_locks(&rollup_type_hash, config, Source::Output)?;
+    check_finalized_custodian_cells(&output_cells, last_finalized_block_number)?;
+
+    // check input finalized assets == output finalized assets, per sUDT type hash
+    let input_assets = build_assets_map_from_cells(input_cells.iter().map(|c| &c.value))?;
+    let mut output_assets = build_assets_map_from_cells(output_cells.iter().map(|c| &c.value))?;
+    for (k, v) in input_assets {
+        let balance = output_assets.entry(k).or_insert(0);
+        *balance = balance
+            .checked_sub(v)
+            .ok_or(Error::InsufficientOutputFinalizedAssets)?;
+    }
+    if output_assets.iter().any(|(_k, v)| v != &0) {
+        debug!("[rebalance custodians] output assets exceed input assets");
+        return Err(Error::InsufficientInputFinalizedAssets);
+    }
+
+    Ok(())
+}
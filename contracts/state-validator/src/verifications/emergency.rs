@@ -0,0 +1,472 @@
+//! Emergency exit
+//!
+//! A rollup can stay halting forever, e.g. after RollupForceHalt or if nobody reverts
+//! or cancels a challenge, and the custodian cells are stuck.
+//! After `emergency_exit_timeout` since the tip block, anyone can enter emergency mode by
+//! RollupEnterEmergency, the account state is restored to the last finalized block.
+//! Then users claim their layer2 balances from finalized custodian cells by
+//! RollupEmergencyExit, with a merkle proof against the account state.
+//! A claimed balance is burned from the account state, so it can't be claimed twice.
+//! Unfinalized deposits and withdrawals aren't in the finalized state, they are reverted.
+
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import heap related library from `alloc`
+// https://doc.rust-lang.org/alloc/index.html
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use gw_common::{
+    blake2b::new_blake2b, builtins::CKB_SUDT_ACCOUNT_ID, ckb_decimal::CKBCapacity,
+    smt::Blake2bHasher, sparse_merkle_tree::CompiledMerkleProof, state::State,
+    CKB_SUDT_SCRIPT_ARGS, H256,
+};
+use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS, kv_state::KVState};
+use gw_types::{
+    core::{SigningType, Status},
+    packed::{
+        AccountMerkleState, GlobalState, KVPairVecReader, RawL2Block, RawL2BlockReader,
+        RollupConfig,
+    },
+    prelude::*,
+    U256,
+};
+use gw_utils::{
+    cells::{
+        lock_cells::{
+            collect_custodian_locks, collect_deposit_locks, collect_forced_withdrawal_cells,
+            collect_withdrawal_locks, fetch_capacity_and_sudt_value, find_challenge_cell,
+        },
+        rollup::{load_emergency_exit_timeout, ROLLUP_STATUS_EMERGENCY},
+        types::CustodianCell,
+        utils::build_l2_sudt_script,
+    },
+    ckb_std::{
+        ckb_constants::Source,
+        debug,
+        high_level::{load_cell_lock_hash, load_input_since, QueryIter},
+        since::{LockValue, Since},
+    },
+    error::Error,
+    mol_ext::table_extra_field,
+    signature::check_l2_account_signature_cell,
+};
+use gw_utils::{gw_common, gw_types};
+
+use super::submit_block::build_assets_map_from_cells;
+use super::{check_rollup_lock_cells, check_stake_cells, check_status, parse_bytes_field};
+
+/// sub codes of `Error::InvalidStatus`
+const EMERGENCY_EXIT_DISABLED: u16 = 1;
+const NOT_IN_EMERGENCY: u16 = 2;
+
+/// sub codes of `Error::InvalidArgs`
+const NO_EMERGENCY_CLAIMS: u16 = 1;
+
+/// struct EmergencyClaim {
+///     account_script_hash: Byte32,
+///     registry_id: Uint32,
+///     sudt_script_hash: Byte32,
+///     owner_lock_hash: Byte32,
+/// }
+struct EmergencyClaim<'a> {
+    raw: &'a [u8],
+    account_script_hash: H256,
+    registry_id: u32,
+    sudt_script_hash: H256,
+    owner_lock_hash: [u8; 32],
+}
+
+const EMERGENCY_CLAIM_SIZE: usize = 32 + 4 + 32 + 32;
+
+impl<'a> EmergencyClaim<'a> {
+    fn from_slice(raw: &'a [u8]) -> Self {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&raw[..32]);
+        let account_script_hash = buf.into();
+        let mut registry_id = [0u8; 4];
+        registry_id.copy_from_slice(&raw[32..36]);
+        buf.copy_from_slice(&raw[36..68]);
+        let sudt_script_hash = buf.into();
+        let mut owner_lock_hash = [0u8; 32];
+        owner_lock_hash.copy_from_slice(&raw[68..]);
+        EmergencyClaim {
+            raw,
+            account_script_hash,
+            registry_id: u32::from_le_bytes(registry_id),
+            sudt_script_hash,
+            owner_lock_hash,
+        }
+    }
+
+    /// The message signed by the layer2 account: blake2b(rollup_type_hash | claim)
+    fn signing_message(&self, rollup_type_hash: &H256) -> H256 {
+        let mut hasher = new_blake2b();
+        hasher.update(rollup_type_hash.as_slice());
+        hasher.update(self.raw);
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        message.into()
+    }
+}
+
+/// table RollupEnterEmergency {
+///     finalized_block: RawL2Block,
+///     block_proof: Bytes,
+/// }
+fn parse_enter_emergency(args: &[u8]) -> Result<(RawL2BlockReader, &[u8]), Error> {
+    let block = table_extra_field(args, 0, 0).ok_or(Error::Encoding)?;
+    if RawL2BlockReader::verify(block, false).is_err() {
+        debug!("[enter emergency] invalid encoding of finalized block");
+        return Err(Error::Encoding);
+    }
+    let block_proof = parse_bytes_field(args, 1)?;
+    Ok((RawL2BlockReader::new_unchecked(block), block_proof))
+}
+
+/// table RollupEmergencyExit {
+///     claims: EmergencyClaimVec,
+///     kv_state: KVPairVec,
+///     kv_state_proof: Bytes,
+/// }
+fn parse_emergency_exit(
+    args: &[u8],
+) -> Result<(Vec<EmergencyClaim>, KVPairVecReader, &[u8]), Error> {
+    let claims = table_extra_field(args, 0, 0).ok_or(Error::Encoding)?;
+    if claims.len() < 4 {
+        return Err(Error::Encoding);
+    }
+    let mut count = [0u8; 4];
+    count.copy_from_slice(&claims[..4]);
+    if claims.len() != 4 + u32::from_le_bytes(count) as usize * EMERGENCY_CLAIM_SIZE {
+        debug!("[emergency exit] invalid encoding of claims");
+        return Err(Error::Encoding);
+    }
+    let claims = claims[4..]
+        .chunks_exact(EMERGENCY_CLAIM_SIZE)
+        .map(EmergencyClaim::from_slice)
+        .collect();
+    let kv_state = table_extra_field(args, 0, 1).ok_or(Error::Encoding)?;
+    if KVPairVecReader::verify(kv_state, false).is_err() {
+        debug!("[emergency exit] invalid encoding of kv state");
+        return Err(Error::Encoding);
+    }
+    let kv_state_proof = parse_bytes_field(args, 2)?;
+    Ok((
+        claims,
+        KVPairVecReader::new_unchecked(kv_state),
+        kv_state_proof,
+    ))
+}
+
+/// Verify RollupEnterEmergency
+///
+/// The rollup has been halting since `emergency_exit_timeout` after the tip block,
+/// the rollup input since proves the time. The account state is restored to the post
+/// account state of the last finalized block.
+pub fn verify_enter_emergency(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    args: &[u8],
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    check_status(prev_global_state, Status::Halting)?;
+
+    let timeout = load_emergency_exit_timeout(config)?.ok_or_else(|| {
+        debug!("[enter emergency] emergency exit is disabled");
+        Error::InvalidStatus.with_sub_code(EMERGENCY_EXIT_DISABLED)
+    })?;
+    // the tip block timestamp doesn't change while the rollup is halting
+    let version: u8 = prev_global_state.version().into();
+    if version == 0 {
+        debug!("[enter emergency] v0 global state has no tip block timestamp");
+        return Err(Error::InvalidSince);
+    }
+    let rollup_input_since = Since::new(load_input_since(0, Source::GroupInput)?);
+    let rollup_input_timestamp = match rollup_input_since.extract_lock_value() {
+        Some(LockValue::Timestamp(time)) if rollup_input_since.is_absolute() => time,
+        _ => return Err(Error::InvalidSince),
+    };
+    let tip_block_timestamp: u64 = prev_global_state.tip_block_timestamp().unpack();
+    if tip_block_timestamp
+        .checked_add(timeout)
+        .map_or(true, |emergency_timestamp| {
+            rollup_input_timestamp < emergency_timestamp
+        })
+    {
+        debug!(
+            "[enter emergency] rollup input timestamp {} isn't timeout, tip block timestamp {}",
+            rollup_input_timestamp, tip_block_timestamp
+        );
+        return Err(Error::InvalidSince);
+    }
+
+    // check the last finalized block
+    let (finalized_block, block_proof) = parse_enter_emergency(args)?;
+    let finalized_block_number: u64 = finalized_block.number().unpack();
+    if finalized_block_number != prev_global_state.last_finalized_block_number().unpack() {
+        debug!(
+            "[enter emergency] block {} isn't the last finalized block",
+            finalized_block_number
+        );
+        return Err(Error::InvalidBlock);
+    }
+    let valid = {
+        let merkle_proof = CompiledMerkleProof(block_proof.to_vec());
+        let leaves = vec![(
+            RawL2Block::compute_smt_key(finalized_block_number).into(),
+            finalized_block.hash().into(),
+        )];
+        merkle_proof
+            .verify::<Blake2bHasher>(&prev_global_state.block().merkle_root().unpack(), leaves)?
+    };
+    if !valid {
+        debug!("[enter emergency] finalized block merkle proof error");
+        return Err(Error::MerkleProof);
+    }
+
+    // do not allow other rollup cells in the transaction
+    check_rollup_lock_cells(&rollup_type_hash, config)?;
+    if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
+        || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
+    {
+        return Err(Error::InvalidChallengeCell);
+    }
+
+    let expected_post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .status(ROLLUP_STATUS_EMERGENCY.into())
+        .account(finalized_block.post_account().to_entity())
+        .build();
+    if &expected_post_global_state != post_global_state {
+        debug!("[enter emergency] unexpected global state change");
+        return Err(Error::InvalidPostGlobalState);
+    }
+
+    Ok(())
+}
+
+/// Unfinalized custodian cells are reverted to deposit cells, see custodian-lock
+fn check_reverted_deposit_cells(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+    unfinalized_custodian_cells: &[CustodianCell],
+) -> Result<(), Error> {
+    let mut reverted_deposit_cells =
+        collect_deposit_locks(rollup_type_hash, config, Source::Output)?;
+    for custodian_cell in unfinalized_custodian_cells {
+        let index = reverted_deposit_cells
+            .iter()
+            .position(|cell| {
+                custodian_cell.args.deposit_lock_args() == cell.args
+                    && custodian_cell.value == cell.value
+            })
+            .ok_or(Error::InvalidCustodianCell)?;
+        reverted_deposit_cells.remove(index);
+    }
+    if !reverted_deposit_cells.is_empty() {
+        return Err(Error::InvalidDepositCell);
+    }
+    Ok(())
+}
+
+/// Check the claimed assets are sent to the owners
+fn check_claim_payouts(
+    config: &RollupConfig,
+    payouts: BTreeMap<([u8; 32], H256), u128>,
+) -> Result<(), Error> {
+    for ((owner_lock_hash, sudt_script_hash), amount) in payouts {
+        let mut paid = 0u128;
+        for (index, lock_hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate() {
+            if lock_hash != owner_lock_hash {
+                continue;
+            }
+            let value = fetch_capacity_and_sudt_value(config, index, Source::Output)?;
+            let output_amount = if sudt_script_hash.as_slice() == CKB_SUDT_SCRIPT_ARGS {
+                value.capacity.into()
+            } else if value.sudt_script_hash == sudt_script_hash {
+                value.amount
+            } else {
+                0
+            };
+            paid = paid
+                .checked_add(output_amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+        if paid < amount {
+            debug!("[emergency exit] claimed {} but paid {}", amount, paid);
+            return Err(Error::InvalidOutput);
+        }
+    }
+    Ok(())
+}
+
+/// Verify RollupEmergencyExit
+///
+/// Each claim burns the whole balance of an asset of a layer2 account, the account signs
+/// the claim by a layer2 account signature cell. In the transaction:
+/// * finalized input custodians + reverted unfinalized withdrawals - claims ==
+///   output custodians, per sUDT type hash
+/// * unfinalized input custodians are reverted to deposit cells
+/// * the claimed assets are sent to `owner_lock_hash`
+pub fn verify_emergency_exit(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    args: &[u8],
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    let status: u8 = prev_global_state.status().into();
+    if status != ROLLUP_STATUS_EMERGENCY {
+        debug!("[emergency exit] rollup isn't in emergency");
+        return Err(Error::InvalidStatus.with_sub_code(NOT_IN_EMERGENCY));
+    }
+    let (claims, kv_pairs, kv_state_proof) = parse_emergency_exit(args)?;
+    if claims.is_empty() {
+        debug!("[emergency exit] no claims");
+        return Err(Error::InvalidArgs.with_sub_code(NO_EMERGENCY_CLAIMS));
+    }
+
+    // do not allow other rollup cells in the transaction
+    if !collect_deposit_locks(&rollup_type_hash, config, Source::Input)?.is_empty()
+        || !collect_forced_withdrawal_cells(&rollup_type_hash, config, Source::Input)?.is_empty()
+    {
+        return Err(Error::InvalidDepositCell);
+    }
+    if !collect_withdrawal_locks(&rollup_type_hash, config, Source::Output)?.is_empty() {
+        return Err(Error::InvalidWithdrawalCell);
+    }
+    check_stake_cells(&rollup_type_hash, config)?;
+    if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
+        || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
+    {
+        return Err(Error::InvalidChallengeCell);
+    }
+
+    // collect finalized assets
+    let last_finalized_block_number: u64 = prev_global_state.last_finalized_block_number().unpack();
+    let (finalized_custodian_cells, unfinalized_custodian_cells): (Vec<_>, Vec<_>) =
+        collect_custodian_locks(&rollup_type_hash, config, Source::Input)?
+            .into_iter()
+            .partition(|cell| {
+                let number: u64 = cell.args.deposit_block_number().unpack();
+                number <= last_finalized_block_number
+            });
+    check_reverted_deposit_cells(&rollup_type_hash, config, &unfinalized_custodian_cells)?;
+    let reverted_withdrawal_cells =
+        collect_withdrawal_locks(&rollup_type_hash, config, Source::Input)?;
+    if reverted_withdrawal_cells.iter().any(|cell| {
+        let number: u64 = cell.args.withdrawal_block_number().unpack();
+        number <= last_finalized_block_number
+    }) {
+        debug!("[emergency exit] finalized withdrawal cells belong to the owners");
+        return Err(Error::InvalidWithdrawalCell);
+    }
+    let input_assets = build_assets_map_from_cells(
+        finalized_custodian_cells
+            .iter()
+            .map(|c| &c.value)
+            .chain(reverted_withdrawal_cells.iter().map(|c| &c.value)),
+    )?;
+    let output_custodian_cells =
+        collect_custodian_locks(&rollup_type_hash, config, Source::Output)?;
+    if output_custodian_cells.iter().any(|cell| {
+        let number: u64 = cell.args.deposit_block_number().unpack();
+        number > last_finalized_block_number
+    }) {
+        debug!("[emergency exit] output custodian cells must be finalized");
+        return Err(Error::InvalidCustodianCell);
+    }
+    let mut output_assets =
+        build_assets_map_from_cells(output_custodian_cells.iter().map(|c| &c.value))?;
+
+    // burn claimed balances from the account state
+    let prev_account = prev_global_state.account();
+    let prev_account_root: H256 = prev_account.merkle_root().unpack();
+    let mut tree_buffer = [Pair::default(); GW_MAX_KV_PAIRS];
+    let mut kv_state = KVState::build(
+        &mut tree_buffer,
+        kv_pairs,
+        kv_state_proof,
+        prev_account.count().unpack(),
+        Some(prev_account_root),
+    )?;
+    if kv_state.calculate_root()? != prev_account_root {
+        debug!("[emergency exit] kv state merkle proof error");
+        return Err(Error::MerkleProof);
+    }
+    let one_shannon = CKBCapacity::from_layer1(1).to_layer2();
+    let mut payouts: BTreeMap<([u8; 32], H256), u128> = BTreeMap::new();
+    for claim in claims.iter() {
+        let address = kv_state
+            .get_registry_address_by_script_hash(claim.registry_id, &claim.account_script_hash)?
+            .ok_or(Error::RegistryAddressNotFound)?;
+        let sudt_id = match build_l2_sudt_script(&rollup_type_hash, config, &claim.sudt_script_hash)
+        {
+            Some(script) => kv_state
+                .get_account_id_by_script_hash(&script.hash().into())?
+                .ok_or(Error::AccountNotFound)?,
+            None => CKB_SUDT_ACCOUNT_ID,
+        };
+        let balance = kv_state.get_sudt_balance(sudt_id, &address)?;
+        if balance.is_zero() {
+            debug!("[emergency exit] nothing to claim");
+            return Err(Error::InsufficientAmount);
+        }
+        kv_state.burn_sudt(sudt_id, &address, balance)?;
+        // layer2 CKB has more decimals, the remainder is dropped
+        let amount = if sudt_id == CKB_SUDT_ACCOUNT_ID {
+            balance / one_shannon
+        } else {
+            balance
+        };
+        if amount > U256::from(u128::MAX) {
+            return Err(Error::AmountOverflow);
+        }
+        let amount = amount.as_u128();
+        check_l2_account_signature_cell(
+            &claim.account_script_hash,
+            SigningType::Raw,
+            claim.signing_message(&rollup_type_hash),
+        )?;
+
+        let payout = payouts
+            .entry((claim.owner_lock_hash, claim.sudt_script_hash))
+            .or_insert(0);
+        *payout = payout.checked_add(amount).ok_or(Error::AmountOverflow)?;
+        // claims are paid by the finalized assets
+        let balance = output_assets.entry(claim.sudt_script_hash).or_insert(0);
+        *balance = balance.checked_add(amount).ok_or(Error::AmountOverflow)?;
+    }
+
+    // check input finalized assets == output custodian assets + claims
+    for (k, v) in input_assets {
+        let balance = output_assets.entry(k).or_insert(0);
+        *balance = balance
+            .checked_sub(v)
+            .ok_or(Error::InsufficientOutputFinalizedAssets)?;
+    }
+    if output_assets.iter().any(|(_k, v)| v != &0) {
+        debug!("[emergency exit] claims exceed input finalized assets");
+        return Err(Error::InsufficientInputFinalizedAssets);
+    }
+    check_claim_payouts(config, payouts)?;
+
+    let post_account = AccountMerkleState::new_builder()
+        .merkle_root(kv_state.calculate_root()?.pack())
+        .count(prev_account.count())
+        .build();
+    let expected_post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .account(post_account)
+        .build();
+    if &expected_post_global_state != post_global_state {
+        debug!("[emergency exit] unexpected global state change");
+        return Err(Error::InvalidPostGlobalState);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+//! Forced withdrawal
+//!
+//! A user posts a deposit lock cell which carries a signed WithdrawalRequest
+//! if the block producer keeps ignoring the request.
+//! The block producer includes the request by consuming the cell in RollupSubmitBlock,
+//! and the cell's capacity is refunded to the owner.
+//! If the cell isn't consumed before `forced_withdrawal_timeout`,
+//! anyone can halt the rollup by RollupForceHalt, there is no way back to running.
+//! The halt proves the request is valid on the current account state: the sender
+//! signs it by a layer2 account signature cell, and its nonce and balances are
+//! proven against `GlobalState.account`, see `check_forced_withdrawal_requests`.
+//! Users exit with their finalized balances after `emergency_exit_timeout`, see `emergency`.
+
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import heap related library from `alloc`
+// https://doc.rust-lang.org/alloc/index.html
+use alloc::collections::BTreeMap;
+
+use gw_common::{
+    blake2b::new_blake2b, builtins::CKB_SUDT_ACCOUNT_ID, ckb_decimal::CKBCapacity,
+    registry_address::RegistryAddress, state::State, H256,
+};
+use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS, kv_state::KVState};
+use gw_types::{
+    core::{SigningType, Status},
+    packed::{GlobalState, KVPairVecReader, L2BlockReader, RawWithdrawalRequest, RollupConfig},
+    prelude::*,
+    U256,
+};
+use gw_utils::{
+    cells::{
+        lock_cells::{collect_forced_withdrawal_cells, find_challenge_cell},
+        rollup::load_forced_withdrawal_timeout,
+        types::ForcedWithdrawalCell,
+        utils::build_l2_sudt_script,
+    },
+    ckb_std::{
+        ckb_constants::Source,
+        debug,
+        high_level::{load_cell_capacity, load_cell_lock_hash, load_input_since, QueryIter},
+        since::Since,
+    },
+    error::Error,
+    mol_ext::table_extra_field,
+    signature::check_l2_account_signature_cell,
+};
+use gw_utils::{gw_common, gw_types};
+
+use super::{
+    check_deposit_withdrawal_custodian_cells, check_stake_cells, check_status, parse_bytes_field,
+};
+
+const FORCED_WITHDRAWAL_DISABLED: u16 = 1;
+const FORCED_WITHDRAWAL_CELL_NOT_FOUND: u16 = 2;
+const FORCED_WITHDRAWAL_REFUND_NOT_FOUND: u16 = 1;
+
+/// sub codes of `Error::InvalidWithdrawalRequest`
+const WRONG_CHAIN_ID: u16 = 1;
+const UNEXPECTED_NONCE: u16 = 2;
+const UNEXPECTED_SUDT_AMOUNT: u16 = 3;
+
+/// Check the capacity of consumed forced withdrawal cells are refunded to the owners
+fn check_forced_withdrawal_refunds(cells: &[ForcedWithdrawalCell]) -> Result<(), Error> {
+    let mut refunds: BTreeMap<[u8; 32], u128> = BTreeMap::new();
+    for cell in cells {
+        let refund = refunds
+            .entry(cell.args.owner_lock_hash().unpack())
+            .or_insert(0u128);
+        *refund = refund
+            .checked_add(cell.value.capacity.into())
+            .ok_or(Error::AmountOverflow)?;
+    }
+    for (owner_lock_hash, refund) in refunds {
+        let output_capacity = QueryIter::new(load_cell_lock_hash, Source::Output)
+            .enumerate()
+            .filter(|(_index, lock_hash)| lock_hash == &owner_lock_hash)
+            .map(|(index, _lock_hash)| load_cell_capacity(index, Source::Output))
+            .try_fold(0u128, |total, capacity| {
+                total
+                    .checked_add(capacity?.into())
+                    .ok_or(Error::AmountOverflow)
+            })?;
+        if output_capacity < refund {
+            debug!("[forced withdrawal] refund not found");
+            return Err(Error::InvalidOutput.with_sub_code(FORCED_WITHDRAWAL_REFUND_NOT_FOUND));
+        }
+    }
+    Ok(())
+}
+
+/// Check forced withdrawal cells consumed by RollupSubmitBlock,
+/// the requests must be included in the submitted blocks.
+pub fn check_included_forced_withdrawals(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+    blocks: &[L2BlockReader],
+) -> Result<(), Error> {
+    let cells = collect_forced_withdrawal_cells(rollup_type_hash, config, Source::Input)?;
+    for cell in cells.iter() {
+        let raw_request = cell.request.raw();
+        let included = blocks.iter().any(|block| {
+            block
+                .withdrawals()
+                .iter()
+                .any(|request| request.raw().as_slice() == raw_request.as_slice())
+        });
+        if !included {
+            debug!(
+                "[forced withdrawal] request of cell {} isn't included",
+                cell.index
+            );
+            return Err(Error::InvalidWithdrawalRequest);
+        }
+    }
+    check_forced_withdrawal_refunds(&cells)
+}
+
+/// table RollupForceHalt {
+///     kv_state: KVPairVec,
+///     kv_state_proof: Bytes,
+/// }
+fn parse_force_halt(args: &[u8]) -> Result<(KVPairVecReader, &[u8]), Error> {
+    let kv_state = table_extra_field(args, 0, 0).ok_or(Error::Encoding)?;
+    if KVPairVecReader::verify(kv_state, false).is_err() {
+        debug!("[force halt] invalid encoding of kv state");
+        return Err(Error::Encoding);
+    }
+    let kv_state_proof = parse_bytes_field(args, 1)?;
+    Ok((KVPairVecReader::new_unchecked(kv_state), kv_state_proof))
+}
+
+/// The message signed by the sender: blake2b(rollup_type_hash | raw_withdrawal)
+fn forced_withdrawal_message(
+    rollup_type_hash: &H256,
+    raw_withdrawal: &RawWithdrawalRequest,
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_type_hash.as_slice());
+    hasher.update(raw_withdrawal.as_slice());
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message.into()
+}
+
+fn burn_balance(
+    kv_state: &mut KVState,
+    sudt_id: u32,
+    address: &RegistryAddress,
+    amount: U256,
+) -> Result<(), Error> {
+    let balance = kv_state.get_sudt_balance(sudt_id, address)?;
+    if balance < amount {
+        debug!(
+            "[force halt] insufficient balance of sUDT {}, balance {}, amount {}",
+            sudt_id, balance, amount
+        );
+        return Err(Error::InsufficientAmount);
+    }
+    kv_state.burn_sudt(sudt_id, address, amount)?;
+    Ok(())
+}
+
+/// Check the forced withdrawal requests would be accepted by RollupSubmitBlock,
+/// a block producer can't be blamed for skipping an invalid request.
+///
+/// The requests are applied in order on the account state of the halted rollup:
+/// * the sender signs blake2b(rollup_type_hash | raw_withdrawal) by a layer2 account
+///   signature cell, wallets which only sign prefixed messages use `SigningType::WithPrefix`
+/// * the nonce of the request is the sender's nonce
+/// * the sender affords the fee, the capacity and the sUDT amount
+fn check_forced_withdrawal_requests(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+    cells: &[ForcedWithdrawalCell],
+    kv_state: &mut KVState,
+) -> Result<(), Error> {
+    let chain_id: u64 = config.chain_id().unpack();
+    for cell in cells {
+        let raw = cell.request.raw();
+        if raw.chain_id().unpack() != chain_id {
+            debug!(
+                "[force halt] request of cell {} has wrong chain id",
+                cell.index
+            );
+            return Err(Error::InvalidWithdrawalRequest.with_sub_code(WRONG_CHAIN_ID));
+        }
+
+        let account_script_hash: H256 = raw.account_script_hash().unpack();
+        let message = forced_withdrawal_message(rollup_type_hash, &raw);
+        check_l2_account_signature_cell(&account_script_hash, SigningType::Raw, message).or_else(
+            |_| {
+                check_l2_account_signature_cell(
+                    &account_script_hash,
+                    SigningType::WithPrefix,
+                    message,
+                )
+            },
+        )?;
+
+        let id = kv_state
+            .get_account_id_by_script_hash(&account_script_hash)?
+            .ok_or(Error::AccountNotFound)?;
+        let address = kv_state
+            .get_registry_address_by_script_hash(raw.registry_id().unpack(), &account_script_hash)?
+            .ok_or(Error::RegistryAddressNotFound)?;
+        let nonce = kv_state.get_nonce(id)?;
+        let withdrawal_nonce: u32 = raw.nonce().unpack();
+        if nonce != withdrawal_nonce {
+            debug!(
+                "[force halt] request of cell {} has nonce {}, the sender's nonce is {}",
+                cell.index, withdrawal_nonce, nonce
+            );
+            return Err(Error::InvalidWithdrawalRequest.with_sub_code(UNEXPECTED_NONCE));
+        }
+
+        let fee: u128 = raw.fee().unpack();
+        burn_balance(kv_state, CKB_SUDT_ACCOUNT_ID, &address, fee.into())?;
+        burn_balance(
+            kv_state,
+            CKB_SUDT_ACCOUNT_ID,
+            &address,
+            CKBCapacity::from_layer1(raw.capacity().unpack()).to_layer2(),
+        )?;
+        let amount: u128 = raw.amount().unpack();
+        match build_l2_sudt_script(rollup_type_hash, config, &raw.sudt_script_hash().unpack()) {
+            Some(script) => {
+                let sudt_id = kv_state
+                    .get_account_id_by_script_hash(&script.hash().into())?
+                    .ok_or(Error::AccountNotFound)?;
+                burn_balance(kv_state, sudt_id, &address, amount.into())?;
+            }
+            None if amount != 0 => {
+                return Err(Error::InvalidWithdrawalRequest.with_sub_code(UNEXPECTED_SUDT_AMOUNT));
+            }
+            None => {}
+        }
+        kv_state.set_nonce(id, nonce.saturating_add(1))?;
+    }
+    Ok(())
+}
+
+/// Verify RollupForceHalt
+///
+/// A forced withdrawal cell which satisfies `forced_withdrawal_timeout` proves
+/// the request isn't included in time, the rollup is halted.
+/// The kv state of the action proves the requests are valid on `prev_global_state.account`.
+pub fn verify_force_halt(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    args: &[u8],
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    check_status(prev_global_state, Status::Running)?;
+
+    let timeout = load_forced_withdrawal_timeout(config)?.ok_or_else(|| {
+        debug!("[force halt] forced withdrawal is disabled");
+        Error::InvalidDepositCell.with_sub_code(FORCED_WITHDRAWAL_DISABLED)
+    })?;
+    let cells = collect_forced_withdrawal_cells(&rollup_type_hash, config, Source::Input)?;
+    if cells.is_empty() {
+        debug!("[force halt] no forced withdrawal cells");
+        return Err(Error::InvalidDepositCell.with_sub_code(FORCED_WITHDRAWAL_CELL_NOT_FOUND));
+    }
+    // the since is a relative value, CKB ensures the cell has waited that long
+    let timeout = Since::new(timeout);
+    for cell in cells.iter() {
+        let input_since = Since::new(load_input_since(cell.index, Source::Input)?);
+        if input_since.flags() != timeout.flags() || input_since.as_u64() < timeout.as_u64() {
+            debug!(
+                "[force halt] forced withdrawal cell {} isn't timeout",
+                cell.index
+            );
+            return Err(Error::InvalidSince);
+        }
+    }
+    check_forced_withdrawal_refunds(&cells)?;
+
+    // the requests are valid on the current account state
+    let (kv_pairs, kv_state_proof) = parse_force_halt(args)?;
+    let prev_account = prev_global_state.account();
+    let prev_account_root: H256 = prev_account.merkle_root().unpack();
+    let mut tree_buffer = [Pair::default(); GW_MAX_KV_PAIRS];
+    let mut kv_state = KVState::build(
+        &mut tree_buffer,
+        kv_pairs,
+        kv_state_proof,
+        prev_account.count().unpack(),
+        Some(prev_account_root),
+    )?;
+    if kv_state.calculate_root()? != prev_account_root {
+        debug!("[force halt] kv state merkle proof error");
+        return Err(Error::MerkleProof);
+    }
+    check_forced_withdrawal_requests(&rollup_type_hash, config, &cells, &mut kv_state)?;
+
+    // do not allow other rollup cells in the transaction
+    check_deposit_withdrawal_custodian_cells(&rollup_type_hash, config)?;
+    check_stake_cells(&rollup_type_hash, config)?;
+    if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
+        || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
+    {
+        return Err(Error::InvalidChallengeCell);
+    }
+
+    // only the status is changed
+    let expected_post_global_state = {
+        let status: u8 = Status::Halting.into();
+        prev_global_state
+            .clone()
+            .as_builder()
+            .status(status.into())
+            .build()
+    };
+    if &expected_post_global_state != post_global_state {
+        debug!("[force halt] unexpected global state change");
+        return Err(Error::InvalidPostGlobalState);
+    }
+
+    Ok(())
+}
@@ -1,25 +1,40 @@
 use gw_common::H256;
 use gw_types::{
     core::Status,
-    packed::{GlobalState, RollupConfig},
+    packed::{BytesReader, GlobalState, RollupConfig},
+    prelude::*,
 };
 use gw_utils::gw_common;
 use gw_utils::gw_types;
 use gw_utils::{
     cells::lock_cells::{
-        collect_custodian_locks, collect_deposit_locks, collect_stake_cells,
-        collect_withdrawal_locks,
+        collect_custodian_locks, collect_deposit_locks, collect_forced_withdrawal_cells,
+        collect_stake_cells, collect_withdrawal_locks,
     },
     ckb_std::{ckb_constants::Source, debug},
     error::Error,
+    mol_ext::table_extra_field,
 };
 
 pub mod challenge;
+pub mod custodian;
+pub mod emergency;
+pub mod forced_withdrawal;
 pub mod revert;
 pub mod submit_block;
+pub mod update_config;
+
+/// Returns the raw data of a `Bytes` field
+fn parse_bytes_field(table: &[u8], index: usize) -> Result<&[u8], Error> {
+    let data = table_extra_field(table, 0, index).ok_or(Error::Encoding)?;
+    match BytesReader::verify(data, false) {
+        Ok(()) => Ok(BytesReader::new_unchecked(data).raw_data()),
+        Err(_) => Err(Error::Encoding),
+    }
+}
 
 /// this function ensure transaction doesn't contains any deposit / withdrawal / custodian
-pub fn check_rollup_lock_cells_except_stake(
+fn check_deposit_withdrawal_custodian_cells(
     rollup_type_hash: &H256,
     config: &RollupConfig,
 ) -> Result<(), Error> {
@@ -44,12 +59,8 @@ pub fn check_rollup_lock_cells_except_stake(
     Ok(())
 }
 
-/// this function ensure transaction doesn't contains any deposit / withdrawal / custodian / stake cells
-pub fn check_rollup_lock_cells(
-    rollup_type_hash: &H256,
-    config: &RollupConfig,
-) -> Result<(), Error> {
-    check_rollup_lock_cells_except_stake(rollup_type_hash, config)?;
+/// this function ensure transaction doesn't contains any stake cells
+fn check_stake_cells(rollup_type_hash: &H256, config: &RollupConfig) -> Result<(), Error> {
     if !collect_stake_cells(rollup_type_hash, config, Source::Input)?.is_empty() {
         debug!("unexpected input stake cell");
         return Err(Error::InvalidStakeCell);
@@ -61,6 +72,28 @@ pub fn check_rollup_lock_cells(
     Ok(())
 }
 
+/// this function ensure transaction doesn't contains any deposit / forced withdrawal / withdrawal / custodian
+pub fn check_rollup_lock_cells_except_stake(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+) -> Result<(), Error> {
+    check_deposit_withdrawal_custodian_cells(rollup_type_hash, config)?;
+    if !collect_forced_withdrawal_cells(rollup_type_hash, config, Source::Input)?.is_empty() {
+        debug!("unexpected input forced withdrawal cell");
+        return Err(Error::InvalidDepositCell);
+    }
+    Ok(())
+}
+
+/// this function ensure transaction doesn't contains any deposit / forced withdrawal / withdrawal / custodian / stake cells
+pub fn check_rollup_lock_cells(
+    rollup_type_hash: &H256,
+    config: &RollupConfig,
+) -> Result<(), Error> {
+    check_rollup_lock_cells_except_stake(rollup_type_hash, config)?;
+    check_stake_cells(rollup_type_hash, config)
+}
+
 pub fn check_status(global_state: &GlobalState, status: Status) -> Result<(), Error> {
     let expected_status: u8 = status.into();
     let status: u8 = global_state.status().into();
@@ -1,7 +1,7 @@
 use gw_common::{
     h256_ext::H256Ext,
     smt::{Blake2bHasher, CompiledMerkleProof},
-    H256,
+    CKB_SUDT_SCRIPT_ARGS, H256,
 };
 use gw_types::{
     core::Status,
@@ -31,7 +31,10 @@ use gw_utils::{
 };
 
 use super::{check_rollup_lock_cells_except_stake, check_status};
-use alloc::{collections::BTreeSet, vec::Vec};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
 use gw_utils::error::Error;
 
 /// Check challenge cell is maturity(on the layer1)
@@ -88,6 +91,40 @@ pub fn get_receiver_cells_capacity(
     Ok(capacity)
 }
 
+pub fn get_receiver_cells_sudt_amount(
+    config: &RollupConfig,
+    lock_hash: &[u8; 32],
+    sudt_script_hash: &H256,
+    source: Source,
+) -> Result<u128, Error> {
+    let mut amount = 0u128;
+    for index in search_lock_hashes(lock_hash, source) {
+        let value = fetch_capacity_and_sudt_value(config, index, source)?;
+        if &value.sudt_script_hash == sudt_script_hash {
+            amount = amount
+                .checked_add(value.amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+    }
+    Ok(amount)
+}
+
+fn get_burned_sudt_amount(
+    config: &RollupConfig,
+    sudt_script_hash: &H256,
+    source: Source,
+) -> Result<u128, Error> {
+    let mut amount = 0u128;
+    for cell in collect_burn_cells(config, source)? {
+        if &cell.value.sudt_script_hash == sudt_script_hash {
+            amount = amount
+                .checked_add(cell.value.amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+    }
+    Ok(amount)
+}
+
 /// Check rewards
 fn check_rewards(
     rollup_type_hash: &H256,
@@ -112,7 +149,10 @@ fn check_rewards(
     }
 
     // calculate rewards assets & burn assets
-    let total_stake_capacity: u128 = stake_cells.iter().map(|cell| cell.capacity as u128).sum();
+    let total_stake_capacity: u128 = stake_cells
+        .iter()
+        .map(|cell| cell.value.capacity as u128)
+        .sum();
     let reward_burn_rate: u8 = config.reward_burn_rate().into();
     let expected_reward_capacity =
         total_stake_capacity.saturating_mul(reward_burn_rate.into()) / 100;
@@ -147,6 +187,57 @@ fn check_rewards(
     if burned_capacity < expected_burn_capacity {
         return Err(Error::InvalidChallengeReward);
     }
+
+    // simple UDT stakes are rewarded and burned in the same rate
+    let mut total_stake_amounts: BTreeMap<H256, u128> = BTreeMap::new();
+    for value in stake_cells
+        .iter()
+        .map(|cell| &cell.value)
+        .filter(|value| value.sudt_script_hash != CKB_SUDT_SCRIPT_ARGS.into())
+    {
+        let total_amount = total_stake_amounts
+            .entry(value.sudt_script_hash)
+            .or_insert(0u128);
+        *total_amount = total_amount
+            .checked_add(value.amount)
+            .ok_or(Error::AmountOverflow)?;
+    }
+    let rewards_receiver_lock_hash = challenge_cell.args.rewards_receiver_lock().hash();
+    for (sudt_script_hash, total_stake_amount) in total_stake_amounts {
+        let expected_reward_amount =
+            total_stake_amount.saturating_mul(reward_burn_rate.into()) / 100;
+        let expected_burn_amount = total_stake_amount.saturating_sub(expected_reward_amount);
+
+        let received_amount = {
+            let input_amount = get_receiver_cells_sudt_amount(
+                config,
+                &rewards_receiver_lock_hash,
+                &sudt_script_hash,
+                Source::Input,
+            )?;
+            let output_amount = get_receiver_cells_sudt_amount(
+                config,
+                &rewards_receiver_lock_hash,
+                &sudt_script_hash,
+                Source::Output,
+            )?;
+            output_amount.saturating_sub(input_amount)
+        };
+        if received_amount < expected_reward_amount {
+            debug!("[check rewards] insufficient simple UDT rewards");
+            return Err(Error::InvalidChallengeReward);
+        }
+
+        let burned_amount = {
+            let input_amount = get_burned_sudt_amount(config, &sudt_script_hash, Source::Input)?;
+            let output_amount = get_burned_sudt_amount(config, &sudt_script_hash, Source::Output)?;
+            output_amount.saturating_sub(input_amount)
+        };
+        if burned_amount < expected_burn_amount {
+            debug!("[check rewards] insufficient simple UDT burned");
+            return Err(Error::InvalidChallengeReward);
+        }
+    }
     Ok(())
 }
 
@@ -9,7 +9,10 @@ use gw_state::constants::GW_MAX_KV_PAIRS;
 use gw_utils::ckb_std::high_level::load_input_since;
 use gw_utils::ckb_std::since::{LockValue, Since};
 use gw_utils::gw_common::registry_address::RegistryAddress;
-use gw_utils::gw_types::packed::{L2BlockReader, WithdrawalRequestReader};
+use gw_utils::gw_types::packed::{
+    BlockMerkleState, L2BlockReader, RollupSubmitBlockReader, WithdrawalRequestReader,
+};
+use gw_utils::mol_ext::{parse_dynvec, table_extra_field};
 
 // Import CKB syscalls and structures
 // https://nervosnetwork.github.io/ckb-std/riscv64imac-unknown-none-elf/doc/ckb_std/index.html
@@ -18,7 +21,7 @@ use gw_state::kv_state::KVState;
 use gw_utils::gw_common::{self, ckb_decimal::CKBCapacity};
 use gw_utils::gw_types::{self, U256};
 
-use super::check_status;
+use super::{check_status, forced_withdrawal::check_included_forced_withdrawals};
 use crate::types::BlockContext;
 use gw_utils::{
     cells::{
@@ -26,10 +29,12 @@ use gw_utils::{
             collect_custodian_locks, collect_deposit_locks, collect_withdrawal_locks,
             find_block_producer_stake_cell, find_challenge_cell,
         },
-        types::{CellValue, DepositRequestCell, WithdrawalCell},
+        rollup::load_allowed_stake_assets,
+        types::{CellValue, DepositRecipient, DepositRequestCell, WithdrawalCell},
         utils::build_l2_sudt_script,
     },
     error::Error,
+    signature::DEPOSIT_CALL_SIGNATURE,
 };
 
 use gw_common::{
@@ -43,11 +48,25 @@ use gw_common::{
 use gw_types::{
     bytes::Bytes,
     core::{ScriptHashType, Status},
-    packed::{Byte32, GlobalState, RawL2Block, RollupConfig},
+    packed::{Byte32, GlobalState, RawL2Block, RawL2Transaction, RollupConfig},
     prelude::*,
 };
 
-fn build_assets_map_from_cells<'a, I: Iterator<Item = &'a CellValue>>(
+/// sub codes of `Error::InvalidBlock`
+const UNEXPECTED_DEPOSIT_CALL: u16 = 1;
+const DEPOSIT_CALL_MISMATCH: u16 = 2;
+const BLOCK_NUMBER_MISMATCH: u16 = 3;
+const PARENT_BLOCK_HASH_MISMATCH: u16 = 4;
+const POST_BLOCK_COUNT_MISMATCH: u16 = 5;
+const PREV_ACCOUNT_MISMATCH: u16 = 6;
+const TX_COUNT_MISMATCH: u16 = 7;
+const WITHDRAWAL_COUNT_MISMATCH: u16 = 8;
+const INVALID_BLOCK_TIMESTAMP: u16 = 9;
+const EMPTY_BATCH: u16 = 10;
+const MIXED_BLOCK_PRODUCERS: u16 = 11;
+const MISSING_DEPOSIT_CALL: u16 = 12;
+
+pub(crate) fn build_assets_map_from_cells<'a, I: Iterator<Item = &'a CellValue>>(
     cells: I,
 ) -> Result<BTreeMap<H256, u128>, Error> {
     let mut assets = BTreeMap::new();
@@ -67,7 +86,7 @@ fn build_assets_map_from_cells<'a, I: Iterator<Item = &'a CellValue>>(
 fn check_withdrawal_cells<'a>(
     context: &BlockContext,
     mut withdrawal_requests: Vec<WithdrawalRequestReader<'a>>,
-    withdrawal_cells: &[WithdrawalCell],
+    withdrawal_cells: &[&WithdrawalCell],
 ) -> Result<(), Error> {
     // iter outputs withdrawal cells, check each cell has a corresponded withdrawal request
     for cell in withdrawal_cells {
@@ -218,6 +237,74 @@ fn check_output_custodian_cells(
     Ok(())
 }
 
+/// Returns the registry address of the recipient, the recipient account must exist
+fn get_deposit_recipient_address(
+    kv_state: &KVState,
+    registry_id: u32,
+    recipient: &DepositRecipient,
+) -> Result<RegistryAddress, Error> {
+    match recipient {
+        DepositRecipient::AccountId(id) => {
+            let script_hash = kv_state.get_script_hash(*id)?;
+            if script_hash.is_zero() {
+                debug!("[check deposit] recipient account {} not found", id);
+                return Err(Error::AccountNotFound);
+            }
+            kv_state
+                .get_registry_address_by_script_hash(registry_id, &script_hash)?
+                .ok_or(Error::RegistryAddressNotFound)
+        }
+        DepositRecipient::RegistryAddress(address) => {
+            if kv_state
+                .get_script_hash_by_registry_address(address)?
+                .is_none()
+            {
+                debug!("[check deposit] recipient registry address not found");
+                return Err(Error::AccountNotFound);
+            }
+            Ok(address.clone())
+        }
+    }
+}
+
+/// Returns the registry address of the `layer2_lock` EOA, creates the EOA if it doesn't exist
+fn find_or_create_eoa(
+    kv_state: &mut KVState,
+    registry_ctx: &gw_common::registry::context::RegistryContext,
+    registry_id: u32,
+    request: &DepositRequestCell,
+) -> Result<RegistryAddress, Error> {
+    // check that account's script is a valid EOA script
+    if request.account_script.hash_type() != ScriptHashType::Type.into() {
+        return Err(Error::UnknownEOAScript);
+    }
+    let address = match kv_state.get_account_id_by_script_hash(&request.account_script_hash)? {
+        Some(_id) => {
+            // account is exist, query registry address
+            kv_state
+                .get_registry_address_by_script_hash(registry_id, &request.account_script_hash)?
+                .ok_or(Error::RegistryAddressNotFound)?
+        }
+        None => {
+            // account isn't exist
+            let _new_id = kv_state.create_account(request.account_script_hash)?;
+            let script = &request.account_script;
+            let addr = registry_ctx.extract_registry_address_from_deposit(
+                registry_id,
+                &script.code_hash(),
+                &script.args().raw_data(),
+            )?;
+            // mapping addr to script hash
+            kv_state.mapping_registry_address_to_script_hash(
+                addr.clone(),
+                request.account_script_hash,
+            )?;
+            addr
+        }
+    };
+    Ok(address)
+}
+
 fn check_layer2_deposit(
     rollup_type_hash: &H256,
     config: &RollupConfig,
@@ -228,36 +315,12 @@ fn check_layer2_deposit(
         config.allowed_eoa_type_hashes().into_iter().collect(),
     );
     for request in deposit_cells {
-        // check that account's script is a valid EOA script
-        if request.account_script.hash_type() != ScriptHashType::Type.into() {
-            return Err(Error::UnknownEOAScript);
-        }
         let registry_id: u32 = request.args.registry_id().unpack();
 
-        // find or create EOA
-        let address = match kv_state.get_account_id_by_script_hash(&request.account_script_hash)? {
-            Some(_id) => {
-                // account is exist, query registry address
-                kv_state
-                    .get_registry_address_by_script_hash(registry_id, &request.account_script_hash)?
-                    .ok_or(Error::RegistryAddressNotFound)?
-            }
-            None => {
-                // account isn't exist
-                let _new_id = kv_state.create_account(request.account_script_hash)?;
-                let script = &request.account_script;
-                let addr = registry_ctx.extract_registry_address_from_deposit(
-                    registry_id,
-                    &script.code_hash(),
-                    &script.args().raw_data(),
-                )?;
-                // mapping addr to script hash
-                kv_state.mapping_registry_address_to_script_hash(
-                    addr.clone(),
-                    request.account_script_hash,
-                )?;
-                addr
-            }
+        let address = match &request.recipient {
+            // credit an existing account, e.g. a user of an exchange or a Polyjuice contract
+            Some(recipient) => get_deposit_recipient_address(kv_state, registry_id, recipient)?,
+            None => find_or_create_eoa(kv_state, &registry_ctx, registry_id, request)?,
         };
 
         // mint CKB
@@ -293,6 +356,108 @@ fn check_layer2_deposit(
     Ok(())
 }
 
+/// Returns the deposit calls which must be included in the block, in the order of deposit cells
+///
+/// A call never blocks its deposit: the deposit is minted before the call, a call which can't
+/// be sent is dropped, and a call failed at execution is still included like other transactions.
+///
+/// The deposit cell isn't authorized by the layer2 account, so a call is only sent when its
+/// deposit creates the account: the account doesn't exist before the block and no other deposit
+/// of the block credits it. Otherwise anyone could send a call from an existing account.
+fn collect_deposit_calls<'a>(
+    config: &RollupConfig,
+    kv_state: &KVState,
+    deposit_cells: &'a [DepositRequestCell],
+) -> Result<Vec<(&'a DepositRequestCell, &'a RawL2Transaction)>, Error> {
+    let mut deposit_calls = Vec::new();
+    for request in deposit_cells {
+        let call = match request.call.as_ref() {
+            Some(call) => call,
+            None => continue,
+        };
+        // the call is sent from `layer2_lock`, which isn't credited if there is a recipient
+        if request.recipient.is_some() {
+            debug!(
+                "[check deposit] drop call of deposit {} with a recipient",
+                request.index
+            );
+            continue;
+        }
+        if call.chain_id().as_slice() != config.chain_id().as_slice() {
+            debug!(
+                "[check deposit] drop call of deposit {} with a wrong chain id",
+                request.index
+            );
+            continue;
+        }
+        let is_created_by_deposit = kv_state
+            .get_account_id_by_script_hash(&request.account_script_hash)?
+            .is_none()
+            && deposit_cells.iter().all(|other| {
+                other.index == request.index
+                    || other.recipient.is_some()
+                    || other.account_script_hash != request.account_script_hash
+            });
+        if !is_created_by_deposit {
+            debug!(
+                "[check deposit] drop call of deposit {} which doesn't create the account",
+                request.index
+            );
+            continue;
+        }
+        deposit_calls.push((request, call));
+    }
+    Ok(deposit_calls)
+}
+
+/// Deposit calls are the first transactions of the block, each one is sent from its created
+/// account right after the deposit mint
+fn check_deposit_calls(
+    kv_state: &KVState,
+    block: &L2BlockReader,
+    deposit_calls: &[(&DepositRequestCell, &RawL2Transaction)],
+) -> Result<(), Error> {
+    // the block producer must include every deposit call
+    let transactions = block.transactions();
+    if transactions.len() < deposit_calls.len() {
+        debug!(
+            "[check deposit] missing deposit calls, deposit calls: {} transactions: {}",
+            deposit_calls.len(),
+            transactions.len()
+        );
+        return Err(Error::InvalidBlock.with_sub_code(MISSING_DEPOSIT_CALL));
+    }
+    for (i, tx) in transactions.iter().enumerate() {
+        let is_deposit_call = tx.signature().raw_data() == DEPOSIT_CALL_SIGNATURE;
+        let (request, call) = match deposit_calls.get(i) {
+            Some(deposit_call) => deposit_call,
+            None if is_deposit_call => {
+                debug!("[check deposit] tx {} isn't a deposit call", i);
+                return Err(Error::InvalidBlock.with_sub_code(UNEXPECTED_DEPOSIT_CALL));
+            }
+            None => continue,
+        };
+        let raw_tx = tx.raw();
+        let from_id: u32 = raw_tx.from_id().unpack();
+        let nonce: u32 = raw_tx.nonce().unpack();
+        if !is_deposit_call
+            || kv_state.get_account_id_by_script_hash(&request.account_script_hash)?
+                != Some(from_id)
+            || nonce != 0
+            || raw_tx.chain_id().as_slice() != call.chain_id().as_slice()
+            || raw_tx.to_id().as_slice() != call.to_id().as_slice()
+            || raw_tx.args().as_slice() != call.args().as_slice()
+        {
+            debug!(
+                "[check deposit] tx {} mismatch the call of deposit {}",
+                i, request.index
+            );
+            return Err(Error::InvalidBlock.with_sub_code(DEPOSIT_CALL_MISMATCH));
+        }
+    }
+    Ok(())
+}
+
 fn check_layer2_withdrawal(
     rollup_type_hash: &H256,
     config: &RollupConfig,
@@ -392,7 +557,7 @@ fn load_block_context_and_state<'a>(
             "[check block context] block number error, number: {}, expected_number: {}",
             number, expected_number
         );
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(BLOCK_NUMBER_MISMATCH));
     }
 
     let timestamp: u64 = raw_block.timestamp().unpack();
@@ -401,7 +566,7 @@ fn load_block_context_and_state<'a>(
     // verify parent block hash
     if raw_block.parent_block_hash().as_slice() != prev_global_state.tip_block_hash().as_slice() {
         debug!("[check block context] parent block hash error");
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(PARENT_BLOCK_HASH_MISMATCH));
     }
 
     // verify prev block merkle proof
@@ -429,7 +594,7 @@ fn load_block_context_and_state<'a>(
     // Check post block merkle proof
     if number + 1 != post_global_state.block().count().unpack() {
         debug!("[check block context] post global state block count error");
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(POST_BLOCK_COUNT_MISMATCH));
     }
 
     let post_block_root: [u8; 32] = post_global_state.block().merkle_root().unpack();
@@ -455,7 +620,7 @@ fn load_block_context_and_state<'a>(
     // Check prev account state
     if raw_block.prev_account().as_slice() != prev_global_state.account().as_slice() {
         debug!("[check block context] block's prev account error");
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(PREV_ACCOUNT_MISMATCH));
     }
 
     // Check post account state
@@ -509,16 +674,33 @@ fn verify_block_producer(
         &owner_lock_hash,
     )?
     .ok_or(Error::InvalidStakeCell)?;
-    // check stake cell capacity
-    let required_staking_capacity: u64 = config.required_staking_capacity().unpack();
-    if output_stake_cell.capacity < required_staking_capacity {
-        debug!(
-            "[verify block producer] stake cell's capacity is insufficient {} {}",
-            output_stake_cell.capacity, required_staking_capacity
-        );
-        return Err(Error::InvalidStakeCell);
+    let output_stake_value = &output_stake_cell.value;
+    if output_stake_value.sudt_script_hash == CKB_SUDT_SCRIPT_ARGS.into() {
+        // check stake cell capacity
+        let required_staking_capacity: u64 = config.required_staking_capacity().unpack();
+        if output_stake_value.capacity < required_staking_capacity {
+            debug!(
+                "[verify block producer] stake cell's capacity is insufficient {} {}",
+                output_stake_value.capacity, required_staking_capacity
+            );
+            return Err(Error::InvalidStakeCell);
+        }
+    } else {
+        // check stake cell simple UDT amount
+        let stake_asset = load_allowed_stake_assets(config)?
+            .into_iter()
+            .find(|asset| asset.sudt_script_hash == output_stake_value.sudt_script_hash)
+            .ok_or(Error::InvalidStakeCell)?;
+        if output_stake_value.amount < stake_asset.minimum_amount {
+            debug!(
+                "[verify block producer] stake cell's amount is insufficient {} {}",
+                output_stake_value.amount, stake_asset.minimum_amount
+            );
+            return Err(Error::InvalidStakeCell);
+        }
     }
-    // make sure input stake cell is identical to the output stake cell if we have one
+    // make sure input stake cell is corresponded to the output stake cell if we have one,
+    // the surplus above the requirement can be withdrawn
     if let Some(input_stake_cell) = find_block_producer_stake_cell(
         &context.rollup_type_hash,
         config,
@@ -531,7 +713,7 @@ fn verify_block_producer(
             .stake_block_number(raw_block.number().to_entity())
             .build();
         if expected_stake_lock_args != output_stake_cell.args
-            || input_stake_cell.capacity > output_stake_cell.capacity
+            || input_stake_cell.value.sudt_script_hash != output_stake_value.sudt_script_hash
         {
             debug!("the output stake cell isn't corresponded to the input one");
             return Err(Error::InvalidStakeCell);
@@ -601,7 +783,7 @@ fn check_block_transactions(block: &L2BlockReader, kv_state: &KVState) -> Result
             tx_count,
             block.transactions().len()
         );
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(TX_COUNT_MISMATCH));
     }
 
     let leaves = block
@@ -662,7 +844,7 @@ fn check_block_withdrawals(block: &L2BlockReader) -> Result<(), Error> {
             withdrawal_count,
             block.withdrawals().len()
         );
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(WITHDRAWAL_COUNT_MISMATCH));
     }
 
     let leaves = block
@@ -733,22 +915,120 @@ fn check_block_timestamp(
             rollup_input_timestamp,
             tip_block_timestamp
         );
-        return Err(Error::InvalidBlock);
+        return Err(Error::InvalidBlock.with_sub_code(INVALID_BLOCK_TIMESTAMP));
     }
 
     Ok(())
 }
 
-/// Verify Deposit & Withdrawal
-pub fn verify(
-    rollup_type_hash: H256,
+/// Extra fields appended to RollupSubmitBlock after `reverted_block_proof`
+mod submit_block_extra_field {
+    /// following_blocks: L2BlockVec, contiguous blocks submitted after `block`
+    pub const FOLLOWING_BLOCKS: usize = 0;
+}
+
+/// Returns the submitted blocks: `block` and the following blocks of a batch
+pub fn parse_submitted_blocks<'a>(
+    args: &RollupSubmitBlockReader<'a>,
+) -> Result<Vec<L2BlockReader<'a>>, Error> {
+    let mut blocks = alloc::vec![args.block()];
+    let args_slice: &'a [u8] = args.as_slice();
+    if let Some(following_blocks) = table_extra_field(
+        args_slice,
+        RollupSubmitBlockReader::FIELD_COUNT,
+        submit_block_extra_field::FOLLOWING_BLOCKS,
+    ) {
+        for raw_block in parse_dynvec(following_blocks)? {
+            let block = L2BlockReader::from_slice(raw_block).map_err(|_err| {
+                debug!("[parse submitted blocks] invalid following block");
+                Error::Encoding
+            })?;
+            blocks.push(block);
+        }
+    }
+    Ok(blocks)
+}
+
+fn build_post_global_state(
+    prev_global_state: &GlobalState,
+    block: &L2BlockReader,
+    block_merkle_state: BlockMerkleState,
+    last_finalized_block_number: u64,
+    version: u8,
+) -> GlobalState {
+    let raw_block = block.raw();
+    // because of the optimistic challenge mechanism,
+    // we just believe the post account in the block,
+    // if the post account state is invalid then someone will send a challenge
+    let account_merkle_state = raw_block.post_account();
+    let tip_block_timestamp: u64 = if version == 0 {
+        0
+    } else {
+        raw_block.timestamp().unpack()
+    };
+
+    prev_global_state
+        .clone()
+        .as_builder()
+        .account(account_merkle_state.to_entity())
+        .block(block_merkle_state)
+        .tip_block_hash(raw_block.hash().pack())
+        .tip_block_timestamp(tip_block_timestamp.pack())
+        .last_finalized_block_number(last_finalized_block_number.pack())
+        .version(version.into())
+        .build()
+}
+
+/// Build the global state after a block in the middle of a batch,
+/// the block merkle root is calculated from the block proof.
+fn build_intermediate_global_state(
     config: &RollupConfig,
     block: &L2BlockReader,
     prev_global_state: &GlobalState,
     post_global_state: &GlobalState,
-) -> Result<(), Error> {
-    check_status(prev_global_state, Status::Running)?;
+) -> Result<GlobalState, Error> {
+    let raw_block = block.raw();
+    let number: u64 = raw_block.number().unpack();
+    let block_hash: H256 = raw_block.hash().into();
+    let block_proof: Bytes = block.block_proof().unpack();
+    let block_merkle_root = {
+        let mut buf = [Pair::default(); 256];
+        let mut block_tree = Tree::new(&mut buf);
+        block_tree
+            .update(&RawL2Block::compute_smt_key(number), &block_hash.into())
+            .map_err(|err| {
+                debug!("[intermediate global state] update kv error: {}", err);
+                Error::MerkleProof
+            })?;
+        block_tree.calculate_root(&block_proof).map_err(|err| {
+            debug!("[intermediate global state] calculate root error: {}", err);
+            Error::MerkleProof
+        })?
+    };
+    let block_merkle_state = BlockMerkleState::new_builder()
+        .merkle_root(block_merkle_root.pack())
+        .count((number + 1).pack())
+        .build();
+
+    let last_finalized_block_number = number.saturating_sub(config.finality_blocks().unpack());
+    Ok(build_post_global_state(
+        prev_global_state,
+        block,
+        block_merkle_state,
+        last_finalized_block_number,
+        post_global_state.version().into(),
+    ))
+}
 
+/// Verify a block's state transition: prev_global_state -> post_global_state
+fn verify_block(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    block: &L2BlockReader,
+    deposit_cells: &[DepositRequestCell],
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<BlockContext, Error> {
     // check checkpoints
     check_state_checkpoints(block)?;
 
@@ -767,24 +1047,67 @@ pub fn verify(
         prev_global_state,
         post_global_state,
     )?;
-    // Verify block producer
-    verify_block_producer(config, &context, block)?;
+
+    // Deposit calls: only sent from the accounts created by their deposits
+    let deposit_calls = collect_deposit_calls(config, &kv_state, deposit_cells)?;
+    // Withdrawal token: Layer2 SUDT -> withdrawals
+    check_layer2_withdrawal(&rollup_type_hash, config, &mut kv_state, block)?;
+    // Mint token: deposit requests -> layer2 SUDT
+    check_layer2_deposit(&rollup_type_hash, config, &mut kv_state, deposit_cells)?;
+    // Deposit calls: credited accounts -> first transactions
+    check_deposit_calls(&kv_state, block, &deposit_calls)?;
+    // Check transactions
+    check_block_transactions(block, &kv_state)?;
+
+    // Verify Post state
+    // we have verified the post block merkle state
+    let actual_post_global_state = build_post_global_state(
+        prev_global_state,
+        block,
+        post_global_state.block(),
+        context.finalized_number,
+        post_global_state.version().into(),
+    );
+    if &actual_post_global_state != post_global_state {
+        return Err(Error::InvalidPostGlobalState);
+    }
+
+    Ok(context)
+}
+
+/// Verify Deposit & Withdrawal
+///
+/// `blocks` is a contiguous run of blocks, each block is verified against the
+/// global state after its parent block, the last one must produce the post global state.
+/// Deposit cells are included in the first block.
+pub fn verify(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    blocks: &[L2BlockReader],
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    check_status(prev_global_state, Status::Running)?;
+
+    let first_block = blocks
+        .first()
+        .ok_or(Error::InvalidBlock.with_sub_code(EMPTY_BATCH))?;
+    // blocks in a batch are produced by the same block producer
+    let stake_owner_lock_hash = first_block.raw().stake_cell_owner_lock_hash();
+    if blocks.iter().any(|block| {
+        block.raw().stake_cell_owner_lock_hash().as_slice() != stake_owner_lock_hash.as_slice()
+    }) {
+        debug!("[verify submit blocks] blocks have different stake cell owner lock hashes");
+        return Err(Error::InvalidBlock.with_sub_code(MIXED_BLOCK_PRODUCERS));
+    }
+
     // collect withdrawal cells
     let withdrawal_cells: Vec<_> =
-        collect_withdrawal_locks(&context.rollup_type_hash, config, Source::Output)?;
+        collect_withdrawal_locks(&rollup_type_hash, config, Source::Output)?;
     // collect deposit cells
-    let deposit_cells = collect_deposit_locks(&context.rollup_type_hash, config, Source::Input)?;
-    // Check new cells and reverted cells: deposit / withdrawal / custodian
-    let withdrawal_requests_vec = block.withdrawals();
-    let withdrawal_requests = withdrawal_requests_vec.iter().collect();
-    check_withdrawal_cells(&context, withdrawal_requests, &withdrawal_cells)?;
-    let input_finalized_assets = check_input_custodian_cells(config, &context, withdrawal_cells)?;
-    check_output_custodian_cells(
-        config,
-        &context,
-        deposit_cells.clone(),
-        input_finalized_assets,
-    )?;
+    let deposit_cells = collect_deposit_locks(&rollup_type_hash, config, Source::Input)?;
+    // Forced withdrawal cells must be included in the submitted blocks
+    check_included_forced_withdrawals(&rollup_type_hash, config, blocks)?;
     // Ensure no challenge cells in submitting block transaction
     if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
         || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
@@ -792,45 +1115,63 @@ pub fn verify(
         return Err(Error::InvalidChallengeCell);
     }
 
-    // Withdrawal token: Layer2 SUDT -> withdrawals
-    check_layer2_withdrawal(&rollup_type_hash, config, &mut kv_state, block)?;
-    // Mint token: deposit requests -> layer2 SUDT
-    check_layer2_deposit(&rollup_type_hash, config, &mut kv_state, &deposit_cells)?;
-    // Check transactions
-    check_block_transactions(block, &kv_state)?;
-
-    // Verify Post state
-    let actual_post_global_state = {
-        // because of the optimistic challenge mechanism,
-        // we just believe the post account in the block,
-        // if the post account state is invalid then someone will send a challenge
-        let account_merkle_state = block.raw().post_account();
-        // we have verified the post block merkle state
-        let block_merkle_state = post_global_state.block();
-        // last finalized block number
-        let last_finalized_block_number = context.finalized_number;
-        let version = post_global_state.version();
-        let tip_block_timestamp = if version == 0.into() {
-            0
+    let mut first_block_context = None;
+    let mut block_prev_global_state = prev_global_state.clone();
+    let mut checked_withdrawal_cells = 0;
+    for (i, block) in blocks.iter().enumerate() {
+        let block_post_global_state = if i + 1 == blocks.len() {
+            post_global_state.clone()
         } else {
-            context.timestamp
+            build_intermediate_global_state(
+                config,
+                block,
+                &block_prev_global_state,
+                post_global_state,
+            )?
         };
+        let block_deposit_cells: &[DepositRequestCell] = if i == 0 { &deposit_cells } else { &[] };
+
+        let context = verify_block(
+            rollup_type_hash,
+            config,
+            block,
+            block_deposit_cells,
+            &block_prev_global_state,
+            &block_post_global_state,
+        )?;
 
-        prev_global_state
-            .clone()
-            .as_builder()
-            .account(account_merkle_state.to_entity())
-            .block(block_merkle_state)
-            .tip_block_hash(context.block_hash.pack())
-            .tip_block_timestamp(tip_block_timestamp.pack())
-            .last_finalized_block_number(last_finalized_block_number.pack())
-            .version(version)
-            .build()
-    };
+        // Check withdrawal cells of the block
+        let block_withdrawal_cells: Vec<&WithdrawalCell> = withdrawal_cells
+            .iter()
+            .filter(|cell| {
+                cell.args.withdrawal_block_hash().as_slice() == context.block_hash.as_slice()
+            })
+            .collect();
+        let withdrawal_requests_vec = block.withdrawals();
+        let withdrawal_requests = withdrawal_requests_vec.iter().collect();
+        check_withdrawal_cells(&context, withdrawal_requests, &block_withdrawal_cells)?;
+        checked_withdrawal_cells += block_withdrawal_cells.len();
+
+        // Verify block producer by the last block
+        if i + 1 == blocks.len() {
+            verify_block_producer(config, &context, block)?;
+        }
 
-    if &actual_post_global_state != post_global_state {
-        return Err(Error::InvalidPostGlobalState);
+        if first_block_context.is_none() {
+            first_block_context = Some(context);
+        }
+        block_prev_global_state = block_post_global_state;
     }
+    if checked_withdrawal_cells != withdrawal_cells.len() {
+        debug!("[verify submit blocks] found withdrawal cells of unknown blocks");
+        return Err(Error::InvalidWithdrawalCell);
+    }
+
+    // Check new cells and reverted cells: deposit / withdrawal / custodian
+    // custodian cells are finalized according to the first block
+    let context = first_block_context.ok_or(Error::InvalidBlock.with_sub_code(EMPTY_BATCH))?;
+    let input_finalized_assets = check_input_custodian_cells(config, &context, withdrawal_cells)?;
+    check_output_custodian_cells(config, &context, deposit_cells, input_finalized_assets)?;
 
     Ok(())
 }
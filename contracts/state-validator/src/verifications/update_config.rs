@@ -0,0 +1,209 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use gw_common::H256;
+use gw_types::{
+    core::Status,
+    packed::{GlobalState, RollupConfig},
+    prelude::*,
+};
+use gw_utils::{
+    cells::{
+        lock_cells::find_challenge_cell,
+        rollup::{
+            load_eip712_domain_version, load_emergency_exit_timeout,
+            load_forced_withdrawal_timeout, load_governance_lock_hash, load_rollup_config,
+        },
+    },
+    ckb_std::{
+        ckb_constants::Source,
+        debug,
+        high_level::{load_cell_lock_hash, QueryIter},
+        since::{LockValue, Since},
+    },
+    error::Error,
+};
+use gw_utils::{gw_common, gw_types};
+
+use super::{check_rollup_lock_cells, check_status};
+
+const GOVERNANCE_NOT_CONFIGURED: u16 = 1;
+const GOVERNANCE_CELL_NOT_FOUND: u16 = 2;
+const IMMUTABLE_CONFIG_CHANGED: u16 = 1;
+const INVALID_REWARD_BURN_RATE: u16 = 2;
+const UNEXPECTED_GLOBAL_STATE_CHANGE: u16 = 3;
+const FINALITY_BLOCKS_DECREASED: u16 = 4;
+const INVALID_TIMEOUT_CHANGE: u16 = 5;
+
+/// One update can at most double a configured timeout
+const MAX_TIMEOUT_GROWTH: u128 = 2;
+
+/// Fields that can't be changed by RollupUpdateConfig,
+/// existing cells reference these scripts, the chain id and the EIP-712 domain version
+/// are part of the signing messages of pending transactions and withdrawals.
+fn check_immutable_config_fields(
+    prev_config: &RollupConfig,
+    post_config: &RollupConfig,
+) -> Result<(), Error> {
+    let immutable_fields = [
+        (
+            prev_config.l1_sudt_script_type_hash(),
+            post_config.l1_sudt_script_type_hash(),
+        ),
+        (
+            prev_config.custodian_script_type_hash(),
+            post_config.custodian_script_type_hash(),
+        ),
+        (
+            prev_config.deposit_script_type_hash(),
+            post_config.deposit_script_type_hash(),
+        ),
+        (
+            prev_config.withdrawal_script_type_hash(),
+            post_config.withdrawal_script_type_hash(),
+        ),
+        (
+            prev_config.challenge_script_type_hash(),
+            post_config.challenge_script_type_hash(),
+        ),
+        (
+            prev_config.stake_script_type_hash(),
+            post_config.stake_script_type_hash(),
+        ),
+        (
+            prev_config.l2_sudt_validator_script_type_hash(),
+            post_config.l2_sudt_validator_script_type_hash(),
+        ),
+        (prev_config.burn_lock_hash(), post_config.burn_lock_hash()),
+    ];
+    if immutable_fields
+        .iter()
+        .any(|(prev, post)| prev.as_slice() != post.as_slice())
+        || prev_config.chain_id().as_slice() != post_config.chain_id().as_slice()
+        || load_eip712_domain_version(prev_config)? != load_eip712_domain_version(post_config)?
+    {
+        debug!("[update config] immutable config fields changed");
+        return Err(Error::InvalidPostGlobalState.with_sub_code(IMMUTABLE_CONFIG_CHANGED));
+    }
+
+    // blocks in their challenge period must not be finalized earlier
+    let prev_finality_blocks: u64 = prev_config.finality_blocks().unpack();
+    let post_finality_blocks: u64 = post_config.finality_blocks().unpack();
+    if post_finality_blocks < prev_finality_blocks {
+        debug!(
+            "[update config] finality blocks decreased {} {}",
+            prev_finality_blocks, post_finality_blocks
+        );
+        return Err(Error::InvalidPostGlobalState.with_sub_code(FINALITY_BLOCKS_DECREASED));
+    }
+
+    check_timeouts(prev_config, post_config)?;
+
+    let reward_burn_rate: u8 = post_config.reward_burn_rate().into();
+    if reward_burn_rate > 100 {
+        debug!(
+            "[update config] invalid reward burn rate {}",
+            reward_burn_rate
+        );
+        return Err(Error::InvalidPostGlobalState.with_sub_code(INVALID_REWARD_BURN_RATE));
+    }
+    Ok(())
+}
+
+/// Returns the value of a since as a fraction, an epoch has fraction parts
+fn since_value(since: &Since) -> Option<(u128, u128)> {
+    match since.extract_lock_value()? {
+        LockValue::BlockNumber(value) | LockValue::Timestamp(value) => Some((value.into(), 1)),
+        LockValue::EpochNumberWithFraction(epoch) => {
+            let length: u128 = core::cmp::max(epoch.length(), 1).into();
+            let index: u128 = epoch.index().into();
+            let number: u128 = epoch.number().into();
+            Some((number * length + index, length))
+        }
+    }
+}
+
+/// Timeouts of the escape hatches, i.e. forced withdrawals and emergency exits,
+/// can't be disabled once configured, and one update can't extend them unboundedly.
+fn check_timeouts(prev_config: &RollupConfig, post_config: &RollupConfig) -> Result<(), Error> {
+    let forced_withdrawal_timeout_changed = match (
+        load_forced_withdrawal_timeout(prev_config)?.map(Since::new),
+        load_forced_withdrawal_timeout(post_config)?.map(Since::new),
+    ) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(prev), Some(post)) => {
+            prev.flags() != post.flags()
+                || match (since_value(&prev), since_value(&post)) {
+                    (Some((prev_value, prev_unit)), Some((post_value, post_unit))) => {
+                        post_value * prev_unit > MAX_TIMEOUT_GROWTH * prev_value * post_unit
+                    }
+                    _ => true,
+                }
+        }
+    };
+    let emergency_exit_timeout_changed = match (
+        load_emergency_exit_timeout(prev_config)?,
+        load_emergency_exit_timeout(post_config)?,
+    ) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(prev), Some(post)) => u128::from(post) > MAX_TIMEOUT_GROWTH * u128::from(prev),
+    };
+    if forced_withdrawal_timeout_changed || emergency_exit_timeout_changed {
+        debug!("[update config] invalid timeout change");
+        return Err(Error::InvalidPostGlobalState.with_sub_code(INVALID_TIMEOUT_CHANGE));
+    }
+    Ok(())
+}
+
+/// Verify RollupUpdateConfig
+///
+/// The action is authorized by an input cell with the governance lock,
+/// the lock decides the policy, e.g. multisig or time-locked.
+/// The post global state must only change `rollup_config_hash`.
+pub fn verify(
+    rollup_type_hash: H256,
+    config: &RollupConfig,
+    prev_global_state: &GlobalState,
+    post_global_state: &GlobalState,
+) -> Result<(), Error> {
+    // the config can't be changed during a challenge
+    check_status(prev_global_state, Status::Running)?;
+
+    // check governance authorization
+    let governance_lock_hash = load_governance_lock_hash(config)?.ok_or_else(|| {
+        debug!("[update config] governance lock isn't configured");
+        Error::OwnerCellNotFound.with_sub_code(GOVERNANCE_NOT_CONFIGURED)
+    })?;
+    if !QueryIter::new(load_cell_lock_hash, Source::Input)
+        .any(|lock_hash| lock_hash == governance_lock_hash)
+    {
+        debug!("[update config] governance cell not found");
+        return Err(Error::OwnerCellNotFound.with_sub_code(GOVERNANCE_CELL_NOT_FOUND));
+    }
+
+    // do not allow other rollup cells in the transaction
+    check_rollup_lock_cells(&rollup_type_hash, config)?;
+    if find_challenge_cell(&rollup_type_hash, config, Source::Input)?.is_some()
+        || find_challenge_cell(&rollup_type_hash, config, Source::Output)?.is_some()
+    {
+        return Err(Error::InvalidChallengeCell);
+    }
+
+    // the new config cell must be in the cell deps
+    let post_config = load_rollup_config(&post_global_state.rollup_config_hash().unpack())?;
+    check_immutable_config_fields(config, &post_config)?;
+
+    let expected_post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(post_global_state.rollup_config_hash())
+        .build();
+    if &expected_post_global_state != post_global_state {
+        debug!("[update config] unexpected global state change");
+        return Err(Error::InvalidPostGlobalState.with_sub_code(UNEXPECTED_GLOBAL_STATE_CHANGE));
+    }
+
+    Ok(())
+}
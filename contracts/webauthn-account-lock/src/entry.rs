@@ -0,0 +1,14 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use crate::webauthn_signature::WebAuthn;
+use gw_account_lock::verify_account_lock;
+use gw_utils::error::Error;
+
+/// WebAuthn account lock
+/// script args: rollup_script_hash(32 bytes) | rp_id_hash(32 bytes) | compressed secp256r1 pubkey(33 bytes)
+/// data: onetime_owner_lock_hash(32 bytes) | signing type (1 byte) | message(32 bytes)
+/// witness lock: signature(64 bytes) | authenticator_data_len(2 bytes) | authenticator_data | client_data_json
+pub fn main() -> Result<(), Error> {
+    verify_account_lock::<WebAuthn>()
+}
@@ -0,0 +1,147 @@
+//! WebAuthn implementation
+//!
+//! Passkeys sign `authenticator_data | sha256(client_data_json)` with secp256r1,
+//! the layer2 message is the WebAuthn challenge carried by `client_data_json`.
+//! The envelope is always required, so `SigningType::WithPrefix` and `SigningType::Raw`
+//! are verified in the same way.
+//!
+//! The signature in the witness lock:
+//! signature(64 bytes r | s) | authenticator_data_len(2 bytes LE) | authenticator_data | client_data_json
+//!
+//! See https://www.w3.org/TR/webauthn-2/#sctn-verifying-assertion
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use gw_account_lock::AccountLockScheme;
+use gw_utils::{ckb_std::debug, error::Error, gw_common::H256};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+pub const SIGNATURE_SIZE: usize = 64;
+const RP_ID_HASH_SIZE: usize = 32;
+const PUBKEY_SIZE: usize = 33;
+/// rp_id_hash(32 bytes) | flags(1 byte) | sign_count(4 bytes)
+const MIN_AUTHENTICATOR_DATA_SIZE: usize = 37;
+/// User Present
+const FLAG_UP: u8 = 0x01;
+const CLIENT_DATA_TYPE: &[u8] = b"\"type\":\"webauthn.get\"";
+const CLIENT_DATA_CHALLENGE: &[u8] = b"\"challenge\":\"";
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub struct WebAuthn;
+
+impl AccountLockScheme for WebAuthn {
+    /// rp_id_hash(32 bytes) | compressed pubkey(33 bytes)
+    const ADDRESS_LEN: usize = RP_ID_HASH_SIZE + PUBKEY_SIZE;
+
+    fn prefix_message(message: &H256) -> Vec<u8> {
+        message.as_slice().to_vec()
+    }
+
+    fn verify_signature(address: &[u8], signature: &[u8], message: &[u8]) -> Result<bool, Error> {
+        let (rp_id_hash, pubkey) = address.split_at(RP_ID_HASH_SIZE);
+        let assertion = Assertion::from_slice(signature)?;
+
+        // check authenticator data
+        let authenticator_data = assertion.authenticator_data;
+        if &authenticator_data[..RP_ID_HASH_SIZE] != rp_id_hash {
+            debug!("mismatch rp_id_hash");
+            return Ok(false);
+        }
+        if authenticator_data[RP_ID_HASH_SIZE] & FLAG_UP == 0 {
+            debug!("user is not present");
+            return Ok(false);
+        }
+
+        // check client data
+        let client_data_json = assertion.client_data_json;
+        if find(client_data_json, CLIENT_DATA_TYPE).is_none() {
+            debug!("client data type isn't webauthn.get");
+            return Ok(false);
+        }
+        let challenge = parse_challenge(client_data_json).ok_or_else(|| {
+            debug!("client data has no challenge");
+            Error::WrongSignature
+        })?;
+        if challenge != base64url_encode(message).as_slice() {
+            debug!("mismatch challenge");
+            return Ok(false);
+        }
+
+        // verify signature
+        let verifying_key = VerifyingKey::from_sec1_bytes(pubkey).map_err(|_| {
+            debug!("invalid secp256r1 pubkey");
+            Error::InvalidArgs
+        })?;
+        let signature = Signature::try_from(assertion.signature).map_err(|_| {
+            debug!("invalid secp256r1 signature");
+            Error::WrongSignature
+        })?;
+        let mut signed_data = Vec::with_capacity(authenticator_data.len() + 32);
+        signed_data.extend_from_slice(authenticator_data);
+        signed_data.extend_from_slice(&Sha256::digest(client_data_json));
+        // the data is hashed with sha256 by the verifier
+        Ok(verifying_key.verify(&signed_data, &signature).is_ok())
+    }
+}
+
+struct Assertion<'a> {
+    signature: &'a [u8],
+    authenticator_data: &'a [u8],
+    client_data_json: &'a [u8],
+}
+
+impl<'a> Assertion<'a> {
+    fn from_slice(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < SIGNATURE_SIZE + 2 {
+            debug!("invalid webauthn assertion len: {}", data.len());
+            return Err(Error::WrongSignature);
+        }
+        let (signature, data) = data.split_at(SIGNATURE_SIZE);
+        let authenticator_data_len = u16::from_le_bytes([data[0], data[1]]) as usize;
+        let data = &data[2..];
+        if authenticator_data_len < MIN_AUTHENTICATOR_DATA_SIZE
+            || authenticator_data_len > data.len()
+        {
+            debug!("invalid authenticator data len: {}", authenticator_data_len);
+            return Err(Error::WrongSignature);
+        }
+        let (authenticator_data, client_data_json) = data.split_at(authenticator_data_len);
+        Ok(Assertion {
+            signature,
+            authenticator_data,
+            client_data_json,
+        })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Return the challenge string of the client data JSON
+fn parse_challenge(client_data_json: &[u8]) -> Option<&[u8]> {
+    let start = find(client_data_json, CLIENT_DATA_CHALLENGE)? + CLIENT_DATA_CHALLENGE.len();
+    let len = client_data_json[start..].iter().position(|&c| c == b'"')?;
+    Some(&client_data_json[start..start + len])
+}
+
+/// Base64url encoding without padding, WebAuthn encodes the challenge in this way
+fn base64url_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity((data.len() * 4 + 2) / 3);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            let index = (n >> (18 - 6 * i)) & 0x3f;
+            encoded.push(BASE64URL_ALPHABET[index as usize]);
+        }
+    }
+    encoded
+}
@@ -1,12 +1,11 @@
 // Import from `core` instead of from `std` since we are in no-std mode
 use core::result::Result;
 
-use gw_types::{
-    packed::{UnlockWithdrawalWitness, UnlockWithdrawalWitnessReader},
-    prelude::*,
-};
+use alloc::{collections::BTreeMap, vec::Vec};
+use gw_types::{packed::Script, prelude::*};
 use gw_utils::cells::rollup::{
     load_rollup_config, parse_rollup_action, search_rollup_cell, search_rollup_state,
+    ROLLUP_STATUS_EMERGENCY,
 };
 use gw_utils::ckb_std::{
     debug,
@@ -16,6 +15,7 @@ use gw_utils::gw_types::packed::{
     CustodianLockArgs, CustodianLockArgsReader, RollupActionUnionReader,
     UnlockWithdrawalWitnessUnion, WithdrawalLockArgs,
 };
+use gw_utils::withdrawal::{parse_unlock_witness, UnlockWithdrawal};
 use gw_utils::{
     cells::rollup::MAX_ROLLUP_WITNESS_SIZE,
     gw_types::{self, core::ScriptHashType},
@@ -37,6 +37,14 @@ use crate::error::Error;
 const FINALIZED_BLOCK_NUMBER: u64 = 0;
 const FINALIZED_BLOCK_HASH: [u8; 32] = [0u8; 32];
 
+/// sub codes of `Error::InvalidArgs`
+const MISSING_UNLOCK_WITNESS: u16 = 1;
+const ROLLUP_TYPE_HASH_MISMATCH: u16 = 2;
+const WITHDRAWAL_UNFINALIZED: u16 = 3;
+
+/// sub codes of `Error::InvalidOutput`
+const TRADE_OUTPUT_LOCK_MISMATCH: u16 = 1;
+
 struct ParsedLockArgs {
     rollup_type_hash: [u8; 32],
     lock_args: WithdrawalLockArgs,
@@ -75,38 +83,53 @@ pub fn main() -> Result<(), Error> {
         let unlock_args: Bytes = witness_args
             .lock()
             .to_opt()
-            .ok_or(Error::InvalidArgs)?
+            .ok_or_else(|| Error::InvalidArgs.with_sub_code(MISSING_UNLOCK_WITNESS))?
             .unpack();
-        match UnlockWithdrawalWitnessReader::verify(&unlock_args, false) {
-            Ok(()) => UnlockWithdrawalWitness::new_unchecked(unlock_args),
-            Err(_) => return Err(Error::ProofNotFound),
+        match parse_unlock_witness(unlock_args)? {
+            UnlockWithdrawal::Witness(unlock_args) => unlock_args,
+            UnlockWithdrawal::ViaTrade { owner_lock } => {
+                return unlock_via_trade(
+                    &script,
+                    &rollup_type_hash,
+                    &lock_args,
+                    &owner_lock_hash,
+                    &owner_lock,
+                );
+            }
         }
     };
 
     // execute verification
     match unlock_args.to_enum() {
         UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(unlock_args) => {
-            let mut rollup_action_witness = [0u8; MAX_ROLLUP_WITNESS_SIZE];
-            let withdrawal_block_hash = lock_args.withdrawal_block_hash();
-            // prove the block is reverted
-            let rollup_action = {
-                let index = search_rollup_cell(&rollup_type_hash, Source::Output)
-                    .ok_or(Error::RollupCellNotFound)?;
-                parse_rollup_action(&mut rollup_action_witness, index, Source::Output)?
-            };
-            match rollup_action.to_enum() {
-                RollupActionUnionReader::RollupSubmitBlock(args) => {
-                    if !args
-                        .reverted_block_hashes()
-                        .iter()
-                        .any(|hash| hash.as_slice() == withdrawal_block_hash.as_slice())
-                    {
+            let global_state = search_rollup_state(&rollup_type_hash, Source::Input)?
+                .ok_or(Error::RollupCellNotFound)?;
+            let config = load_rollup_config(&global_state.rollup_config_hash().unpack())?;
+            // the unfinalized state is dropped in emergency mode, skip the reverted block check
+            let status: u8 = global_state.status().into();
+            if status != ROLLUP_STATUS_EMERGENCY {
+                let mut rollup_action_witness = [0u8; MAX_ROLLUP_WITNESS_SIZE];
+                let withdrawal_block_hash = lock_args.withdrawal_block_hash();
+                // prove the block is reverted
+                let rollup_action = {
+                    let index = search_rollup_cell(&rollup_type_hash, Source::Output)
+                        .ok_or(Error::RollupCellNotFound)?;
+                    parse_rollup_action(&mut rollup_action_witness, index, Source::Output)?
+                };
+                match rollup_action.to_enum() {
+                    RollupActionUnionReader::RollupSubmitBlock(args) => {
+                        if !args
+                            .reverted_block_hashes()
+                            .iter()
+                            .any(|hash| hash.as_slice() == withdrawal_block_hash.as_slice())
+                        {
+                            return Err(Error::InvalidRevertedBlocks);
+                        }
+                    }
+                    _ => {
                         return Err(Error::InvalidRevertedBlocks);
                     }
                 }
-                _ => {
-                    return Err(Error::InvalidRevertedBlocks);
-                }
             }
             let custodian_lock_hash: [u8; 32] = unlock_args.custodian_lock_hash().unpack();
             // check there are a reverted custodian lock in the output
@@ -124,10 +147,11 @@ pub fn main() -> Result<(), Error> {
                     return Err(Error::InvalidArgs);
                 }
                 if args[..32] != rollup_type_hash {
-                    return Err(Error::InvalidArgs);
+                    return Err(Error::InvalidArgs.with_sub_code(ROLLUP_TYPE_HASH_MISMATCH));
                 }
 
-                match CustodianLockArgsReader::verify(&args.slice(32..), false) {
+                // accept extra fields of the deposit lock args, e.g. `recipient`
+                match CustodianLockArgsReader::verify(&args.slice(32..), true) {
                     Ok(_) => CustodianLockArgs::new_unchecked(args.slice(32..)),
                     Err(_) => return Err(Error::InvalidOutput),
                 }
@@ -136,9 +160,6 @@ pub fn main() -> Result<(), Error> {
                 custodian_lock_args.deposit_block_hash().unpack();
             let custodian_deposit_block_number: u64 =
                 custodian_lock_args.deposit_block_number().unpack();
-            let global_state = search_rollup_state(&rollup_type_hash, Source::Input)?
-                .ok_or(Error::RollupCellNotFound)?;
-            let config = load_rollup_config(&global_state.rollup_config_hash().unpack())?;
             if custodian_lock.code_hash().as_slice()
                 != config.custodian_script_type_hash().as_slice()
                 || custodian_lock.hash_type() != ScriptHashType::Type.into()
@@ -169,7 +190,7 @@ pub fn main() -> Result<(), Error> {
 
             if withdrawal_block_number > last_finalized_block_number {
                 // not yet finalized
-                return Err(Error::InvalidArgs);
+                return Err(Error::InvalidArgs.with_sub_code(WITHDRAWAL_UNFINALIZED));
             }
 
             // withdrawal lock is finalized, unlock for owner
@@ -183,14 +204,14 @@ pub fn main() -> Result<(), Error> {
                     .filter(|(_idx, lock_hash)| lock_hash == &withdrawal_lock_hash)
                 {
                     if check_output_cell_has_same_content(index, Source::Input, index).is_err() {
-                        debug!("[via finalize] output cell content not match, fallback to input owner cell");
+                        debug!("[via finalize] output cell content not match, fallback to owner outputs");
                         invalid_output_found = true;
                         break;
                     }
 
                     let maybe_output_lock_hash = load_cell_lock_hash(index, Source::Output);
                     if maybe_output_lock_hash != Ok(owner_lock_hash) {
-                        debug!("[via finalize] output cell owner lock not match, fallback to input owner cell");
+                        debug!("[via finalize] output cell owner lock not match, fallback to owner outputs");
                         invalid_output_found = true;
                         break;
                     }
@@ -201,6 +222,19 @@ pub fn main() -> Result<(), Error> {
                 }
             }
 
+            // withdrawal cells are split or merged into owner lock outputs
+            if check_owner_outputs_conserve_withdrawals(
+                &script,
+                &rollup_type_hash,
+                &owner_lock_hash,
+                last_finalized_block_number,
+            )
+            .is_ok()
+            {
+                return Ok(());
+            }
+            debug!("[via finalize] owner outputs don't conserve withdrawals, fallback to input owner cell");
+
             // fallback to input owner cell way
             if search_lock_hash(&lock_args.owner_lock_hash().unpack(), Source::Input).is_none() {
                 return Err(Error::OwnerCellNotFound);
@@ -211,6 +245,152 @@ pub fn main() -> Result<(), Error> {
     }
 }
 
+/// Transfer the withdrawal cells to a new owner before finality, a liquidity
+/// provider buys the withdrawal by paying the owner in the same transaction,
+/// and unlocks it via finalize once the withdrawal block is finalized.
+///
+/// The owner authorizes the trade by providing an owner cell in the inputs.
+/// The output cell at the same index must have same content and a withdrawal
+/// lock that only changes the owner lock.
+fn unlock_via_trade(
+    script: &ckb_types::packed::Script,
+    rollup_type_hash: &[u8; 32],
+    lock_args: &WithdrawalLockArgs,
+    owner_lock_hash: &[u8; 32],
+    new_owner_lock: &Script,
+) -> Result<(), Error> {
+    if search_lock_hash(owner_lock_hash, Source::Input).is_none() {
+        debug!("[via trade] owner cell not found");
+        return Err(Error::OwnerCellNotFound);
+    }
+
+    // args: rollup_type_hash | withdrawal lock args | owner lock len | owner lock
+    let expected_args = {
+        let new_lock_args = lock_args
+            .clone()
+            .as_builder()
+            .owner_lock_hash(new_owner_lock.hash().pack())
+            .build();
+        let owner_lock_len = new_owner_lock.as_slice().len() as u32;
+
+        let mut args =
+            Vec::with_capacity(32 + WithdrawalLockArgs::TOTAL_SIZE + 4 + owner_lock_len as usize);
+        args.extend_from_slice(rollup_type_hash);
+        args.extend_from_slice(new_lock_args.as_slice());
+        args.extend_from_slice(&owner_lock_len.to_be_bytes());
+        args.extend_from_slice(new_owner_lock.as_slice());
+        args
+    };
+
+    let withdrawal_lock_hash = load_cell_lock_hash(0, Source::GroupInput)?;
+    for (index, _) in QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_idx, lock_hash)| lock_hash == &withdrawal_lock_hash)
+    {
+        check_output_cell_has_same_content(index, Source::Input, index)?;
+
+        let output_lock = load_cell_lock(index, Source::Output)?;
+        let output_args: Bytes = output_lock.args().unpack();
+        if output_lock.code_hash().as_slice() != script.code_hash().as_slice()
+            || output_lock.hash_type().as_slice() != script.hash_type().as_slice()
+            || output_args.as_ref() != expected_args.as_slice()
+        {
+            debug!("[via trade] output withdrawal lock not match");
+            return Err(Error::InvalidOutput.with_sub_code(TRADE_OUTPUT_LOCK_MISMATCH));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct CellsAssets {
+    capacity: u128,
+    // sUDT type hash -> amount
+    sudt_amounts: BTreeMap<[u8; 32], u128>,
+}
+
+impl CellsAssets {
+    fn add_cell(&mut self, index: usize, source: Source) -> Result<(), Error> {
+        let capacity = load_cell_capacity(index, source)?;
+        self.capacity = self
+            .capacity
+            .checked_add(capacity.into())
+            .ok_or(Error::AmountOverflow)?;
+
+        if let Some(type_hash) = load_cell_type_hash(index, source)? {
+            let data = load_cell_data(index, source)?;
+            if data.len() < 16 {
+                return Err(Error::InvalidSUDTCell);
+            }
+            let mut buf = [0u8; 16];
+            buf.copy_from_slice(&data[..16]);
+            let sudt_amount = self.sudt_amounts.entry(type_hash).or_insert(0);
+            *sudt_amount = sudt_amount
+                .checked_add(u128::from_le_bytes(buf))
+                .ok_or(Error::AmountOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finalized withdrawal cells of the same owner can be split or merged, the outputs
+/// locked by the owner lock must receive all capacity and sUDT of these withdrawal cells.
+fn check_owner_outputs_conserve_withdrawals(
+    script: &ckb_types::packed::Script,
+    rollup_type_hash: &[u8; 32],
+    owner_lock_hash: &[u8; 32],
+    last_finalized_block_number: u64,
+) -> Result<(), Error> {
+    let mut withdrawal_assets = CellsAssets::default();
+    for (index, lock) in QueryIter::new(load_cell_lock, Source::Input).enumerate() {
+        let args: Bytes = lock.args().unpack();
+        if lock.code_hash().as_slice() != script.code_hash().as_slice()
+            || lock.hash_type().as_slice() != script.hash_type().as_slice()
+            || args.len() < rollup_type_hash.len()
+            || &args[..32] != rollup_type_hash
+        {
+            continue;
+        }
+
+        let parsed = gw_utils::withdrawal::parse_lock_args(&args)?;
+        if &parsed.owner_lock.hash() != owner_lock_hash {
+            continue;
+        }
+        let withdrawal_block_number: u64 = parsed.lock_args.withdrawal_block_number().unpack();
+        if withdrawal_block_number > last_finalized_block_number {
+            return Err(Error::InvalidArgs.with_sub_code(WITHDRAWAL_UNFINALIZED));
+        }
+        withdrawal_assets.add_cell(index, Source::Input)?;
+    }
+
+    let mut owner_assets = CellsAssets::default();
+    for (index, _) in QueryIter::new(load_cell_lock_hash, Source::Output)
+        .enumerate()
+        .filter(|(_idx, lock_hash)| lock_hash == owner_lock_hash)
+    {
+        owner_assets.add_cell(index, Source::Output)?;
+    }
+
+    if owner_assets.capacity < withdrawal_assets.capacity {
+        debug!("[via finalize] insufficient owner outputs capacity");
+        return Err(Error::InsufficientAmount);
+    }
+    for (type_hash, amount) in withdrawal_assets.sudt_amounts {
+        if owner_assets
+            .sudt_amounts
+            .get(&type_hash)
+            .cloned()
+            .unwrap_or(0)
+            < amount
+        {
+            debug!("[via finalize] insufficient owner outputs sUDT amount");
+            return Err(Error::InsufficientAmount);
+        }
+    }
+    Ok(())
+}
+
 fn check_output_cell_has_same_content(
     input_index: usize,
     input_source: Source,
@@ -0,0 +1,439 @@
+use crate::script_tests::utils::layer1::*;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, ALWAYS_SUCCESS_PROGRAM, BITCOIN_ACCOUNT_LOCK_CODE_HASH,
+    BITCOIN_ACCOUNT_LOCK_PROGRAM, SECP256K1_DATA,
+};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_crypto::secp::{Generator, Privkey, Pubkey};
+use ckb_error::assert_error_eq;
+use ckb_script::{ScriptError, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_types::core::hardfork::HardForkSwitch;
+use ckb_types::core::{Cycle, HeaderView};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+};
+use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH};
+use gw_types::core::SigningType;
+use rand::{thread_rng, Rng};
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use std::sync::atomic::Ordering;
+
+const ERROR_WRONG_SIGNATURE: i8 = 41;
+
+/// BIP-137 header bases
+const HEADER_P2PKH_UNCOMPRESSED: u8 = 27;
+const HEADER_P2PKH_COMPRESSED: u8 = 31;
+const HEADER_P2SH_P2WPKH: u8 = 35;
+const HEADER_P2WPKH: u8 = 39;
+
+fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    lock_args: Bytes,
+    signing_type: SigningType,
+    message: Bytes,
+) -> TransactionView {
+    let mut rng = thread_rng();
+    // setup sighash_all dep
+    let script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let owner_lock_script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    // dep contract code
+    // bitcoin account lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(BITCOIN_ACCOUNT_LOCK_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    let script_cell_data_hash = CellOutput::calc_data_hash(&BITCOIN_ACCOUNT_LOCK_PROGRAM);
+    dummy.cells.insert(
+        script_out_point.clone(),
+        (script_cell, BITCOIN_ACCOUNT_LOCK_PROGRAM.clone()),
+    );
+    // owner lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ALWAYS_SUCCESS_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        owner_lock_script_out_point.clone(),
+        (script_cell, ALWAYS_SUCCESS_PROGRAM.clone()),
+    );
+    // owner lock cell
+    let owner_lock_cell = CellOutput::new_builder()
+        .lock(
+            Script::new_builder()
+                .code_hash((*ALWAYS_SUCCESS_CODE_HASH).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        )
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock_cell.lock().calc_script_hash().unpack();
+    let owner_lock_cell_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    dummy.cells.insert(
+        owner_lock_cell_out_point.clone(),
+        (owner_lock_cell, Bytes::default()),
+    );
+    // setup secp256k1_data dep
+    let secp256k1_data_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let secp256k1_data_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(SECP256K1_DATA.len())
+                .expect("data capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        secp256k1_data_out_point.clone(),
+        (secp256k1_data_cell, SECP256K1_DATA.clone()),
+    );
+    // setup default tx builder
+    let dummy_capacity = Capacity::shannons(42);
+    let tx_builder = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_data_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(owner_lock_script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(dummy_capacity.pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack());
+
+    let previous_out_point = {
+        let previous_tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(previous_tx_hash, 0)
+    };
+    let previous_output_cell = {
+        let script = Script::new_builder()
+            .args(lock_args.pack())
+            .code_hash(script_cell_data_hash)
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        CellOutput::new_builder()
+            .capacity(dummy_capacity.pack())
+            .lock(script)
+            .build()
+    };
+    let mut input_data = owner_lock_hash.to_vec();
+    input_data.push(signing_type.into());
+    input_data.extend_from_slice(&message);
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, input_data.into()),
+    );
+    tx_builder
+        .input(CellInput::new(previous_out_point, 0))
+        .input(CellInput::new(owner_lock_cell_out_point, 0))
+        .build()
+}
+
+/// Key of the bitcoinjs-message example, address 1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV,
+/// WIF L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1
+const WALLET_PRIVKEY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+const WALLET_PUBKEY_HASH: &str = "9a1c78a507689f6f54b847ad1cef1e614ee23f1e";
+
+/// double_sha256(magic | varint(len) | text), texts are shorter than 253 bytes
+fn text_message_digest(text: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"\x18Bitcoin Signed Message:\n");
+    hasher.update(&[text.len() as u8]);
+    hasher.update(text);
+    Sha256::digest(&hasher.finalize()).into()
+}
+
+/// Wallets sign the 64 chars hex string of the message
+fn signing_message(message: [u8; 32]) -> [u8; 32] {
+    text_message_digest(hex::encode(message).as_bytes())
+}
+
+fn sign_message(key: &Privkey, message: [u8; 32], header_base: u8) -> Bytes {
+    sign_digest(key, signing_message(message), header_base)
+}
+
+fn sign_digest(key: &Privkey, digest: [u8; 32], header_base: u8) -> Bytes {
+    let message = ckb_types::H256::from(digest);
+    let sig = key.sign_recoverable(&message).expect("sign");
+    let sig = sig.serialize();
+    // header | r | s
+    let mut signature = vec![header_base + sig[64]];
+    signature.extend_from_slice(&sig[..64]);
+    signature.into()
+}
+
+fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(&Sha256::digest(data)).into()
+}
+
+pub fn bitcoin_pubkey_hash(pubkey: &Pubkey, header_base: u8) -> Bytes {
+    let compressed_pubkey_hash = hash160(&pubkey.serialize());
+    let pubkey_hash = match header_base {
+        HEADER_P2PKH_UNCOMPRESSED => {
+            let mut uncompressed_pubkey = vec![0x04];
+            uncompressed_pubkey.extend_from_slice(pubkey.as_bytes());
+            hash160(&uncompressed_pubkey)
+        }
+        HEADER_P2SH_P2WPKH => {
+            let mut redeem_script = vec![0x00, 0x14];
+            redeem_script.extend_from_slice(&compressed_pubkey_hash);
+            hash160(&redeem_script)
+        }
+        _ => compressed_pubkey_hash,
+    };
+    pubkey_hash.to_vec().into()
+}
+
+fn build_lock_args(pubkey_hash: &[u8]) -> Bytes {
+    let rollup_script_hash = [42u8; 32];
+    let mut args = rollup_script_hash.to_vec();
+    args.extend_from_slice(pubkey_hash);
+    args.into()
+}
+
+fn verify_tx(
+    data_loader: &DummyDataLoader,
+    tx: TransactionView,
+    signature: Bytes,
+) -> Result<Cycle, ckb_error::Error> {
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(signature).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    let hardfork_switch = {
+        let switch = GLOBAL_HARDFORK_SWITCH.load_full();
+        HardForkSwitch::new_without_any_enabled()
+            .as_builder()
+            .rfc_0028(switch.rfc_0028())
+            .rfc_0029(switch.rfc_0029())
+            .rfc_0030(switch.rfc_0030())
+            .rfc_0031(switch.rfc_0031())
+            .rfc_0032(switch.rfc_0032())
+            .rfc_0036(switch.rfc_0036())
+            .rfc_0038(switch.rfc_0038())
+            .build()
+            .unwrap()
+    };
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let current_epoch_number = GLOBAL_CURRENT_EPOCH_NUMBER.load(Ordering::SeqCst);
+    let tx_verify_env = TxVerifyEnv::new_submit(
+        &HeaderView::new_advanced_builder()
+            .epoch(current_epoch_number.pack())
+            .build(),
+    );
+    let resolved_tx = build_resolved_tx(data_loader, &tx);
+    let mut verifier =
+        TransactionScriptsVerifier::new(&resolved_tx, &consensus, data_loader, &tx_verify_env);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+#[test]
+fn test_sign_bitcoin_message() {
+    let privkey = Generator::random_privkey();
+    let pubkey = privkey.pubkey().expect("pubkey");
+    let mut rng = thread_rng();
+    for header_base in [
+        HEADER_P2PKH_UNCOMPRESSED,
+        HEADER_P2PKH_COMPRESSED,
+        HEADER_P2SH_P2WPKH,
+        HEADER_P2WPKH,
+    ] {
+        let mut data_loader = DummyDataLoader::default();
+        let mut message = [0u8; 32];
+        rng.fill(&mut message);
+        let signature = sign_message(&privkey, message, header_base);
+        let lock_args = build_lock_args(&bitcoin_pubkey_hash(&pubkey, header_base));
+        let tx = gen_tx(
+            &mut data_loader,
+            lock_args,
+            SigningType::WithPrefix,
+            message.to_vec().into(),
+        );
+        verify_tx(&data_loader, tx, signature).expect("pass verification");
+    }
+}
+
+#[test]
+fn test_bitcoin_wallet_signature() {
+    // the text message and signature of the bitcoinjs-message example,
+    // the lock verifies the digest of the text as a raw message
+    let text = b"This is an example of a signed message.";
+    let signature = Bytes::from(
+        base64::decode(
+            "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+        )
+        .unwrap(),
+    );
+    let digest = text_message_digest(text);
+
+    // RFC 6979 signatures are deterministic, the signer of the tests signs like wallets
+    let privkey = Privkey::from_slice(&hex::decode(WALLET_PRIVKEY).unwrap());
+    assert_eq!(
+        sign_digest(&privkey, digest, HEADER_P2PKH_COMPRESSED),
+        signature
+    );
+
+    let mut data_loader = DummyDataLoader::default();
+    let lock_args = build_lock_args(&hex::decode(WALLET_PUBKEY_HASH).unwrap());
+    let tx = gen_tx(
+        &mut data_loader,
+        lock_args,
+        SigningType::Raw,
+        digest.to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature).expect("pass verification");
+}
+
+#[test]
+fn test_sign_bitcoin_hex_message_vector() {
+    // the wallet key of `test_bitcoin_wallet_signature` signs the text
+    // "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+    let mut message = [0u8; 32];
+    for (i, byte) in message.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let signature = Bytes::from(
+        hex::decode(
+            "20336b0effcea1e2ca757899a28a974763156162d8df1afc5edb610fc7607e84\
+             79558f372f4ed5c015570ac44a5160f5022893c61a78b548c13ae4c0346156ff21",
+        )
+        .unwrap(),
+    );
+    let privkey = Privkey::from_slice(&hex::decode(WALLET_PRIVKEY).unwrap());
+    assert_eq!(
+        sign_message(&privkey, message, HEADER_P2PKH_COMPRESSED),
+        signature
+    );
+
+    let mut data_loader = DummyDataLoader::default();
+    let lock_args = build_lock_args(&hex::decode(WALLET_PUBKEY_HASH).unwrap());
+    let tx = gen_tx(
+        &mut data_loader,
+        lock_args,
+        SigningType::WithPrefix,
+        message.to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature).expect("pass verification");
+}
+
+#[test]
+fn test_submit_signing_bitcoin_message() {
+    let mut data_loader = DummyDataLoader::default();
+    let privkey = Generator::random_privkey();
+    let pubkey = privkey.pubkey().expect("pubkey");
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = sign_message(&privkey, message, HEADER_P2WPKH);
+    let lock_args = build_lock_args(&bitcoin_pubkey_hash(&pubkey, HEADER_P2WPKH));
+    let tx = gen_tx(
+        &mut data_loader,
+        lock_args,
+        SigningType::Raw,
+        signing_message(message).to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature).expect("pass verification");
+}
+
+#[test]
+fn test_wrong_signature() {
+    let privkey = Generator::random_privkey();
+    let pubkey = privkey.pubkey().expect("pubkey");
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+
+    // sign a wrong message
+    let wrong_message_signature = {
+        let mut wrong_message = [0u8; 32];
+        rng.fill(&mut wrong_message);
+        sign_message(&privkey, wrong_message, HEADER_P2PKH_COMPRESSED)
+    };
+    // the header doesn't match the address type of the pubkey hash
+    let wrong_header_signature = sign_message(&privkey, message, HEADER_P2PKH_UNCOMPRESSED);
+
+    for signature in [wrong_message_signature, wrong_header_signature] {
+        let mut data_loader = DummyDataLoader::default();
+        let lock_args = build_lock_args(&bitcoin_pubkey_hash(&pubkey, HEADER_P2PKH_COMPRESSED));
+        let tx = gen_tx(
+            &mut data_loader,
+            lock_args,
+            SigningType::WithPrefix,
+            message.to_vec().into(),
+        );
+        let verify_result = verify_tx(&data_loader, tx, signature);
+        let script_cell_index = 0;
+        assert_error_eq!(
+            verify_result.unwrap_err(),
+            ScriptError::ValidationFailure(
+                format!(
+                    "by-data-hash/{}",
+                    ckb_types::H256(*BITCOIN_ACCOUNT_LOCK_CODE_HASH)
+                ),
+                ERROR_WRONG_SIGNATURE
+            )
+            .input_lock_script(script_cell_index)
+        );
+    }
+}
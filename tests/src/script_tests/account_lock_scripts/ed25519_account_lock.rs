@@ -0,0 +1,371 @@
+use crate::script_tests::utils::layer1::*;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, ALWAYS_SUCCESS_PROGRAM, ED25519_ACCOUNT_LOCK_CODE_HASH,
+    ED25519_ACCOUNT_LOCK_PROGRAM, SECP256K1_DATA,
+};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_error::assert_error_eq;
+use ckb_script::{ScriptError, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_types::core::hardfork::HardForkSwitch;
+use ckb_types::core::{Cycle, HeaderView};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH};
+use gw_types::core::SigningType;
+use rand::{thread_rng, Rng};
+
+use std::sync::atomic::Ordering;
+
+const ERROR_WRONG_SIGNATURE: i8 = 41;
+
+fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    lock_args: Bytes,
+    signing_type: SigningType,
+    message: Bytes,
+) -> TransactionView {
+    let mut rng = thread_rng();
+    // setup sighash_all dep
+    let script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let owner_lock_script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    // dep contract code
+    // ed25519 account lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ED25519_ACCOUNT_LOCK_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    let script_cell_data_hash = CellOutput::calc_data_hash(&ED25519_ACCOUNT_LOCK_PROGRAM);
+    dummy.cells.insert(
+        script_out_point.clone(),
+        (script_cell, ED25519_ACCOUNT_LOCK_PROGRAM.clone()),
+    );
+    // owner lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ALWAYS_SUCCESS_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        owner_lock_script_out_point.clone(),
+        (script_cell, ALWAYS_SUCCESS_PROGRAM.clone()),
+    );
+    // owner lock cell
+    let owner_lock_cell = CellOutput::new_builder()
+        .lock(
+            Script::new_builder()
+                .code_hash((*ALWAYS_SUCCESS_CODE_HASH).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        )
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock_cell.lock().calc_script_hash().unpack();
+    let owner_lock_cell_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    dummy.cells.insert(
+        owner_lock_cell_out_point.clone(),
+        (owner_lock_cell, Bytes::default()),
+    );
+    // setup secp256k1_data dep
+    let secp256k1_data_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let secp256k1_data_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(SECP256K1_DATA.len())
+                .expect("data capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        secp256k1_data_out_point.clone(),
+        (secp256k1_data_cell, SECP256K1_DATA.clone()),
+    );
+    // setup default tx builder
+    let dummy_capacity = Capacity::shannons(42);
+    let tx_builder = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_data_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(owner_lock_script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(dummy_capacity.pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack());
+
+    let previous_out_point = {
+        let previous_tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(previous_tx_hash, 0)
+    };
+    let previous_output_cell = {
+        let script = Script::new_builder()
+            .args(lock_args.pack())
+            .code_hash(script_cell_data_hash)
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        CellOutput::new_builder()
+            .capacity(dummy_capacity.pack())
+            .lock(script)
+            .build()
+    };
+    let mut input_data = owner_lock_hash.to_vec();
+    input_data.push(signing_type.into());
+    input_data.extend_from_slice(&message);
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, input_data.into()),
+    );
+    tx_builder
+        .input(CellInput::new(previous_out_point, 0))
+        .input(CellInput::new(owner_lock_cell_out_point, 0))
+        .build()
+}
+
+fn verify_tx(
+    data_loader: &DummyDataLoader,
+    tx: TransactionView,
+    signature: Bytes,
+) -> Result<Cycle, ckb_error::Error> {
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(signature).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    let hardfork_switch = {
+        let switch = GLOBAL_HARDFORK_SWITCH.load_full();
+        HardForkSwitch::new_without_any_enabled()
+            .as_builder()
+            .rfc_0028(switch.rfc_0028())
+            .rfc_0029(switch.rfc_0029())
+            .rfc_0030(switch.rfc_0030())
+            .rfc_0031(switch.rfc_0031())
+            .rfc_0032(switch.rfc_0032())
+            .rfc_0036(switch.rfc_0036())
+            .rfc_0038(switch.rfc_0038())
+            .build()
+            .unwrap()
+    };
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let current_epoch_number = GLOBAL_CURRENT_EPOCH_NUMBER.load(Ordering::SeqCst);
+    let tx_verify_env = TxVerifyEnv::new_submit(
+        &HeaderView::new_advanced_builder()
+            .epoch(current_epoch_number.pack())
+            .build(),
+    );
+    let resolved_tx = build_resolved_tx(data_loader, &tx);
+    let mut verifier =
+        TransactionScriptsVerifier::new(&resolved_tx, &consensus, data_loader, &tx_verify_env);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+fn random_keypair() -> Keypair {
+    let mut rng = thread_rng();
+    let mut secret = [0u8; 32];
+    rng.fill(&mut secret);
+    let secret = SecretKey::from_bytes(&secret).expect("secret key");
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+/// Solana off-chain message format v0 of a printable ASCII text
+fn offchain_text_message(text: &[u8]) -> Vec<u8> {
+    let mut signing_message = b"\xffsolana offchain".to_vec();
+    // header version
+    signing_message.push(0);
+    // message format: restricted ASCII
+    signing_message.push(0);
+    signing_message.extend_from_slice(&(text.len() as u16).to_le_bytes());
+    signing_message.extend_from_slice(text);
+    signing_message
+}
+
+/// Wallets sign the 64 chars hex string of the message
+fn offchain_message(message: [u8; 32]) -> Vec<u8> {
+    offchain_text_message(hex::encode(message).as_bytes())
+}
+
+fn build_lock_args(pubkey: &PublicKey) -> Bytes {
+    let rollup_script_hash = [42u8; 32];
+    let mut args = rollup_script_hash.to_vec();
+    args.extend_from_slice(pubkey.as_bytes());
+    args.into()
+}
+
+#[test]
+fn test_sign_ed25519_message() {
+    let mut data_loader = DummyDataLoader::default();
+    let keypair = random_keypair();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = keypair.sign(&offchain_message(message));
+    let tx = gen_tx(
+        &mut data_loader,
+        build_lock_args(&keypair.public),
+        SigningType::WithPrefix,
+        message.to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature.to_bytes().to_vec().into()).expect("pass verification");
+}
+
+#[test]
+fn test_solana_offchain_message_vector() {
+    // `OffchainMessage::new(0, b"Test Message")` of the Solana SDK
+    let serialized = [
+        255, 115, 111, 108, 97, 110, 97, 32, 111, 102, 102, 99, 104, 97, 105, 110, 0, 0, 12, 0, 84,
+        101, 115, 116, 32, 77, 101, 115, 115, 97, 103, 101,
+    ];
+    assert_eq!(offchain_text_message(b"Test Message"), serialized);
+
+    // the secret key of RFC 8032 test 1 signs the text
+    // "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+    let secret = SecretKey::from_bytes(
+        &hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap(),
+    )
+    .expect("secret key");
+    let public = PublicKey::from(&secret);
+    assert_eq!(
+        hex::encode(public.as_bytes()),
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+    );
+    let keypair = Keypair { secret, public };
+    let mut message = [0u8; 32];
+    for (i, byte) in message.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let signature = hex::decode(
+        "99f35f4747d2b060c6c9428c5d2c14662786c4e61357c1bb80ac6172f5c20e15\
+         38319372482f25f377175be5bdb0654f0a424b3ba21355c05dc183b95acff202",
+    )
+    .unwrap();
+    assert_eq!(
+        keypair.sign(&offchain_message(message)).to_bytes().to_vec(),
+        signature
+    );
+
+    let mut data_loader = DummyDataLoader::default();
+    let tx = gen_tx(
+        &mut data_loader,
+        build_lock_args(&keypair.public),
+        SigningType::WithPrefix,
+        message.to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature.into()).expect("pass verification");
+}
+
+#[test]
+fn test_submit_signing_ed25519_message() {
+    let mut data_loader = DummyDataLoader::default();
+    let keypair = random_keypair();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = keypair.sign(&message);
+    let tx = gen_tx(
+        &mut data_loader,
+        build_lock_args(&keypair.public),
+        SigningType::Raw,
+        message.to_vec().into(),
+    );
+    verify_tx(&data_loader, tx, signature.to_bytes().to_vec().into()).expect("pass verification");
+}
+
+#[test]
+fn test_wrong_signature() {
+    let keypair = random_keypair();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+
+    // sign a wrong message
+    let wrong_message_signature = {
+        let mut wrong_message = [0u8; 32];
+        rng.fill(&mut wrong_message);
+        keypair.sign(&wrong_message)
+    };
+    // signed by another key
+    let wrong_key_signature = random_keypair().sign(&message);
+
+    for signature in [wrong_message_signature, wrong_key_signature] {
+        let mut data_loader = DummyDataLoader::default();
+        let tx = gen_tx(
+            &mut data_loader,
+            build_lock_args(&keypair.public),
+            SigningType::Raw,
+            message.to_vec().into(),
+        );
+        let verify_result = verify_tx(&data_loader, tx, signature.to_bytes().to_vec().into());
+        let script_cell_index = 0;
+        assert_error_eq!(
+            verify_result.unwrap_err(),
+            ScriptError::ValidationFailure(
+                format!(
+                    "by-data-hash/{}",
+                    ckb_types::H256(*ED25519_ACCOUNT_LOCK_CODE_HASH)
+                ),
+                ERROR_WRONG_SIGNATURE
+            )
+            .input_lock_script(script_cell_index)
+        );
+    }
+}
@@ -1,2 +1,6 @@
+mod bitcoin_account_lock;
+mod ed25519_account_lock;
 mod eth_account_lock;
+mod multisig_account_lock;
 mod tron_account_lock;
+mod webauthn_account_lock;
@@ -0,0 +1,384 @@
+use crate::script_tests::utils::layer1::*;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, ALWAYS_SUCCESS_PROGRAM, MULTISIG_ACCOUNT_LOCK_CODE_HASH,
+    MULTISIG_ACCOUNT_LOCK_PROGRAM, SECP256K1_DATA,
+};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_crypto::secp::{Generator, Privkey};
+use ckb_error::assert_error_eq;
+use ckb_hash::blake2b_256;
+use ckb_script::{ScriptError, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_types::core::hardfork::HardForkSwitch;
+use ckb_types::core::{Cycle, HeaderView};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+};
+use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH};
+use gw_types::core::SigningType;
+use rand::{thread_rng, Rng};
+use sha3::{Digest, Keccak256};
+
+use std::sync::atomic::Ordering;
+
+use super::eth_account_lock::sha3_pubkey_hash;
+
+const ERROR_INVALID_ARGS: i8 = 5;
+const ERROR_WRONG_SIGNATURE: i8 = 41;
+
+fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    lock_args: Bytes,
+    signing_type: SigningType,
+    message: Bytes,
+) -> TransactionView {
+    let mut rng = thread_rng();
+    // setup sighash_all dep
+    let script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let owner_lock_script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    // dep contract code
+    // multisig account lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(MULTISIG_ACCOUNT_LOCK_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    let script_cell_data_hash = CellOutput::calc_data_hash(&MULTISIG_ACCOUNT_LOCK_PROGRAM);
+    dummy.cells.insert(
+        script_out_point.clone(),
+        (script_cell, MULTISIG_ACCOUNT_LOCK_PROGRAM.clone()),
+    );
+    // owner lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ALWAYS_SUCCESS_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        owner_lock_script_out_point.clone(),
+        (script_cell, ALWAYS_SUCCESS_PROGRAM.clone()),
+    );
+    // owner lock cell
+    let owner_lock_cell = CellOutput::new_builder()
+        .lock(
+            Script::new_builder()
+                .code_hash((*ALWAYS_SUCCESS_CODE_HASH).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        )
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock_cell.lock().calc_script_hash().unpack();
+    let owner_lock_cell_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    dummy.cells.insert(
+        owner_lock_cell_out_point.clone(),
+        (owner_lock_cell, Bytes::default()),
+    );
+    // setup secp256k1_data dep
+    let secp256k1_data_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let secp256k1_data_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(SECP256K1_DATA.len())
+                .expect("data capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        secp256k1_data_out_point.clone(),
+        (secp256k1_data_cell, SECP256K1_DATA.clone()),
+    );
+    // setup default tx builder
+    let dummy_capacity = Capacity::shannons(42);
+    let tx_builder = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_data_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(owner_lock_script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(dummy_capacity.pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack());
+
+    let previous_out_point = {
+        let previous_tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(previous_tx_hash, 0)
+    };
+    let previous_output_cell = {
+        let script = Script::new_builder()
+            .args(lock_args.pack())
+            .code_hash(script_cell_data_hash)
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        CellOutput::new_builder()
+            .capacity(dummy_capacity.pack())
+            .lock(script)
+            .build()
+    };
+    let mut input_data = owner_lock_hash.to_vec();
+    input_data.push(signing_type.into());
+    input_data.extend_from_slice(&message);
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, input_data.into()),
+    );
+    tx_builder
+        .input(CellInput::new(previous_out_point, 0))
+        .input(CellInput::new(owner_lock_cell_out_point, 0))
+        .build()
+}
+
+fn verify_tx(
+    data_loader: &DummyDataLoader,
+    tx: TransactionView,
+    signature: Bytes,
+) -> Result<Cycle, ckb_error::Error> {
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(signature).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    let hardfork_switch = {
+        let switch = GLOBAL_HARDFORK_SWITCH.load_full();
+        HardForkSwitch::new_without_any_enabled()
+            .as_builder()
+            .rfc_0028(switch.rfc_0028())
+            .rfc_0029(switch.rfc_0029())
+            .rfc_0030(switch.rfc_0030())
+            .rfc_0031(switch.rfc_0031())
+            .rfc_0032(switch.rfc_0032())
+            .rfc_0036(switch.rfc_0036())
+            .rfc_0038(switch.rfc_0038())
+            .build()
+            .unwrap()
+    };
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let current_epoch_number = GLOBAL_CURRENT_EPOCH_NUMBER.load(Ordering::SeqCst);
+    let tx_verify_env = TxVerifyEnv::new_submit(
+        &HeaderView::new_advanced_builder()
+            .epoch(current_epoch_number.pack())
+            .build(),
+    );
+    let resolved_tx = build_resolved_tx(data_loader, &tx);
+    let mut verifier =
+        TransactionScriptsVerifier::new(&resolved_tx, &consensus, data_loader, &tx_verify_env);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+/// reserved | require_first_n | threshold | pubkeys_cnt | pubkey_hashes
+fn multisig_config(keys: &[Privkey], require_first_n: u8, threshold: u8) -> Vec<u8> {
+    let mut config = vec![0u8, require_first_n, threshold, keys.len() as u8];
+    for key in keys {
+        let pubkey = key.pubkey().expect("pubkey");
+        config.extend_from_slice(&sha3_pubkey_hash(&pubkey));
+    }
+    config
+}
+
+fn build_lock_args(config: &[u8]) -> Bytes {
+    let rollup_script_hash = [42u8; 32];
+    let mut args = rollup_script_hash.to_vec();
+    args.extend_from_slice(&blake2b_256(config)[..20]);
+    args.into()
+}
+
+fn signing_message(signing_type: SigningType, message: [u8; 32]) -> [u8; 32] {
+    match signing_type {
+        SigningType::WithPrefix => {
+            let mut hasher = Keccak256::new();
+            hasher.update("\x19Ethereum Signed Message:\n32");
+            hasher.update(&message);
+            hasher.finalize().into()
+        }
+        SigningType::Raw => message,
+    }
+}
+
+/// Sign with the keys, Ethereum style recovery ids if `eth_recovery_id` is set
+fn sign_message(keys: &[&Privkey], message: [u8; 32], eth_recovery_id: bool) -> Vec<u8> {
+    let mut signatures = Vec::new();
+    for key in keys {
+        let sig = key
+            .sign_recoverable(&ckb_types::H256::from(message))
+            .expect("sign");
+        let mut signature = sig.serialize();
+        if eth_recovery_id {
+            signature[64] += 27;
+        }
+        signatures.extend_from_slice(&signature);
+    }
+    signatures
+}
+
+fn random_message() -> [u8; 32] {
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    message
+}
+
+fn expect_err_code(verify_result: Result<Cycle, ckb_error::Error>, err_code: i8) {
+    let script_cell_index = 0;
+    assert_error_eq!(
+        verify_result.unwrap_err(),
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*MULTISIG_ACCOUNT_LOCK_CODE_HASH)
+            ),
+            err_code
+        )
+        .input_lock_script(script_cell_index)
+    );
+}
+
+#[test]
+fn test_sign_multisig_message() {
+    let keys: Vec<Privkey> = (0..3).map(|_| Generator::random_privkey()).collect();
+    let config = multisig_config(&keys, 1, 2);
+
+    for signing_type in [SigningType::WithPrefix, SigningType::Raw] {
+        for (signers, eth_recovery_id) in [
+            (vec![&keys[0], &keys[1]], false),
+            (vec![&keys[0], &keys[2]], true),
+        ] {
+            let mut data_loader = DummyDataLoader::default();
+            let message = random_message();
+            let mut witness = config.clone();
+            witness.extend(sign_message(
+                &signers,
+                signing_message(signing_type, message),
+                eth_recovery_id,
+            ));
+            let tx = gen_tx(
+                &mut data_loader,
+                build_lock_args(&config),
+                signing_type,
+                message.to_vec().into(),
+            );
+            verify_tx(&data_loader, tx, witness.into()).expect("pass verification");
+        }
+    }
+}
+
+#[test]
+fn test_wrong_multisig_signatures() {
+    let keys: Vec<Privkey> = (0..3).map(|_| Generator::random_privkey()).collect();
+    let config = multisig_config(&keys, 1, 2);
+    let message = random_message();
+    let outsider = Generator::random_privkey();
+
+    for signers in [
+        // the first key is required
+        vec![&keys[1], &keys[2]],
+        // out of order
+        vec![&keys[2], &keys[0]],
+        // duplicated signer
+        vec![&keys[0], &keys[0]],
+        // not in the config
+        vec![&keys[0], &outsider],
+    ] {
+        let mut data_loader = DummyDataLoader::default();
+        let mut witness = config.clone();
+        witness.extend(sign_message(&signers, message, false));
+        let tx = gen_tx(
+            &mut data_loader,
+            build_lock_args(&config),
+            SigningType::Raw,
+            message.to_vec().into(),
+        );
+        expect_err_code(
+            verify_tx(&data_loader, tx, witness.into()),
+            ERROR_WRONG_SIGNATURE,
+        );
+    }
+
+    // not enough signatures
+    let mut data_loader = DummyDataLoader::default();
+    let mut witness = config.clone();
+    witness.extend(sign_message(&[&keys[0]], message, false));
+    let tx = gen_tx(
+        &mut data_loader,
+        build_lock_args(&config),
+        SigningType::Raw,
+        message.to_vec().into(),
+    );
+    expect_err_code(
+        verify_tx(&data_loader, tx, witness.into()),
+        ERROR_WRONG_SIGNATURE,
+    );
+
+    // config doesn't match the args
+    let mut data_loader = DummyDataLoader::default();
+    let other_config = multisig_config(&keys, 0, 2);
+    let mut witness = other_config;
+    witness.extend(sign_message(&[&keys[1], &keys[2]], message, false));
+    let tx = gen_tx(
+        &mut data_loader,
+        build_lock_args(&config),
+        SigningType::Raw,
+        message.to_vec().into(),
+    );
+    expect_err_code(
+        verify_tx(&data_loader, tx, witness.into()),
+        ERROR_INVALID_ARGS,
+    );
+}
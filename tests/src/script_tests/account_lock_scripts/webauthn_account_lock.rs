@@ -0,0 +1,370 @@
+use crate::script_tests::utils::layer1::*;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, ALWAYS_SUCCESS_PROGRAM, SECP256K1_DATA,
+    WEBAUTHN_ACCOUNT_LOCK_CODE_HASH, WEBAUTHN_ACCOUNT_LOCK_PROGRAM,
+};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_error::assert_error_eq;
+use ckb_script::{ScriptError, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_types::core::hardfork::HardForkSwitch;
+use ckb_types::core::{Cycle, HeaderView};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+};
+use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH};
+use gw_types::core::SigningType;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey, VerifyingKey};
+use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
+
+use std::sync::atomic::Ordering;
+
+const ERROR_WRONG_SIGNATURE: i8 = 41;
+const RP_ID: &str = "godwoken.example";
+/// User Present | User Verified
+const AUTHENTICATOR_FLAGS: u8 = 0x05;
+
+fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    lock_args: Bytes,
+    signing_type: SigningType,
+    message: Bytes,
+) -> TransactionView {
+    let mut rng = thread_rng();
+    // setup sighash_all dep
+    let script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let owner_lock_script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    // dep contract code
+    // webauthn account lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(WEBAUTHN_ACCOUNT_LOCK_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    let script_cell_data_hash = CellOutput::calc_data_hash(&WEBAUTHN_ACCOUNT_LOCK_PROGRAM);
+    dummy.cells.insert(
+        script_out_point.clone(),
+        (script_cell, WEBAUTHN_ACCOUNT_LOCK_PROGRAM.clone()),
+    );
+    // owner lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ALWAYS_SUCCESS_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        owner_lock_script_out_point.clone(),
+        (script_cell, ALWAYS_SUCCESS_PROGRAM.clone()),
+    );
+    // owner lock cell
+    let owner_lock_cell = CellOutput::new_builder()
+        .lock(
+            Script::new_builder()
+                .code_hash((*ALWAYS_SUCCESS_CODE_HASH).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        )
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock_cell.lock().calc_script_hash().unpack();
+    let owner_lock_cell_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    dummy.cells.insert(
+        owner_lock_cell_out_point.clone(),
+        (owner_lock_cell, Bytes::default()),
+    );
+    // setup secp256k1_data dep
+    let secp256k1_data_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let secp256k1_data_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(SECP256K1_DATA.len())
+                .expect("data capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        secp256k1_data_out_point.clone(),
+        (secp256k1_data_cell, SECP256K1_DATA.clone()),
+    );
+    // setup default tx builder
+    let dummy_capacity = Capacity::shannons(42);
+    let tx_builder = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_data_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(owner_lock_script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(dummy_capacity.pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack());
+
+    let previous_out_point = {
+        let previous_tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(previous_tx_hash, 0)
+    };
+    let previous_output_cell = {
+        let script = Script::new_builder()
+            .args(lock_args.pack())
+            .code_hash(script_cell_data_hash)
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        CellOutput::new_builder()
+            .capacity(dummy_capacity.pack())
+            .lock(script)
+            .build()
+    };
+    let mut input_data = owner_lock_hash.to_vec();
+    input_data.push(signing_type.into());
+    input_data.extend_from_slice(&message);
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, input_data.into()),
+    );
+    tx_builder
+        .input(CellInput::new(previous_out_point, 0))
+        .input(CellInput::new(owner_lock_cell_out_point, 0))
+        .build()
+}
+
+fn verify_tx(
+    data_loader: &DummyDataLoader,
+    tx: TransactionView,
+    signature: Bytes,
+) -> Result<Cycle, ckb_error::Error> {
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(signature).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    let hardfork_switch = {
+        let switch = GLOBAL_HARDFORK_SWITCH.load_full();
+        HardForkSwitch::new_without_any_enabled()
+            .as_builder()
+            .rfc_0028(switch.rfc_0028())
+            .rfc_0029(switch.rfc_0029())
+            .rfc_0030(switch.rfc_0030())
+            .rfc_0031(switch.rfc_0031())
+            .rfc_0032(switch.rfc_0032())
+            .rfc_0036(switch.rfc_0036())
+            .rfc_0038(switch.rfc_0038())
+            .build()
+            .unwrap()
+    };
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let current_epoch_number = GLOBAL_CURRENT_EPOCH_NUMBER.load(Ordering::SeqCst);
+    let tx_verify_env = TxVerifyEnv::new_submit(
+        &HeaderView::new_advanced_builder()
+            .epoch(current_epoch_number.pack())
+            .build(),
+    );
+    let resolved_tx = build_resolved_tx(data_loader, &tx);
+    let mut verifier =
+        TransactionScriptsVerifier::new(&resolved_tx, &consensus, data_loader, &tx_verify_env);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+fn random_signing_key() -> SigningKey {
+    let mut rng = thread_rng();
+    loop {
+        let mut secret = [0u8; 32];
+        rng.fill(&mut secret);
+        if let Ok(key) = SigningKey::from_bytes(&secret) {
+            return key;
+        }
+    }
+}
+
+fn rp_id_hash(rp_id: &str) -> [u8; 32] {
+    Sha256::digest(rp_id.as_bytes()).into()
+}
+
+fn build_lock_args(key: &SigningKey) -> Bytes {
+    let rollup_script_hash = [42u8; 32];
+    let mut args = rollup_script_hash.to_vec();
+    args.extend_from_slice(&rp_id_hash(RP_ID));
+    let pubkey = VerifyingKey::from(key).to_encoded_point(true);
+    args.extend_from_slice(pubkey.as_bytes());
+    args.into()
+}
+
+fn authenticator_data(rp_id: &str, flags: u8) -> Vec<u8> {
+    let mut data = rp_id_hash(rp_id).to_vec();
+    data.push(flags);
+    // sign count
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data
+}
+
+fn client_data_json(type_: &str, challenge: &[u8]) -> Vec<u8> {
+    format!(
+        r#"{{"type":"{}","challenge":"{}","origin":"https://{}","crossOrigin":false}}"#,
+        type_,
+        base64::encode_config(challenge, base64::URL_SAFE_NO_PAD),
+        RP_ID
+    )
+    .into_bytes()
+}
+
+/// signature | authenticator_data_len | authenticator_data | client_data_json
+fn sign_assertion(key: &SigningKey, authenticator_data: &[u8], client_data_json: &[u8]) -> Bytes {
+    let mut signed_data = authenticator_data.to_vec();
+    signed_data.extend_from_slice(&Sha256::digest(client_data_json));
+    let signature: Signature = key.sign(&signed_data);
+
+    let mut assertion = signature.as_ref().to_vec();
+    assertion.extend_from_slice(&(authenticator_data.len() as u16).to_le_bytes());
+    assertion.extend_from_slice(authenticator_data);
+    assertion.extend_from_slice(client_data_json);
+    assertion.into()
+}
+
+fn random_message() -> [u8; 32] {
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    message
+}
+
+fn expect_wrong_signature(verify_result: Result<Cycle, ckb_error::Error>) {
+    let script_cell_index = 0;
+    assert_error_eq!(
+        verify_result.unwrap_err(),
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*WEBAUTHN_ACCOUNT_LOCK_CODE_HASH)
+            ),
+            ERROR_WRONG_SIGNATURE
+        )
+        .input_lock_script(script_cell_index)
+    );
+}
+
+#[test]
+fn test_sign_webauthn_assertion() {
+    let key = random_signing_key();
+    for signing_type in [SigningType::WithPrefix, SigningType::Raw] {
+        let mut data_loader = DummyDataLoader::default();
+        let message = random_message();
+        let signature = sign_assertion(
+            &key,
+            &authenticator_data(RP_ID, AUTHENTICATOR_FLAGS),
+            &client_data_json("webauthn.get", &message),
+        );
+        let tx = gen_tx(
+            &mut data_loader,
+            build_lock_args(&key),
+            signing_type,
+            message.to_vec().into(),
+        );
+        verify_tx(&data_loader, tx, signature).expect("pass verification");
+    }
+}
+
+#[test]
+fn test_wrong_webauthn_assertion() {
+    let key = random_signing_key();
+    let message = random_message();
+
+    let wrong_challenge = sign_assertion(
+        &key,
+        &authenticator_data(RP_ID, AUTHENTICATOR_FLAGS),
+        &client_data_json("webauthn.get", &random_message()),
+    );
+    let wrong_rp_id = sign_assertion(
+        &key,
+        &authenticator_data("evil.example", AUTHENTICATOR_FLAGS),
+        &client_data_json("webauthn.get", &message),
+    );
+    let user_not_present = sign_assertion(
+        &key,
+        &authenticator_data(RP_ID, 0),
+        &client_data_json("webauthn.get", &message),
+    );
+    let wrong_type = sign_assertion(
+        &key,
+        &authenticator_data(RP_ID, AUTHENTICATOR_FLAGS),
+        &client_data_json("webauthn.create", &message),
+    );
+    let wrong_key = sign_assertion(
+        &random_signing_key(),
+        &authenticator_data(RP_ID, AUTHENTICATOR_FLAGS),
+        &client_data_json("webauthn.get", &message),
+    );
+
+    for signature in [
+        wrong_challenge,
+        wrong_rp_id,
+        user_not_present,
+        wrong_type,
+        wrong_key,
+    ] {
+        let mut data_loader = DummyDataLoader::default();
+        let tx = gen_tx(
+            &mut data_loader,
+            build_lock_args(&key),
+            SigningType::Raw,
+            message.to_vec().into(),
+        );
+        expect_wrong_signature(verify_tx(&data_loader, tx, signature));
+    }
+}
@@ -23,6 +23,7 @@ use crate::testing_tool::chain::build_backend_manage;
 
 mod examples;
 mod meta_contract;
+mod rust_ports;
 mod sudt;
 
 const EXAMPLES_DIR: &str = "../../godwoken-scripts/c/build/examples";
@@ -119,11 +120,17 @@ pub const GW_LOG_SUDT_PAY_FEE: u8 = 0x1;
 pub const GW_LOG_POLYJUICE_SYSTEM: u8 = 0x2;
 #[allow(dead_code)]
 pub const GW_LOG_POLYJUICE_USER: u8 = 0x3;
+pub const GW_LOG_SUDT_APPROVE: u8 = 0x4;
+pub const GW_LOG_SUDT_MINT: u8 = 0x5;
+pub const GW_LOG_SUDT_BURN: u8 = 0x6;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum SudtLogType {
     Transfer,
     PayFee,
+    Approve,
+    Mint,
+    Burn,
 }
 
 impl SudtLogType {
@@ -131,8 +138,11 @@ impl SudtLogType {
         match service_flag {
             GW_LOG_SUDT_TRANSFER => Ok(Self::Transfer),
             GW_LOG_SUDT_PAY_FEE => Ok(Self::PayFee),
+            GW_LOG_SUDT_APPROVE => Ok(Self::Approve),
+            GW_LOG_SUDT_MINT => Ok(Self::Mint),
+            GW_LOG_SUDT_BURN => Ok(Self::Burn),
             _ => Err(format!(
-                "Not a sudt transfer/payfee prefix: {}",
+                "Not a sudt transfer/payfee/approve/mint/burn prefix: {}",
                 service_flag
             )),
         }
@@ -0,0 +1,787 @@
+//! Run the same transactions through the C builtin contracts and their Rust ports in
+//! `gw-context`, the exit codes, return data, logs and state roots must be the same.
+
+use super::super::utils::init_env_log;
+use super::sudt::{
+    build_address, build_fee, build_sudt_args, SUDT_ARGS_ALLOWANCE, SUDT_ARGS_APPROVE,
+    SUDT_ARGS_BATCH_TRANSFER, SUDT_ARGS_BURN, SUDT_ARGS_MINT, SUDT_ARGS_TRANSFER_FROM,
+};
+use super::{new_block_info, DummyChainStore};
+use crate::script_tests::utils::context::TestingContext;
+use crate::script_tests::utils::rollup::build_dynvec;
+use crate::testing_tool::chain::{
+    META_GENERATOR_PATH, META_VALIDATOR_PATH, META_VALIDATOR_SCRIPT_TYPE_HASH, SUDT_GENERATOR_PATH,
+    SUDT_VALIDATOR_PATH,
+};
+use crate::testing_tool::programs::ETH_ADDR_REG_CONTRACT_CODE_HASH;
+use gw_common::builtins::{CKB_SUDT_ACCOUNT_ID, RESERVED_ACCOUNT_ID};
+use gw_common::registry_address::RegistryAddress;
+use gw_common::state::State;
+use gw_common::H256;
+use gw_config::{BackendConfig, BackendType};
+use gw_generator::constants::L2TX_MAX_CYCLES;
+use gw_generator::{
+    account_lock_manage::AccountLockManage, backend_manage::BackendManage, error::TransactionError,
+    traits::StateExt, Generator,
+};
+use gw_types::core::{AllowedContractType, AllowedEoaType, ScriptHashType};
+use gw_types::offchain::RollupContext;
+use gw_types::U256;
+use gw_types::{
+    bytes::Bytes,
+    packed::{
+        AllowedTypeHash, BatchCreateEthAccounts, BatchSetMapping, BlockInfo, Byte20, Byte32Vec,
+        CreateAccount, ETHAddrRegArgs, EthToGw, Fee, GwToEth, MetaContractArgs, RawL2Transaction,
+        RollupConfig, SUDTArgs, SUDTQuery, Script, ScriptVec, SetMapping,
+    },
+    prelude::*,
+};
+
+const RUST_META_GENERATOR_PATH: &str = "../build/debug/meta-contract-generator";
+const RUST_SUDT_GENERATOR_PATH: &str = "../build/debug/sudt-generator";
+const RUST_ETH_ADDR_REG_GENERATOR_PATH: &str = "../build/debug/eth-addr-reg-generator";
+const ETH_ADDR_REG_VALIDATOR_PATH: &str = "../c/build/eth-addr-reg-validator";
+const ETH_ADDR_REG_GENERATOR_PATH: &str = "../c/build/eth-addr-reg-generator";
+
+const SUDT_VALIDATOR_SCRIPT_TYPE_HASH: [u8; 32] = [3u8; 32];
+const ETH_EOA_TYPE_HASH: [u8; 32] = [4u8; 32];
+const POLYJUICE_TYPE_HASH: [u8; 32] = [5u8; 32];
+const ROLLUP_SCRIPT_HASH: [u8; 32] = [42u8; 32];
+
+const SUDT_ARGS_TRANSFER: u32 = 1;
+
+const GW_FATAL_INVALID_DATA: i8 = 52;
+const GW_ERROR_DUPLICATED_SCRIPT_HASH: i8 = 80;
+const GW_ERROR_ACCOUNT_NOT_EXISTS: i8 = 85;
+const GW_SUDT_ERROR_INSUFFICIENT_BALANCE: i8 = 92;
+const GW_SUDT_ERROR_AMOUNT_OVERFLOW: i8 = 93;
+const GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE: i8 = 96;
+const GW_SUDT_ERROR_NOT_ISSUER: i8 = 97;
+const GW_REGISTRY_ERROR_DUPLICATE_MAPPING: i8 = 101;
+
+#[derive(Debug, Clone, Copy)]
+enum Backends {
+    C,
+    Rust,
+}
+
+fn build_ported_backend_manage(rollup_config: &RollupConfig, backends: Backends) -> BackendManage {
+    let (meta_generator_path, sudt_generator_path, eth_addr_reg_generator_path) = match backends {
+        Backends::C => (
+            META_GENERATOR_PATH,
+            SUDT_GENERATOR_PATH,
+            ETH_ADDR_REG_GENERATOR_PATH,
+        ),
+        Backends::Rust => (
+            RUST_META_GENERATOR_PATH,
+            RUST_SUDT_GENERATOR_PATH,
+            RUST_ETH_ADDR_REG_GENERATOR_PATH,
+        ),
+    };
+    let sudt_validator_script_type_hash: [u8; 32] =
+        rollup_config.l2_sudt_validator_script_type_hash().unpack();
+    let configs = vec![
+        BackendConfig {
+            validator_path: META_VALIDATOR_PATH.into(),
+            generator_path: meta_generator_path.into(),
+            validator_script_type_hash: META_VALIDATOR_SCRIPT_TYPE_HASH.into(),
+            backend_type: BackendType::Meta,
+        },
+        BackendConfig {
+            validator_path: SUDT_VALIDATOR_PATH.into(),
+            generator_path: sudt_generator_path.into(),
+            validator_script_type_hash: sudt_validator_script_type_hash.into(),
+            backend_type: BackendType::Sudt,
+        },
+        BackendConfig {
+            validator_path: ETH_ADDR_REG_VALIDATOR_PATH.into(),
+            generator_path: eth_addr_reg_generator_path.into(),
+            validator_script_type_hash: (*ETH_ADDR_REG_CONTRACT_CODE_HASH).into(),
+            backend_type: BackendType::EthAddrReg,
+        },
+    ];
+    BackendManage::from_config(configs).expect("default backend")
+}
+
+struct L2Tx {
+    name: &'static str,
+    from_id: u32,
+    to_id: u32,
+    args: Bytes,
+    exit_code: i8,
+}
+
+/// What a transaction does to the state, failed transactions change nothing
+#[derive(Debug, PartialEq, Eq)]
+struct Outcome {
+    exit_code: i8,
+    return_data: Vec<u8>,
+    logs: Vec<Bytes>,
+    state_root: H256,
+}
+
+fn run_txs(
+    rollup_config: &RollupConfig,
+    backends: Backends,
+    ctx: &mut TestingContext,
+    block_info: &BlockInfo,
+    txs: &[L2Tx],
+) -> Vec<Outcome> {
+    let rollup_ctx = RollupContext {
+        rollup_config: rollup_config.clone(),
+        rollup_script_hash: ROLLUP_SCRIPT_HASH.into(),
+    };
+    let generator = Generator::new(
+        build_ported_backend_manage(rollup_config, backends),
+        AccountLockManage::default(),
+        rollup_ctx,
+    );
+    txs.iter()
+        .map(|tx| {
+            let raw_tx = RawL2Transaction::new_builder()
+                .from_id(tx.from_id.pack())
+                .to_id(tx.to_id.pack())
+                .args(tx.args.pack())
+                .build();
+            let result = generator.execute_transaction(
+                &DummyChainStore,
+                &mut ctx.state,
+                block_info,
+                &raw_tx,
+                L2TX_MAX_CYCLES,
+                None,
+            );
+            match result {
+                Ok(run_result) => {
+                    ctx.state
+                        .apply_run_result(&run_result)
+                        .expect("update state");
+                    Outcome {
+                        exit_code: 0,
+                        return_data: run_result.return_data.clone(),
+                        logs: run_result.logs.iter().map(|log| log.as_bytes()).collect(),
+                        state_root: ctx.state.calculate_root().expect("state root"),
+                    }
+                }
+                Err(TransactionError::InvalidExitCode(code)) => Outcome {
+                    exit_code: code,
+                    return_data: Vec::new(),
+                    logs: Vec::new(),
+                    state_root: ctx.state.calculate_root().expect("state root"),
+                },
+                Err(err) => panic!("{:?} {}: unexpected {:?}", backends, tx.name, err),
+            }
+        })
+        .collect()
+}
+
+/// Run the transactions on the states built by `setup` through both backends
+fn check_ported_contracts<F: Fn() -> TestingContext>(
+    rollup_config: &RollupConfig,
+    setup: F,
+    block_info: &BlockInfo,
+    txs: &[L2Tx],
+) {
+    let c_outcomes = run_txs(rollup_config, Backends::C, &mut setup(), block_info, txs);
+    let rust_outcomes = run_txs(rollup_config, Backends::Rust, &mut setup(), block_info, txs);
+    for ((tx, c_outcome), rust_outcome) in txs.iter().zip(c_outcomes).zip(rust_outcomes) {
+        assert_eq!(c_outcome.exit_code, tx.exit_code, "C {}", tx.name);
+        assert_eq!(c_outcome, rust_outcome, "{}", tx.name);
+    }
+}
+
+fn build_rollup_config() -> RollupConfig {
+    RollupConfig::new_builder()
+        .l2_sudt_validator_script_type_hash(SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+        .allowed_eoa_type_hashes(
+            vec![AllowedTypeHash::new(AllowedEoaType::Eth, ETH_EOA_TYPE_HASH)].pack(),
+        )
+        .allowed_contract_type_hashes(
+            vec![
+                AllowedTypeHash::new(AllowedContractType::Sudt, SUDT_VALIDATOR_SCRIPT_TYPE_HASH),
+                AllowedTypeHash::new(AllowedContractType::Polyjuice, POLYJUICE_TYPE_HASH),
+            ]
+            .pack(),
+        )
+        .build()
+}
+
+/// rollup_script_hash(32 bytes) | eth_address(20 bytes)
+fn build_eoa_script(eth_address: [u8; 20]) -> Script {
+    let mut args = ROLLUP_SCRIPT_HASH.to_vec();
+    args.extend_from_slice(&eth_address);
+    Script::new_builder()
+        .code_hash(ETH_EOA_TYPE_HASH.pack())
+        .args(args.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build()
+}
+
+/// Create an ETH EOA with the eth address registered and some CKB to pay fee
+fn create_eoa(ctx: &mut TestingContext, eth_address: [u8; 20]) -> (u32, RegistryAddress) {
+    let id = ctx
+        .state
+        .create_account_from_script(build_eoa_script(eth_address))
+        .expect("create account");
+    let script_hash = ctx.state.get_script_hash(id).expect("get script hash");
+    let address = ctx.create_eth_address(script_hash, eth_address);
+    ctx.state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &address, U256::from(1000u64))
+        .expect("init CKB");
+    (id, address)
+}
+
+/// Create an account without registering its address
+fn create_account(ctx: &mut TestingContext, script: Script) -> H256 {
+    let id = ctx
+        .state
+        .create_account_from_script(script)
+        .expect("create account");
+    ctx.state.get_script_hash(id).expect("get script hash")
+}
+
+fn create_sudt(ctx: &mut TestingContext, args: Vec<u8>) -> u32 {
+    ctx.state
+        .create_account_from_script(
+            Script::new_builder()
+                .code_hash(SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+                .args(args.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .build(),
+        )
+        .expect("create account")
+}
+
+#[test]
+fn test_ported_sudt() {
+    init_env_log();
+    let rollup_config = build_rollup_config();
+    let setup = || {
+        let mut ctx = TestingContext::setup(&rollup_config);
+        let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+        let (b_id, b_address) = create_eoa(&mut ctx, [2u8; 20]);
+        let (c_id, c_address) = create_eoa(&mut ctx, [3u8; 20]);
+        let (_, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+        // a bridged token and a layer2 native token issued by A
+        let sudt_id = create_sudt(&mut ctx, [0u8; 64].to_vec());
+        let native_sudt_id = {
+            let mut args = [0u8; 32].to_vec();
+            args.extend_from_slice(&[7u8; 32]);
+            args.extend_from_slice(&a_address.to_bytes());
+            create_sudt(&mut ctx, args)
+        };
+        ctx.state
+            .mint_sudt(sudt_id, &a_address, U256::from(10000u64))
+            .expect("init balance");
+        let accounts = (
+            (a_id, a_address),
+            (b_id, b_address),
+            (c_id, c_address),
+            block_producer,
+            sudt_id,
+            native_sudt_id,
+        );
+        (ctx, accounts)
+    };
+    let (_, accounts) = setup();
+    let ((a_id, a), (b_id, b), (c_id, c), block_producer, sudt_id, native_sudt_id) = accounts;
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let fee = || build_fee(a.registry_id, 10);
+    let amount = |amount: U256| amount.pack().as_bytes();
+    let transfer_item =
+        |to: &RegistryAddress, value: U256| build_dynvec(&[build_address(to), amount(value)]);
+    let invalid_address = Bytes::from(vec![0u8; 4]).pack().as_bytes();
+
+    let txs = vec![
+        L2Tx {
+            name: "query",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: SUDTArgs::new_builder()
+                .set(
+                    SUDTQuery::new_builder()
+                        .address(Bytes::from(a.to_bytes()).pack())
+                        .build(),
+                )
+                .build()
+                .as_bytes(),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "transfer",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER,
+                &[build_address(&b), amount(1000u64.into()), fee()],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "transfer with insufficient balance",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER,
+                &[build_address(&b), amount(100000u64.into()), fee()],
+            ),
+            exit_code: GW_SUDT_ERROR_INSUFFICIENT_BALANCE,
+        },
+        L2Tx {
+            name: "transfer with an extra field",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER,
+                &[
+                    build_address(&b),
+                    amount(1u64.into()),
+                    fee(),
+                    amount(1u64.into()),
+                ],
+            ),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+        L2Tx {
+            name: "transfer to an invalid address",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER,
+                &[invalid_address.clone(), amount(1u64.into()), fee()],
+            ),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+        L2Tx {
+            name: "unknown message",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(8, &[build_address(&a)]),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+        L2Tx {
+            name: "approve",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_APPROVE,
+                &[build_address(&c), amount(3000u64.into()), fee()],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "allowance",
+            from_id: b_id,
+            to_id: sudt_id,
+            args: build_sudt_args(SUDT_ARGS_ALLOWANCE, &[build_address(&a), build_address(&c)]),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "transfer from",
+            from_id: c_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER_FROM,
+                &[
+                    build_address(&a),
+                    build_address(&b),
+                    amount(2000u64.into()),
+                    fee(),
+                ],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "transfer from with insufficient allowance",
+            from_id: c_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_TRANSFER_FROM,
+                &[
+                    build_address(&a),
+                    build_address(&b),
+                    amount(2000u64.into()),
+                    fee(),
+                ],
+            ),
+            exit_code: GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE,
+        },
+        L2Tx {
+            name: "batch transfer",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_BATCH_TRANSFER,
+                &[
+                    build_dynvec(&[
+                        transfer_item(&b, 100u64.into()),
+                        transfer_item(&c, 200u64.into()),
+                    ]),
+                    fee(),
+                ],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "batch transfer with an invalid item",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_BATCH_TRANSFER,
+                &[
+                    build_dynvec(&[
+                        transfer_item(&b, 100u64.into()),
+                        build_dynvec(&[invalid_address, amount(100u64.into())]),
+                    ]),
+                    fee(),
+                ],
+            ),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+        L2Tx {
+            name: "batch transfer with total amount overflow",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_BATCH_TRANSFER,
+                &[
+                    build_dynvec(&[transfer_item(&b, U256::MAX), transfer_item(&c, U256::one())]),
+                    fee(),
+                ],
+            ),
+            exit_code: GW_SUDT_ERROR_AMOUNT_OVERFLOW,
+        },
+        L2Tx {
+            name: "batch transfer with insufficient balance",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_BATCH_TRANSFER,
+                &[build_dynvec(&[transfer_item(&b, 1000000u64.into())]), fee()],
+            ),
+            exit_code: GW_SUDT_ERROR_INSUFFICIENT_BALANCE,
+        },
+        L2Tx {
+            name: "mint",
+            from_id: a_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_MINT,
+                &[build_address(&b), amount(500u64.into()), fee()],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "mint by a non-issuer",
+            from_id: b_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_MINT,
+                &[build_address(&b), amount(500u64.into()), fee()],
+            ),
+            exit_code: GW_SUDT_ERROR_NOT_ISSUER,
+        },
+        L2Tx {
+            name: "mint a bridged token",
+            from_id: a_id,
+            to_id: sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_MINT,
+                &[build_address(&a), amount(500u64.into()), fee()],
+            ),
+            exit_code: GW_SUDT_ERROR_NOT_ISSUER,
+        },
+        L2Tx {
+            name: "mint with total supply overflow",
+            from_id: a_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_MINT,
+                &[build_address(&a), amount(U256::MAX), fee()],
+            ),
+            exit_code: GW_SUDT_ERROR_AMOUNT_OVERFLOW,
+        },
+        L2Tx {
+            name: "mint to the issuer",
+            from_id: a_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(
+                SUDT_ARGS_MINT,
+                &[build_address(&a), amount(300u64.into()), fee()],
+            ),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "burn",
+            from_id: a_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(SUDT_ARGS_BURN, &[amount(100u64.into()), fee()]),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "burn with insufficient balance",
+            from_id: a_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(SUDT_ARGS_BURN, &[amount(1000u64.into()), fee()]),
+            exit_code: GW_SUDT_ERROR_INSUFFICIENT_BALANCE,
+        },
+        L2Tx {
+            name: "burn by a non-issuer",
+            from_id: b_id,
+            to_id: native_sudt_id,
+            args: build_sudt_args(SUDT_ARGS_BURN, &[amount(100u64.into()), fee()]),
+            exit_code: GW_SUDT_ERROR_NOT_ISSUER,
+        },
+    ];
+    check_ported_contracts(&rollup_config, || setup().0, &block_info, &txs);
+}
+
+#[test]
+fn test_ported_meta_contract() {
+    init_env_log();
+    let rollup_config = build_rollup_config();
+    let setup = || {
+        let mut ctx = TestingContext::setup(&rollup_config);
+        let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+        let (_, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+        (ctx, (a_id, a_address, block_producer))
+    };
+    let (_, (a_id, a, block_producer)) = setup();
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let fee = |amount: u128| {
+        Fee::new_builder()
+            .registry_id(a.registry_id.pack())
+            .amount(amount.pack())
+            .build()
+    };
+    let create_account = |script: Script, fee: Fee| {
+        MetaContractArgs::new_builder()
+            .set(CreateAccount::new_builder().script(script).fee(fee).build())
+            .build()
+            .as_bytes()
+    };
+    let batch_create_eth_accounts = |scripts: Vec<Script>| {
+        MetaContractArgs::new_builder()
+            .set(
+                BatchCreateEthAccounts::new_builder()
+                    .scripts(ScriptVec::new_builder().set(scripts).build())
+                    .fee(fee(10))
+                    .build(),
+            )
+            .build()
+            .as_bytes()
+    };
+    let sudt_script = {
+        let mut args = ROLLUP_SCRIPT_HASH.to_vec();
+        args.extend_from_slice(&[9u8; 32]);
+        Script::new_builder()
+            .code_hash(SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+            .args(args.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build()
+    };
+    // rollup_script_hash(32 bytes) | creator_account_id(4 bytes) | eth_address(20 bytes)
+    let polyjuice_script = {
+        let mut args = ROLLUP_SCRIPT_HASH.to_vec();
+        args.extend_from_slice(&a_id.to_le_bytes());
+        args.extend_from_slice(&[11u8; 20]);
+        Script::new_builder()
+            .code_hash(POLYJUICE_TYPE_HASH.pack())
+            .args(args.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build()
+    };
+
+    let txs = vec![
+        L2Tx {
+            name: "create account",
+            from_id: a_id,
+            to_id: RESERVED_ACCOUNT_ID,
+            args: create_account(sudt_script.clone(), fee(10)),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "create a duplicated account",
+            from_id: a_id,
+            to_id: RESERVED_ACCOUNT_ID,
+            args: create_account(sudt_script, fee(10)),
+            exit_code: GW_ERROR_DUPLICATED_SCRIPT_HASH,
+        },
+        L2Tx {
+            name: "create account with insufficient fee",
+            from_id: a_id,
+            to_id: RESERVED_ACCOUNT_ID,
+            args: create_account(build_eoa_script([5u8; 20]), fee(100000)),
+            exit_code: GW_SUDT_ERROR_INSUFFICIENT_BALANCE,
+        },
+        L2Tx {
+            name: "batch create eth accounts",
+            from_id: a_id,
+            to_id: RESERVED_ACCOUNT_ID,
+            args: batch_create_eth_accounts(vec![
+                build_eoa_script([6u8; 20]),
+                build_eoa_script([7u8; 20]),
+            ]),
+            exit_code: 0,
+        },
+        // `c/gw_eth_addr_reg.h` reads Polyjuice code hashes from `allowed_eoa_type_hashes`
+        L2Tx {
+            name: "batch create a Polyjuice contract account",
+            from_id: a_id,
+            to_id: RESERVED_ACCOUNT_ID,
+            args: batch_create_eth_accounts(vec![polyjuice_script]),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+    ];
+    check_ported_contracts(&rollup_config, || setup().0, &block_info, &txs);
+}
+
+#[test]
+fn test_ported_eth_addr_reg() {
+    init_env_log();
+    let rollup_config = build_rollup_config();
+    let setup = || {
+        let mut ctx = TestingContext::setup(&rollup_config);
+        let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+        let (_, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+        let x = create_account(&mut ctx, build_eoa_script([7u8; 20]));
+        let y = create_account(&mut ctx, build_eoa_script([8u8; 20]));
+        let z = create_account(&mut ctx, build_eoa_script([9u8; 20]));
+        // the eth address of A with another script
+        let duplicated = create_account(
+            &mut ctx,
+            build_eoa_script([1u8; 20])
+                .as_builder()
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        );
+        let polyjuice = {
+            let mut args = ROLLUP_SCRIPT_HASH.to_vec();
+            args.extend_from_slice(&a_id.to_le_bytes());
+            args.extend_from_slice(&[11u8; 20]);
+            create_account(
+                &mut ctx,
+                Script::new_builder()
+                    .code_hash(POLYJUICE_TYPE_HASH.pack())
+                    .args(args.pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .build(),
+            )
+        };
+        let accounts = (
+            (a_id, a_address),
+            block_producer,
+            [x, y, z],
+            duplicated,
+            polyjuice,
+        );
+        (ctx, accounts)
+    };
+    let (ctx, accounts) = setup();
+    let registry_id = ctx.eth_registry_id;
+    let ((a_id, a), block_producer, [x, y, z], duplicated, polyjuice) = accounts;
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let fee = || {
+        Fee::new_builder()
+            .registry_id(a.registry_id.pack())
+            .amount(10u128.pack())
+            .build()
+    };
+    let set_mapping = |script_hash: H256| {
+        ETHAddrRegArgs::new_builder()
+            .set(
+                SetMapping::new_builder()
+                    .gw_script_hash(script_hash.pack())
+                    .fee(fee())
+                    .build(),
+            )
+            .build()
+            .as_bytes()
+    };
+
+    let txs = vec![
+        L2Tx {
+            name: "set mapping",
+            from_id: a_id,
+            to_id: registry_id,
+            args: set_mapping(x),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "set mapping twice",
+            from_id: a_id,
+            to_id: registry_id,
+            args: set_mapping(x),
+            exit_code: GW_REGISTRY_ERROR_DUPLICATE_MAPPING,
+        },
+        L2Tx {
+            name: "eth address to script hash",
+            from_id: a_id,
+            to_id: registry_id,
+            args: ETHAddrRegArgs::new_builder()
+                .set(
+                    EthToGw::new_builder()
+                        .eth_address(Byte20::new_unchecked(Bytes::from(vec![7u8; 20])))
+                        .build(),
+                )
+                .build()
+                .as_bytes(),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "script hash to eth address",
+            from_id: a_id,
+            to_id: registry_id,
+            args: ETHAddrRegArgs::new_builder()
+                .set(GwToEth::new_builder().gw_script_hash(x.pack()).build())
+                .build()
+                .as_bytes(),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "batch set mapping",
+            from_id: a_id,
+            to_id: registry_id,
+            args: ETHAddrRegArgs::new_builder()
+                .set(
+                    BatchSetMapping::new_builder()
+                        .gw_script_hashes(
+                            Byte32Vec::new_builder()
+                                .push(y.pack())
+                                .push(z.pack())
+                                .build(),
+                        )
+                        .fee(fee())
+                        .build(),
+                )
+                .build()
+                .as_bytes(),
+            exit_code: 0,
+        },
+        L2Tx {
+            name: "set mapping of a registered eth address",
+            from_id: a_id,
+            to_id: registry_id,
+            args: set_mapping(duplicated),
+            exit_code: GW_REGISTRY_ERROR_DUPLICATE_MAPPING,
+        },
+        // `c/gw_eth_addr_reg.h` reads Polyjuice code hashes from `allowed_eoa_type_hashes`,
+        // so any account not an ETH EOA fails once the list is shorter than
+        // `allowed_contract_type_hashes`
+        L2Tx {
+            name: "set mapping of a Polyjuice contract account",
+            from_id: a_id,
+            to_id: registry_id,
+            args: set_mapping(polyjuice),
+            exit_code: GW_FATAL_INVALID_DATA,
+        },
+        L2Tx {
+            name: "set mapping of a non-existent account",
+            from_id: a_id,
+            to_id: registry_id,
+            args: set_mapping([12u8; 32].into()),
+            exit_code: GW_ERROR_ACCOUNT_NOT_EXISTS,
+        },
+    ];
+    check_ported_contracts(&rollup_config, || setup().0, &block_info, &txs);
+}
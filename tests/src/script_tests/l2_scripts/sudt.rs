@@ -1,7 +1,11 @@
 use super::super::utils::init_env_log;
 use crate::script_tests::utils::context::TestingContext;
 
-use super::{check_transfer_logs, new_block_info, run_contract, run_contract_get_result};
+use super::{
+    check_transfer_logs, new_block_info, run_contract, run_contract_get_result, SudtLog,
+    SudtLogType,
+};
+use crate::script_tests::utils::rollup::build_dynvec;
 use ckb_vm::Bytes;
 use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
 use gw_common::registry_address::RegistryAddress;
@@ -21,6 +25,37 @@ use gw_types::{
 
 const DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH: [u8; 32] = [3u8; 32];
 
+const GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE: i8 = 96;
+const GW_SUDT_ERROR_NOT_ISSUER: i8 = 97;
+const GW_FATAL_INVALID_DATA: i8 = 52;
+
+/// SUDTArgs items gw-types doesn't know yet
+pub(super) const SUDT_ARGS_APPROVE: u32 = 2;
+pub(super) const SUDT_ARGS_ALLOWANCE: u32 = 3;
+pub(super) const SUDT_ARGS_TRANSFER_FROM: u32 = 4;
+pub(super) const SUDT_ARGS_BATCH_TRANSFER: u32 = 5;
+pub(super) const SUDT_ARGS_MINT: u32 = 6;
+pub(super) const SUDT_ARGS_BURN: u32 = 7;
+
+/// union header: item id | table fields
+pub(super) fn build_sudt_args(item_id: u32, fields: &[Bytes]) -> Bytes {
+    let mut args = item_id.to_le_bytes().to_vec();
+    args.extend_from_slice(&build_dynvec(fields));
+    Bytes::from(args)
+}
+
+pub(super) fn build_fee(registry_id: u32, amount: u128) -> Bytes {
+    Fee::new_builder()
+        .registry_id(registry_id.pack())
+        .amount(amount.pack())
+        .build()
+        .as_bytes()
+}
+
+pub(super) fn build_address(address: &RegistryAddress) -> Bytes {
+    Bytes::from(address.to_bytes()).pack().as_bytes()
+}
+
 #[test]
 fn test_sudt() {
     init_env_log();
@@ -1211,6 +1246,664 @@ fn test_transfer_overflow() {
     }
 }
 
+#[test]
+fn test_approve_and_transfer_from() {
+    init_env_log();
+    let rollup_config = RollupConfig::new_builder()
+        .l2_sudt_validator_script_type_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+        .build();
+    let mut ctx = TestingContext::setup(&rollup_config);
+
+    let init_a_balance = U256::from(10000u64);
+
+    // init accounts
+    let sudt_id = ctx
+        .state
+        .create_account_from_script(
+            Script::new_builder()
+                .code_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.clone().pack())
+                .args([0u8; 64].to_vec().pack())
+                .hash_type(ScriptHashType::Type.into())
+                .build(),
+        )
+        .expect("create account");
+    let create_eoa = |ctx: &mut TestingContext, eth_address: [u8; 20]| {
+        let id = ctx
+            .state
+            .create_account_from_script(
+                Script::new_builder()
+                    .code_hash([0u8; 32].pack())
+                    .args(eth_address.to_vec().pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .build(),
+            )
+            .expect("create account");
+        let script_hash = ctx.state.get_script_hash(id).expect("get script hash");
+        let address = ctx.create_eth_address(script_hash.into(), eth_address);
+        (id, address)
+    };
+    let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+    let (b_id, b_address) = create_eoa(&mut ctx, [2u8; 20]);
+    let (c_id, c_address) = create_eoa(&mut ctx, [3u8; 20]);
+    let (_block_producer_id, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let registry_id = a_address.registry_id;
+
+    // init balance for a, and ckb for a & c to pay fee
+    ctx.state
+        .mint_sudt(sudt_id, &a_address, init_a_balance)
+        .expect("init balance");
+    let init_ckb: U256 = 100u64.into();
+    ctx.state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &a_address, init_ckb)
+        .expect("init balance");
+    ctx.state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &c_address, init_ckb)
+        .expect("init balance");
+
+    // A approves C
+    let approved: U256 = 3000u64.into();
+    let fee = 42u128;
+    {
+        let args = build_sudt_args(
+            SUDT_ARGS_APPROVE,
+            &[
+                build_address(&c_address),
+                approved.pack().as_bytes(),
+                build_fee(registry_id, fee),
+            ],
+        );
+        let run_result = run_contract_get_result(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect("approve");
+        assert!(run_result.return_data.is_empty());
+        assert_eq!(run_result.logs.len(), 2);
+        let approve_log = SudtLog::from_log_item(&run_result.logs[1]).unwrap();
+        assert_eq!(approve_log.sudt_id, sudt_id);
+        assert_eq!(approve_log.from_addr, a_address);
+        assert_eq!(approve_log.to_addr, c_address);
+        assert_eq!(approve_log.amount, approved);
+        assert_eq!(approve_log.log_type, SudtLogType::Approve);
+
+        check_allowance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            &a_address,
+            &c_address,
+            approved,
+        );
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            CKB_SUDT_ACCOUNT_ID,
+            &a_address,
+            init_ckb - fee,
+        );
+    }
+
+    // C transfers from A to B
+    let value: U256 = 1000u64.into();
+    {
+        let args = build_sudt_args(
+            SUDT_ARGS_TRANSFER_FROM,
+            &[
+                build_address(&a_address),
+                build_address(&b_address),
+                value.pack().as_bytes(),
+                build_fee(registry_id, fee),
+            ],
+        );
+        let run_result = run_contract_get_result(
+            &rollup_config,
+            &mut ctx.state,
+            c_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect("transfer from");
+        assert_eq!(run_result.logs.len(), 2);
+        // the fee is paid by the spender
+        let fee_log = SudtLog::from_log_item(&run_result.logs[0]).unwrap();
+        assert_eq!(fee_log.from_addr, c_address);
+        assert_eq!(fee_log.log_type, SudtLogType::PayFee);
+        let transfer_log = SudtLog::from_log_item(&run_result.logs[1]).unwrap();
+        assert_eq!(transfer_log.from_addr, a_address);
+        assert_eq!(transfer_log.to_addr, b_address);
+        assert_eq!(transfer_log.amount, value);
+        assert_eq!(transfer_log.log_type, SudtLogType::Transfer);
+
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            &a_address,
+            init_a_balance - value,
+        );
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            &b_address,
+            value,
+        );
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            CKB_SUDT_ACCOUNT_ID,
+            &c_address,
+            init_ckb - fee,
+        );
+        check_allowance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            &a_address,
+            &c_address,
+            approved - value,
+        );
+    }
+
+    // C can't transfer more than the allowance, B isn't approved
+    for (sender_id, amount) in [(c_id, approved - value + U256::one()), (b_id, U256::one())] {
+        let args = build_sudt_args(
+            SUDT_ARGS_TRANSFER_FROM,
+            &[
+                build_address(&a_address),
+                build_address(&b_address),
+                amount.pack().as_bytes(),
+                build_fee(registry_id, 0),
+            ],
+        );
+        let err = run_contract(
+            &rollup_config,
+            &mut ctx.state,
+            sender_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect_err("err");
+        let err_code = match err {
+            TransactionError::InvalidExitCode(code) => code,
+            err => panic!("unexpected {:?}", err),
+        };
+        assert_eq!(err_code, GW_SUDT_ERROR_INSUFFICIENT_ALLOWANCE);
+    }
+
+    // approve overwrites the allowance
+    {
+        let args = build_sudt_args(
+            SUDT_ARGS_APPROVE,
+            &[
+                build_address(&c_address),
+                U256::zero().pack().as_bytes(),
+                build_fee(registry_id, 0),
+            ],
+        );
+        run_contract(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect("approve");
+        check_allowance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            &a_address,
+            &c_address,
+            U256::zero(),
+        );
+    }
+}
+
+#[test]
+fn test_batch_transfer() {
+    init_env_log();
+    let rollup_config = RollupConfig::new_builder()
+        .l2_sudt_validator_script_type_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+        .build();
+    let mut ctx = TestingContext::setup(&rollup_config);
+
+    let init_a_balance = U256::from(10000u64);
+
+    // init accounts
+    let sudt_id = ctx
+        .state
+        .create_account_from_script(
+            Script::new_builder()
+                .code_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.clone().pack())
+                .args([0u8; 64].to_vec().pack())
+                .hash_type(ScriptHashType::Type.into())
+                .build(),
+        )
+        .expect("create account");
+    let create_eoa = |ctx: &mut TestingContext, eth_address: [u8; 20]| {
+        let id = ctx
+            .state
+            .create_account_from_script(
+                Script::new_builder()
+                    .code_hash([0u8; 32].pack())
+                    .args(eth_address.to_vec().pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .build(),
+            )
+            .expect("create account");
+        let script_hash = ctx.state.get_script_hash(id).expect("get script hash");
+        let address = ctx.create_eth_address(script_hash.into(), eth_address);
+        (id, address)
+    };
+    let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+    let (_b_id, b_address) = create_eoa(&mut ctx, [2u8; 20]);
+    let (_c_id, c_address) = create_eoa(&mut ctx, [3u8; 20]);
+    let (_block_producer_id, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let registry_id = a_address.registry_id;
+
+    ctx.state
+        .mint_sudt(sudt_id, &a_address, init_a_balance)
+        .expect("init balance");
+    let init_ckb: U256 = 100u64.into();
+    ctx.state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &a_address, init_ckb)
+        .expect("init balance");
+
+    let build_batch_transfer_args = |transfers: &[(&RegistryAddress, U256)], fee: u128| {
+        let items: Vec<Bytes> = transfers
+            .iter()
+            .map(|(to, amount)| build_dynvec(&[build_address(to), amount.pack().as_bytes()]))
+            .collect();
+        build_sudt_args(
+            SUDT_ARGS_BATCH_TRANSFER,
+            &[build_dynvec(&items), build_fee(registry_id, fee)],
+        )
+    };
+
+    // A pays B twice and C once, only one fee is charged
+    let fee = 42u128;
+    let transfers = [
+        (&b_address, U256::from(100u64)),
+        (&c_address, U256::from(200u64)),
+        (&b_address, U256::from(300u64)),
+    ];
+    {
+        let args = build_batch_transfer_args(&transfers, fee);
+        let run_result = run_contract_get_result(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect("batch transfer");
+        assert!(run_result.return_data.is_empty());
+        assert_eq!(run_result.logs.len(), 1 + transfers.len());
+        let fee_log = SudtLog::from_log_item(&run_result.logs[0]).unwrap();
+        assert_eq!(fee_log.sudt_id, CKB_SUDT_ACCOUNT_ID);
+        assert_eq!(fee_log.from_addr, a_address);
+        assert_eq!(fee_log.to_addr, block_producer);
+        assert_eq!(fee_log.amount, fee.into());
+        assert_eq!(fee_log.log_type, SudtLogType::PayFee);
+        for (log, (to, amount)) in run_result.logs[1..].iter().zip(transfers.iter()) {
+            let log = SudtLog::from_log_item(log).unwrap();
+            assert_eq!(log.sudt_id, sudt_id);
+            assert_eq!(&log.from_addr, &a_address);
+            assert_eq!(&log.to_addr, *to);
+            assert_eq!(&log.amount, amount);
+            assert_eq!(log.log_type, SudtLogType::Transfer);
+        }
+    }
+    let expected_balances = [
+        (&a_address, init_a_balance - 600u64),
+        (&b_address, U256::from(400u64)),
+        (&c_address, U256::from(200u64)),
+    ];
+    for (address, balance) in expected_balances {
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            address,
+            balance,
+        );
+    }
+    check_balance(
+        &rollup_config,
+        &mut ctx.state,
+        &block_info,
+        a_id,
+        CKB_SUDT_ACCOUNT_ID,
+        &a_address,
+        init_ckb - fee,
+    );
+
+    // the batch fails as a whole if the total exceeds the balance
+    {
+        let transfers = [
+            (&b_address, U256::from(100u64)),
+            (&c_address, init_a_balance),
+        ];
+        let args = build_batch_transfer_args(&transfers, 0);
+        let err = run_contract(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect_err("err");
+        let err_code = match err {
+            TransactionError::InvalidExitCode(code) => code,
+            err => panic!("unexpected {:?}", err),
+        };
+        assert_eq!(err_code, GW_SUDT_ERROR_INSUFFICIENT_BALANCE);
+    }
+    // an invalid later item fails the batch, neither the fee nor the earlier items are applied
+    {
+        let items = vec![
+            build_dynvec(&[
+                build_address(&b_address),
+                U256::from(100u64).pack().as_bytes(),
+            ]),
+            build_dynvec(&[
+                Bytes::from(vec![1u8; 4]).pack().as_bytes(),
+                U256::from(100u64).pack().as_bytes(),
+            ]),
+        ];
+        let args = build_sudt_args(
+            SUDT_ARGS_BATCH_TRANSFER,
+            &[build_dynvec(&items), build_fee(registry_id, fee)],
+        );
+        let err = run_contract(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            sudt_id,
+            args,
+            &block_info,
+        )
+        .expect_err("err");
+        let err_code = match err {
+            TransactionError::InvalidExitCode(code) => code,
+            err => panic!("unexpected {:?}", err),
+        };
+        assert_eq!(err_code, GW_FATAL_INVALID_DATA);
+    }
+    for (address, balance) in expected_balances {
+        check_balance(
+            &rollup_config,
+            &mut ctx.state,
+            &block_info,
+            a_id,
+            sudt_id,
+            address,
+            balance,
+        );
+    }
+    check_balance(
+        &rollup_config,
+        &mut ctx.state,
+        &block_info,
+        a_id,
+        CKB_SUDT_ACCOUNT_ID,
+        &a_address,
+        init_ckb - fee,
+    );
+}
+
+#[test]
+fn test_mint_and_burn() {
+    init_env_log();
+    let rollup_config = RollupConfig::new_builder()
+        .l2_sudt_validator_script_type_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.pack())
+        .build();
+    let mut ctx = TestingContext::setup(&rollup_config);
+
+    let create_eoa = |ctx: &mut TestingContext, eth_address: [u8; 20]| {
+        let id = ctx
+            .state
+            .create_account_from_script(
+                Script::new_builder()
+                    .code_hash([0u8; 32].pack())
+                    .args(eth_address.to_vec().pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .build(),
+            )
+            .expect("create account");
+        let script_hash = ctx.state.get_script_hash(id).expect("get script hash");
+        let address = ctx.create_eth_address(script_hash.into(), eth_address);
+        (id, address)
+    };
+    let (a_id, a_address) = create_eoa(&mut ctx, [1u8; 20]);
+    let (b_id, b_address) = create_eoa(&mut ctx, [2u8; 20]);
+    let (_block_producer_id, block_producer) = create_eoa(&mut ctx, [42u8; 20]);
+    let block_info = new_block_info(&block_producer, 1, 0);
+    let registry_id = a_address.registry_id;
+
+    // a bridged token and a layer2 native token issued by A
+    let create_sudt = |ctx: &mut TestingContext, args: Vec<u8>| {
+        ctx.state
+            .create_account_from_script(
+                Script::new_builder()
+                    .code_hash(DUMMY_SUDT_VALIDATOR_SCRIPT_TYPE_HASH.clone().pack())
+                    .args(args.pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .build(),
+            )
+            .expect("create account")
+    };
+    let bridged_sudt_id = create_sudt(&mut ctx, [0u8; 64].to_vec());
+    let native_sudt_id = {
+        let mut args = [0u8; 32].to_vec();
+        args.extend_from_slice(&[7u8; 32]);
+        args.extend_from_slice(&a_address.to_bytes());
+        create_sudt(&mut ctx, args)
+    };
+
+    let init_ckb: U256 = 100u64.into();
+    ctx.state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &a_address, init_ckb)
+        .expect("init balance");
+
+    let build_mint_args = |to: &RegistryAddress, amount: U256, fee: u128| {
+        build_sudt_args(
+            SUDT_ARGS_MINT,
+            &[
+                build_address(to),
+                amount.pack().as_bytes(),
+                build_fee(registry_id, fee),
+            ],
+        )
+    };
+    let build_burn_args = |amount: U256, fee: u128| {
+        build_sudt_args(
+            SUDT_ARGS_BURN,
+            &[amount.pack().as_bytes(), build_fee(registry_id, fee)],
+        )
+    };
+    let expect_err_code = |err: TransactionError| match err {
+        TransactionError::InvalidExitCode(code) => code,
+        err => panic!("unexpected {:?}", err),
+    };
+
+    // A mints to B
+    let fee = 42u128;
+    let minted: U256 = 1000u64.into();
+    {
+        let run_result = run_contract_get_result(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            native_sudt_id,
+            build_mint_args(&b_address, minted, fee),
+            &block_info,
+        )
+        .expect("mint");
+        assert!(run_result.return_data.is_empty());
+        assert_eq!(run_result.logs.len(), 2);
+        let mint_log = SudtLog::from_log_item(&run_result.logs[1]).unwrap();
+        assert_eq!(mint_log.sudt_id, native_sudt_id);
+        assert_eq!(mint_log.from_addr, a_address);
+        assert_eq!(mint_log.to_addr, b_address);
+        assert_eq!(mint_log.amount, minted);
+        assert_eq!(mint_log.log_type, SudtLogType::Mint);
+    }
+    check_balance(
+        &rollup_config,
+        &mut ctx.state,
+        &block_info,
+        a_id,
+        native_sudt_id,
+        &b_address,
+        minted,
+    );
+    check_balance(
+        &rollup_config,
+        &mut ctx.state,
+        &block_info,
+        a_id,
+        CKB_SUDT_ACCOUNT_ID,
+        &a_address,
+        init_ckb - fee,
+    );
+    assert_eq!(
+        ctx.state.get_sudt_total_supply(native_sudt_id).unwrap(),
+        minted
+    );
+
+    // only the issuer of a layer2 native token can mint
+    for (sender_id, sudt_id) in [(b_id, native_sudt_id), (a_id, bridged_sudt_id)] {
+        let err = run_contract(
+            &rollup_config,
+            &mut ctx.state,
+            sender_id,
+            sudt_id,
+            build_mint_args(&b_address, minted, 0),
+            &block_info,
+        )
+        .expect_err("err");
+        assert_eq!(expect_err_code(err), GW_SUDT_ERROR_NOT_ISSUER);
+    }
+
+    // A mints to itself then burns part of it
+    let burned: U256 = 300u64.into();
+    run_contract(
+        &rollup_config,
+        &mut ctx.state,
+        a_id,
+        native_sudt_id,
+        build_mint_args(&a_address, minted, 0),
+        &block_info,
+    )
+    .expect("mint");
+    {
+        let run_result = run_contract_get_result(
+            &rollup_config,
+            &mut ctx.state,
+            a_id,
+            native_sudt_id,
+            build_burn_args(burned, 0),
+            &block_info,
+        )
+        .expect("burn");
+        assert_eq!(run_result.logs.len(), 2);
+        let burn_log = SudtLog::from_log_item(&run_result.logs[1]).unwrap();
+        assert_eq!(burn_log.sudt_id, native_sudt_id);
+        assert_eq!(burn_log.from_addr, a_address);
+        assert_eq!(burn_log.amount, burned);
+        assert_eq!(burn_log.log_type, SudtLogType::Burn);
+    }
+    check_balance(
+        &rollup_config,
+        &mut ctx.state,
+        &block_info,
+        a_id,
+        native_sudt_id,
+        &a_address,
+        minted - burned,
+    );
+    assert_eq!(
+        ctx.state.get_sudt_total_supply(native_sudt_id).unwrap(),
+        minted + minted - burned
+    );
+
+    // the issuer can only burn its own balance
+    let err = run_contract(
+        &rollup_config,
+        &mut ctx.state,
+        a_id,
+        native_sudt_id,
+        build_burn_args(minted, 0),
+        &block_info,
+    )
+    .expect_err("err");
+    assert_eq!(expect_err_code(err), GW_SUDT_ERROR_INSUFFICIENT_BALANCE);
+    let err = run_contract(
+        &rollup_config,
+        &mut ctx.state,
+        b_id,
+        native_sudt_id,
+        build_burn_args(U256::one(), 0),
+        &block_info,
+    )
+    .expect_err("err");
+    assert_eq!(expect_err_code(err), GW_SUDT_ERROR_NOT_ISSUER);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_allowance<S: State + CodeStore>(
+    rollup_config: &RollupConfig,
+    tree: &mut S,
+    block_info: &BlockInfo,
+    sender_id: u32,
+    sudt_id: u32,
+    owner: &RegistryAddress,
+    spender: &RegistryAddress,
+    expected_allowance: U256,
+) {
+    let args = build_sudt_args(
+        SUDT_ARGS_ALLOWANCE,
+        &[build_address(owner), build_address(spender)],
+    );
+    let return_data =
+        run_contract(rollup_config, tree, sender_id, sudt_id, args, block_info).expect("execute");
+    let allowance = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&return_data);
+        U256::from_little_endian(&buf)
+    };
+    assert_eq!(allowance, expected_allowance);
+}
+
 fn check_balance<S: State + CodeStore>(
     rollup_config: &RollupConfig,
     tree: &mut S,
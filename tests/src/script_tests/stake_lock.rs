@@ -0,0 +1,203 @@
+use super::utils::init_env_log;
+use super::utils::layer1::{
+    always_success_script, build_simple_tx_with_out_point, random_out_point,
+};
+use super::utils::rollup::{
+    build_always_success_cell, build_rollup_config_with_extra_fields, build_rollup_locked_cell,
+    build_stake_asset_vec, build_type_id_script, CellContext, CellContextParam,
+};
+
+use crate::testing_tool::programs::STAKE_LOCK_PROGRAM;
+
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
+use ckb_types::{
+    core::Cycle,
+    packed::{CellDep, CellInput, CellOutput, Script},
+    prelude::{Pack as CKBPack, Unpack},
+};
+use gw_types::bytes::Bytes;
+use gw_types::core::ScriptHashType;
+use gw_types::packed::{GlobalState, RollupConfig, StakeLockArgs};
+use gw_types::prelude::{Pack as GWPack, *};
+
+const INVALID_STAKE_CELL_UNLOCK_ERROR: i8 = 22;
+const INVALID_STAKE_CELL_ERROR: i8 = 25;
+
+const REQUIRED_STAKING_CAPACITY: u64 = 5000_00000000u64;
+const MINIMUM_STAKE_AMOUNT: u128 = 1000u128;
+const SUDT_STAKE_CAPACITY: u64 = 500_00000000u64;
+
+/// Stake value of a stake cell, the CKB capacity or the sUDT amount
+#[derive(Clone, Copy)]
+enum Stake {
+    Ckb(u64),
+    Sudt(u128),
+}
+
+#[test]
+fn test_withdraw_surplus_ckb_stake() {
+    init_env_log();
+
+    let input = Stake::Ckb(REQUIRED_STAKING_CAPACITY * 2);
+    withdraw_surplus_stake(input, &[Stake::Ckb(REQUIRED_STAKING_CAPACITY)])
+        .expect("decrease to the requirement");
+
+    let err =
+        withdraw_surplus_stake(input, &[Stake::Ckb(REQUIRED_STAKING_CAPACITY - 1)]).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STAKE_CELL_ERROR));
+}
+
+#[test]
+fn test_withdraw_surplus_sudt_stake() {
+    init_env_log();
+
+    let input = Stake::Sudt(MINIMUM_STAKE_AMOUNT + 500);
+    withdraw_surplus_stake(input, &[Stake::Sudt(MINIMUM_STAKE_AMOUNT)])
+        .expect("decrease to the minimum amount");
+
+    let err = withdraw_surplus_stake(input, &[Stake::Sudt(MINIMUM_STAKE_AMOUNT - 1)]).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STAKE_CELL_ERROR));
+}
+
+#[test]
+fn test_withdraw_surplus_stake_with_changed_asset() {
+    init_env_log();
+
+    let input = Stake::Ckb(REQUIRED_STAKING_CAPACITY * 2);
+    let err = withdraw_surplus_stake(input, &[Stake::Sudt(MINIMUM_STAKE_AMOUNT)]).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STAKE_CELL_UNLOCK_ERROR));
+}
+
+#[test]
+fn test_withdraw_surplus_stake_to_multiple_stake_cells() {
+    init_env_log();
+
+    let input = Stake::Ckb(REQUIRED_STAKING_CAPACITY * 2);
+    let outputs = [
+        Stake::Ckb(REQUIRED_STAKING_CAPACITY),
+        Stake::Ckb(REQUIRED_STAKING_CAPACITY),
+    ];
+    let err = withdraw_surplus_stake(input, &outputs).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STAKE_CELL_UNLOCK_ERROR));
+}
+
+fn expected_err(code: i8) -> ckb_error::Error {
+    let stake_script_type_hash: [u8; 32] = build_type_id_script(b"stake_lock_type_id")
+        .calc_script_hash()
+        .unpack();
+    ScriptError::ValidationFailure(
+        format!("by-type-hash/{}", ckb_types::H256(stake_script_type_hash)),
+        code,
+    )
+    .input_lock_script(0)
+    .into()
+}
+
+/// Unlock an unfinalized stake cell by its owner, the rollup cell is in the cell deps
+fn withdraw_surplus_stake(input: Stake, outputs: &[Stake]) -> Result<Cycle, ckb_error::Error> {
+    // rollup lock & config
+    let rollup_type_script = build_type_id_script(b"rollup_type_id");
+    let rollup_type_hash: [u8; 32] = rollup_type_script.calc_script_hash().unpack();
+    let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
+    let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let l1_sudt_type = build_type_id_script(b"l1_sudt_type_id");
+    let l1_sudt_script_type_hash: [u8; 32] = l1_sudt_type.calc_script_hash().unpack();
+    let sudt_script = Script::new_builder()
+        .code_hash(CKBPack::pack(&l1_sudt_script_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(CKBPack::pack(&Bytes::from(b"governance_token".to_vec())))
+        .build();
+    let sudt_script_hash: [u8; 32] = sudt_script.calc_script_hash().unpack();
+    let rollup_config = {
+        let rollup_config = RollupConfig::new_builder()
+            .stake_script_type_hash(GWPack::pack(&stake_script_type_hash))
+            .l1_sudt_script_type_hash(GWPack::pack(&l1_sudt_script_type_hash))
+            .required_staking_capacity(GWPack::pack(&REQUIRED_STAKING_CAPACITY))
+            .build();
+        let allowed_stake_assets =
+            build_stake_asset_vec(&[(sudt_script_hash, MINIMUM_STAKE_AMOUNT)]);
+        build_rollup_config_with_extra_fields(&rollup_config, vec![allowed_stake_assets])
+    };
+    // deploy scripts, the l1 sUDT type is always success
+    let param = CellContextParam {
+        stake_lock_type: stake_lock_type.clone(),
+        always_success_type: l1_sudt_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    // deploy stake-lock instead of the always success lock
+    let stake_lock_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(STAKE_LOCK_PROGRAM.len() as u64)))
+            .type_(CKBPack::pack(&Some(stake_lock_type)))
+            .build();
+        let out_point = ctx.insert_cell(cell, STAKE_LOCK_PROGRAM.clone());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    // the stake block isn't finalized yet
+    let last_finalized_block_number = 100u64;
+    let rollup_dep = {
+        let global_state = GlobalState::new_builder()
+            .rollup_config_hash(GWPack::pack(&rollup_config.hash()))
+            .last_finalized_block_number(GWPack::pack(&last_finalized_block_number))
+            .build();
+        let cell = build_always_success_cell(1000_00000000u64, Some(rollup_type_script));
+        let out_point = ctx.insert_cell(cell, global_state.as_bytes());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+
+    let owner_lock_hash: [u8; 32] = always_success_script().calc_script_hash().unpack();
+    let build_stake_cell = |stake: Stake| {
+        let lock_args = StakeLockArgs::new_builder()
+            .owner_lock_hash(GWPack::pack(&owner_lock_hash))
+            .stake_block_number(GWPack::pack(&(last_finalized_block_number + 1)))
+            .build();
+        match stake {
+            Stake::Ckb(capacity) => {
+                let cell = build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &stake_script_type_hash,
+                    capacity,
+                    lock_args.as_bytes(),
+                );
+                (cell, Bytes::default())
+            }
+            Stake::Sudt(amount) => {
+                let cell = build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &stake_script_type_hash,
+                    SUDT_STAKE_CAPACITY,
+                    lock_args.as_bytes(),
+                )
+                .as_builder()
+                .type_(CKBPack::pack(&Some(sudt_script.clone())))
+                .build();
+                (cell, Bytes::from(amount.to_le_bytes().to_vec()))
+            }
+        }
+    };
+    let owner_cell = build_always_success_cell(1000_00000000u64, None);
+    let owner_input = {
+        let out_point = ctx.insert_cell(owner_cell.clone(), Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    let mut tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        build_stake_cell(input),
+        random_out_point(),
+        (owner_cell, Bytes::default()),
+    )
+    .as_advanced_builder()
+    .input(owner_input)
+    .cell_dep(stake_lock_dep)
+    .cell_dep(rollup_dep)
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone());
+    for stake in outputs {
+        let (cell, data) = build_stake_cell(*stake);
+        tx = tx.output(cell).output_data(CKBPack::pack(&data));
+    }
+    ctx.verify_tx(tx.build())
+}
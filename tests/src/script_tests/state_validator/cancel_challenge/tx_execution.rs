@@ -7,17 +7,21 @@ use crate::script_tests::utils::init_env_log;
 use crate::script_tests::utils::layer1::build_simple_tx_with_out_point;
 use crate::script_tests::utils::layer1::random_out_point;
 use crate::script_tests::utils::rollup::{
-    build_always_success_cell, build_rollup_locked_cell, build_type_id_script,
+    build_always_success_cell, build_dynvec, build_rollup_locked_cell, build_type_id_script,
     calculate_state_validator_type_id, CellContext, CellContextParam,
 };
 use crate::testing_tool::chain::setup_chain;
 use crate::testing_tool::chain::{apply_block_result, construct_block};
-use crate::testing_tool::programs::{ALWAYS_SUCCESS_CODE_HASH, STATE_VALIDATOR_CODE_HASH};
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, STATE_VALIDATOR_CODE_HASH, SUDT_VALIDATOR_PROGRAM,
+};
 use ckb_types::{
-    packed::{CellInput, CellOutput},
+    core::Cycle,
+    packed::{CellDep, CellInput, CellOutput},
     prelude::{Pack as CKBPack, Unpack},
 };
-use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+use gw_common::blake2b::new_blake2b;
+use gw_common::builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID};
 use gw_common::merkle_utils::ckb_merkle_leaf_hash;
 use gw_common::registry_address::RegistryAddress;
 use gw_common::{state::State, H256};
@@ -42,9 +46,83 @@ use gw_types::{
     },
 };
 
+/// SUDTArgs item gw-types doesn't know yet
+const SUDT_ARGS_BATCH_TRANSFER: u32 = 5;
+
+fn receiver_address() -> RegistryAddress {
+    RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![2u8; 20])
+}
+
+fn build_fee(amount: u128) -> Fee {
+    Fee::new_builder()
+        .amount(Pack::pack(&amount))
+        .registry_id(Pack::pack(&ETH_REGISTRY_ACCOUNT_ID))
+        .build()
+}
+
 #[tokio::test]
 async fn test_cancel_tx_execute() {
     init_env_log();
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to_address(Pack::pack(&Bytes::from(receiver_address().to_bytes())))
+                .amount(Pack::pack(&U256::from(150_00000000u128)))
+                .fee(build_fee(1_00000000u128))
+                .build(),
+        )
+        .build()
+        .as_bytes();
+    cancel_sudt_tx_execution(args, &[receiver_address()], None)
+        .await
+        .expect("return success");
+}
+
+#[tokio::test]
+async fn test_cancel_batch_transfer_tx_execute() {
+    init_env_log();
+    // pays the receiver twice and an address without an account once
+    let other_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![3u8; 20]);
+    let transfers = [
+        (receiver_address(), 100_00000000u128),
+        (other_address.clone(), 20_00000000u128),
+        (receiver_address(), 30_00000000u128),
+    ];
+    let items: Vec<Bytes> = transfers
+        .iter()
+        .map(|(to, amount)| {
+            build_dynvec(&[
+                Pack::pack(&Bytes::from(to.to_bytes())).as_bytes(),
+                Pack::pack(&U256::from(*amount)).as_bytes(),
+            ])
+        })
+        .collect();
+    let args = {
+        let mut args = SUDT_ARGS_BATCH_TRANSFER.to_le_bytes().to_vec();
+        args.extend_from_slice(&build_dynvec(&[
+            build_dynvec(&items),
+            build_fee(1_00000000u128).as_bytes(),
+        ]));
+        Bytes::from(args)
+    };
+    // the sUDT validator re-executes the batch
+    cancel_sudt_tx_execution(
+        args,
+        &[receiver_address(), other_address],
+        Some(&SUDT_VALIDATOR_PROGRAM),
+    )
+    .await
+    .expect("return success");
+}
+
+/// Cancel a TxExecution challenge of a transaction sent to the CKB sUDT account with `args`,
+/// the balances of `receivers` are added to the kv state.
+/// The l2 sUDT is always success unless the `sudt_validator` program is deployed.
+async fn cancel_sudt_tx_execution(
+    args: Bytes,
+    receivers: &[RegistryAddress],
+    sudt_validator: Option<&Bytes>,
+) -> Result<Cycle, ckb_error::Error> {
     let input_out_point = random_out_point();
     let type_id = calculate_state_validator_type_id(input_out_point.clone());
     let rollup_type_script = {
@@ -95,8 +173,7 @@ async fn test_cancel_tx_execute() {
             rollup_type_script.as_bytes(),
         )),
     );
-    // CKB built-in account id
-    let sudt_id = 1;
+    let sudt_id = CKB_SUDT_ACCOUNT_ID;
     let rollup_script_hash = rollup_type_script.hash();
     // produce a block so we can challenge it
     let (sender_script, receiver_script, sudt_script) = {
@@ -115,7 +192,6 @@ async fn test_cancel_tx_execute() {
             .hash_type(ScriptHashType::Type.into())
             .args(Pack::pack(&Bytes::from(receiver_args)))
             .build();
-        let receiver_address = RegistryAddress::new(eth_registry_id, vec![2u8; 20]);
         let deposit_requests = vec![
             DepositRequest::new_builder()
                 .capacity(Pack::pack(&300_00000000u64))
@@ -153,23 +229,6 @@ async fn test_cancel_tx_execute() {
             .unwrap();
         let sudt_script_hash = tree.get_script_hash(sudt_id).unwrap();
         let sudt_script = tree.get_script(&sudt_script_hash).unwrap();
-        let transfer_capacity = 150_00000000u128;
-        let fee_capacity = 1_00000000u128;
-        let args = SUDTArgs::new_builder()
-            .set(
-                SUDTTransfer::new_builder()
-                    .to_address(Pack::pack(&Bytes::from(receiver_address.to_bytes())))
-                    .amount(Pack::pack(&U256::from(transfer_capacity)))
-                    .fee(
-                        Fee::new_builder()
-                            .amount(Pack::pack(&fee_capacity))
-                            .registry_id(Pack::pack(&receiver_address.registry_id))
-                            .build(),
-                    )
-                    .build(),
-            )
-            .build()
-            .as_bytes();
         let tx = L2Transaction::new_builder()
             .raw(
                 RawL2Transaction::new_builder()
@@ -204,7 +263,7 @@ async fn test_cancel_tx_execute() {
         stake_lock_type,
         challenge_lock_type,
         eoa_lock_type,
-        l2_sudt_type,
+        l2_sudt_type: l2_sudt_type.clone(),
         ..Default::default()
     };
     let mut ctx = CellContext::new(&rollup_config, param);
@@ -298,12 +357,22 @@ async fn test_cancel_tx_execute() {
             tree.get_script_hash(receiver_id).unwrap();
             tree.get_nonce(receiver_id).unwrap();
             tree.get_script_hash(sudt_id).unwrap();
-            tree.get_registry_address_by_script_hash(
-                ETH_REGISTRY_ACCOUNT_ID,
-                &sender_script.hash().into(),
-            )
-            .unwrap()
-            .expect("get sender address");
+            let sender_address = tree
+                .get_registry_address_by_script_hash(
+                    ETH_REGISTRY_ACCOUNT_ID,
+                    &sender_script.hash().into(),
+                )
+                .unwrap()
+                .expect("get sender address");
+            // the balances paid from and to, the chain produces blocks with this address
+            let block_producer = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, Vec::default());
+            for address in [&sender_address, &block_producer]
+                .iter()
+                .copied()
+                .chain(receivers)
+            {
+                tree.get_sudt_balance(sudt_id, address).unwrap();
+            }
             let account_count = tree.get_account_count().unwrap();
             let touched_keys: Vec<H256> = {
                 let keys = tree.tracker_mut().touched_keys().unwrap();
@@ -348,7 +417,12 @@ async fn test_cancel_tx_execute() {
                     .0
                     .into()
             };
-            let return_data_hash = [42u8; 32];
+            // the sUDT messages return no data
+            let return_data_hash = {
+                let mut buf = [0u8; 32];
+                new_blake2b().finalize(&mut buf);
+                buf
+            };
             CCTransactionWitness::new_builder()
                 .l2tx(tx)
                 .raw_l2block(challenged_block.raw())
@@ -387,6 +461,17 @@ async fn test_cancel_tx_execute() {
         .status(Status::Running.into())
         .build()
         .as_bytes();
+    let l2_sudt_dep = match sudt_validator {
+        Some(program) => {
+            let cell = CellOutput::new_builder()
+                .capacity(CKBPack::pack(&(program.len() as u64)))
+                .type_(CKBPack::pack(&Some(l2_sudt_type)))
+                .build();
+            let out_point = ctx.insert_cell(cell, program.clone());
+            CellDep::new_builder().out_point(out_point).build()
+        }
+        None => ctx.l2_sudt_dep.clone(),
+    };
     let tx = build_simple_tx_with_out_point(
         &mut ctx.inner,
         (rollup_cell.clone(), initial_rollup_cell_data),
@@ -405,7 +490,7 @@ async fn test_cancel_tx_execute() {
     .cell_dep(ctx.state_validator_dep.clone())
     .cell_dep(ctx.rollup_config_dep.clone())
     .cell_dep(ctx.eoa_lock_dep.clone())
-    .cell_dep(ctx.l2_sudt_dep.clone())
+    .cell_dep(l2_sudt_dep)
     .build();
-    ctx.verify_tx(tx).expect("return success");
+    ctx.verify_tx(tx)
 }
@@ -3,22 +3,30 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 
+use super::super::submit_block::DEPOSIT_CALL_SIGNATURE;
+use super::withdrawal::{
+    chain_id_domain, expected_signature_err, expected_wrong_signature_err, rollup_domain,
+};
 use crate::script_tests::utils::init_env_log;
 use crate::script_tests::utils::layer1::build_simple_tx_with_out_point;
 use crate::script_tests::utils::layer1::random_out_point;
 use crate::script_tests::utils::rollup::{
-    build_always_success_cell, build_rollup_locked_cell, build_type_id_script,
-    calculate_state_validator_type_id, CellContext, CellContextParam,
+    build_always_success_cell, build_rollup_config_with_extra_fields, build_rollup_locked_cell,
+    build_stake_asset_vec, build_type_id_script, calculate_state_validator_type_id, CellContext,
+    CellContextParam,
 };
+use crate::script_tests::utils::state::SMTState;
 use crate::testing_tool::chain::setup_chain_with_account_lock_manage;
 use crate::testing_tool::chain::{apply_block_result, construct_block};
 use crate::testing_tool::programs::STATE_VALIDATOR_CODE_HASH;
+use ckb_error::assert_error_eq;
 use ckb_types::{
+    core::Cycle,
     packed::{CellInput, CellOutput},
     prelude::{Pack as CKBPack, Unpack as CKBUnpack},
 };
 use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
-use gw_common::merkle_utils::ckb_merkle_leaf_hash;
+use gw_common::merkle_utils::{calculate_state_checkpoint, ckb_merkle_leaf_hash, CBMT};
 use gw_common::registry_address::RegistryAddress;
 use gw_common::{state::State, H256};
 use gw_generator::account_lock_manage::always_success::AlwaysSuccess;
@@ -42,11 +50,15 @@ use gw_types::{
     bytes::Bytes,
     core::{ChallengeTargetType, ScriptHashType, Status},
     packed::{
-        ChallengeLockArgs, ChallengeTarget, DepositRequest, L2Transaction, RawL2Transaction,
-        RollupAction, RollupActionUnion, RollupCancelChallenge, RollupConfig, SUDTArgs,
-        SUDTTransfer, Script,
+        BatchCreateEthAccounts, ChallengeLockArgs, ChallengeTarget, CreateAccount, DepositRequest,
+        ETHAddrRegArgs, ETHAddrRegArgsUnion, GlobalState, L2Transaction, MetaContractArgs,
+        MetaContractArgsUnion, RawL2Block, RawL2Transaction, RollupAction, RollupActionUnion,
+        RollupCancelChallenge, RollupConfig, SUDTArgs, SUDTArgsUnion, SUDTTransfer, Script,
+        SetMapping, SubmitTransactions,
     },
 };
+use sha3::{Digest, Keccak256};
+use std::convert::TryFrom;
 
 #[tokio::test]
 async fn test_cancel_tx_signature() {
@@ -422,3 +434,578 @@ async fn test_cancel_tx_signature() {
     .build();
     ctx.verify_tx(tx).expect("return success");
 }
+
+#[test]
+fn test_cancel_sudt_transfer_signature() {
+    let args = SUDTArgs::new_builder()
+        .set(
+            SUDTTransfer::new_builder()
+                .to_address(Pack::pack(&Bytes::from(
+                    RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![2u8; 20]).to_bytes(),
+                )))
+                .amount(Pack::pack(&U256::from(2_00000000u128)))
+                .fee(build_fee(1_00000000u128))
+                .build(),
+        )
+        .build()
+        .as_bytes();
+    check_typed_message_signature(AllowedContractType::Sudt, args, sudt_transfer_hash_struct);
+}
+
+#[test]
+fn test_cancel_create_account_signature() {
+    let script = Script::new_builder()
+        .code_hash(Pack::pack(&[3u8; 32]))
+        .hash_type(ScriptHashType::Type.into())
+        .args(Pack::pack(&Bytes::from(vec![3u8; 52])))
+        .build();
+    let args = MetaContractArgs::new_builder()
+        .set(
+            CreateAccount::new_builder()
+                .script(script)
+                .fee(build_fee(1_00000000u128))
+                .build(),
+        )
+        .build()
+        .as_bytes();
+    check_typed_message_signature(AllowedContractType::Meta, args, create_account_hash_struct);
+}
+
+#[test]
+fn test_cancel_batch_create_eth_accounts_signature() {
+    let scripts = (3u8..5)
+        .map(|n| {
+            Script::new_builder()
+                .code_hash(Pack::pack(&[n; 32]))
+                .hash_type(ScriptHashType::Type.into())
+                .args(Pack::pack(&Bytes::from(vec![n; 52])))
+                .build()
+        })
+        .collect::<Vec<_>>();
+    let args = MetaContractArgs::new_builder()
+        .set(
+            BatchCreateEthAccounts::new_builder()
+                .scripts(PackVec::pack(scripts))
+                .fee(build_fee(1_00000000u128))
+                .build(),
+        )
+        .build()
+        .as_bytes();
+    check_typed_message_signature(
+        AllowedContractType::Meta,
+        args,
+        batch_create_eth_accounts_hash_struct,
+    );
+}
+
+#[test]
+fn test_cancel_set_mapping_signature() {
+    let args = ETHAddrRegArgs::new_builder()
+        .set(
+            SetMapping::new_builder()
+                .gw_script_hash(Pack::pack(&[3u8; 32]))
+                .fee(build_fee(1_00000000u128))
+                .build(),
+        )
+        .build()
+        .as_bytes();
+    check_typed_message_signature(
+        AllowedContractType::EthAddrReg,
+        args,
+        set_mapping_hash_struct,
+    );
+}
+
+#[test]
+fn test_cancel_deposit_call_signature() {
+    // a deposit call is signed by its deposit cell, there is no signature cell
+    let args = Bytes::from(vec![42u8; 8]);
+    cancel_signature_challenge(
+        AllowedContractType::Meta,
+        args.clone(),
+        1,
+        0,
+        Bytes::from(DEPOSIT_CALL_SIGNATURE),
+        None,
+    )
+    .expect("return success");
+
+    // a deposit call is the first tx of the account created by the deposit
+    let err = cancel_signature_challenge(
+        AllowedContractType::Meta,
+        args.clone(),
+        1,
+        1,
+        Bytes::from(DEPOSIT_CALL_SIGNATURE),
+        None,
+    )
+    .unwrap_err();
+    assert_error_eq!(err, expected_wrong_signature_err());
+
+    // other txs are checked against the signature cell
+    let err = cancel_signature_challenge(
+        AllowedContractType::Meta,
+        args,
+        1,
+        0,
+        Bytes::default(),
+        None,
+    )
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+}
+
+/// The typed message of a builtin contract call is signed since EIP-712 domain version 1,
+/// rollups of version 0 keep the generic `L2Transaction`
+fn check_typed_message_signature(
+    contract_type: AllowedContractType,
+    args: Bytes,
+    typed_hash_struct: fn(&RawL2Transaction, &RegistryAddress, H256) -> [u8; 32],
+) {
+    cancel_builtin_call_signature_challenge(contract_type, args.clone(), 1, typed_hash_struct)
+        .expect("return success");
+    cancel_builtin_call_signature_challenge(
+        contract_type,
+        args.clone(),
+        0,
+        l2_transaction_hash_struct,
+    )
+    .expect("return success");
+
+    let err =
+        cancel_builtin_call_signature_challenge(contract_type, args.clone(), 0, typed_hash_struct)
+            .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+    let err =
+        cancel_builtin_call_signature_challenge(contract_type, args, 1, l2_transaction_hash_struct)
+            .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+}
+
+/// Cancel a signature challenge of a tx calling a builtin contract of `contract_type`,
+/// the sender signs the EIP-712 message of `hash_struct` under the domain of
+/// `domain_version`
+fn cancel_builtin_call_signature_challenge(
+    contract_type: AllowedContractType,
+    args: Bytes,
+    domain_version: u8,
+    hash_struct: fn(&RawL2Transaction, &RegistryAddress, H256) -> [u8; 32],
+) -> Result<Cycle, ckb_error::Error> {
+    cancel_signature_challenge(
+        contract_type,
+        args,
+        domain_version,
+        0,
+        Bytes::default(),
+        Some(hash_struct),
+    )
+}
+
+/// Cancel a signature challenge of a tx with `signature` sent at the sender's `nonce`,
+/// without a signature cell if `hash_struct` is none
+fn cancel_signature_challenge(
+    contract_type: AllowedContractType,
+    args: Bytes,
+    domain_version: u8,
+    nonce: u32,
+    signature: Bytes,
+    hash_struct: Option<fn(&RawL2Transaction, &RegistryAddress, H256) -> [u8; 32]>,
+) -> Result<Cycle, ckb_error::Error> {
+    init_env_log();
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    let rollup_script_hash = rollup_type_script.hash();
+    // rollup lock & config
+    let challenge_lock_type = build_type_id_script(b"challenge_lock_type_id");
+    let eoa_lock_type = build_type_id_script(b"eoa_lock_type_id");
+    let challenge_script_type_hash: [u8; 32] = challenge_lock_type.calc_script_hash().unpack();
+    let eoa_lock_type_hash: [u8; 32] = eoa_lock_type.calc_script_hash().unpack();
+    let contract_type_hash = [contract_type as u8; 32];
+    let rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .challenge_script_type_hash(Pack::pack(&challenge_script_type_hash))
+            .allowed_eoa_type_hashes(PackVec::pack(vec![AllowedTypeHash::new(
+                AllowedEoaType::Eth,
+                eoa_lock_type_hash,
+            )]))
+            .allowed_contract_type_hashes(PackVec::pack(vec![AllowedTypeHash::new(
+                contract_type,
+                contract_type_hash,
+            )]))
+            .build(),
+        vec![
+            build_stake_asset_vec(&[]),
+            Bytes::from(vec![0u8; 32]),
+            Bytes::from(0u64.to_le_bytes().to_vec()),
+            Bytes::from(vec![domain_version]),
+        ],
+    );
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+    let param = CellContextParam {
+        challenge_lock_type,
+        eoa_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+
+    // the state before the tx
+    let mut sender_args = rollup_script_hash.to_vec();
+    sender_args.extend_from_slice(&[1u8; 20]);
+    let sender_script = Script::new_builder()
+        .code_hash(Pack::pack(&eoa_lock_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(Pack::pack(&Bytes::from(sender_args)))
+        .build();
+    let receiver_script = Script::new_builder()
+        .code_hash(Pack::pack(&contract_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(Pack::pack(&Bytes::from(rollup_script_hash.to_vec())))
+        .build();
+    let mut state = SMTState::default();
+    let receiver_id = state.create_account(receiver_script.hash().into()).unwrap();
+    let sender_id = state.create_account(sender_script.hash().into()).unwrap();
+    let sender_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![1u8; 20]);
+    state
+        .mapping_registry_address_to_script_hash(
+            sender_address.clone(),
+            sender_script.hash().into(),
+        )
+        .unwrap();
+    state.set_nonce(sender_id, nonce).unwrap();
+    let prev_state_checkpoint = calculate_state_checkpoint(
+        &state.calculate_root().unwrap(),
+        state.get_account_count().unwrap(),
+    );
+    // the keys read by challenge-lock
+    state.clear_touched_keys();
+    state.get_script_hash(sender_id).unwrap();
+    state.get_script_hash(receiver_id).unwrap();
+    state.get_nonce(sender_id).unwrap();
+    state
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script.hash().into())
+        .unwrap();
+    let (kv_state, kv_state_proof) = state.touched_kv_state();
+
+    // the challenged block with the tx
+    let tx = L2Transaction::new_builder()
+        .raw(
+            RawL2Transaction::new_builder()
+                .from_id(Pack::pack(&sender_id))
+                .to_id(Pack::pack(&receiver_id))
+                .nonce(Pack::pack(&nonce))
+                .args(Pack::pack(&args))
+                .build(),
+        )
+        .signature(Pack::pack(&signature))
+        .build();
+    let challenge_target_index = 0u32;
+    let leaves = vec![ckb_merkle_leaf_hash(
+        challenge_target_index,
+        &tx.witness_hash().into(),
+    )];
+    let raw_block = RawL2Block::new_builder()
+        .submit_transactions(
+            SubmitTransactions::new_builder()
+                .tx_witness_root(Pack::pack(&CBMT::build_merkle_root(&leaves)))
+                .tx_count(Pack::pack(&1u32))
+                .prev_state_checkpoint(Pack::pack(&prev_state_checkpoint))
+                .build(),
+        )
+        .build();
+
+    let challenge_witness = {
+        let witness = CCTransactionSignatureWitness::new_builder()
+            .l2tx(tx.clone())
+            .raw_l2block(raw_block.clone())
+            .kv_state_proof(Pack::pack(&kv_state_proof))
+            .tx_proof(super::build_merkle_proof(
+                &leaves,
+                &[challenge_target_index],
+            ))
+            .sender(sender_script.clone())
+            .receiver(receiver_script.clone())
+            .account_count(Pack::pack(&state.get_account_count().unwrap()))
+            .kv_state(kv_state)
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .lock(CKBPack::pack(&Some(witness.as_bytes())))
+            .build()
+    };
+    let input_challenge_cell = {
+        let lock_args = ChallengeLockArgs::new_builder()
+            .target(
+                ChallengeTarget::new_builder()
+                    .target_index(Pack::pack(&challenge_target_index))
+                    .target_type(ChallengeTargetType::TxSignature.into())
+                    .block_hash(Pack::pack(&raw_block.hash()))
+                    .build(),
+            )
+            .build();
+        let cell = build_rollup_locked_cell(
+            &rollup_script_hash,
+            &challenge_script_type_hash,
+            10000_00000000u64,
+            lock_args.as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let input_unlock_cell = hash_struct.map(|hash_struct| {
+        let cell = CellOutput::new_builder()
+            .lock(ckb_types::packed::Script::new_unchecked(
+                sender_script.as_bytes(),
+            ))
+            .capacity(CKBPack::pack(&42u64))
+            .build();
+        let message = {
+            let chain_id = Unpack::unpack(&tx.raw().chain_id());
+            let domain = match domain_version {
+                0 => chain_id_domain(chain_id, rollup_script_hash),
+                _ => rollup_domain(chain_id, rollup_script_hash),
+            };
+            let mut hasher = Keccak256::new();
+            hasher.update(&[0x19, 0x01]);
+            hasher.update(&domain.hash_struct());
+            hasher.update(&hash_struct(
+                &tx.raw(),
+                &sender_address,
+                receiver_script.hash().into(),
+            ));
+            let message: [u8; 32] = hasher.finalize().into();
+            message
+        };
+        let mut data = vec![42u8; 32];
+        data.push(SigningType::Raw.into());
+        data.extend_from_slice(&message);
+        let out_point = ctx.insert_cell(cell, Bytes::from(data));
+        CellInput::new_builder().previous_output(out_point).build()
+    });
+
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .status(Status::Halting.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = global_state
+        .clone()
+        .as_builder()
+        .status(Status::Running.into())
+        .build();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupCancelChallenge(
+                RollupCancelChallenge::default(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let mut tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        (rollup_cell.clone(), global_state.as_bytes()),
+        input_out_point,
+        (rollup_cell, post_global_state.as_bytes()),
+    )
+    .as_advanced_builder()
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .input(input_challenge_cell)
+    .witness(CKBPack::pack(&challenge_witness.as_bytes()))
+    .cell_dep(ctx.challenge_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .cell_dep(ctx.eoa_lock_dep.clone());
+    if let Some(input_unlock_cell) = input_unlock_cell {
+        tx = tx.input(input_unlock_cell).witness(Default::default());
+    }
+    ctx.verify_tx(tx.build())
+}
+
+fn build_fee(amount: u128) -> Fee {
+    Fee::new_builder()
+        .amount(Pack::pack(&amount))
+        .registry_id(Pack::pack(&ETH_REGISTRY_ACCOUNT_ID))
+        .build()
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// ABI encoded uint256
+fn uint(n: u128) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    buf[16..].copy_from_slice(&n.to_be_bytes());
+    buf
+}
+
+/// EIP-712 `hashStruct` of an ETH `RegistryAddress`
+fn registry_address_hash_struct(address: &[u8]) -> [u8; 32] {
+    let mut buf = keccak256(b"RegistryAddress(string registry,address address)").to_vec();
+    buf.extend_from_slice(&keccak256(b"ETH"));
+    buf.extend_from_slice(&[0u8; 12]);
+    buf.extend_from_slice(address);
+    keccak256(&buf)
+}
+
+/// EIP-712 `hashStruct` of the `Fee` of a builtin contract call
+fn fee_hash_struct(fee: &Fee) -> [u8; 32] {
+    let mut buf = keccak256(b"Fee(string registry,uint256 amount)").to_vec();
+    buf.extend_from_slice(&keccak256(b"ETH"));
+    buf.extend_from_slice(&uint(Unpack::unpack(&fee.amount())));
+    keccak256(&buf)
+}
+
+/// EIP-712 `hashStruct` of a `Script`
+fn script_hash_struct(script: &Script) -> [u8; 32] {
+    let hash_type = match ScriptHashType::try_from(script.hash_type()).unwrap() {
+        ScriptHashType::Data => "data",
+        ScriptHashType::Type => "type",
+    };
+    let mut buf = keccak256(b"Script(bytes32 codeHash,string hashType,bytes args)").to_vec();
+    buf.extend_from_slice(script.code_hash().as_slice());
+    buf.extend_from_slice(&keccak256(hash_type.as_bytes()));
+    buf.extend_from_slice(&keccak256(&script.args().raw_data()));
+    keccak256(&buf)
+}
+
+/// EIP-712 `hashStruct` of the generic `L2Transaction` message
+fn l2_transaction_hash_struct(
+    raw_tx: &RawL2Transaction,
+    sender_address: &RegistryAddress,
+    receiver_script_hash: H256,
+) -> [u8; 32] {
+    eip712::types::L2Transaction::from_raw(
+        raw_tx.clone(),
+        sender_address.clone(),
+        receiver_script_hash,
+    )
+    .unwrap()
+    .hash_struct()
+}
+
+fn sudt_transfer_hash_struct(
+    raw_tx: &RawL2Transaction,
+    sender_address: &RegistryAddress,
+    sudt_script_hash: H256,
+) -> [u8; 32] {
+    let transfer = match SUDTArgs::from_slice(&raw_tx.args().raw_data())
+        .unwrap()
+        .to_enum()
+    {
+        SUDTArgsUnion::SUDTTransfer(transfer) => transfer,
+        _ => panic!("expect SUDTTransfer"),
+    };
+    let mut amount = [0u8; 32];
+    amount.copy_from_slice(transfer.amount().as_slice());
+    amount.reverse();
+
+    let mut buf = keccak256(
+        b"SUDTTransfer(uint256 chainId,uint256 nonce,RegistryAddress from,bytes32 sudt,RegistryAddress to,uint256 amount,Fee fee)Fee(string registry,uint256 amount)RegistryAddress(string registry,address address)",
+    )
+    .to_vec();
+    buf.extend_from_slice(&uint(Unpack::<u64>::unpack(&raw_tx.chain_id()).into()));
+    buf.extend_from_slice(&uint(Unpack::<u32>::unpack(&raw_tx.nonce()).into()));
+    buf.extend_from_slice(&registry_address_hash_struct(&sender_address.address));
+    buf.extend_from_slice(sudt_script_hash.as_slice());
+    buf.extend_from_slice(&registry_address_hash_struct(
+        &transfer.to_address().raw_data()[8..],
+    ));
+    buf.extend_from_slice(&amount);
+    buf.extend_from_slice(&fee_hash_struct(&transfer.fee()));
+    keccak256(&buf)
+}
+
+fn create_account_hash_struct(
+    raw_tx: &RawL2Transaction,
+    sender_address: &RegistryAddress,
+    _receiver_script_hash: H256,
+) -> [u8; 32] {
+    let create = match MetaContractArgs::from_slice(&raw_tx.args().raw_data())
+        .unwrap()
+        .to_enum()
+    {
+        MetaContractArgsUnion::CreateAccount(create) => create,
+        _ => panic!("expect CreateAccount"),
+    };
+    let mut buf = keccak256(
+        b"CreateAccount(uint256 chainId,uint256 nonce,RegistryAddress from,Script script,Fee fee)Fee(string registry,uint256 amount)RegistryAddress(string registry,address address)Script(bytes32 codeHash,string hashType,bytes args)",
+    )
+    .to_vec();
+    buf.extend_from_slice(&uint(Unpack::<u64>::unpack(&raw_tx.chain_id()).into()));
+    buf.extend_from_slice(&uint(Unpack::<u32>::unpack(&raw_tx.nonce()).into()));
+    buf.extend_from_slice(&registry_address_hash_struct(&sender_address.address));
+    buf.extend_from_slice(&script_hash_struct(&create.script()));
+    buf.extend_from_slice(&fee_hash_struct(&create.fee()));
+    keccak256(&buf)
+}
+
+fn batch_create_eth_accounts_hash_struct(
+    raw_tx: &RawL2Transaction,
+    sender_address: &RegistryAddress,
+    _receiver_script_hash: H256,
+) -> [u8; 32] {
+    let batch = match MetaContractArgs::from_slice(&raw_tx.args().raw_data())
+        .unwrap()
+        .to_enum()
+    {
+        MetaContractArgsUnion::BatchCreateEthAccounts(batch) => batch,
+        _ => panic!("expect BatchCreateEthAccounts"),
+    };
+    let scripts: Vec<u8> = batch
+        .scripts()
+        .into_iter()
+        .flat_map(|script| script_hash_struct(&script))
+        .collect();
+    let mut buf = keccak256(
+        b"BatchCreateEthAccounts(uint256 chainId,uint256 nonce,RegistryAddress from,Script[] scripts,Fee fee)Fee(string registry,uint256 amount)RegistryAddress(string registry,address address)Script(bytes32 codeHash,string hashType,bytes args)",
+    )
+    .to_vec();
+    buf.extend_from_slice(&uint(Unpack::<u64>::unpack(&raw_tx.chain_id()).into()));
+    buf.extend_from_slice(&uint(Unpack::<u32>::unpack(&raw_tx.nonce()).into()));
+    buf.extend_from_slice(&registry_address_hash_struct(&sender_address.address));
+    buf.extend_from_slice(&keccak256(&scripts));
+    buf.extend_from_slice(&fee_hash_struct(&batch.fee()));
+    keccak256(&buf)
+}
+
+fn set_mapping_hash_struct(
+    raw_tx: &RawL2Transaction,
+    sender_address: &RegistryAddress,
+    _receiver_script_hash: H256,
+) -> [u8; 32] {
+    let mapping = match ETHAddrRegArgs::from_slice(&raw_tx.args().raw_data())
+        .unwrap()
+        .to_enum()
+    {
+        ETHAddrRegArgsUnion::SetMapping(mapping) => mapping,
+        _ => panic!("expect SetMapping"),
+    };
+    let mut buf = keccak256(
+        b"SetMapping(uint256 chainId,uint256 nonce,RegistryAddress from,bytes32 gwScriptHash,Fee fee)Fee(string registry,uint256 amount)RegistryAddress(string registry,address address)",
+    )
+    .to_vec();
+    buf.extend_from_slice(&uint(Unpack::<u64>::unpack(&raw_tx.chain_id()).into()));
+    buf.extend_from_slice(&uint(Unpack::<u32>::unpack(&raw_tx.nonce()).into()));
+    buf.extend_from_slice(&registry_address_hash_struct(&sender_address.address));
+    buf.extend_from_slice(mapping.gw_script_hash().as_slice());
+    buf.extend_from_slice(&fee_hash_struct(&mapping.fee()));
+    keccak256(&buf)
+}
@@ -8,18 +8,25 @@ use crate::script_tests::utils::init_env_log;
 use crate::script_tests::utils::layer1::build_simple_tx_with_out_point;
 use crate::script_tests::utils::layer1::random_out_point;
 use crate::script_tests::utils::rollup::{
-    build_always_success_cell, build_rollup_locked_cell, build_type_id_script,
-    calculate_state_validator_type_id, CellContext, CellContextParam,
+    build_always_success_cell, build_rollup_config_with_extra_fields, build_rollup_locked_cell,
+    build_stake_asset_vec, build_type_id_script, calculate_state_validator_type_id, CellContext,
+    CellContextParam,
 };
+use crate::script_tests::utils::state::SMTState;
 use crate::testing_tool::chain::{
     apply_block_result, construct_block, setup_chain_with_account_lock_manage,
 };
 use crate::testing_tool::programs::STATE_VALIDATOR_CODE_HASH;
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
 use ckb_types::{
+    core::Cycle,
     packed::{CellInput, CellOutput},
     prelude::{Pack as CKBPack, Unpack as CKBUnpack},
 };
+use gw_common::blake2b::new_blake2b;
 use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+use gw_common::merkle_utils::{ckb_merkle_leaf_hash, CBMT};
 use gw_common::state::State;
 use gw_common::H256;
 use gw_generator::account_lock_manage::{
@@ -31,23 +38,214 @@ use gw_generator::account_lock_manage::{
 };
 use gw_store::mem_pool_state::MemPoolState;
 use gw_store::mem_pool_state::MemStore;
-use gw_types::core::AllowedEoaType;
-use gw_types::core::SigningType;
-use gw_types::packed::AllowedTypeHash;
-use gw_types::packed::CCWithdrawalWitness;
+use gw_types::core::{AllowedContractType, SigningType};
 use gw_types::packed::WithdrawalRequestExtra;
+use gw_types::packed::{AllowedTypeHash, Byte};
+use gw_types::packed::{CCTransactionWitness, CCWithdrawalWitness};
 use gw_types::prelude::*;
 use gw_types::{
     bytes::Bytes,
     core::{ChallengeTargetType, ScriptHashType, Status},
     packed::{
-        ChallengeLockArgs, ChallengeTarget, DepositRequest, RawWithdrawalRequest, RollupAction,
-        RollupActionUnion, RollupCancelChallenge, RollupConfig, Script, WithdrawalRequest,
+        AccountMerkleState, ChallengeLockArgs, ChallengeTarget, DepositRequest, GlobalState,
+        L2Transaction, RawL2Block, RawL2Transaction, RawWithdrawalRequest, RollupAction,
+        RollupActionUnion, RollupCancelChallenge, RollupConfig, Script, SubmitWithdrawals,
+        WithdrawalRequest,
     },
 };
 
+const INVALID_ARGS_ERROR: i8 = 5;
+const MERKLE_PROOF_ERROR: i8 = 13;
+const ACCOUNT_LOCK_CELL_NOT_FOUND_ERROR: i8 = 37;
+const WRONG_SIGNATURE_ERROR: i8 = 41;
+/// `GW_ALLOWED_EOA_*` of `c/gw_def.h`
+const ALLOWED_EOA_ETH: u8 = 1;
+const ALLOWED_EOA_TRON: u8 = 2;
+/// An EOA type challenge-lock doesn't know yet
+const ALLOWED_EOA_FUTURE: u8 = 42;
+
+/// The message put in the sender's account lock cell
+enum WithdrawalMessage {
+    /// EIP-712 typed Withdrawal under the domain of (chain_id, rollup_script_hash)
+    EIP712(fn(u64, [u8; 32]) -> EIP712Domain),
+    /// blake2b(rollup_script_hash | raw_withdrawal)
+    Raw(SigningType),
+}
+
+pub(super) fn expected_signature_err() -> ckb_error::Error {
+    expected_challenge_lock_err(ACCOUNT_LOCK_CELL_NOT_FOUND_ERROR)
+}
+
+pub(super) fn expected_wrong_signature_err() -> ckb_error::Error {
+    expected_challenge_lock_err(WRONG_SIGNATURE_ERROR)
+}
+
+fn expected_challenge_lock_err(code: i8) -> ckb_error::Error {
+    let challenge_script_type_hash: [u8; 32] = build_type_id_script(b"challenge_lock_type_id")
+        .calc_script_hash()
+        .unpack();
+    ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(challenge_script_type_hash)
+        ),
+        code,
+    )
+    .input_lock_script(1)
+    .into()
+}
+
 #[tokio::test]
 async fn test_cancel_withdrawal() {
+    cancel_withdrawal_challenge(
+        ALLOWED_EOA_ETH,
+        None,
+        WithdrawalMessage::EIP712(chain_id_domain),
+    )
+    .await
+    .expect("return success");
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_with_rollup_domain() {
+    cancel_withdrawal_challenge(
+        ALLOWED_EOA_ETH,
+        Some(1),
+        WithdrawalMessage::EIP712(rollup_domain),
+    )
+    .await
+    .expect("return success");
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_signed_for_other_rollup() {
+    // replay a signature of another rollup sharing the chain id
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_ETH,
+        Some(1),
+        WithdrawalMessage::EIP712(|chain_id, _rollup_script_hash| {
+            rollup_domain(chain_id, [42u8; 32])
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+
+    // the chain id only domain is rejected once the rollup upgrades its domain
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_ETH,
+        Some(1),
+        WithdrawalMessage::EIP712(chain_id_domain),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_of_eth_account_with_raw_message() {
+    for signing_type in [SigningType::Raw, SigningType::WithPrefix] {
+        let err = cancel_withdrawal_challenge(
+            ALLOWED_EOA_ETH,
+            None,
+            WithdrawalMessage::Raw(signing_type),
+        )
+        .await
+        .unwrap_err();
+        assert_error_eq!(err, expected_signature_err());
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_of_tron_account() {
+    cancel_withdrawal_challenge(
+        ALLOWED_EOA_TRON,
+        None,
+        WithdrawalMessage::Raw(SigningType::WithPrefix),
+    )
+    .await
+    .expect("return success");
+
+    // Tron wallets sign prefixed messages only
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_TRON,
+        None,
+        WithdrawalMessage::Raw(SigningType::Raw),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_TRON,
+        None,
+        WithdrawalMessage::EIP712(chain_id_domain),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+}
+
+#[tokio::test]
+async fn test_cancel_withdrawal_of_future_eoa_type() {
+    cancel_withdrawal_challenge(
+        ALLOWED_EOA_FUTURE,
+        None,
+        WithdrawalMessage::Raw(SigningType::Raw),
+    )
+    .await
+    .expect("return success");
+
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_FUTURE,
+        None,
+        WithdrawalMessage::Raw(SigningType::WithPrefix),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+
+    let err = cancel_withdrawal_challenge(
+        ALLOWED_EOA_FUTURE,
+        None,
+        WithdrawalMessage::EIP712(chain_id_domain),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_signature_err());
+}
+
+/// Version 0 EIP-712 domain, chain id only
+pub(super) fn chain_id_domain(chain_id: u64, _rollup_script_hash: [u8; 32]) -> EIP712Domain {
+    EIP712Domain {
+        name: "Godwoken".to_string(),
+        version: "1".to_string(),
+        chain_id,
+        verifying_contract: None,
+        salt: None,
+    }
+}
+
+/// Version 1 EIP-712 domain of a rollup
+pub(super) fn rollup_domain(chain_id: u64, rollup_script_hash: [u8; 32]) -> EIP712Domain {
+    let mut verifying_contract = [0u8; 20];
+    verifying_contract.copy_from_slice(&rollup_script_hash[12..]);
+    EIP712Domain {
+        name: "Godwoken".to_string(),
+        version: "2".to_string(),
+        chain_id,
+        verifying_contract: Some(verifying_contract),
+        salt: Some(rollup_script_hash),
+    }
+}
+
+/// Cancel a withdrawal challenge of an `eoa_type` sender with `withdrawal_message`,
+/// the challenged rollup uses the `eip712_domain_version` extra field if it is given
+async fn cancel_withdrawal_challenge(
+    eoa_type: u8,
+    domain_version: Option<u8>,
+    withdrawal_message: WithdrawalMessage,
+) -> Result<Cycle, ckb_error::Error> {
     init_env_log();
     let input_out_point = random_out_point();
     let type_id = calculate_state_validator_type_id(input_out_point.clone());
@@ -64,10 +262,10 @@ async fn test_cancel_withdrawal() {
     let eoa_lock_type = build_type_id_script(b"eoa_lock_type_id");
     let challenge_script_type_hash: [u8; 32] = challenge_lock_type.calc_script_hash().unpack();
     let eoa_lock_type_hash: [u8; 32] = eoa_lock_type.calc_script_hash().unpack();
-    let allowed_eoa_type_hashes: Vec<AllowedTypeHash> = vec![AllowedTypeHash::new(
-        AllowedEoaType::Eth,
-        eoa_lock_type_hash,
-    )];
+    let allowed_eoa_type_hashes: Vec<AllowedTypeHash> = vec![AllowedTypeHash::new_builder()
+        .type_(Byte::new(eoa_type))
+        .hash(Pack::pack(&eoa_lock_type_hash))
+        .build()];
     let finality_blocks = 10;
     let eth_registry_id = gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
     let rollup_config = RollupConfig::new_builder()
@@ -191,6 +389,21 @@ async fn test_cancel_withdrawal() {
         eoa_lock_type,
         ..Default::default()
     };
+    // the chain doesn't know extra fields, challenge with the extended config
+    let rollup_config = match domain_version {
+        Some(domain_version) => build_rollup_config_with_extra_fields(
+            &rollup_config,
+            vec![
+                build_stake_asset_vec(&[]),
+                Bytes::from(vec![0u8; 32]),
+                Bytes::from(0u64.to_le_bytes().to_vec()),
+                Bytes::from(vec![domain_version]),
+            ],
+        ),
+        None => rollup_config,
+    };
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
     let mut ctx = CellContext::new(&rollup_config, param);
     let challenge_capacity = 10000_00000000u64;
     let challenged_block = chain.local_state().tip().clone();
@@ -219,6 +432,7 @@ async fn test_cancel_withdrawal() {
         .last_global_state()
         .clone()
         .as_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
         .status(Status::Halting.into())
         .build();
     let initial_rollup_cell_data = global_state.as_bytes();
@@ -246,7 +460,6 @@ async fn test_cancel_withdrawal() {
     tree.tracker_mut().enable();
     let withdrawal_address = tree
         .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script.hash().into())
-        .unwrap()
         .unwrap();
     let account_count = tree.get_account_count().unwrap();
     let touched_keys: Vec<H256> = {
@@ -306,24 +519,34 @@ async fn test_cancel_withdrawal() {
             .build();
         let owner_lock_hash = vec![42u8; 32];
 
-        let message = {
-            let withdrawal = Withdrawal::from_raw(
-                withdrawal.raw(),
-                withdrawal_extra.owner_lock(),
-                withdrawal_address,
-            )
-            .unwrap();
-            let domain = EIP712Domain {
-                name: "Godwoken".to_string(),
-                version: "1".to_string(),
-                chain_id: withdrawal_extra.raw().chain_id().unpack(),
-                verifying_contract: None,
-                salt: None,
-            };
-            withdrawal.eip712_message(domain.hash_struct())
+        let (signing_type, message) = match withdrawal_message {
+            WithdrawalMessage::EIP712(signing_domain) => {
+                let withdrawal = Withdrawal::from_raw(
+                    withdrawal.raw(),
+                    withdrawal_extra.owner_lock(),
+                    withdrawal_address.expect("withdrawal address"),
+                )
+                .unwrap();
+                let domain = signing_domain(
+                    withdrawal_extra.raw().chain_id().unpack(),
+                    rollup_script_hash,
+                );
+                (
+                    SigningType::Raw,
+                    withdrawal.eip712_message(domain.hash_struct()),
+                )
+            }
+            WithdrawalMessage::Raw(signing_type) => {
+                let mut hasher = new_blake2b();
+                hasher.update(&rollup_script_hash);
+                hasher.update(withdrawal.raw().as_slice());
+                let mut message = [0u8; 32];
+                hasher.finalize(&mut message);
+                (signing_type, message)
+            }
         };
         let mut buf = owner_lock_hash;
-        buf.push(SigningType::Raw.into());
+        buf.push(signing_type.into());
         buf.extend_from_slice(&message);
         let out_point = ctx.insert_cell(cell, Bytes::from(buf));
         CellInput::new_builder().previous_output(out_point).build()
@@ -352,5 +575,306 @@ async fn test_cancel_withdrawal() {
     .cell_dep(ctx.rollup_config_dep.clone())
     .cell_dep(ctx.eoa_lock_dep.clone())
     .build();
-    ctx.verify_tx(tx).expect("return success");
+    ctx.verify_tx(tx)
+}
+
+/// The authorization call of a contract sender, see `verify_contract_authorization`
+/// of challenge-lock
+enum AuthorizationCall {
+    /// `isValidSignature(message, signature)` returns the EIP-1271 magic value
+    MagicValue,
+    /// Calls another function of the contract
+    WrongSelector,
+    /// `isValidSignature` of another message
+    WrongMessage,
+    /// `isValidSignature` returns another value
+    WrongReturnValue,
+    /// The call writes the contract storage, the kv state of the witness is the
+    /// changed state rather than the state before the withdrawal
+    StateChanging,
+}
+
+/// `isValidSignature(bytes32,bytes)` selector of EIP-1271, also the magic value
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+#[test]
+fn test_cancel_withdrawal_of_contract_account() {
+    cancel_contract_withdrawal_challenge(AuthorizationCall::MagicValue).expect("return success");
+}
+
+#[test]
+fn test_cancel_withdrawal_of_contract_account_with_wrong_call() {
+    for call in [
+        AuthorizationCall::WrongSelector,
+        AuthorizationCall::WrongMessage,
+    ] {
+        let err = cancel_contract_withdrawal_challenge(call).unwrap_err();
+        assert_error_eq!(err, expected_challenge_lock_err(INVALID_ARGS_ERROR));
+    }
+}
+
+#[test]
+fn test_cancel_withdrawal_of_contract_account_with_wrong_return_value() {
+    let err =
+        cancel_contract_withdrawal_challenge(AuthorizationCall::WrongReturnValue).unwrap_err();
+    assert_error_eq!(err, expected_wrong_signature_err());
+}
+
+#[test]
+fn test_cancel_withdrawal_of_contract_account_with_state_changing_call() {
+    let err = cancel_contract_withdrawal_challenge(AuthorizationCall::StateChanging).unwrap_err();
+    assert_error_eq!(err, expected_challenge_lock_err(MERKLE_PROOF_ERROR));
+}
+
+/// Cancel a withdrawal challenge of a contract account sender by `call`,
+/// the backend of the contract is always success
+fn cancel_contract_withdrawal_challenge(
+    call: AuthorizationCall,
+) -> Result<Cycle, ckb_error::Error> {
+    init_env_log();
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    let rollup_script_hash = rollup_type_script.hash();
+    // rollup lock & config
+    let challenge_lock_type = build_type_id_script(b"challenge_lock_type_id");
+    let contract_type = build_type_id_script(b"contract_type_id");
+    let challenge_script_type_hash: [u8; 32] = challenge_lock_type.calc_script_hash().unpack();
+    let contract_type_hash: [u8; 32] = contract_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .challenge_script_type_hash(Pack::pack(&challenge_script_type_hash))
+        .allowed_contract_type_hashes(PackVec::pack(vec![AllowedTypeHash::new(
+            AllowedContractType::Polyjuice,
+            contract_type_hash,
+        )]))
+        .build();
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+    let param = CellContextParam {
+        challenge_lock_type,
+        l2_sudt_type: contract_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+
+    // the state before the withdrawal
+    let sender_script = Script::new_builder()
+        .code_hash(Pack::pack(&contract_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(Pack::pack(&Bytes::from(rollup_script_hash.to_vec())))
+        .build();
+    let mut state = SMTState::default();
+    let sender_id = state.create_account(sender_script.hash().into()).unwrap();
+    let prev_account = AccountMerkleState::new_builder()
+        .merkle_root(Pack::pack(&state.calculate_root().unwrap()))
+        .count(Pack::pack(&state.get_account_count().unwrap()))
+        .build();
+
+    // the challenged block with one withdrawal
+    let owner_lock = Script::default();
+    let withdrawal = WithdrawalRequest::new_builder()
+        .raw(
+            RawWithdrawalRequest::new_builder()
+                .capacity(Pack::pack(&400_00000000u64))
+                .account_script_hash(Pack::pack(&sender_script.hash()))
+                .owner_lock_hash(Pack::pack(&owner_lock.hash()))
+                .registry_id(Pack::pack(&ETH_REGISTRY_ACCOUNT_ID))
+                .build(),
+        )
+        .build();
+    let challenge_target_index = 0u32;
+    let leaves = vec![ckb_merkle_leaf_hash(
+        challenge_target_index,
+        &withdrawal.witness_hash().into(),
+    )];
+    let raw_block = RawL2Block::new_builder()
+        .prev_account(prev_account)
+        .submit_withdrawals(
+            SubmitWithdrawals::new_builder()
+                .withdrawal_witness_root(Pack::pack(&CBMT::build_merkle_root(&leaves)))
+                .withdrawal_count(Pack::pack(&1u32))
+                .build(),
+        )
+        .state_checkpoint_list(PackVec::pack(vec![H256::from([42u8; 32])]))
+        .build();
+
+    // the withdrawal is authorized by isValidSignature(message, signature)
+    let message = {
+        let mut hasher = new_blake2b();
+        hasher.update(&rollup_script_hash);
+        hasher.update(withdrawal.raw().as_slice());
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        message
+    };
+    let call_args = {
+        let (selector, message) = match call {
+            AuthorizationCall::WrongSelector => ([0xde, 0xad, 0xbe, 0xef], message),
+            AuthorizationCall::WrongMessage => (EIP1271_MAGIC_VALUE, [42u8; 32]),
+            _ => (EIP1271_MAGIC_VALUE, message),
+        };
+        // selector | message | offset of signature | empty signature
+        let mut input = selector.to_vec();
+        input.extend_from_slice(&message);
+        let mut signature_offset = [0u8; 32];
+        signature_offset[31] = 0x40;
+        input.extend_from_slice(&signature_offset);
+        input.extend_from_slice(&[0u8; 32]);
+        // polyjuice args header: POLY flag | call kind | gas limit | gas price | value |
+        // input size
+        let mut args = b"\xFF\xFF\xFFPOLY".to_vec();
+        args.push(0);
+        args.extend_from_slice(&1_000_000u64.to_le_bytes());
+        args.extend_from_slice(&[0u8; 16]);
+        args.extend_from_slice(&[0u8; 16]);
+        args.extend_from_slice(&(input.len() as u32).to_le_bytes());
+        args.extend_from_slice(&input);
+        Bytes::from(args)
+    };
+    let return_data_hash = {
+        let mut return_data = [0u8; 32];
+        if !matches!(call, AuthorizationCall::WrongReturnValue) {
+            return_data[..4].copy_from_slice(&EIP1271_MAGIC_VALUE);
+        }
+        let mut hasher = new_blake2b();
+        hasher.update(&return_data);
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash
+    };
+
+    // kv states read by challenge-lock
+    state.clear_touched_keys();
+    state
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script.hash().into())
+        .unwrap();
+    let (withdrawal_kv_state, withdrawal_kv_state_proof) = state.touched_kv_state();
+    state.clear_touched_keys();
+    state.get_script_hash(sender_id).unwrap();
+    if let AuthorizationCall::StateChanging = call {
+        state
+            .update_value(sender_id, &[1u8; 32], [1u8; 32].into())
+            .unwrap();
+    }
+    let (auth_kv_state, auth_kv_state_proof) = state.touched_kv_state();
+
+    let challenge_witness = {
+        let withdrawal_witness = CCWithdrawalWitness::new_builder()
+            .raw_l2block(raw_block.clone())
+            .withdrawal(withdrawal)
+            .sender(sender_script.clone())
+            .owner_lock(owner_lock)
+            .withdrawal_proof(super::build_merkle_proof(
+                &leaves,
+                &[challenge_target_index],
+            ))
+            .kv_state_proof(Pack::pack(&withdrawal_kv_state_proof))
+            .account_count(Pack::pack(&state.get_account_count().unwrap()))
+            .kv_state(withdrawal_kv_state)
+            .build();
+        let auth_witness = CCTransactionWitness::new_builder()
+            .l2tx(
+                L2Transaction::new_builder()
+                    .raw(
+                        RawL2Transaction::new_builder()
+                            .from_id(Pack::pack(&sender_id))
+                            .to_id(Pack::pack(&sender_id))
+                            .args(Pack::pack(&call_args))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .raw_l2block(raw_block.clone())
+            .kv_state_proof(Pack::pack(&auth_kv_state_proof))
+            .account_count(Pack::pack(&state.get_account_count().unwrap()))
+            .kv_state(auth_kv_state)
+            .return_data_hash(Pack::pack(&return_data_hash))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .lock(CKBPack::pack(&Some(withdrawal_witness.as_bytes())))
+            .input_type(CKBPack::pack(&Some(auth_witness.as_bytes())))
+            .build()
+    };
+    let input_challenge_cell = {
+        let lock_args = ChallengeLockArgs::new_builder()
+            .target(
+                ChallengeTarget::new_builder()
+                    .target_index(Pack::pack(&challenge_target_index))
+                    .target_type(ChallengeTargetType::Withdrawal.into())
+                    .block_hash(Pack::pack(&raw_block.hash()))
+                    .build(),
+            )
+            .build();
+        let cell = build_rollup_locked_cell(
+            &rollup_script_hash,
+            &challenge_script_type_hash,
+            10000_00000000u64,
+            lock_args.as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::new());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    // the backend of the sender verifies the call
+    let input_backend_cell = {
+        let cell = CellOutput::new_builder()
+            .lock(ckb_types::packed::Script::new_unchecked(
+                sender_script.as_bytes(),
+            ))
+            .capacity(CKBPack::pack(&42u64))
+            .build();
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .status(Status::Halting.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = global_state
+        .clone()
+        .as_builder()
+        .status(Status::Running.into())
+        .build();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupCancelChallenge(
+                RollupCancelChallenge::default(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        (rollup_cell.clone(), global_state.as_bytes()),
+        input_out_point,
+        (rollup_cell, post_global_state.as_bytes()),
+    )
+    .as_advanced_builder()
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .input(input_challenge_cell)
+    .witness(CKBPack::pack(&challenge_witness.as_bytes()))
+    .input(input_backend_cell)
+    .witness(Default::default())
+    .cell_dep(ctx.challenge_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .cell_dep(ctx.l2_sudt_dep.clone())
+    .build();
+    ctx.verify_tx(tx)
 }
@@ -0,0 +1,217 @@
+use crate::script_tests::utils::layer1::{build_simple_tx, random_out_point};
+use crate::script_tests::utils::rollup::{
+    build_always_success_cell, build_rollup_locked_cell, build_type_id_script,
+    calculate_state_validator_type_id, CellContext, CellContextParam,
+};
+use crate::testing_tool::programs::STATE_VALIDATOR_CODE_HASH;
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
+use ckb_types::{
+    packed::{CellInput, CellOutput, WitnessArgs},
+    prelude::{Pack as CKBPack, Unpack},
+};
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, Status},
+    packed::{CustodianLockArgs, GlobalState, RollupConfig, Script},
+};
+
+const INSUFFICIENT_INPUT_FINALIZED_ASSETS_ERROR: i8 = 16;
+const INSUFFICIENT_OUTPUT_FINALIZED_ASSETS_ERROR: i8 = 17;
+const INVALID_POST_GLOBAL_STATE_ERROR: i8 = 23;
+const INVALID_CUSTODIAN_CELL_ERROR: i8 = 28;
+
+/// RollupAction::RollupRebalanceCustodians, gw-types doesn't know it yet
+fn build_rebalance_custodians_witness() -> WitnessArgs {
+    // union header: item id | table RollupRebalanceCustodians {}
+    let mut rollup_action = 6u32.to_le_bytes().to_vec();
+    rollup_action.extend_from_slice(&4u32.to_le_bytes());
+    WitnessArgs::new_builder()
+        .output_type(CKBPack::pack(&Some(Bytes::from(rollup_action))))
+        .build()
+}
+
+/// A custodian cell of `amount` sUDT, or of CKB if `amount` is None
+struct TestCustodian {
+    deposit_block_number: u64,
+    capacity: u64,
+    amount: Option<u128>,
+}
+
+#[test]
+fn test_rebalance_custodians() {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup lock & config
+    let custodian_lock_type = build_type_id_script(b"custodian_lock_type_id");
+    let custodian_script_type_hash: [u8; 32] = custodian_lock_type.calc_script_hash().unpack();
+    let l1_sudt_type = build_type_id_script(b"l1_sudt_type_id");
+    let l1_sudt_script_type_hash: [u8; 32] = l1_sudt_type.calc_script_hash().unpack();
+    let sudt_script = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&l1_sudt_script_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(CKBPack::pack(&Bytes::from(b"sudt".to_vec())))
+        .build();
+    let rollup_config = RollupConfig::new_builder()
+        .custodian_script_type_hash(Pack::pack(&custodian_script_type_hash))
+        .l1_sudt_script_type_hash(Pack::pack(&l1_sudt_script_type_hash))
+        .build();
+    // deploy scripts, the l1 sUDT type is always success
+    let param = CellContextParam {
+        custodian_lock_type,
+        always_success_type: l1_sudt_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+
+    // global states
+    let last_finalized_block_number = 100u64;
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .last_finalized_block_number(Pack::pack(&last_finalized_block_number))
+        .status(Status::Running.into())
+        .version(1u8.into())
+        .build();
+
+    let build_custodian_cell = |custodian: &TestCustodian| {
+        let args = CustodianLockArgs::new_builder()
+            .deposit_block_number(Pack::pack(&custodian.deposit_block_number))
+            .build();
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &custodian_script_type_hash,
+            custodian.capacity,
+            args.as_bytes(),
+        );
+        match custodian.amount {
+            Some(amount) => (
+                cell.as_builder()
+                    .type_(CKBPack::pack(&Some(sudt_script.clone())))
+                    .build(),
+                Bytes::from(amount.to_le_bytes().to_vec()),
+            ),
+            None => (cell, Bytes::default()),
+        }
+    };
+    let build_tx = |ctx: &mut CellContext,
+                    post_global_state: &GlobalState,
+                    inputs: &[TestCustodian],
+                    outputs: &[TestCustodian]| {
+        let mut builder = build_simple_tx(
+            &mut ctx.inner,
+            (rollup_cell.clone(), global_state.as_bytes()),
+            Default::default(),
+            (rollup_cell.clone(), post_global_state.as_bytes()),
+        )
+        .as_advanced_builder();
+        for custodian in inputs {
+            let (cell, data) = build_custodian_cell(custodian);
+            let out_point = ctx.insert_cell(cell, data);
+            builder = builder.input(CellInput::new_builder().previous_output(out_point).build());
+        }
+        for custodian in outputs {
+            let (cell, data) = build_custodian_cell(custodian);
+            builder = builder.output(cell).output_data(CKBPack::pack(&data));
+        }
+        builder
+            .cell_dep(ctx.always_success_dep.clone())
+            .cell_dep(ctx.custodian_lock_dep.clone())
+            .cell_dep(ctx.state_validator_dep.clone())
+            .cell_dep(ctx.rollup_config_dep.clone())
+            .witness(CKBPack::pack(
+                &build_rebalance_custodians_witness().as_bytes(),
+            ))
+            .build()
+    };
+    let expected_err = |code: i8| {
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+            ),
+            code,
+        )
+        .input_type_script(0)
+    };
+    let custodian =
+        |deposit_block_number: u64, capacity: u64, amount: Option<u128>| TestCustodian {
+            deposit_block_number,
+            capacity,
+            amount,
+        };
+
+    // merge CKB custodians and split a sUDT custodian
+    let inputs = [
+        custodian(10, 300_00000000, None),
+        custodian(20, 200_00000000, None),
+        custodian(30, 200_00000000, Some(1000)),
+    ];
+    let outputs = [
+        custodian(0, 300_00000000, None),
+        custodian(0, 200_00000000, Some(600)),
+        custodian(0, 200_00000000, Some(400)),
+    ];
+    let tx = build_tx(&mut ctx, &global_state, &inputs, &outputs);
+    ctx.verify_tx(tx).expect("return success");
+
+    // sUDT amount isn't conserved
+    let outputs = [
+        custodian(0, 300_00000000, None),
+        custodian(0, 200_00000000, Some(600)),
+        custodian(0, 200_00000000, Some(500)),
+    ];
+    let tx = build_tx(&mut ctx, &global_state, &inputs, &outputs);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INSUFFICIENT_INPUT_FINALIZED_ASSETS_ERROR));
+
+    // CKB capacity isn't conserved, the capacity of sUDT cells is counted too
+    let outputs = [
+        custodian(0, 400_00000000, None),
+        custodian(0, 200_00000000, Some(1000)),
+    ];
+    let tx = build_tx(&mut ctx, &global_state, &inputs, &outputs);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        expected_err(INSUFFICIENT_OUTPUT_FINALIZED_ASSETS_ERROR)
+    );
+
+    // output custodian isn't finalized
+    let outputs = [
+        custodian(last_finalized_block_number + 1, 500_00000000, None),
+        custodian(0, 200_00000000, Some(1000)),
+    ];
+    let tx = build_tx(&mut ctx, &global_state, &inputs, &outputs);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_CUSTODIAN_CELL_ERROR));
+
+    // global state is changed
+    let post_global_state = global_state
+        .clone()
+        .as_builder()
+        .status(Status::Halting.into())
+        .build();
+    let outputs = [
+        custodian(0, 500_00000000, None),
+        custodian(0, 200_00000000, Some(1000)),
+    ];
+    let tx = build_tx(&mut ctx, &post_global_state, &inputs, &outputs);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE_ERROR));
+}
@@ -0,0 +1,733 @@
+use crate::script_tests::utils::layer1::{build_simple_tx, random_out_point, since_timestamp};
+use crate::script_tests::utils::rollup::{
+    build_always_success_cell, build_dynvec, build_rollup_config_with_extra_fields,
+    build_rollup_locked_cell, build_stake_asset_vec, build_type_id_script,
+    calculate_state_validator_type_id, CellContext, CellContextParam,
+};
+use crate::script_tests::utils::state::SMTState;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, CUSTODIAN_LOCK_PROGRAM, STATE_VALIDATOR_CODE_HASH,
+    WITHDRAWAL_LOCK_PROGRAM,
+};
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
+use ckb_types::{
+    core::Cycle,
+    packed::{CellDep, CellInput, CellOutput, WitnessArgs},
+    prelude::{Pack as CKBPack, Unpack},
+};
+use gw_common::{
+    blake2b::new_blake2b,
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    ckb_decimal::CKBCapacity,
+    registry_address::RegistryAddress,
+    sparse_merkle_tree::default_store::DefaultStore,
+    state::State,
+    H256,
+};
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, SigningType, Status},
+    packed::{
+        AccountMerkleState, BlockMerkleState, CustodianLockArgs, DepositLockArgs, GlobalState,
+        KVPairVec, RawL2Block, RollupConfig, Script, UnlockCustodianViaRevertWitness,
+        UnlockWithdrawalViaRevert, UnlockWithdrawalWitness, UnlockWithdrawalWitnessUnion,
+        WithdrawalLockArgs,
+    },
+};
+
+const INVALID_SINCE_ERROR: i8 = 6;
+const INVALID_OUTPUT_ERROR: i8 = 7;
+const INSUFFICIENT_AMOUNT_ERROR: i8 = 15;
+const INSUFFICIENT_INPUT_FINALIZED_ASSETS_ERROR: i8 = 16;
+const INVALID_BLOCK_ERROR: i8 = 20;
+const INVALID_STATUS_ERROR: i8 = 21;
+const INVALID_POST_GLOBAL_STATE_ERROR: i8 = 23;
+const INVALID_DEPOSIT_CELL_ERROR: i8 = 26;
+
+/// `GlobalState.status` in emergency mode, gw-types doesn't know it yet
+const STATUS_EMERGENCY: u8 = 2;
+
+/// RollupAction::RollupEnterEmergency, gw-types doesn't know it yet
+fn build_enter_emergency_witness(finalized_block: &RawL2Block, block_proof: Bytes) -> WitnessArgs {
+    // union header: item id | table RollupEnterEmergency
+    let mut rollup_action = 7u32.to_le_bytes().to_vec();
+    rollup_action.extend_from_slice(&build_dynvec(&[
+        finalized_block.as_bytes(),
+        Pack::pack(&block_proof).as_bytes(),
+    ]));
+    WitnessArgs::new_builder()
+        .output_type(CKBPack::pack(&Some(Bytes::from(rollup_action))))
+        .build()
+}
+
+/// RollupAction::RollupEmergencyExit without claims
+fn build_empty_emergency_exit_witness() -> WitnessArgs {
+    build_emergency_exit_witness(&[], &KVPairVec::default(), &Bytes::default())
+}
+
+/// RollupAction::RollupEmergencyExit, gw-types doesn't know it yet
+fn build_emergency_exit_witness(
+    claims: &[Bytes],
+    kv_state: &KVPairVec,
+    kv_state_proof: &Bytes,
+) -> WitnessArgs {
+    // fixvec EmergencyClaimVec: item count | items
+    let mut claim_vec = (claims.len() as u32).to_le_bytes().to_vec();
+    for claim in claims {
+        claim_vec.extend_from_slice(claim);
+    }
+    // union header: item id | table RollupEmergencyExit
+    let mut rollup_action = 8u32.to_le_bytes().to_vec();
+    rollup_action.extend_from_slice(&build_dynvec(&[
+        Bytes::from(claim_vec),
+        kv_state.as_bytes(),
+        Pack::pack(kv_state_proof).as_bytes(),
+    ]));
+    WitnessArgs::new_builder()
+        .output_type(CKBPack::pack(&Some(Bytes::from(rollup_action))))
+        .build()
+}
+
+/// struct EmergencyClaim: account_script_hash | registry_id | sudt_script_hash | owner_lock_hash
+fn build_emergency_claim(
+    account_script_hash: &[u8; 32],
+    registry_id: u32,
+    sudt_script_hash: &[u8; 32],
+    owner_lock_hash: &[u8; 32],
+) -> Bytes {
+    let mut buf = account_script_hash.to_vec();
+    buf.extend_from_slice(&registry_id.to_le_bytes());
+    buf.extend_from_slice(sudt_script_hash);
+    buf.extend_from_slice(owner_lock_hash);
+    Bytes::from(buf)
+}
+
+#[test]
+fn test_enter_emergency() {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup config, 1 hour emergency exit timeout
+    let emergency_exit_timeout = 3600 * 1000u64;
+    let rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder().build(),
+        vec![
+            build_stake_asset_vec(&[]),
+            Bytes::from(vec![0u8; 32]),
+            Bytes::from(0u64.to_le_bytes().to_vec()),
+            Bytes::from(vec![0u8]),
+            Bytes::from(emergency_exit_timeout.to_le_bytes().to_vec()),
+        ],
+    );
+    let mut ctx = CellContext::new(&rollup_config, CellContextParam::default());
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+
+    // block 0..=10, block 5 is the last finalized block
+    let blocks: Vec<RawL2Block> = (0..=10u64)
+        .map(|number| {
+            let account = AccountMerkleState::new_builder()
+                .merkle_root(Pack::pack(&[number as u8; 32]))
+                .count(Pack::pack(&(number as u32 + 1)))
+                .build();
+            RawL2Block::new_builder()
+                .number(Pack::pack(&number))
+                .post_account(account)
+                .build()
+        })
+        .collect();
+    let mut block_tree: gw_common::smt::SMT<DefaultStore<H256>> = Default::default();
+    for block in blocks.iter() {
+        let number: u64 = block.number().unpack();
+        block_tree
+            .update(
+                RawL2Block::compute_smt_key(number).into(),
+                block.hash().into(),
+            )
+            .unwrap();
+    }
+    let block_proof = |block: &RawL2Block| -> Bytes {
+        let number: u64 = block.number().unpack();
+        let key: H256 = RawL2Block::compute_smt_key(number).into();
+        block_tree
+            .merkle_proof(vec![key])
+            .unwrap()
+            .compile(vec![(key, block.hash().into())])
+            .unwrap()
+            .0
+            .into()
+    };
+    let finalized_block = blocks[5].clone();
+    let tip_block = blocks[10].clone();
+
+    // global states
+    let tip_block_timestamp = 1_600_000_000_000u64;
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .account(tip_block.post_account())
+        .block(
+            BlockMerkleState::new_builder()
+                .merkle_root(Pack::pack(block_tree.root()))
+                .count(Pack::pack(&11u64))
+                .build(),
+        )
+        .tip_block_hash(Pack::pack(&tip_block.hash()))
+        .tip_block_timestamp(Pack::pack(&tip_block_timestamp))
+        .last_finalized_block_number(Pack::pack(&5u64))
+        .status(Status::Halting.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .account(finalized_block.post_account())
+        .status(STATUS_EMERGENCY.into())
+        .build();
+
+    let build_tx = |ctx: &mut CellContext,
+                    prev_global_state: &GlobalState,
+                    post_global_state: &GlobalState,
+                    since: u64,
+                    witness: WitnessArgs| {
+        build_simple_tx(
+            &mut ctx.inner,
+            (rollup_cell.clone(), prev_global_state.as_bytes()),
+            since_timestamp(since),
+            (rollup_cell.clone(), post_global_state.as_bytes()),
+        )
+        .as_advanced_builder()
+        .cell_dep(ctx.always_success_dep.clone())
+        .cell_dep(ctx.state_validator_dep.clone())
+        .cell_dep(ctx.rollup_config_dep.clone())
+        .witness(CKBPack::pack(&witness.as_bytes()))
+        .build()
+    };
+    let expected_err = |code: i8| {
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+            ),
+            code,
+        )
+        .input_type_script(0)
+    };
+    let timeout_since = tip_block_timestamp + emergency_exit_timeout;
+    let witness = build_enter_emergency_witness(&finalized_block, block_proof(&finalized_block));
+
+    // enter emergency mode, the account state is restored to the last finalized block
+    let tx = build_tx(
+        &mut ctx,
+        &prev_global_state,
+        &post_global_state,
+        timeout_since,
+        witness.clone(),
+    );
+    ctx.verify_tx(tx).expect("return success");
+
+    // the rollup isn't timeout
+    let tx = build_tx(
+        &mut ctx,
+        &prev_global_state,
+        &post_global_state,
+        timeout_since - 2000,
+        witness.clone(),
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_SINCE_ERROR));
+
+    // the block isn't the last finalized block
+    let unfinalized_block = blocks[6].clone();
+    let tx = build_tx(
+        &mut ctx,
+        &prev_global_state,
+        &post_global_state
+            .clone()
+            .as_builder()
+            .account(unfinalized_block.post_account())
+            .build(),
+        timeout_since,
+        build_enter_emergency_witness(&unfinalized_block, block_proof(&unfinalized_block)),
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_BLOCK_ERROR));
+
+    // the account state isn't restored
+    let tx = build_tx(
+        &mut ctx,
+        &prev_global_state,
+        &prev_global_state
+            .clone()
+            .as_builder()
+            .status(STATUS_EMERGENCY.into())
+            .build(),
+        timeout_since,
+        witness.clone(),
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE_ERROR));
+
+    // the rollup is running
+    let running_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .status(Status::Running.into())
+        .build();
+    let tx = build_tx(
+        &mut ctx,
+        &running_global_state,
+        &post_global_state,
+        timeout_since,
+        witness,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STATUS_ERROR));
+
+    // emergency exits are only accepted in emergency mode
+    let tx = build_tx(
+        &mut ctx,
+        &prev_global_state,
+        &prev_global_state,
+        timeout_since,
+        build_empty_emergency_exit_witness(),
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STATUS_ERROR));
+}
+
+/// The cells of `emergency_exit` besides the claim of the whole 300 CKB layer2 balance
+/// of an account, which is paid by a finalized custodian cell of 1000 CKB
+enum TestEmergencyExit {
+    Claim,
+    /// the post account state keeps the claimed balance
+    BalanceNotBurned,
+    /// the claimed CKB isn't sent to `owner_lock_hash`
+    MissingPayout,
+    /// the balance has been claimed by a previous emergency exit
+    ClaimedBefore,
+    /// the finalized custodian cell has only 200 CKB
+    ExceedFinalizedCustodians,
+    /// an unfinalized custodian cell is reverted to its deposit cell
+    RevertUnfinalizedCustodian,
+    /// the unfinalized custodian cell is reverted, with another deposit cell in the outputs
+    UnexpectedDepositCell,
+    /// an unfinalized withdrawal cell is reverted to a custodian cell
+    RevertUnfinalizedWithdrawal,
+}
+
+#[test]
+fn test_emergency_exit_claim() {
+    emergency_exit(TestEmergencyExit::Claim).expect("return success");
+
+    let err = emergency_exit(TestEmergencyExit::BalanceNotBurned).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE_ERROR));
+
+    let err = emergency_exit(TestEmergencyExit::MissingPayout).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_OUTPUT_ERROR));
+}
+
+#[test]
+fn test_emergency_exit_double_claim() {
+    let err = emergency_exit(TestEmergencyExit::ClaimedBefore).unwrap_err();
+    assert_error_eq!(err, expected_err(INSUFFICIENT_AMOUNT_ERROR));
+}
+
+#[test]
+fn test_emergency_exit_claims_exceed_finalized_custodians() {
+    let err = emergency_exit(TestEmergencyExit::ExceedFinalizedCustodians).unwrap_err();
+    assert_error_eq!(err, expected_err(INSUFFICIENT_INPUT_FINALIZED_ASSETS_ERROR));
+}
+
+#[test]
+fn test_emergency_exit_revert_unfinalized_custodian() {
+    emergency_exit(TestEmergencyExit::RevertUnfinalizedCustodian).expect("return success");
+
+    let err = emergency_exit(TestEmergencyExit::UnexpectedDepositCell).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_DEPOSIT_CELL_ERROR));
+}
+
+#[test]
+fn test_emergency_exit_revert_unfinalized_withdrawal() {
+    emergency_exit(TestEmergencyExit::RevertUnfinalizedWithdrawal).expect("return success");
+}
+
+fn expected_err(code: i8) -> ScriptError {
+    ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        code,
+    )
+    .input_type_script(0)
+}
+
+/// Custodian and withdrawal cells are unlocked by the custodian-lock and withdrawal-lock
+/// programs, which skip the reverted block check in emergency mode
+fn emergency_exit(case: TestEmergencyExit) -> Result<Cycle, ckb_error::Error> {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    let rollup_type_hash = rollup_type_script.hash();
+    // rollup lock & config
+    let deposit_lock_type = build_type_id_script(b"deposit_lock_type_id");
+    let deposit_script_type_hash: [u8; 32] = deposit_lock_type.calc_script_hash().unpack();
+    let custodian_lock_type = build_type_id_script(b"custodian_lock_type_id");
+    let custodian_script_type_hash: [u8; 32] = custodian_lock_type.calc_script_hash().unpack();
+    let withdrawal_lock_type = build_type_id_script(b"withdrawal_lock_type_id");
+    let withdrawal_script_type_hash: [u8; 32] = withdrawal_lock_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .deposit_script_type_hash(Pack::pack(&deposit_script_type_hash))
+        .custodian_script_type_hash(Pack::pack(&custodian_script_type_hash))
+        .withdrawal_script_type_hash(Pack::pack(&withdrawal_script_type_hash))
+        .build();
+    let param = CellContextParam {
+        deposit_lock_type,
+        custodian_lock_type: custodian_lock_type.clone(),
+        withdrawal_lock_type: withdrawal_lock_type.clone(),
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+    // deploy custodian-lock and withdrawal-lock instead of the always success locks
+    let custodian_lock_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(CUSTODIAN_LOCK_PROGRAM.len() as u64)))
+            .type_(CKBPack::pack(&Some(custodian_lock_type)))
+            .build();
+        let out_point = ctx.insert_cell(cell, CUSTODIAN_LOCK_PROGRAM.clone());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    let withdrawal_lock_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(WITHDRAWAL_LOCK_PROGRAM.len() as u64)))
+            .type_(CKBPack::pack(&Some(withdrawal_lock_type)))
+            .build();
+        let out_point = ctx.insert_cell(cell, WITHDRAWAL_LOCK_PROGRAM.clone());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+
+    // the account has 300 CKB on layer2, its layer2 script is also the lock of its
+    // signature cell
+    let account_script = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![7u8; 32])))
+        .build();
+    let account_script_hash: [u8; 32] = account_script.calc_script_hash().unpack();
+    let account_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![7u8; 20]);
+    let claim_capacity = 300_00000000u64;
+    let build_state = || {
+        let mut state = SMTState::default();
+        for reserved in 0..=CKB_SUDT_ACCOUNT_ID {
+            state.create_account([reserved as u8; 32].into()).unwrap();
+        }
+        state.create_account(account_script_hash.into()).unwrap();
+        state
+            .mapping_registry_address_to_script_hash(
+                account_address.clone(),
+                account_script_hash.into(),
+            )
+            .unwrap();
+        state
+            .mint_sudt(
+                CKB_SUDT_ACCOUNT_ID,
+                &account_address,
+                CKBCapacity::from_layer1(claim_capacity).to_layer2(),
+            )
+            .unwrap();
+        state
+    };
+    let burn_balance = |state: &mut SMTState| {
+        let balance = state
+            .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &account_address)
+            .unwrap();
+        state
+            .burn_sudt(CKB_SUDT_ACCOUNT_ID, &account_address, balance)
+            .unwrap();
+        balance
+    };
+    let mut prev_state = build_state();
+    if let TestEmergencyExit::ClaimedBefore = case {
+        burn_balance(&mut prev_state);
+    }
+    // the keys read and written by the claim, the burned balance is minted back to keep
+    // the prev values
+    prev_state.clear_touched_keys();
+    prev_state
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &account_script_hash.into())
+        .unwrap();
+    let balance = burn_balance(&mut prev_state);
+    prev_state
+        .mint_sudt(CKB_SUDT_ACCOUNT_ID, &account_address, balance)
+        .unwrap();
+    let (kv_state, kv_state_proof) = prev_state.touched_kv_state();
+    let prev_account = AccountMerkleState::new_builder()
+        .merkle_root(Pack::pack(&prev_state.calculate_root().unwrap()))
+        .count(Pack::pack(&prev_state.get_account_count().unwrap()))
+        .build();
+    let post_account = {
+        let mut post_state = build_state();
+        burn_balance(&mut post_state);
+        AccountMerkleState::new_builder()
+            .merkle_root(Pack::pack(&post_state.calculate_root().unwrap()))
+            .count(Pack::pack(&post_state.get_account_count().unwrap()))
+            .build()
+    };
+
+    // the claim is signed by the account's signature cell and paid to the owner
+    let owner_lock = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![42u8; 32])))
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock.calc_script_hash().unpack();
+    let claim = build_emergency_claim(
+        &account_script_hash,
+        ETH_REGISTRY_ACCOUNT_ID,
+        &[0u8; 32],
+        &owner_lock_hash,
+    );
+    let input_signature_cell = {
+        let mut hasher = new_blake2b();
+        hasher.update(&rollup_type_hash);
+        hasher.update(&claim);
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        let mut data = vec![0u8; 32];
+        data.push(SigningType::Raw.into());
+        data.extend_from_slice(&message);
+        let cell = CellOutput::new_builder()
+            .lock(account_script)
+            .capacity(CKBPack::pack(&100_00000000u64))
+            .build();
+        let out_point = ctx.insert_cell(cell, Bytes::from(data));
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let output_payout_cell = CellOutput::new_builder()
+        .lock(owner_lock)
+        .capacity(CKBPack::pack(&claim_capacity))
+        .build();
+
+    // block 5 is the last finalized block
+    let last_finalized_block_number = 5u64;
+    let unfinalized_block_number = 6u64;
+    let finalized_custodian_capacity = match case {
+        TestEmergencyExit::ExceedFinalizedCustodians => 200_00000000u64,
+        _ => 1000_00000000u64,
+    };
+    let input_finalized_custodian_cell = {
+        let args = CustodianLockArgs::new_builder()
+            .deposit_block_number(Pack::pack(&last_finalized_block_number))
+            .build();
+        let cell = build_rollup_locked_cell(
+            &rollup_type_hash,
+            &custodian_script_type_hash,
+            finalized_custodian_capacity,
+            args.as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    // the change of the finalized custodian cell
+    let output_finalized_custodian_cell = finalized_custodian_capacity
+        .checked_sub(claim_capacity)
+        .map(|capacity| {
+            let args = CustodianLockArgs::new_builder()
+                .deposit_block_number(Pack::pack(&last_finalized_block_number))
+                .build();
+            build_rollup_locked_cell(
+                &rollup_type_hash,
+                &custodian_script_type_hash,
+                capacity,
+                args.as_bytes(),
+            )
+        });
+
+    // global states
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .account(prev_account)
+        .last_finalized_block_number(Pack::pack(&last_finalized_block_number))
+        .status(STATUS_EMERGENCY.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = match case {
+        TestEmergencyExit::BalanceNotBurned => prev_global_state.clone(),
+        _ => prev_global_state
+            .clone()
+            .as_builder()
+            .account(post_account)
+            .build(),
+    };
+
+    let witness = build_emergency_exit_witness(&[claim], &kv_state, &kv_state_proof);
+    let mut tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), prev_global_state.as_bytes()),
+        Default::default(),
+        (rollup_cell, post_global_state.as_bytes()),
+    )
+    .as_advanced_builder()
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .input(input_finalized_custodian_cell)
+    .witness(Default::default())
+    .input(input_signature_cell)
+    .witness(Default::default())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .cell_dep(custodian_lock_dep)
+    .cell_dep(withdrawal_lock_dep);
+    if let Some(cell) = output_finalized_custodian_cell {
+        tx = tx
+            .output(cell)
+            .output_data(CKBPack::pack(&Bytes::default()));
+    }
+    if !matches!(case, TestEmergencyExit::MissingPayout) {
+        tx = tx
+            .output(output_payout_cell)
+            .output_data(CKBPack::pack(&Bytes::default()));
+    }
+
+    match case {
+        TestEmergencyExit::RevertUnfinalizedCustodian
+        | TestEmergencyExit::UnexpectedDepositCell => {
+            let deposit_capacity = 500_00000000u64;
+            let deposit_lock_args = DepositLockArgs::new_builder()
+                .owner_lock_hash(Pack::pack(&owner_lock_hash))
+                .build();
+            let output_deposit_cell = build_rollup_locked_cell(
+                &rollup_type_hash,
+                &deposit_script_type_hash,
+                deposit_capacity,
+                deposit_lock_args.as_bytes(),
+            );
+            let input_unfinalized_custodian_cell = {
+                let args = CustodianLockArgs::new_builder()
+                    .deposit_lock_args(deposit_lock_args)
+                    .deposit_block_number(Pack::pack(&unfinalized_block_number))
+                    .build();
+                let cell = build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &custodian_script_type_hash,
+                    deposit_capacity,
+                    args.as_bytes(),
+                );
+                let out_point = ctx.insert_cell(cell, Bytes::default());
+                CellInput::new_builder().previous_output(out_point).build()
+            };
+            let unlock_witness = {
+                let deposit_lock_hash: [u8; 32] =
+                    output_deposit_cell.lock().calc_script_hash().unpack();
+                let witness = UnlockCustodianViaRevertWitness::new_builder()
+                    .deposit_lock_hash(Pack::pack(&deposit_lock_hash))
+                    .build();
+                WitnessArgs::new_builder()
+                    .lock(CKBPack::pack(&Some(witness.as_bytes())))
+                    .build()
+            };
+            tx = tx
+                .input(input_unfinalized_custodian_cell)
+                .witness(CKBPack::pack(&unlock_witness.as_bytes()))
+                .output(output_deposit_cell)
+                .output_data(CKBPack::pack(&Bytes::default()));
+            if let TestEmergencyExit::UnexpectedDepositCell = case {
+                let cell = build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &deposit_script_type_hash,
+                    deposit_capacity,
+                    DepositLockArgs::default().as_bytes(),
+                );
+                tx = tx
+                    .output(cell)
+                    .output_data(CKBPack::pack(&Bytes::default()));
+            }
+        }
+        TestEmergencyExit::RevertUnfinalizedWithdrawal => {
+            let withdrawal_capacity = 200_00000000u64;
+            let input_unfinalized_withdrawal_cell = {
+                let owner_lock = Script::default();
+                let lock_args = WithdrawalLockArgs::new_builder()
+                    .withdrawal_block_number(Pack::pack(&unfinalized_block_number))
+                    .owner_lock_hash(Pack::pack(&owner_lock.hash()))
+                    .build();
+                let mut args = Vec::new();
+                args.extend_from_slice(&lock_args.as_bytes());
+                args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+                args.extend_from_slice(&owner_lock.as_bytes());
+                let cell = build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &withdrawal_script_type_hash,
+                    withdrawal_capacity,
+                    args.into(),
+                );
+                let out_point = ctx.insert_cell(cell, Bytes::default());
+                CellInput::new_builder().previous_output(out_point).build()
+            };
+            // reverted withdrawals are finalized custodians
+            let output_reverted_custodian_cell = {
+                let args = CustodianLockArgs::new_builder()
+                    .deposit_block_hash(Pack::pack(&[0u8; 32]))
+                    .deposit_block_number(Pack::pack(&0))
+                    .build();
+                build_rollup_locked_cell(
+                    &rollup_type_hash,
+                    &custodian_script_type_hash,
+                    withdrawal_capacity,
+                    args.as_bytes(),
+                )
+            };
+            let unlock_witness = {
+                let custodian_lock_hash: [u8; 32] = output_reverted_custodian_cell
+                    .lock()
+                    .calc_script_hash()
+                    .unpack();
+                let witness = UnlockWithdrawalWitness::new_builder()
+                    .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(
+                        UnlockWithdrawalViaRevert::new_builder()
+                            .custodian_lock_hash(Pack::pack(&custodian_lock_hash))
+                            .build(),
+                    ))
+                    .build();
+                WitnessArgs::new_builder()
+                    .lock(CKBPack::pack(&Some(witness.as_bytes())))
+                    .build()
+            };
+            tx = tx
+                .input(input_unfinalized_withdrawal_cell)
+                .witness(CKBPack::pack(&unlock_witness.as_bytes()))
+                .output(output_reverted_custodian_cell)
+                .output_data(CKBPack::pack(&Bytes::default()));
+        }
+        _ => {}
+    }
+    ctx.verify_tx(tx.build())
+}
@@ -0,0 +1,428 @@
+use crate::script_tests::utils::layer1::{build_simple_tx, random_out_point};
+use crate::script_tests::utils::rollup::{
+    build_always_success_cell, build_dynvec, build_rollup_config_with_extra_fields,
+    build_rollup_locked_cell, build_stake_asset_vec, build_type_id_script,
+    calculate_state_validator_type_id, CellContext, CellContextParam,
+};
+use crate::script_tests::utils::state::SMTState;
+use crate::testing_tool::programs::{ALWAYS_SUCCESS_CODE_HASH, STATE_VALIDATOR_CODE_HASH};
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
+use ckb_types::{
+    packed::{CellInput, CellOutput, WitnessArgs},
+    prelude::{Pack as CKBPack, Unpack},
+};
+use gw_common::{
+    blake2b::new_blake2b,
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    ckb_decimal::CKBCapacity,
+    registry_address::RegistryAddress,
+    state::State,
+};
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, SigningType, Status},
+    packed::{
+        AccountMerkleState, DepositLockArgs, GlobalState, KVPairVec, RawWithdrawalRequest,
+        RollupConfig, Script, WithdrawalRequest,
+    },
+};
+
+const INVALID_SINCE_ERROR: i8 = 6;
+const INVALID_OUTPUT_ERROR: i8 = 7;
+const MERKLE_PROOF_ERROR: i8 = 13;
+const INSUFFICIENT_AMOUNT_ERROR: i8 = 15;
+const INVALID_DEPOSIT_CELL_ERROR: i8 = 26;
+const INVALID_WITHDRAWAL_REQUEST_ERROR: i8 = 33;
+const ACCOUNT_LOCK_CELL_NOT_FOUND_ERROR: i8 = 37;
+
+/// relative since flag, the value is a block number
+const SINCE_RELATIVE_BLOCK_NUMBER_FLAG: u64 = 0x8000_0000_0000_0000;
+
+/// RollupAction::RollupForceHalt, gw-types doesn't know it yet
+fn build_force_halt_witness(kv_state: &KVPairVec, kv_state_proof: &Bytes) -> WitnessArgs {
+    // union header: item id | table RollupForceHalt
+    let mut rollup_action = 5u32.to_le_bytes().to_vec();
+    rollup_action.extend_from_slice(&build_dynvec(&[
+        kv_state.as_bytes(),
+        Pack::pack(kv_state_proof).as_bytes(),
+    ]));
+    WitnessArgs::new_builder()
+        .output_type(CKBPack::pack(&Some(Bytes::from(rollup_action))))
+        .build()
+}
+
+#[test]
+fn test_force_halt_by_forced_withdrawal() {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup lock & config
+    let deposit_lock_type = build_type_id_script(b"deposit_lock_type_id");
+    let deposit_script_type_hash: [u8; 32] = deposit_lock_type.calc_script_hash().unpack();
+    let forced_withdrawal_timeout = SINCE_RELATIVE_BLOCK_NUMBER_FLAG | 100;
+    let rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .deposit_script_type_hash(Pack::pack(&deposit_script_type_hash))
+            .build(),
+        vec![
+            build_stake_asset_vec(&[]),
+            Bytes::from(vec![0u8; 32]),
+            Bytes::from(forced_withdrawal_timeout.to_le_bytes().to_vec()),
+        ],
+    );
+    let param = CellContextParam {
+        deposit_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+
+    // forced withdrawal cell
+    let owner_lock = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![42u8; 32])))
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock.calc_script_hash().unpack();
+    let forced_withdrawal_capacity = 500_00000000u64;
+    let forced_withdrawal_cell = {
+        let lock_args = DepositLockArgs::new_builder()
+            .owner_lock_hash(Pack::pack(&owner_lock_hash))
+            .build();
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &deposit_script_type_hash,
+            forced_withdrawal_capacity,
+            lock_args.as_bytes(),
+        )
+    };
+    // the sender has 1000 CKB on layer2, its layer2 script is also the lock of its
+    // signature cell
+    let sender_script = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![7u8; 32])))
+        .build();
+    let sender_script_hash: [u8; 32] = sender_script.calc_script_hash().unpack();
+    let mut state = SMTState::default();
+    for reserved in 0..=CKB_SUDT_ACCOUNT_ID {
+        state.create_account([reserved as u8; 32].into()).unwrap();
+    }
+    let sender_id = state.create_account(sender_script_hash.into()).unwrap();
+    let sender_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![7u8; 20]);
+    state
+        .mapping_registry_address_to_script_hash(sender_address.clone(), sender_script_hash.into())
+        .unwrap();
+    state
+        .mint_sudt(
+            CKB_SUDT_ACCOUNT_ID,
+            &sender_address,
+            CKBCapacity::from_layer1(1000_00000000u64).to_layer2(),
+        )
+        .unwrap();
+    // the keys read by the force halt
+    state.clear_touched_keys();
+    state
+        .get_account_id_by_script_hash(&sender_script_hash.into())
+        .unwrap();
+    state
+        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash.into())
+        .unwrap();
+    state.get_nonce(sender_id).unwrap();
+    state
+        .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &sender_address)
+        .unwrap();
+    let (kv_state, kv_state_proof) = state.touched_kv_state();
+    let account = AccountMerkleState::new_builder()
+        .merkle_root(Pack::pack(&state.calculate_root().unwrap()))
+        .count(Pack::pack(&state.get_account_count().unwrap()))
+        .build();
+
+    let build_request = |nonce: u32, capacity: u64| {
+        let raw = RawWithdrawalRequest::new_builder()
+            .nonce(Pack::pack(&nonce))
+            .capacity(Pack::pack(&capacity))
+            .account_script_hash(Pack::pack(&sender_script_hash))
+            .registry_id(Pack::pack(&ETH_REGISTRY_ACCOUNT_ID))
+            .owner_lock_hash(Pack::pack(&owner_lock_hash))
+            .build();
+        WithdrawalRequest::new_builder()
+            .raw(raw)
+            .signature(Pack::pack(&Bytes::from(vec![1u8; 65])))
+            .build()
+    };
+    let forced_withdrawal_request = build_request(0, 400_00000000u64);
+    // signature cell of the sender: onetime lock hash | signing type | message
+    let build_signature_cell_data = |request: &WithdrawalRequest| {
+        let mut hasher = new_blake2b();
+        hasher.update(&rollup_type_script.hash());
+        hasher.update(request.raw().as_slice());
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        let mut data = vec![0u8; 32];
+        data.push(SigningType::Raw.into());
+        data.extend_from_slice(&message);
+        Bytes::from(data)
+    };
+    let signature_cell = CellOutput::new_builder()
+        .lock(sender_script)
+        .capacity(CKBPack::pack(&100_00000000u64))
+        .build();
+    let refund_cell = CellOutput::new_builder()
+        .lock(owner_lock)
+        .capacity(CKBPack::pack(&forced_withdrawal_capacity))
+        .build();
+
+    // global states
+    let capacity = 1000_00000000u64;
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .account(account)
+        .status(Status::Running.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .status(Status::Halting.into())
+        .build();
+
+    let build_tx = |ctx: &mut CellContext,
+                    since: u64,
+                    with_refund: bool,
+                    request: &WithdrawalRequest,
+                    signature_cell_data: Option<Bytes>,
+                    kv_state_proof: &Bytes| {
+        let out_point = ctx.insert_cell(forced_withdrawal_cell.clone(), request.as_bytes());
+        let builder = build_simple_tx(
+            &mut ctx.inner,
+            (rollup_cell.clone(), prev_global_state.as_bytes()),
+            Default::default(),
+            (rollup_cell.clone(), post_global_state.as_bytes()),
+        )
+        .as_advanced_builder()
+        .input(
+            CellInput::new_builder()
+                .previous_output(out_point)
+                .since(CKBPack::pack(&since))
+                .build(),
+        );
+        let builder = match signature_cell_data {
+            Some(data) => {
+                let out_point = ctx.insert_cell(signature_cell.clone(), data);
+                builder.input(CellInput::new_builder().previous_output(out_point).build())
+            }
+            None => builder,
+        };
+        let builder = if with_refund {
+            builder
+                .output(refund_cell.clone())
+                .output_data(CKBPack::pack(&Bytes::default()))
+        } else {
+            builder
+        };
+        builder
+            .cell_dep(ctx.always_success_dep.clone())
+            .cell_dep(ctx.deposit_lock_dep.clone())
+            .cell_dep(ctx.state_validator_dep.clone())
+            .cell_dep(ctx.rollup_config_dep.clone())
+            .witness(CKBPack::pack(
+                &build_force_halt_witness(&kv_state, kv_state_proof).as_bytes(),
+            ))
+            .build()
+    };
+    let expected_err = |code: i8| {
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+            ),
+            code,
+        )
+        .input_type_script(0)
+    };
+
+    let signature_cell_data = build_signature_cell_data(&forced_withdrawal_request);
+
+    // halt the rollup by a timeout forced withdrawal cell
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &forced_withdrawal_request,
+        Some(signature_cell_data.clone()),
+        &kv_state_proof,
+    );
+    ctx.verify_tx(tx).expect("return success");
+
+    // the forced withdrawal cell isn't timeout
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout - 1,
+        true,
+        &forced_withdrawal_request,
+        Some(signature_cell_data.clone()),
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_SINCE_ERROR));
+
+    // the forced withdrawal cell isn't refunded
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        false,
+        &forced_withdrawal_request,
+        Some(signature_cell_data.clone()),
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_OUTPUT_ERROR));
+
+    // the request isn't signed by the sender
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &forced_withdrawal_request,
+        None,
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(ACCOUNT_LOCK_CELL_NOT_FOUND_ERROR));
+
+    // the signature is of another request
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &forced_withdrawal_request,
+        Some(build_signature_cell_data(&build_request(
+            0,
+            300_00000000u64,
+        ))),
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(ACCOUNT_LOCK_CELL_NOT_FOUND_ERROR));
+
+    // the kv state isn't proven against the account state
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &forced_withdrawal_request,
+        Some(signature_cell_data),
+        &Bytes::default(),
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(MERKLE_PROOF_ERROR));
+
+    // the request has been included, the sender's nonce moved on
+    let included_request = build_request(1, 400_00000000u64);
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &included_request,
+        Some(build_signature_cell_data(&included_request)),
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_WITHDRAWAL_REQUEST_ERROR));
+
+    // the sender can't afford the request
+    let unaffordable_request = build_request(0, 2000_00000000u64);
+    let tx = build_tx(
+        &mut ctx,
+        forced_withdrawal_timeout,
+        true,
+        &unaffordable_request,
+        Some(build_signature_cell_data(&unaffordable_request)),
+        &kv_state_proof,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INSUFFICIENT_AMOUNT_ERROR));
+}
+
+#[test]
+fn test_force_halt_with_zero_forced_withdrawal_timeout() {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // a zero timeout disables forced withdrawals, later extra fields are set
+    let rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::default(),
+        vec![
+            build_stake_asset_vec(&[]),
+            Bytes::from(vec![0u8; 32]),
+            Bytes::from(0u64.to_le_bytes().to_vec()),
+            Bytes::from(vec![1u8]),
+        ],
+    );
+    let mut ctx = CellContext::new(&rollup_config, CellContextParam::default());
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+
+    let rollup_cell = build_always_success_cell(
+        1000_00000000u64,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .status(Status::Running.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .status(Status::Halting.into())
+        .build();
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), prev_global_state.as_bytes()),
+        Default::default(),
+        (rollup_cell, post_global_state.as_bytes()),
+    )
+    .as_advanced_builder()
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(
+        &build_force_halt_witness(&KVPairVec::default(), &Bytes::default()).as_bytes(),
+    ))
+    .build();
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_DEPOSIT_CELL_ERROR,
+    )
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
+}
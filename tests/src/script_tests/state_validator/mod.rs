@@ -1,4 +1,8 @@
 mod cancel_challenge;
+mod custodian;
+mod emergency;
 mod enter_challenge;
+mod forced_withdrawal;
 mod revert;
 mod submit_block;
+mod update_config;
@@ -1,14 +1,19 @@
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::script_tests::utils::layer1::{
     build_simple_tx_with_out_point_and_since, random_out_point, since_timestamp,
 };
 use crate::script_tests::utils::rollup::{
-    build_always_success_cell, build_rollup_locked_cell, build_type_id_script,
-    calculate_state_validator_type_id, CellContext, CellContextParam,
+    build_always_success_cell, build_dynvec, build_rollup_config_with_extra_fields,
+    build_rollup_locked_cell, build_stake_asset_vec, build_table_with_extra_fields,
+    build_type_id_script, calculate_state_validator_type_id, CellContext, CellContextParam,
 };
 use crate::testing_tool::chain::{build_sync_tx, construct_block_from_timestamp};
-use crate::testing_tool::programs::{ALWAYS_SUCCESS_CODE_HASH, STATE_VALIDATOR_CODE_HASH};
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, STAKE_LOCK_PROGRAM, STATE_VALIDATOR_CODE_HASH,
+};
 use crate::{script_tests::utils::layer1::build_simple_tx, testing_tool::chain::construct_block};
 use crate::{
     script_tests::utils::layer1::build_simple_tx_with_out_point, testing_tool::chain::setup_chain,
@@ -16,14 +21,21 @@ use crate::{
 use ckb_error::assert_error_eq;
 use ckb_script::ScriptError;
 use ckb_types::{
+    core::Cycle,
     packed::CellInput,
     prelude::{Pack as CKBPack, Unpack},
 };
 use gw_chain::chain::{L1Action, L1ActionContext, SyncParam};
+use gw_common::merkle_utils::{calculate_state_checkpoint, ckb_merkle_leaf_hash, CBMT};
+use gw_common::registry_address::RegistryAddress;
+use gw_common::smt::Blake2bHasher;
+use gw_common::sparse_merkle_tree::CompiledMerkleProof;
+use gw_common::{state::State, H256};
+use gw_store::mem_pool_state::{MemPoolState, MemStore};
 use gw_types::core::AllowedEoaType;
 use gw_types::packed::{
-    AllowedTypeHash, DepositRequest, L2BlockCommittedInfo, RawWithdrawalRequest, WithdrawalRequest,
-    WithdrawalRequestExtra,
+    AllowedTypeHash, DepositRequest, L2Block, L2BlockCommittedInfo, L2Transaction, RawL2Block,
+    RawL2Transaction, RawWithdrawalRequest, WithdrawalRequest, WithdrawalRequestExtra,
 };
 use gw_types::prelude::{Pack as GWPack, Unpack as GWUnpack, *};
 use gw_types::{
@@ -35,8 +47,14 @@ use gw_types::{
     },
 };
 
+const ENCODING_ERROR: i8 = 4;
+const ACCOUNT_NOT_FOUND_ERROR: i8 = 12;
 const INVALID_BLOCK_ERROR: i8 = 20;
 const INVALID_POST_GLOBAL_STATE: i8 = 23;
+const INVALID_STAKE_CELL_ERROR: i8 = 25;
+
+/// Signature of a deposit call transaction, see `gw_utils::signature::DEPOSIT_CALL_SIGNATURE`
+pub(super) const DEPOSIT_CALL_SIGNATURE: &[u8] = b"deposit call";
 
 #[tokio::test]
 async fn test_submit_block() {
@@ -158,7 +176,7 @@ async fn test_submit_block() {
 }
 
 #[tokio::test]
-async fn test_downgrade_rollup_cell() {
+async fn test_submit_batched_blocks() {
     // calculate type id
     let capacity = 1000_00000000u64;
     let spend_cell = build_always_success_cell(capacity, None);
@@ -196,9 +214,10 @@ async fn test_downgrade_rollup_cell() {
         let out_point = ctx.insert_cell(cell, Bytes::default());
         CellInput::new_builder().previous_output(out_point).build()
     };
+    // the stake cell is updated by the last block of the batch
     let output_stake_cell = {
         let lock_args = StakeLockArgs::new_builder()
-            .stake_block_number(Pack::pack(&1))
+            .stake_block_number(Pack::pack(&2))
             .build();
         build_rollup_locked_cell(
             &rollup_type_script.hash(),
@@ -215,12 +234,7 @@ async fn test_downgrade_rollup_cell() {
         )),
     );
     let global_state = chain.local_state().last_global_state();
-    let initial_rollup_cell_data = global_state
-        .clone()
-        .as_builder()
-        .version(1u8.into())
-        .build()
-        .as_bytes();
+    let initial_rollup_cell_data = global_state.as_bytes();
     let tx = build_simple_tx_with_out_point(
         &mut ctx.inner,
         (spend_cell, Default::default()),
@@ -233,66 +247,141 @@ async fn test_downgrade_rollup_cell() {
     .cell_dep(ctx.rollup_config_dep.clone())
     .build();
     ctx.verify_tx(tx).expect("return success");
-    // submit a new block
-    let block_result = {
+
+    // produce two blocks
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("timestamp")
+        .as_millis() as u64;
+    let first_block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
         let mut mem_pool = mem_pool.lock().await;
-        construct_block(&chain, &mut mem_pool, Vec::default())
+        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), timestamp)
             .await
             .unwrap()
     };
-    // verify submit block
-    let tip_block_timestamp = block_result.block.raw().timestamp();
-    let rollup_cell_data = block_result
-        .global_state
-        .as_builder()
-        .tip_block_timestamp(tip_block_timestamp.clone())
-        .version(0u8.into())
-        .build()
-        .as_bytes();
-    let witness = {
+    let first_block = first_block_result.block.clone();
+    let first_global_state = first_block_result.global_state.clone();
+    let sync_first_block = L1Action {
+        context: L1ActionContext::SubmitBlock {
+            l2block: first_block.clone(),
+            deposit_requests: Default::default(),
+            deposit_asset_scripts: Default::default(),
+            withdrawals: Default::default(),
+        },
+        transaction: build_sync_tx(
+            gw_types::packed::CellOutput::new_unchecked(rollup_cell.as_bytes()),
+            first_block_result,
+        ),
+        l2block_committed_info: L2BlockCommittedInfo::new_builder()
+            .number(Pack::pack(&1u64))
+            .build(),
+    };
+    let param = SyncParam {
+        updates: vec![sync_first_block],
+        reverts: Default::default(),
+    };
+    chain.sync(param).await.unwrap();
+    assert!(chain.last_sync_event().is_success());
+    let second_block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        mem_pool.reset_mem_block().await.unwrap();
+        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), timestamp + 1)
+            .await
+            .unwrap()
+    };
+    let second_block = second_block_result.block;
+    assert_eq!(GWUnpack::<u64>::unpack(&second_block.raw().number()), 2);
+
+    // submit both blocks in one action
+    let build_witness = |block: L2Block, following_blocks: Vec<Bytes>| {
+        let submit_block = RollupSubmitBlock::new_builder().block(block).build();
+        let submit_block = RollupSubmitBlock::new_unchecked(build_table_with_extra_fields(
+            submit_block.as_slice(),
+            submit_block.field_count(),
+            vec![build_dynvec(&following_blocks)],
+        ));
         let rollup_action = RollupAction::new_builder()
-            .set(RollupActionUnion::RollupSubmitBlock(
-                RollupSubmitBlock::new_builder()
-                    .block(block_result.block)
-                    .build(),
-            ))
+            .set(RollupActionUnion::RollupSubmitBlock(submit_block))
             .build();
         ckb_types::packed::WitnessArgs::new_builder()
             .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
             .build()
     };
-    let tx = build_simple_tx(
-        &mut ctx.inner,
-        (rollup_cell.clone(), initial_rollup_cell_data),
-        since_timestamp(GWUnpack::unpack(&tip_block_timestamp)),
-        (rollup_cell, rollup_cell_data),
+    let build_submit_tx = |ctx: &mut CellContext,
+                           rollup_cell_data: Bytes,
+                           witness: ckb_types::packed::WitnessArgs| {
+        build_simple_tx(
+            &mut ctx.inner,
+            (rollup_cell.clone(), initial_rollup_cell_data.clone()),
+            since_timestamp(timestamp + 1),
+            (rollup_cell.clone(), rollup_cell_data),
+        )
+        .as_advanced_builder()
+        .input(input_stake_cell.clone())
+        .output(output_stake_cell.clone())
+        .output_data(CKBPack::pack(&Bytes::default()))
+        .cell_dep(ctx.stake_lock_dep.clone())
+        .cell_dep(ctx.always_success_dep.clone())
+        .cell_dep(ctx.state_validator_dep.clone())
+        .cell_dep(ctx.rollup_config_dep.clone())
+        .witness(CKBPack::pack(&witness.as_bytes()))
+        .build()
+    };
+    let rollup_cell_data = second_block_result
+        .global_state
+        .as_builder()
+        .tip_block_timestamp(second_block.raw().timestamp())
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let witness = build_witness(first_block.clone(), vec![second_block.as_bytes()]);
+    let tx = build_submit_tx(&mut ctx, rollup_cell_data.clone(), witness);
+    ctx.verify_tx(tx).expect("return success");
+
+    // only RollupSubmitBlock carries extra fields, the submitted block is verified strictly
+    let block_with_extra_field = L2Block::new_unchecked(build_table_with_extra_fields(
+        first_block.as_slice(),
+        first_block.field_count(),
+        vec![Bytes::default()],
+    ));
+    let witness = build_witness(block_with_extra_field, vec![second_block.as_bytes()]);
+    let tx = build_submit_tx(&mut ctx, rollup_cell_data, witness);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        ENCODING_ERROR,
     )
-    .as_advanced_builder()
-    .input(input_stake_cell)
-    .output(output_stake_cell)
-    .output_data(CKBPack::pack(&Bytes::default()))
-    .cell_dep(ctx.stake_lock_dep.clone())
-    .cell_dep(ctx.always_success_dep.clone())
-    .cell_dep(ctx.state_validator_dep.clone())
-    .cell_dep(ctx.rollup_config_dep.clone())
-    .witness(CKBPack::pack(&witness.as_bytes()))
-    .build();
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
 
+    // the post global state must be the state after the last block
+    let rollup_cell_data = first_global_state
+        .as_builder()
+        .tip_block_timestamp(first_block.raw().timestamp())
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let witness = build_witness(first_block.clone(), vec![second_block.as_bytes()]);
+    let tx = build_submit_tx(&mut ctx, rollup_cell_data, witness);
     let err = ctx.verify_tx(tx).unwrap_err();
     let expected_err = ScriptError::ValidationFailure(
         format!(
             "by-data-hash/{}",
             ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
         ),
-        INVALID_POST_GLOBAL_STATE,
+        INVALID_BLOCK_ERROR,
     )
     .input_type_script(0);
     assert_error_eq!(err, expected_err);
 }
 
 #[tokio::test]
-async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_block() {
+async fn test_submit_block_with_sudt_stake() {
     // calculate type id
     let capacity = 1000_00000000u64;
     let spend_cell = build_always_success_cell(capacity, None);
@@ -308,31 +397,38 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
     // rollup lock & config
     let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
     let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
-    let rollup_config = RollupConfig::new_builder()
-        .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+    let l1_sudt_type = build_type_id_script(b"l1_sudt_type_id");
+    let l1_sudt_script_type_hash: [u8; 32] = l1_sudt_type.calc_script_hash().unpack();
+    let sudt_script = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&l1_sudt_script_type_hash))
+        .hash_type(ScriptHashType::Type.into())
+        .args(CKBPack::pack(&Bytes::from(b"governance_token".to_vec())))
         .build();
+    let sudt_script_hash: [u8; 32] = sudt_script.calc_script_hash().unpack();
+    let minimum_stake_amount = 1000u128;
+    let rollup_config = {
+        let rollup_config = RollupConfig::new_builder()
+            .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+            .l1_sudt_script_type_hash(Pack::pack(&l1_sudt_script_type_hash))
+            .build();
+        let allowed_stake_assets =
+            build_stake_asset_vec(&[(sudt_script_hash, minimum_stake_amount)]);
+        build_rollup_config_with_extra_fields(&rollup_config, vec![allowed_stake_assets])
+    };
     // setup chain
     let chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
-    // deploy scripts
+    // deploy scripts, the l1 sUDT type is always success
     let param = CellContextParam {
         stake_lock_type,
+        always_success_type: l1_sudt_type,
         ..Default::default()
     };
     let mut ctx = CellContext::new(&rollup_config, param);
-    let stake_capacity = 10000_00000000u64;
-    let input_stake_cell = {
-        let cell = build_rollup_locked_cell(
-            &rollup_type_script.hash(),
-            &stake_script_type_hash,
-            stake_capacity,
-            StakeLockArgs::default().as_bytes(),
-        );
-        let out_point = ctx.insert_cell(cell, Bytes::default());
-        CellInput::new_builder().previous_output(out_point).build()
-    };
-    let output_stake_cell = {
+    // stake cells only carry the minimal capacity
+    let stake_capacity = 500_00000000u64;
+    let build_sudt_stake_cell = |stake_block_number: u64| {
         let lock_args = StakeLockArgs::new_builder()
-            .stake_block_number(Pack::pack(&1))
+            .stake_block_number(Pack::pack(&stake_block_number))
             .build();
         build_rollup_locked_cell(
             &rollup_type_script.hash(),
@@ -340,7 +436,18 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
             stake_capacity,
             lock_args.as_bytes(),
         )
+        .as_builder()
+        .type_(CKBPack::pack(&Some(sudt_script.clone())))
+        .build()
+    };
+    let input_stake_cell = {
+        let out_point = ctx.insert_cell(
+            build_sudt_stake_cell(0),
+            Bytes::from(minimum_stake_amount.to_le_bytes().to_vec()),
+        );
+        CellInput::new_builder().previous_output(out_point).build()
     };
+    let output_stake_cell = build_sudt_stake_cell(1);
     // create a rollup cell
     let rollup_cell = build_always_success_cell(
         capacity,
@@ -349,21 +456,7 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
         )),
     );
     let global_state = chain.local_state().last_global_state();
-    let initial_timestamp = {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("timestamp")
-            .as_millis() as u64;
-        assert!(timestamp > 100);
-        timestamp - 100
-    };
-    let initial_rollup_cell_data = global_state
-        .clone()
-        .as_builder()
-        .tip_block_timestamp(GWPack::pack(&initial_timestamp))
-        .version(1u8.into())
-        .build()
-        .as_bytes();
+    let initial_rollup_cell_data = global_state.as_bytes();
     let tx = build_simple_tx_with_out_point(
         &mut ctx.inner,
         (spend_cell, Default::default()),
@@ -376,84 +469,20 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
     .cell_dep(ctx.rollup_config_dep.clone())
     .build();
     ctx.verify_tx(tx).expect("return success");
-
-    // #### Submit a smaller block timestamp
-    let tip_block_timestamp = initial_timestamp;
-    assert!(tip_block_timestamp > 100);
-    let block_result = {
-        let timestamp = tip_block_timestamp.saturating_sub(100);
-        let mem_pool = chain.mem_pool().as_ref().unwrap();
-        let mut mem_pool = mem_pool.lock().await;
-        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), timestamp)
-            .await
-            .unwrap()
-    };
-    // verify submit block
-    let block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
-    assert!(block_timestamp == tip_block_timestamp.saturating_sub(100));
-    let rollup_cell_data = {
-        let block_timestamp = GWPack::pack(&block_timestamp);
-        let builder = block_result.global_state.clone().as_builder();
-        builder
-            .tip_block_timestamp(block_timestamp)
-            .version(1u8.into())
-            .build()
-    };
-    let witness = {
-        let rollup_action = RollupAction::new_builder()
-            .set(RollupActionUnion::RollupSubmitBlock(
-                RollupSubmitBlock::new_builder()
-                    .block(block_result.block)
-                    .build(),
-            ))
-            .build();
-        ckb_types::packed::WitnessArgs::new_builder()
-            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
-            .build()
-    };
-    let tx = build_simple_tx(
-        &mut ctx.inner,
-        (rollup_cell.clone(), initial_rollup_cell_data.clone()),
-        since_timestamp(tip_block_timestamp.saturating_add(100)),
-        (rollup_cell.clone(), rollup_cell_data.as_bytes()),
-    )
-    .as_advanced_builder()
-    .input(input_stake_cell.clone())
-    .output(output_stake_cell.clone())
-    .output_data(CKBPack::pack(&Bytes::default()))
-    .cell_dep(ctx.stake_lock_dep.clone())
-    .cell_dep(ctx.always_success_dep.clone())
-    .cell_dep(ctx.state_validator_dep.clone())
-    .cell_dep(ctx.rollup_config_dep.clone())
-    .witness(CKBPack::pack(&witness.as_bytes()))
-    .build();
-
-    let err = ctx.verify_tx(tx).unwrap_err();
-    let expected_err = ScriptError::ValidationFailure(
-        format!(
-            "by-data-hash/{}",
-            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
-        ),
-        INVALID_BLOCK_ERROR,
-    )
-    .input_type_script(0);
-    assert_error_eq!(err, expected_err);
-
-    // #### Submit a equal block timestamp
+    // submit a new block
     let block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
         let mut mem_pool = mem_pool.lock().await;
-        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), tip_block_timestamp)
+        construct_block(&chain, &mut mem_pool, Vec::default())
             .await
             .unwrap()
     };
     // verify submit block
-    let block_timestamp = block_result.block.raw().timestamp();
+    let tip_block_timestamp = block_result.block.raw().timestamp();
     let rollup_cell_data = block_result
         .global_state
-        .clone()
         .as_builder()
-        .tip_block_timestamp(block_timestamp)
+        .tip_block_timestamp(tip_block_timestamp.clone())
         .version(1u8.into())
         .build()
         .as_bytes();
@@ -472,13 +501,11 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
     let tx = build_simple_tx(
         &mut ctx.inner,
         (rollup_cell.clone(), initial_rollup_cell_data),
-        since_timestamp(tip_block_timestamp.saturating_add(1000)),
+        since_timestamp(GWUnpack::unpack(&tip_block_timestamp)),
         (rollup_cell, rollup_cell_data),
     )
     .as_advanced_builder()
     .input(input_stake_cell)
-    .output(output_stake_cell)
-    .output_data(CKBPack::pack(&Bytes::default()))
     .cell_dep(ctx.stake_lock_dep.clone())
     .cell_dep(ctx.always_success_dep.clone())
     .cell_dep(ctx.state_validator_dep.clone())
@@ -486,20 +513,37 @@ async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_
     .witness(CKBPack::pack(&witness.as_bytes()))
     .build();
 
-    let err = ctx.verify_tx(tx).unwrap_err();
+    // stake amount is lower than the minimum
+    let err_tx = tx
+        .as_advanced_builder()
+        .output(output_stake_cell.clone())
+        .output_data(CKBPack::pack(&Bytes::from(
+            (minimum_stake_amount - 1).to_le_bytes().to_vec(),
+        )))
+        .build();
+    let err = ctx.verify_tx(err_tx).unwrap_err();
     let expected_err = ScriptError::ValidationFailure(
         format!(
             "by-data-hash/{}",
             ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
         ),
-        INVALID_BLOCK_ERROR,
+        INVALID_STAKE_CELL_ERROR,
     )
     .input_type_script(0);
     assert_error_eq!(err, expected_err);
+
+    let tx = tx
+        .as_advanced_builder()
+        .output(output_stake_cell)
+        .output_data(CKBPack::pack(&Bytes::from(
+            minimum_stake_amount.to_le_bytes().to_vec(),
+        )))
+        .build();
+    ctx.verify_tx(tx).expect("return success");
 }
 
 #[tokio::test]
-async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block() {
+async fn test_submit_block_with_decreased_stake() {
     // calculate type id
     let capacity = 1000_00000000u64;
     let spend_cell = build_always_success_cell(capacity, None);
@@ -515,29 +559,41 @@ async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block(
     // rollup lock & config
     let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
     let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let required_staking_capacity = 5000_00000000u64;
     let rollup_config = RollupConfig::new_builder()
         .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .required_staking_capacity(Pack::pack(&required_staking_capacity))
         .build();
     // setup chain
     let chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
     // deploy scripts
     let param = CellContextParam {
-        stake_lock_type,
+        stake_lock_type: stake_lock_type.clone(),
         ..Default::default()
     };
     let mut ctx = CellContext::new(&rollup_config, param);
-    let stake_capacity = 10000_00000000u64;
+    // deploy stake-lock instead of the always success lock
+    let stake_lock_dep = {
+        let cell = ckb_types::packed::CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(STAKE_LOCK_PROGRAM.len() as u64)))
+            .type_(CKBPack::pack(&Some(stake_lock_type)))
+            .build();
+        let out_point = ctx.insert_cell(cell, STAKE_LOCK_PROGRAM.clone());
+        ckb_types::packed::CellDep::new_builder()
+            .out_point(out_point)
+            .build()
+    };
     let input_stake_cell = {
         let cell = build_rollup_locked_cell(
             &rollup_type_script.hash(),
             &stake_script_type_hash,
-            stake_capacity,
+            required_staking_capacity * 2,
             StakeLockArgs::default().as_bytes(),
         );
         let out_point = ctx.insert_cell(cell, Bytes::default());
         CellInput::new_builder().previous_output(out_point).build()
     };
-    let output_stake_cell = {
+    let build_output_stake_cell = |stake_capacity: u64| {
         let lock_args = StakeLockArgs::new_builder()
             .stake_block_number(Pack::pack(&1))
             .build();
@@ -556,12 +612,7 @@ async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block(
         )),
     );
     let global_state = chain.local_state().last_global_state();
-    let initial_rollup_cell_data = global_state
-        .clone()
-        .as_builder()
-        .version(1u8.into())
-        .build()
-        .as_bytes();
+    let initial_rollup_cell_data = global_state.as_bytes();
     let tx = build_simple_tx_with_out_point(
         &mut ctx.inner,
         (spend_cell, Default::default()),
@@ -583,11 +634,11 @@ async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block(
             .unwrap()
     };
     // verify submit block
-    let tip_block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
+    let tip_block_timestamp = block_result.block.raw().timestamp();
     let rollup_cell_data = block_result
         .global_state
         .as_builder()
-        .tip_block_timestamp(GWPack::pack(&tip_block_timestamp))
+        .tip_block_timestamp(tip_block_timestamp.clone())
         .version(1u8.into())
         .build()
         .as_bytes();
@@ -603,38 +654,49 @@ async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block(
             .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
             .build()
     };
-    // NOTE: since_timestamp() will increase tip_block_timestamp by 1 second, so we have have to minus 2 seconds
     let tx = build_simple_tx(
         &mut ctx.inner,
         (rollup_cell.clone(), initial_rollup_cell_data),
-        since_timestamp(tip_block_timestamp.saturating_sub(2000)),
+        since_timestamp(GWUnpack::unpack(&tip_block_timestamp)),
         (rollup_cell, rollup_cell_data),
     )
     .as_advanced_builder()
     .input(input_stake_cell)
-    .output(output_stake_cell)
-    .output_data(CKBPack::pack(&Bytes::default()))
-    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(stake_lock_dep)
     .cell_dep(ctx.always_success_dep.clone())
     .cell_dep(ctx.state_validator_dep.clone())
     .cell_dep(ctx.rollup_config_dep.clone())
     .witness(CKBPack::pack(&witness.as_bytes()))
     .build();
 
-    let err = ctx.verify_tx(tx).unwrap_err();
+    // stake capacity is decreased below the requirement
+    let err_tx = tx
+        .as_advanced_builder()
+        .output(build_output_stake_cell(required_staking_capacity - 1))
+        .output_data(CKBPack::pack(&Bytes::default()))
+        .build();
+    let err = ctx.verify_tx(err_tx).unwrap_err();
     let expected_err = ScriptError::ValidationFailure(
         format!(
             "by-data-hash/{}",
             ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
         ),
-        INVALID_BLOCK_ERROR,
+        INVALID_STAKE_CELL_ERROR,
     )
     .input_type_script(0);
     assert_error_eq!(err, expected_err);
+
+    // stake capacity is decreased to the requirement
+    let tx = tx
+        .as_advanced_builder()
+        .output(build_output_stake_cell(required_staking_capacity))
+        .output_data(CKBPack::pack(&Bytes::default()))
+        .build();
+    ctx.verify_tx(tx).expect("return success");
 }
 
 #[tokio::test]
-async fn test_v0_v1_wrong_global_state_tip_block_timestamp_in_submit_block() {
+async fn test_downgrade_rollup_cell() {
     // calculate type id
     let capacity = 1000_00000000u64;
     let spend_cell = build_always_success_cell(capacity, None);
@@ -694,7 +756,7 @@ async fn test_v0_v1_wrong_global_state_tip_block_timestamp_in_submit_block() {
     let initial_rollup_cell_data = global_state
         .clone()
         .as_builder()
-        .tip_block_timestamp(GWPack::pack(&0u64))
+        .version(1u8.into())
         .build()
         .as_bytes();
     let tx = build_simple_tx_with_out_point(
@@ -709,8 +771,7 @@ async fn test_v0_v1_wrong_global_state_tip_block_timestamp_in_submit_block() {
     .cell_dep(ctx.rollup_config_dep.clone())
     .build();
     ctx.verify_tx(tx).expect("return success");
-
-    // #### Submit a version 0 global state but block timestamp isn't 0
+    // submit a new block
     let block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
         let mut mem_pool = mem_pool.lock().await;
@@ -719,7 +780,484 @@ async fn test_v0_v1_wrong_global_state_tip_block_timestamp_in_submit_block() {
             .unwrap()
     };
     // verify submit block
-    let tip_block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
+    let tip_block_timestamp = block_result.block.raw().timestamp();
+    let rollup_cell_data = block_result
+        .global_state
+        .as_builder()
+        .tip_block_timestamp(tip_block_timestamp.clone())
+        .version(0u8.into())
+        .build()
+        .as_bytes();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupSubmitBlock(
+                RollupSubmitBlock::new_builder()
+                    .block(block_result.block)
+                    .build(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), initial_rollup_cell_data),
+        since_timestamp(GWUnpack::unpack(&tip_block_timestamp)),
+        (rollup_cell, rollup_cell_data),
+    )
+    .as_advanced_builder()
+    .input(input_stake_cell)
+    .output(output_stake_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .build();
+
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_POST_GLOBAL_STATE,
+    )
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_v1_block_timestamp_smaller_or_equal_than_previous_block_in_submit_block() {
+    // calculate type id
+    let capacity = 1000_00000000u64;
+    let spend_cell = build_always_success_cell(capacity, None);
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup lock & config
+    let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
+    let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .build();
+    // setup chain
+    let chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
+    // deploy scripts
+    let param = CellContextParam {
+        stake_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let stake_capacity = 10000_00000000u64;
+    let input_stake_cell = {
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            StakeLockArgs::default().as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let output_stake_cell = {
+        let lock_args = StakeLockArgs::new_builder()
+            .stake_block_number(Pack::pack(&1))
+            .build();
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            lock_args.as_bytes(),
+        )
+    };
+    // create a rollup cell
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = chain.local_state().last_global_state();
+    let initial_timestamp = {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("timestamp")
+            .as_millis() as u64;
+        assert!(timestamp > 100);
+        timestamp - 100
+    };
+    let initial_rollup_cell_data = global_state
+        .clone()
+        .as_builder()
+        .tip_block_timestamp(GWPack::pack(&initial_timestamp))
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        (spend_cell, Default::default()),
+        input_out_point,
+        (rollup_cell.clone(), initial_rollup_cell_data.clone()),
+    )
+    .as_advanced_builder()
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .build();
+    ctx.verify_tx(tx).expect("return success");
+
+    // #### Submit a smaller block timestamp
+    let tip_block_timestamp = initial_timestamp;
+    assert!(tip_block_timestamp > 100);
+    let block_result = {
+        let timestamp = tip_block_timestamp.saturating_sub(100);
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), timestamp)
+            .await
+            .unwrap()
+    };
+    // verify submit block
+    let block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
+    assert!(block_timestamp == tip_block_timestamp.saturating_sub(100));
+    let rollup_cell_data = {
+        let block_timestamp = GWPack::pack(&block_timestamp);
+        let builder = block_result.global_state.clone().as_builder();
+        builder
+            .tip_block_timestamp(block_timestamp)
+            .version(1u8.into())
+            .build()
+    };
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupSubmitBlock(
+                RollupSubmitBlock::new_builder()
+                    .block(block_result.block)
+                    .build(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), initial_rollup_cell_data.clone()),
+        since_timestamp(tip_block_timestamp.saturating_add(100)),
+        (rollup_cell.clone(), rollup_cell_data.as_bytes()),
+    )
+    .as_advanced_builder()
+    .input(input_stake_cell.clone())
+    .output(output_stake_cell.clone())
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .build();
+
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // #### Submit a equal block timestamp
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block_from_timestamp(&chain, &mut mem_pool, Vec::default(), tip_block_timestamp)
+            .await
+            .unwrap()
+    };
+    // verify submit block
+    let block_timestamp = block_result.block.raw().timestamp();
+    let rollup_cell_data = block_result
+        .global_state
+        .clone()
+        .as_builder()
+        .tip_block_timestamp(block_timestamp)
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupSubmitBlock(
+                RollupSubmitBlock::new_builder()
+                    .block(block_result.block)
+                    .build(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), initial_rollup_cell_data),
+        since_timestamp(tip_block_timestamp.saturating_add(1000)),
+        (rollup_cell, rollup_cell_data),
+    )
+    .as_advanced_builder()
+    .input(input_stake_cell)
+    .output(output_stake_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .build();
+
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_v1_block_timestamp_bigger_than_rollup_input_since_in_submit_block() {
+    // calculate type id
+    let capacity = 1000_00000000u64;
+    let spend_cell = build_always_success_cell(capacity, None);
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup lock & config
+    let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
+    let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .build();
+    // setup chain
+    let chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
+    // deploy scripts
+    let param = CellContextParam {
+        stake_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let stake_capacity = 10000_00000000u64;
+    let input_stake_cell = {
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            StakeLockArgs::default().as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let output_stake_cell = {
+        let lock_args = StakeLockArgs::new_builder()
+            .stake_block_number(Pack::pack(&1))
+            .build();
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            lock_args.as_bytes(),
+        )
+    };
+    // create a rollup cell
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = chain.local_state().last_global_state();
+    let initial_rollup_cell_data = global_state
+        .clone()
+        .as_builder()
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        (spend_cell, Default::default()),
+        input_out_point,
+        (rollup_cell.clone(), initial_rollup_cell_data.clone()),
+    )
+    .as_advanced_builder()
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .build();
+    ctx.verify_tx(tx).expect("return success");
+    // submit a new block
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block(&chain, &mut mem_pool, Vec::default())
+            .await
+            .unwrap()
+    };
+    // verify submit block
+    let tip_block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
+    let rollup_cell_data = block_result
+        .global_state
+        .as_builder()
+        .tip_block_timestamp(GWPack::pack(&tip_block_timestamp))
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupSubmitBlock(
+                RollupSubmitBlock::new_builder()
+                    .block(block_result.block)
+                    .build(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    // NOTE: since_timestamp() will increase tip_block_timestamp by 1 second, so we have have to minus 2 seconds
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), initial_rollup_cell_data),
+        since_timestamp(tip_block_timestamp.saturating_sub(2000)),
+        (rollup_cell, rollup_cell_data),
+    )
+    .as_advanced_builder()
+    .input(input_stake_cell)
+    .output(output_stake_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .build();
+
+    let err = ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_v0_v1_wrong_global_state_tip_block_timestamp_in_submit_block() {
+    // calculate type id
+    let capacity = 1000_00000000u64;
+    let spend_cell = build_always_success_cell(capacity, None);
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // rollup lock & config
+    let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
+    let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .build();
+    // setup chain
+    let chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
+    // deploy scripts
+    let param = CellContextParam {
+        stake_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+    let stake_capacity = 10000_00000000u64;
+    let input_stake_cell = {
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            StakeLockArgs::default().as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let output_stake_cell = {
+        let lock_args = StakeLockArgs::new_builder()
+            .stake_block_number(Pack::pack(&1))
+            .build();
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            lock_args.as_bytes(),
+        )
+    };
+    // create a rollup cell
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let global_state = chain.local_state().last_global_state();
+    let initial_rollup_cell_data = global_state
+        .clone()
+        .as_builder()
+        .tip_block_timestamp(GWPack::pack(&0u64))
+        .build()
+        .as_bytes();
+    let tx = build_simple_tx_with_out_point(
+        &mut ctx.inner,
+        (spend_cell, Default::default()),
+        input_out_point,
+        (rollup_cell.clone(), initial_rollup_cell_data.clone()),
+    )
+    .as_advanced_builder()
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .build();
+    ctx.verify_tx(tx).expect("return success");
+
+    // #### Submit a version 0 global state but block timestamp isn't 0
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block(&chain, &mut mem_pool, Vec::default())
+            .await
+            .unwrap()
+    };
+    // verify submit block
+    let tip_block_timestamp = GWUnpack::unpack(&block_result.block.raw().timestamp());
     let rollup_cell_data = block_result
         .global_state
         .clone()
@@ -1029,14 +1567,267 @@ async fn test_check_reverted_cells_in_submit_block() {
             })
             .collect()
     };
-    // submit a new block
-    let block_result = {
-        let mem_pool = chain.mem_pool().as_ref().unwrap();
-        let mut mem_pool = mem_pool.lock().await;
-        construct_block(&chain, &mut mem_pool, Vec::default())
-            .await
-            .unwrap()
+    // submit a new block
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block(&chain, &mut mem_pool, Vec::default())
+            .await
+            .unwrap()
+    };
+    // verify submit block
+    let tip_block_timestamp = block_result.block.raw().timestamp();
+    let rollup_cell_data = block_result
+        .global_state
+        .as_builder()
+        .tip_block_timestamp(tip_block_timestamp.clone())
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+    let witness = {
+        let rollup_action = RollupAction::new_builder()
+            .set(RollupActionUnion::RollupSubmitBlock(
+                RollupSubmitBlock::new_builder()
+                    .block(block_result.block)
+                    .build(),
+            ))
+            .build();
+        ckb_types::packed::WitnessArgs::new_builder()
+            .output_type(CKBPack::pack(&Some(rollup_action.as_bytes())))
+            .build()
+    };
+    let tx = build_simple_tx_with_out_point_and_since(
+        &mut ctx.inner,
+        (rollup_cell.clone(), initial_rollup_cell_data),
+        (
+            input_out_point,
+            since_timestamp(GWUnpack::unpack(&tip_block_timestamp)),
+        ),
+        (rollup_cell, rollup_cell_data),
+    )
+    .as_advanced_builder()
+    .input(input_stake_cell)
+    .output(output_stake_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .input(input_reverted_custodian_cell)
+    .output(output_reverted_deposit_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .input(input_reverted_withdrawal_cell)
+    .output(output_reverted_custodian_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .inputs(input_finalized_cells)
+    .outputs(output_finalized_cells.clone())
+    .outputs_data(
+        (0..output_finalized_cells.len())
+            .into_iter()
+            .map(|_| CKBPack::pack(&Bytes::new())),
+    )
+    .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.deposit_lock_dep.clone())
+    .cell_dep(ctx.custodian_lock_dep.clone())
+    .cell_dep(ctx.withdrawal_lock_dep.clone())
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .witness(CKBPack::pack(&witness.as_bytes()))
+    .build();
+    ctx.verify_tx(tx).expect("return success");
+}
+
+#[tokio::test]
+async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let capacity = 1000_00000000u64;
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point.clone());
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+
+    // rollup lock & config
+    let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
+    let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let custodian_lock_type = build_type_id_script(b"custodian_lock_type_id");
+    let custodian_script_type_hash: [u8; 32] = custodian_lock_type.calc_script_hash().unpack();
+    let withdrawal_lock_type = build_type_id_script(b"withdrawal_lock_type_id");
+    let withdrawal_script_type_hash: [u8; 32] = withdrawal_lock_type.calc_script_hash().unpack();
+    let rollup_config = RollupConfig::new_builder()
+        .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .custodian_script_type_hash(Pack::pack(&custodian_script_type_hash))
+        .withdrawal_script_type_hash(Pack::pack(&withdrawal_script_type_hash))
+        .allowed_eoa_type_hashes(PackVec::pack(vec![AllowedTypeHash::new(
+            AllowedEoaType::Eth,
+            *ALWAYS_SUCCESS_CODE_HASH,
+        )]))
+        .build();
+
+    // setup chain
+    let mut chain = setup_chain(rollup_type_script.clone(), rollup_config.clone()).await;
+
+    // create a rollup cell
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let eth_registry_id = gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+
+    // Deposit account
+    let deposit_capacity: u64 = 1000000 * 10u64.pow(8);
+    let deposit_lock_args = {
+        let mut args = rollup_type_script.hash().to_vec();
+        args.extend_from_slice(&[1u8; 20]);
+        Pack::pack(&Bytes::from(args))
+    };
+    let account_script = Script::new_builder()
+        .code_hash(Pack::pack(&ALWAYS_SUCCESS_CODE_HASH.clone()))
+        .hash_type(ScriptHashType::Type.into())
+        .args(deposit_lock_args)
+        .build();
+    let deposit = DepositRequest::new_builder()
+        .capacity(Pack::pack(&deposit_capacity))
+        .script(account_script.to_owned())
+        .registry_id(Pack::pack(&eth_registry_id))
+        .build();
+
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block(&chain, &mut mem_pool, vec![deposit.clone()])
+            .await
+            .unwrap()
+    };
+    let apply_deposits = L1Action {
+        context: L1ActionContext::SubmitBlock {
+            l2block: block_result.block.clone(),
+            deposit_requests: vec![deposit],
+            deposit_asset_scripts: Default::default(),
+            withdrawals: Default::default(),
+        },
+        transaction: build_sync_tx(
+            gw_types::packed::CellOutput::new_unchecked(rollup_cell.as_bytes()),
+            block_result,
+        ),
+        l2block_committed_info: L2BlockCommittedInfo::new_builder()
+            .number(Pack::pack(&1u64))
+            .build(),
+    };
+    let param = SyncParam {
+        updates: vec![apply_deposits],
+        reverts: Default::default(),
+    };
+    chain.sync(param).await.unwrap();
+    assert!(chain.last_sync_event().is_success());
+
+    // Withdraw
+    let withdrawal = {
+        let raw = RawWithdrawalRequest::new_builder()
+            .capacity(Pack::pack(&deposit_capacity))
+            .account_script_hash(Pack::pack(&account_script.hash()))
+            .owner_lock_hash(Pack::pack(&account_script.hash()))
+            .registry_id(Pack::pack(&eth_registry_id))
+            .build();
+        let request = WithdrawalRequest::new_builder().raw(raw).build();
+        WithdrawalRequestExtra::new_builder()
+            .request(request)
+            .owner_lock(account_script.clone())
+            .build()
+    };
+
+    // submit a new block
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        mem_pool.push_withdrawal_request(withdrawal).await.unwrap();
+        mem_pool.reset_mem_block().await.unwrap();
+        construct_block(&chain, &mut mem_pool, Vec::default())
+            .await
+            .unwrap()
+    };
+    assert_eq!(block_result.block.withdrawals().len(), 1);
+
+    // deploy scripts
+    let param = CellContextParam {
+        stake_lock_type,
+        custodian_lock_type,
+        withdrawal_lock_type,
+        ..Default::default()
+    };
+    let mut ctx = CellContext::new(&rollup_config, param);
+
+    // build stake input and output
+    let stake_capacity = 10000_00000000u64;
+    let input_stake_cell = {
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            StakeLockArgs::default().as_bytes(),
+        );
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let output_stake_cell = {
+        let block_number = block_result.block.raw().number();
+        let lock_args = StakeLockArgs::new_builder()
+            .stake_block_number(block_number)
+            .build();
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &stake_script_type_hash,
+            stake_capacity,
+            lock_args.as_bytes(),
+        )
+    };
+
+    let global_state = chain.local_state().last_global_state();
+    let initial_rollup_cell_data = global_state
+        .clone()
+        .as_builder()
+        .version(1u8.into())
+        .build()
+        .as_bytes();
+
+    // build custodian input
+    let input_custodian_cell = {
+        let cell = build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &custodian_script_type_hash,
+            deposit_capacity,
+            CustodianLockArgs::default().as_bytes(),
+        );
+
+        let out_point = ctx.insert_cell(cell, Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+
+    // build withdrawal output
+    let output_withdrawal_cell = {
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .withdrawal_block_number(block_result.block.raw().number())
+            .withdrawal_block_hash(Pack::pack(&block_result.block.raw().hash()))
+            .account_script_hash(Pack::pack(&account_script.hash()))
+            .owner_lock_hash(Pack::pack(&account_script.hash()))
+            .build();
+
+        let mut args = lock_args.as_slice().to_vec();
+        args.extend_from_slice(&(account_script.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&account_script.as_bytes());
+
+        build_rollup_locked_cell(
+            &rollup_type_script.hash(),
+            &withdrawal_script_type_hash,
+            deposit_capacity,
+            Bytes::from(args),
+        )
     };
+
     // verify submit block
     let tip_block_timestamp = block_result.block.raw().timestamp();
     let rollup_cell_data = block_result
@@ -1071,21 +1862,10 @@ async fn test_check_reverted_cells_in_submit_block() {
     .input(input_stake_cell)
     .output(output_stake_cell)
     .output_data(CKBPack::pack(&Bytes::default()))
-    .input(input_reverted_custodian_cell)
-    .output(output_reverted_deposit_cell)
-    .output_data(CKBPack::pack(&Bytes::default()))
-    .input(input_reverted_withdrawal_cell)
-    .output(output_reverted_custodian_cell)
+    .input(input_custodian_cell)
+    .output(output_withdrawal_cell)
     .output_data(CKBPack::pack(&Bytes::default()))
-    .inputs(input_finalized_cells)
-    .outputs(output_finalized_cells.clone())
-    .outputs_data(
-        (0..output_finalized_cells.len())
-            .into_iter()
-            .map(|_| CKBPack::pack(&Bytes::new())),
-    )
     .cell_dep(ctx.stake_lock_dep.clone())
-    .cell_dep(ctx.deposit_lock_dep.clone())
     .cell_dep(ctx.custodian_lock_dep.clone())
     .cell_dep(ctx.withdrawal_lock_dep.clone())
     .cell_dep(ctx.always_success_dep.clone())
@@ -1096,8 +1876,214 @@ async fn test_check_reverted_cells_in_submit_block() {
     ctx.verify_tx(tx).expect("return success");
 }
 
+/// How the deposit of `submit_block_with_deposit_recipient` names its recipient
+enum TestDepositRecipient {
+    /// No recipient, the deposit creates the account of its `layer2_lock`
+    Layer2Lock,
+    /// No recipient, the account of `layer2_lock` exists before the deposit
+    ExistingLayer2Lock,
+    AccountId,
+    RegistryAddress,
+    UnknownAccountId,
+    UnknownRegistryAddress,
+}
+
 #[tokio::test]
-async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
+async fn test_deposit_to_recipient_account_id() {
+    submit_block_with_deposit_recipient(TestDepositRecipient::AccountId, None, None)
+        .await
+        .expect("return success");
+}
+
+#[tokio::test]
+async fn test_deposit_to_recipient_registry_address() {
+    submit_block_with_deposit_recipient(TestDepositRecipient::RegistryAddress, None, None)
+        .await
+        .expect("return success");
+}
+
+#[tokio::test]
+async fn test_deposit_to_unknown_recipient() {
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        ACCOUNT_NOT_FOUND_ERROR,
+    )
+    .input_type_script(0);
+
+    let err =
+        submit_block_with_deposit_recipient(TestDepositRecipient::UnknownAccountId, None, None)
+            .await
+            .unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    let err = submit_block_with_deposit_recipient(
+        TestDepositRecipient::UnknownRegistryAddress,
+        None,
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_deposit_call_not_included() {
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+
+    // the submitted block has no transactions
+    let call = build_deposit_call(0);
+    let err =
+        submit_block_with_deposit_recipient(TestDepositRecipient::Layer2Lock, Some(call), None)
+            .await
+            .unwrap_err();
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_unsendable_deposit_call_is_dropped() {
+    // wrong chain id
+    let call = build_deposit_call(42);
+    submit_block_with_deposit_recipient(TestDepositRecipient::Layer2Lock, Some(call), None)
+        .await
+        .expect("return success");
+
+    // the call is sent from `layer2_lock`, which isn't credited by a deposit to a recipient
+    let call = build_deposit_call(0);
+    submit_block_with_deposit_recipient(TestDepositRecipient::AccountId, Some(call), None)
+        .await
+        .expect("return success");
+
+    // the deposit isn't authorized by an existing account, the call can't be sent from it
+    let call = build_deposit_call(0);
+    submit_block_with_deposit_recipient(TestDepositRecipient::ExistingLayer2Lock, Some(call), None)
+        .await
+        .expect("return success");
+}
+
+#[tokio::test]
+async fn test_deposit_call_included() {
+    let call = build_deposit_call(0);
+    submit_block_with_deposit_recipient(
+        TestDepositRecipient::Layer2Lock,
+        Some(call.clone()),
+        Some(call),
+    )
+    .await
+    .expect("return success");
+}
+
+#[tokio::test]
+async fn test_unexpected_deposit_call() {
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+
+    // the deposit has no call
+    let tx = build_deposit_call(0);
+    let err = submit_block_with_deposit_recipient(TestDepositRecipient::Layer2Lock, None, Some(tx))
+        .await
+        .unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // the call of the deposit is dropped
+    let call = build_deposit_call(42);
+    let tx = build_deposit_call(0);
+    let err =
+        submit_block_with_deposit_recipient(TestDepositRecipient::Layer2Lock, Some(call), Some(tx))
+            .await
+            .unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // the call of the deposit to an existing account is dropped
+    let call = build_deposit_call(0);
+    let err = submit_block_with_deposit_recipient(
+        TestDepositRecipient::ExistingLayer2Lock,
+        Some(call.clone()),
+        Some(call),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_err);
+}
+
+#[tokio::test]
+async fn test_deposit_call_mismatch() {
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_BLOCK_ERROR,
+    )
+    .input_type_script(0);
+    let call = build_deposit_call(0);
+
+    // wrong to_id
+    let tx = call.clone().as_builder().to_id(Pack::pack(&1u32)).build();
+    let err = submit_block_with_deposit_recipient(
+        TestDepositRecipient::Layer2Lock,
+        Some(call.clone()),
+        Some(tx),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // not the first tx of the created account
+    let tx = call.clone().as_builder().nonce(Pack::pack(&1u32)).build();
+    let err = submit_block_with_deposit_recipient(
+        TestDepositRecipient::Layer2Lock,
+        Some(call.clone()),
+        Some(tx),
+    )
+    .await
+    .unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // wrong args
+    let tx = call
+        .clone()
+        .as_builder()
+        .args(Pack::pack(&Bytes::from(vec![43u8; 8])))
+        .build();
+    let err =
+        submit_block_with_deposit_recipient(TestDepositRecipient::Layer2Lock, Some(call), Some(tx))
+            .await
+            .unwrap_err();
+    assert_error_eq!(err, expected_err);
+}
+
+/// A third party deposits to an existing recipient account:
+/// the deposit lock args name the recipient in the `recipient` extra field,
+/// `layer2_lock` is the depositor's script which is never created on layer2.
+/// Without a recipient, the deposit credits `layer2_lock`, which is a new account or the
+/// existing recipient account.
+/// `call` is appended as the `call` extra field.
+///
+/// The generator doesn't know the fields, it produces the block by depositing to the
+/// credited script, then the keys read to resolve the recipient are added to the block.
+/// `block_tx` is sent from the credited account with the deposit call signature as the first
+/// transaction of the block, it doesn't change the state.
+async fn submit_block_with_deposit_recipient(
+    recipient: TestDepositRecipient,
+    call: Option<RawL2Transaction>,
+    block_tx: Option<RawL2Transaction>,
+) -> Result<Cycle, ckb_error::Error> {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let capacity = 1000_00000000u64;
@@ -1114,18 +2100,19 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
     // rollup lock & config
     let stake_lock_type = build_type_id_script(b"stake_lock_type_id");
     let stake_script_type_hash: [u8; 32] = stake_lock_type.calc_script_hash().unpack();
+    let deposit_lock_type = build_type_id_script(b"deposit_lock_type_id");
+    let deposit_script_type_hash: [u8; 32] = deposit_lock_type.calc_script_hash().unpack();
     let custodian_lock_type = build_type_id_script(b"custodian_lock_type_id");
     let custodian_script_type_hash: [u8; 32] = custodian_lock_type.calc_script_hash().unpack();
-    let withdrawal_lock_type = build_type_id_script(b"withdrawal_lock_type_id");
-    let withdrawal_script_type_hash: [u8; 32] = withdrawal_lock_type.calc_script_hash().unpack();
     let rollup_config = RollupConfig::new_builder()
         .stake_script_type_hash(Pack::pack(&stake_script_type_hash))
+        .deposit_script_type_hash(Pack::pack(&deposit_script_type_hash))
         .custodian_script_type_hash(Pack::pack(&custodian_script_type_hash))
-        .withdrawal_script_type_hash(Pack::pack(&withdrawal_script_type_hash))
         .allowed_eoa_type_hashes(PackVec::pack(vec![AllowedTypeHash::new(
             AllowedEoaType::Eth,
             *ALWAYS_SUCCESS_CODE_HASH,
         )]))
+        .finality_blocks(Pack::pack(&100u64))
         .build();
 
     // setup chain
@@ -1140,24 +2127,21 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
     );
     let eth_registry_id = gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
 
-    // Deposit account
-    let deposit_capacity: u64 = 1000000 * 10u64.pow(8);
-    let deposit_lock_args = {
+    // create the recipient account
+    let recipient_script = {
         let mut args = rollup_type_script.hash().to_vec();
         args.extend_from_slice(&[1u8; 20]);
-        Pack::pack(&Bytes::from(args))
+        Script::new_builder()
+            .code_hash(Pack::pack(&ALWAYS_SUCCESS_CODE_HASH.clone()))
+            .hash_type(ScriptHashType::Type.into())
+            .args(Pack::pack(&Bytes::from(args)))
+            .build()
     };
-    let account_script = Script::new_builder()
-        .code_hash(Pack::pack(&ALWAYS_SUCCESS_CODE_HASH.clone()))
-        .hash_type(ScriptHashType::Type.into())
-        .args(deposit_lock_args)
-        .build();
     let deposit = DepositRequest::new_builder()
-        .capacity(Pack::pack(&deposit_capacity))
-        .script(account_script.to_owned())
+        .capacity(Pack::pack(&(500 * 10u64.pow(8))))
+        .script(recipient_script.clone())
         .registry_id(Pack::pack(&eth_registry_id))
         .build();
-
     let block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
         let mut mem_pool = mem_pool.lock().await;
@@ -1187,38 +2171,183 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
     chain.sync(param).await.unwrap();
     assert!(chain.last_sync_event().is_success());
 
-    // Withdraw
-    let withdrawal = {
-        let raw = RawWithdrawalRequest::new_builder()
-            .capacity(Pack::pack(&deposit_capacity))
-            .account_script_hash(Pack::pack(&account_script.hash()))
-            .owner_lock_hash(Pack::pack(&account_script.hash()))
-            .registry_id(Pack::pack(&eth_registry_id))
-            .build();
-        let request = WithdrawalRequest::new_builder().raw(raw).build();
-        WithdrawalRequestExtra::new_builder()
-            .request(request)
-            .owner_lock(account_script.clone())
-            .build()
+    // resolve the recipient on the state before the deposit block
+    let state = {
+        let mem_store = MemStore::new(chain.store().get_snapshot());
+        MemPoolState::new(Arc::new(mem_store), true)
+    };
+    let snap = state.load();
+    let mut tree = snap.state().unwrap();
+    tree.tracker_mut().enable();
+    let recipient_args = match recipient {
+        TestDepositRecipient::Layer2Lock | TestDepositRecipient::ExistingLayer2Lock => {
+            Bytes::default()
+        }
+        TestDepositRecipient::AccountId => {
+            let id = tree
+                .get_account_id_by_script_hash(&recipient_script.hash().into())
+                .unwrap()
+                .unwrap();
+            tree.get_script_hash(id).unwrap();
+            build_deposit_recipient_account_id(id)
+        }
+        TestDepositRecipient::RegistryAddress => {
+            let address = tree
+                .get_registry_address_by_script_hash(
+                    eth_registry_id,
+                    &recipient_script.hash().into(),
+                )
+                .unwrap()
+                .unwrap();
+            tree.get_script_hash_by_registry_address(&address).unwrap();
+            build_deposit_recipient_registry_address(&address)
+        }
+        TestDepositRecipient::UnknownAccountId => {
+            let id = 42;
+            assert!(tree.get_script_hash(id).unwrap().is_zero());
+            build_deposit_recipient_account_id(id)
+        }
+        TestDepositRecipient::UnknownRegistryAddress => {
+            let address = RegistryAddress::new(eth_registry_id, vec![42u8; 20]);
+            assert!(tree
+                .get_script_hash_by_registry_address(&address)
+                .unwrap()
+                .is_none());
+            build_deposit_recipient_registry_address(&address)
+        }
     };
 
-    // submit a new block
+    // the generator credits the recipient's script, or creates the account of a new script
+    let credited_script = match recipient {
+        TestDepositRecipient::Layer2Lock => {
+            let mut args = rollup_type_script.hash().to_vec();
+            args.extend_from_slice(&[2u8; 20]);
+            Script::new_builder()
+                .code_hash(Pack::pack(&ALWAYS_SUCCESS_CODE_HASH.clone()))
+                .hash_type(ScriptHashType::Type.into())
+                .args(Pack::pack(&Bytes::from(args)))
+                .build()
+        }
+        _ => recipient_script.clone(),
+    };
+    let deposit_capacity: u64 = 1000 * 10u64.pow(8);
+    let deposit = DepositRequest::new_builder()
+        .capacity(Pack::pack(&deposit_capacity))
+        .script(credited_script.clone())
+        .registry_id(Pack::pack(&eth_registry_id))
+        .build();
     let block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
         let mut mem_pool = mem_pool.lock().await;
-        mem_pool.push_withdrawal_request(withdrawal).await.unwrap();
-        mem_pool.reset_mem_block().await.unwrap();
-        construct_block(&chain, &mut mem_pool, Vec::default())
+        construct_block(&chain, &mut mem_pool, vec![deposit])
             .await
             .unwrap()
     };
-    assert_eq!(block_result.block.withdrawals().len(), 1);
+    let block = {
+        let block = block_result.block.clone();
+        let mut keys: HashSet<H256> = {
+            let keys = tree.tracker_mut().touched_keys().unwrap();
+            let unlock = keys.lock().unwrap();
+            unlock.clone()
+        };
+        keys.extend(block.kv_state().into_iter().map(|kv| {
+            let k: [u8; 32] = kv.k().unpack();
+            H256::from(k)
+        }));
+        let keys: Vec<H256> = keys.into_iter().collect();
+        let kv_state: Vec<(H256, H256)> = keys
+            .iter()
+            .map(|k| (*k, tree.get_raw(k).unwrap()))
+            .collect();
+        let kv_state_proof: Bytes = {
+            let db = chain.store().begin_transaction();
+            let account_smt = db.account_smt().unwrap();
+            account_smt
+                .merkle_proof(keys)
+                .unwrap()
+                .compile(kv_state.clone())
+                .unwrap()
+                .0
+                .into()
+        };
+        block
+            .as_builder()
+            .kv_state(kv_state.pack())
+            .kv_state_proof(Pack::pack(&kv_state_proof))
+            .build()
+    };
+
+    // the generator doesn't execute deposit calls, the transaction is checkpointed with the
+    // post state of the block
+    let block = match block_tx {
+        Some(raw_tx) => {
+            let from_id = match tree
+                .get_account_id_by_script_hash(&credited_script.hash().into())
+                .unwrap()
+            {
+                Some(id) => id,
+                // the account is created by the deposit
+                None => tree.get_account_count().unwrap(),
+            };
+            let tx = L2Transaction::new_builder()
+                .raw(raw_tx.as_builder().from_id(Pack::pack(&from_id)).build())
+                .signature(Pack::pack(&Bytes::from(DEPOSIT_CALL_SIGNATURE)))
+                .build();
+            let leaves = vec![ckb_merkle_leaf_hash(0, &tx.witness_hash().into())];
+            let post_account = block.raw().post_account();
+            let checkpoint = calculate_state_checkpoint(
+                &post_account.merkle_root().unpack(),
+                post_account.count().unpack(),
+            );
+            let submit_transactions = block
+                .raw()
+                .submit_transactions()
+                .as_builder()
+                .tx_witness_root(Pack::pack(&CBMT::build_merkle_root(&leaves)))
+                .tx_count(Pack::pack(&1u32))
+                .build();
+            let raw_block = block
+                .raw()
+                .as_builder()
+                .submit_transactions(submit_transactions)
+                .state_checkpoint_list(PackVec::pack(vec![checkpoint]))
+                .build();
+            block
+                .as_builder()
+                .raw(raw_block)
+                .transactions(PackVec::pack(vec![tx]))
+                .build()
+        }
+        None => block,
+    };
+    // the block is changed, update its hash in the block tree
+    let post_global_state = {
+        let block_number: u64 = block.raw().number().unpack();
+        let block_root = CompiledMerkleProof(block.block_proof().unpack())
+            .compute_root::<Blake2bHasher>(vec![(
+                RawL2Block::compute_smt_key(block_number).into(),
+                block.raw().hash().into(),
+            )])
+            .unwrap();
+        let block_merkle_state = block_result
+            .global_state
+            .block()
+            .as_builder()
+            .merkle_root(Pack::pack(&block_root))
+            .build();
+        block_result
+            .global_state
+            .as_builder()
+            .block(block_merkle_state)
+            .tip_block_hash(Pack::pack(&block.raw().hash()))
+            .build()
+    };
 
     // deploy scripts
     let param = CellContextParam {
         stake_lock_type,
+        deposit_lock_type,
         custodian_lock_type,
-        withdrawal_lock_type,
         ..Default::default()
     };
     let mut ctx = CellContext::new(&rollup_config, param);
@@ -1236,9 +2365,8 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
         CellInput::new_builder().previous_output(out_point).build()
     };
     let output_stake_cell = {
-        let block_number = block_result.block.raw().number();
         let lock_args = StakeLockArgs::new_builder()
-            .stake_block_number(block_number)
+            .stake_block_number(block.raw().number())
             .build();
         build_rollup_locked_cell(
             &rollup_type_script.hash(),
@@ -1256,44 +2384,59 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
         .build()
         .as_bytes();
 
-    // build custodian input
-    let input_custodian_cell = {
+    // the depositor's layer2 lock isn't an EOA script, it's never created
+    let layer2_lock = match recipient {
+        TestDepositRecipient::Layer2Lock | TestDepositRecipient::ExistingLayer2Lock => {
+            credited_script
+        }
+        _ => Script::new_builder()
+            .code_hash(Pack::pack(&ALWAYS_SUCCESS_CODE_HASH.clone()))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(b"exchange".to_vec())))
+            .build(),
+    };
+    let deposit_args = {
+        let args = DepositLockArgs::new_builder()
+            .owner_lock_hash(Pack::pack(&[0u8; 32]))
+            .layer2_lock(layer2_lock)
+            .cancel_timeout(Pack::pack(&0))
+            .registry_id(Pack::pack(&eth_registry_id))
+            .build();
+        let mut extra_fields = vec![recipient_args];
+        extra_fields.extend(call.map(|call| call.as_bytes()));
+        DepositLockArgs::new_unchecked(build_table_with_extra_fields(
+            args.as_slice(),
+            args.field_count(),
+            extra_fields,
+        ))
+    };
+    let input_deposit_cell = {
         let cell = build_rollup_locked_cell(
             &rollup_type_script.hash(),
-            &custodian_script_type_hash,
+            &deposit_script_type_hash,
             deposit_capacity,
-            CustodianLockArgs::default().as_bytes(),
+            deposit_args.as_bytes(),
         );
-
         let out_point = ctx.insert_cell(cell, Bytes::default());
         CellInput::new_builder().previous_output(out_point).build()
     };
-
-    // build withdrawal output
-    let output_withdrawal_cell = {
-        let lock_args = WithdrawalLockArgs::new_builder()
-            .withdrawal_block_number(block_result.block.raw().number())
-            .withdrawal_block_hash(Pack::pack(&block_result.block.raw().hash()))
-            .account_script_hash(Pack::pack(&account_script.hash()))
-            .owner_lock_hash(Pack::pack(&account_script.hash()))
+    let output_custodian_cell = {
+        let args = CustodianLockArgs::new_builder()
+            .deposit_lock_args(deposit_args)
+            .deposit_block_hash(Pack::pack(&block.raw().hash()))
+            .deposit_block_number(block.raw().number())
             .build();
-
-        let mut args = lock_args.as_slice().to_vec();
-        args.extend_from_slice(&(account_script.as_bytes().len() as u32).to_be_bytes());
-        args.extend_from_slice(&account_script.as_bytes());
-
         build_rollup_locked_cell(
             &rollup_type_script.hash(),
-            &withdrawal_script_type_hash,
+            &custodian_script_type_hash,
             deposit_capacity,
-            Bytes::from(args),
+            args.as_bytes(),
         )
     };
 
     // verify submit block
-    let tip_block_timestamp = block_result.block.raw().timestamp();
-    let rollup_cell_data = block_result
-        .global_state
+    let tip_block_timestamp = block.raw().timestamp();
+    let rollup_cell_data = post_global_state
         .as_builder()
         .tip_block_timestamp(tip_block_timestamp.clone())
         .version(1u8.into())
@@ -1302,9 +2445,7 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
     let witness = {
         let rollup_action = RollupAction::new_builder()
             .set(RollupActionUnion::RollupSubmitBlock(
-                RollupSubmitBlock::new_builder()
-                    .block(block_result.block)
-                    .build(),
+                RollupSubmitBlock::new_builder().block(block).build(),
             ))
             .build();
         ckb_types::packed::WitnessArgs::new_builder()
@@ -1324,16 +2465,41 @@ async fn test_withdrawal_cell_lock_args_with_owner_lock_in_submit_block() {
     .input(input_stake_cell)
     .output(output_stake_cell)
     .output_data(CKBPack::pack(&Bytes::default()))
-    .input(input_custodian_cell)
-    .output(output_withdrawal_cell)
+    .input(input_deposit_cell)
+    .output(output_custodian_cell)
     .output_data(CKBPack::pack(&Bytes::default()))
     .cell_dep(ctx.stake_lock_dep.clone())
+    .cell_dep(ctx.deposit_lock_dep.clone())
     .cell_dep(ctx.custodian_lock_dep.clone())
-    .cell_dep(ctx.withdrawal_lock_dep.clone())
     .cell_dep(ctx.always_success_dep.clone())
     .cell_dep(ctx.state_validator_dep.clone())
     .cell_dep(ctx.rollup_config_dep.clone())
     .witness(CKBPack::pack(&witness.as_bytes()))
     .build();
-    ctx.verify_tx(tx).expect("return success");
+    ctx.verify_tx(tx)
+}
+
+/// union DepositRecipient { Uint32, Bytes }, the account id item
+fn build_deposit_recipient_account_id(id: u32) -> Bytes {
+    let mut buf = 0u32.to_le_bytes().to_vec();
+    buf.extend_from_slice(&id.to_le_bytes());
+    Bytes::from(buf)
+}
+
+/// union DepositRecipient { Uint32, Bytes }, the registry address item
+fn build_deposit_recipient_registry_address(address: &RegistryAddress) -> Bytes {
+    let address = address.to_bytes();
+    let mut buf = 1u32.to_le_bytes().to_vec();
+    buf.extend_from_slice(&(address.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&address);
+    Bytes::from(buf)
+}
+
+/// A call of the Meta contract from the depositor
+fn build_deposit_call(chain_id: u64) -> RawL2Transaction {
+    RawL2Transaction::new_builder()
+        .chain_id(Pack::pack(&chain_id))
+        .to_id(Pack::pack(&gw_common::builtins::RESERVED_ACCOUNT_ID))
+        .args(Pack::pack(&Bytes::from(vec![42u8; 8])))
+        .build()
 }
@@ -0,0 +1,454 @@
+use crate::script_tests::utils::layer1::{build_simple_tx, random_out_point};
+use crate::script_tests::utils::rollup::{
+    build_always_success_cell, build_rollup_config_with_extra_fields, build_stake_asset_vec,
+    calculate_state_validator_type_id, CellContext, CellContextParam,
+};
+use crate::testing_tool::programs::{ALWAYS_SUCCESS_CODE_HASH, STATE_VALIDATOR_CODE_HASH};
+use ckb_error::assert_error_eq;
+use ckb_script::ScriptError;
+use ckb_types::{
+    core::Cycle,
+    packed::{CellDep, CellInput, CellOutput, WitnessArgs},
+    prelude::{Pack as CKBPack, Unpack},
+};
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, Status},
+    packed::{BlockMerkleState, GlobalState, RollupConfig, Script},
+};
+
+const OWNER_CELL_NOT_FOUND_ERROR: i8 = 8;
+const INVALID_STATUS_ERROR: i8 = 21;
+const INVALID_POST_GLOBAL_STATE: i8 = 23;
+
+/// RollupAction::RollupUpdateConfig, gw-types doesn't know it yet
+fn build_update_config_witness() -> WitnessArgs {
+    // union header: item id | table RollupUpdateConfig {}
+    let mut rollup_action = 4u32.to_le_bytes().to_vec();
+    rollup_action.extend_from_slice(&4u32.to_le_bytes());
+    WitnessArgs::new_builder()
+        .output_type(CKBPack::pack(&Some(Bytes::from(rollup_action))))
+        .build()
+}
+
+#[test]
+fn test_update_rollup_config() {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = {
+        Script::new_builder()
+            .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+            .hash_type(ScriptHashType::Data.into())
+            .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+            .build()
+    };
+    // governance lock
+    let governance_lock = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![42u8; 32])))
+        .build();
+    let governance_lock_hash: [u8; 32] = governance_lock.calc_script_hash().unpack();
+    // rollup configs
+    let config_extra_fields = vec![
+        build_stake_asset_vec(&[]),
+        Bytes::from(governance_lock_hash.to_vec()),
+    ];
+    let rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .finality_blocks(Pack::pack(&10u64))
+            .build(),
+        config_extra_fields.clone(),
+    );
+    let new_rollup_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .finality_blocks(Pack::pack(&20u64))
+            .build(),
+        config_extra_fields.clone(),
+    );
+    // deploy scripts
+    let mut ctx = CellContext::new(&rollup_config, CellContextParam::default());
+    let new_rollup_config_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(new_rollup_config.as_bytes().len() as u64)))
+            .build();
+        let out_point = ctx.insert_cell(cell, new_rollup_config.as_bytes());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+    let new_rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&new_rollup_config.as_bytes()).unpack();
+
+    // global states
+    let capacity = 1000_00000000u64;
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .status(Status::Running.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&new_rollup_config_hash))
+        .build();
+
+    let build_tx = |ctx: &mut CellContext,
+                    prev_global_state: &GlobalState,
+                    post_global_state: &GlobalState,
+                    with_governance_cell: bool| {
+        let builder = build_simple_tx(
+            &mut ctx.inner,
+            (rollup_cell.clone(), prev_global_state.as_bytes()),
+            Default::default(),
+            (rollup_cell.clone(), post_global_state.as_bytes()),
+        )
+        .as_advanced_builder();
+        let builder = if with_governance_cell {
+            let governance_cell = CellOutput::new_builder()
+                .lock(governance_lock.clone())
+                .capacity(CKBPack::pack(&capacity))
+                .build();
+            let out_point = ctx.insert_cell(governance_cell.clone(), Bytes::default());
+            builder
+                .input(CellInput::new_builder().previous_output(out_point).build())
+                .output(governance_cell)
+                .output_data(CKBPack::pack(&Bytes::default()))
+        } else {
+            builder
+        };
+        builder
+            .cell_dep(ctx.always_success_dep.clone())
+            .cell_dep(ctx.state_validator_dep.clone())
+            .cell_dep(ctx.rollup_config_dep.clone())
+            .cell_dep(new_rollup_config_dep.clone())
+            .witness(CKBPack::pack(&build_update_config_witness().as_bytes()))
+            .build()
+    };
+    let expected_err = |code: i8| {
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+            ),
+            code,
+        )
+        .input_type_script(0)
+    };
+
+    // update config
+    let tx = build_tx(&mut ctx, &prev_global_state, &post_global_state, true);
+    ctx.verify_tx(tx).expect("return success");
+
+    // update config without the governance cell
+    let tx = build_tx(&mut ctx, &prev_global_state, &post_global_state, false);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(OWNER_CELL_NOT_FOUND_ERROR));
+
+    // update config during halting
+    let halting_global_state = |global_state: &GlobalState| {
+        global_state
+            .clone()
+            .as_builder()
+            .status(Status::Halting.into())
+            .build()
+    };
+    let tx = build_tx(
+        &mut ctx,
+        &halting_global_state(&prev_global_state),
+        &halting_global_state(&post_global_state),
+        true,
+    );
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_STATUS_ERROR));
+
+    // update config with other global state changes
+    let changed_global_state = post_global_state
+        .clone()
+        .as_builder()
+        .block(
+            BlockMerkleState::new_builder()
+                .count(Pack::pack(&1u64))
+                .build(),
+        )
+        .build();
+    let tx = build_tx(&mut ctx, &prev_global_state, &changed_global_state, true);
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE));
+
+    // update an immutable config field
+    let immutable_changed_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .finality_blocks(Pack::pack(&20u64))
+            .chain_id(Pack::pack(&42u64))
+            .build(),
+        config_extra_fields.clone(),
+    );
+    let immutable_changed_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&immutable_changed_config.as_bytes()).unpack();
+    let immutable_changed_config_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(
+                &(immutable_changed_config.as_bytes().len() as u64),
+            ))
+            .build();
+        let out_point = ctx.insert_cell(cell, immutable_changed_config.as_bytes());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&immutable_changed_config_hash))
+        .build();
+    let tx = build_tx(&mut ctx, &prev_global_state, &post_global_state, true)
+        .as_advanced_builder()
+        .cell_dep(immutable_changed_config_dep)
+        .build();
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE));
+
+    let deploy_config = |ctx: &mut CellContext, config: &RollupConfig| {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(config.as_bytes().len() as u64)))
+            .build();
+        let out_point = ctx.insert_cell(cell, config.as_bytes());
+        let config_hash: [u8; 32] = CellOutput::calc_data_hash(&config.as_bytes()).unpack();
+        (
+            config_hash,
+            CellDep::new_builder().out_point(out_point).build(),
+        )
+    };
+
+    // update the EIP-712 domain version, signed messages of the rollup would change
+    let mut domain_changed_extra_fields = config_extra_fields.clone();
+    domain_changed_extra_fields.push(Bytes::from(0u64.to_le_bytes().to_vec()));
+    domain_changed_extra_fields.push(Bytes::from(vec![1u8]));
+    let domain_changed_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .finality_blocks(Pack::pack(&20u64))
+            .build(),
+        domain_changed_extra_fields,
+    );
+    let (domain_changed_config_hash, domain_changed_config_dep) =
+        deploy_config(&mut ctx, &domain_changed_config);
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&domain_changed_config_hash))
+        .build();
+    let tx = build_tx(&mut ctx, &prev_global_state, &post_global_state, true)
+        .as_advanced_builder()
+        .cell_dep(domain_changed_config_dep)
+        .build();
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(INVALID_POST_GLOBAL_STATE));
+
+    // a zero governance lock hash disables config updates
+    let immutable_config = build_rollup_config_with_extra_fields(
+        &RollupConfig::new_builder()
+            .finality_blocks(Pack::pack(&10u64))
+            .build(),
+        vec![build_stake_asset_vec(&[]), Bytes::from(vec![0u8; 32])],
+    );
+    let (immutable_config_hash, immutable_config_dep) = deploy_config(&mut ctx, &immutable_config);
+    let immutable_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&immutable_config_hash))
+        .build();
+    let post_global_state = immutable_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&new_rollup_config_hash))
+        .build();
+    let tx = build_tx(&mut ctx, &immutable_global_state, &post_global_state, true)
+        .as_advanced_builder()
+        .cell_dep(immutable_config_dep)
+        .build();
+    let err = ctx.verify_tx(tx).unwrap_err();
+    assert_error_eq!(err, expected_err(OWNER_CELL_NOT_FOUND_ERROR));
+}
+
+#[test]
+fn test_update_rollup_config_finality_and_timeouts() {
+    // relative since, timestamp metric
+    const RELATIVE_TIMESTAMP_SINCE_FLAGS: u64 = 0xc000_0000_0000_0000;
+    // relative since, block number metric
+    const RELATIVE_BLOCK_NUMBER_SINCE_FLAGS: u64 = 0x8000_0000_0000_0000;
+
+    let governance_lock = ckb_types::packed::Script::new_builder()
+        .code_hash(CKBPack::pack(&*ALWAYS_SUCCESS_CODE_HASH))
+        .hash_type(ckb_types::core::ScriptHashType::Data.into())
+        .args(CKBPack::pack(&Bytes::from(vec![42u8; 32])))
+        .build();
+    let governance_lock_hash: [u8; 32] = governance_lock.calc_script_hash().unpack();
+    let build_config = |finality_blocks: u64,
+                        forced_withdrawal_timeout: u64,
+                        emergency_exit_timeout: Option<u64>| {
+        let mut extra_fields = vec![
+            build_stake_asset_vec(&[]),
+            Bytes::from(governance_lock_hash.to_vec()),
+            Bytes::from(forced_withdrawal_timeout.to_le_bytes().to_vec()),
+            Bytes::from(vec![0u8]),
+        ];
+        if let Some(timeout) = emergency_exit_timeout {
+            extra_fields.push(Bytes::from(timeout.to_le_bytes().to_vec()));
+        }
+        build_rollup_config_with_extra_fields(
+            &RollupConfig::new_builder()
+                .finality_blocks(Pack::pack(&finality_blocks))
+                .build(),
+            extra_fields,
+        )
+    };
+    let forced_withdrawal_timeout = RELATIVE_TIMESTAMP_SINCE_FLAGS | 3600;
+    let emergency_exit_timeout = 7 * 24 * 3600 * 1000;
+    let rollup_config = build_config(10, forced_withdrawal_timeout, Some(emergency_exit_timeout));
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*STATE_VALIDATOR_CODE_HASH)
+        ),
+        INVALID_POST_GLOBAL_STATE,
+    )
+    .input_type_script(0);
+
+    // extend the finality and the timeouts to the limit
+    let new_rollup_config = build_config(
+        20,
+        RELATIVE_TIMESTAMP_SINCE_FLAGS | 7200,
+        Some(emergency_exit_timeout * 2),
+    );
+    update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config)
+        .expect("return success");
+
+    // finalize blocks in their challenge period
+    let new_rollup_config =
+        build_config(9, forced_withdrawal_timeout, Some(emergency_exit_timeout));
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // disable forced withdrawals
+    let new_rollup_config = build_config(10, 0, Some(emergency_exit_timeout));
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // extend the forced withdrawal timeout beyond the limit
+    let new_rollup_config = build_config(
+        10,
+        RELATIVE_TIMESTAMP_SINCE_FLAGS | 7201,
+        Some(emergency_exit_timeout),
+    );
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // change the metric of the forced withdrawal timeout
+    let new_rollup_config = build_config(
+        10,
+        RELATIVE_BLOCK_NUMBER_SINCE_FLAGS | 3600,
+        Some(emergency_exit_timeout),
+    );
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // disable emergency exits
+    let new_rollup_config = build_config(10, forced_withdrawal_timeout, None);
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+
+    // extend the emergency exit timeout beyond the limit
+    let new_rollup_config = build_config(
+        10,
+        forced_withdrawal_timeout,
+        Some(emergency_exit_timeout * 2 + 1),
+    );
+    let err =
+        update_rollup_config(&governance_lock, &rollup_config, &new_rollup_config).unwrap_err();
+    assert_error_eq!(err, expected_err);
+}
+
+/// Update the rollup config from `rollup_config` to `new_rollup_config`,
+/// authorized by a governance cell
+fn update_rollup_config(
+    governance_lock: &ckb_types::packed::Script,
+    rollup_config: &RollupConfig,
+    new_rollup_config: &RollupConfig,
+) -> Result<Cycle, ckb_error::Error> {
+    let input_out_point = random_out_point();
+    let type_id = calculate_state_validator_type_id(input_out_point);
+    let rollup_type_script = Script::new_builder()
+        .code_hash(Pack::pack(&*STATE_VALIDATOR_CODE_HASH))
+        .hash_type(ScriptHashType::Data.into())
+        .args(Pack::pack(&Bytes::from(type_id.to_vec())))
+        .build();
+    // deploy scripts
+    let mut ctx = CellContext::new(rollup_config, CellContextParam::default());
+    let new_rollup_config_dep = {
+        let cell = CellOutput::new_builder()
+            .capacity(CKBPack::pack(&(new_rollup_config.as_bytes().len() as u64)))
+            .build();
+        let out_point = ctx.insert_cell(cell, new_rollup_config.as_bytes());
+        CellDep::new_builder().out_point(out_point).build()
+    };
+    let rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&rollup_config.as_bytes()).unpack();
+    let new_rollup_config_hash: [u8; 32] =
+        CellOutput::calc_data_hash(&new_rollup_config.as_bytes()).unpack();
+
+    // global states
+    let capacity = 1000_00000000u64;
+    let rollup_cell = build_always_success_cell(
+        capacity,
+        Some(ckb_types::packed::Script::new_unchecked(
+            rollup_type_script.as_bytes(),
+        )),
+    );
+    let prev_global_state = GlobalState::new_builder()
+        .rollup_config_hash(Pack::pack(&rollup_config_hash))
+        .status(Status::Running.into())
+        .version(1u8.into())
+        .build();
+    let post_global_state = prev_global_state
+        .clone()
+        .as_builder()
+        .rollup_config_hash(Pack::pack(&new_rollup_config_hash))
+        .build();
+
+    let governance_cell = CellOutput::new_builder()
+        .lock(governance_lock.clone())
+        .capacity(CKBPack::pack(&capacity))
+        .build();
+    let governance_input = {
+        let out_point = ctx.insert_cell(governance_cell.clone(), Bytes::default());
+        CellInput::new_builder().previous_output(out_point).build()
+    };
+    let tx = build_simple_tx(
+        &mut ctx.inner,
+        (rollup_cell.clone(), prev_global_state.as_bytes()),
+        Default::default(),
+        (rollup_cell, post_global_state.as_bytes()),
+    )
+    .as_advanced_builder()
+    .input(governance_input)
+    .output(governance_cell)
+    .output_data(CKBPack::pack(&Bytes::default()))
+    .cell_dep(ctx.always_success_dep.clone())
+    .cell_dep(ctx.state_validator_dep.clone())
+    .cell_dep(ctx.rollup_config_dep.clone())
+    .cell_dep(new_rollup_config_dep)
+    .witness(CKBPack::pack(&build_update_config_witness().as_bytes()))
+    .build();
+    ctx.verify_tx(tx)
+}
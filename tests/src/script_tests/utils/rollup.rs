@@ -360,3 +360,70 @@ pub fn calculate_state_validator_type_id(input_out_point: ckb_types::packed::Out
     hasher.finalize(&mut expected_type_id);
     expected_type_id
 }
+
+/// Append extra fields to RollupConfig, gw-types doesn't know them yet
+pub fn build_rollup_config_with_extra_fields(
+    rollup_config: &RollupConfig,
+    extra_fields: Vec<Bytes>,
+) -> RollupConfig {
+    let buf = build_table_with_extra_fields(
+        rollup_config.as_slice(),
+        rollup_config.field_count(),
+        extra_fields,
+    );
+    RollupConfig::from_compatible_slice(&buf).expect("rollup config with extra fields")
+}
+
+/// Append extra fields to a molecule table
+pub fn build_table_with_extra_fields(
+    table: &[u8],
+    field_count: usize,
+    extra_fields: Vec<Bytes>,
+) -> Bytes {
+    let read_offset = |i: usize| {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&table[4 + i * 4..8 + i * 4]);
+        u32::from_le_bytes(buf) as usize
+    };
+    let mut fields: Vec<Bytes> = (0..field_count)
+        .map(|i| {
+            let start = read_offset(i);
+            let end = if i + 1 == field_count {
+                table.len()
+            } else {
+                read_offset(i + 1)
+            };
+            Bytes::copy_from_slice(&table[start..end])
+        })
+        .collect();
+    fields.extend(extra_fields);
+    build_dynvec(&fields)
+}
+
+/// Molecule dynvec and table share the layout: total size | item offsets | items
+pub fn build_dynvec(items: &[Bytes]) -> Bytes {
+    let header_size = 4 * (items.len() + 1);
+    let total_size = header_size + items.iter().map(|item| item.len()).sum::<usize>();
+    let mut buf = Vec::with_capacity(total_size);
+    buf.extend_from_slice(&(total_size as u32).to_le_bytes());
+    let mut offset = header_size;
+    for item in items {
+        buf.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += item.len();
+    }
+    for item in items {
+        buf.extend_from_slice(item);
+    }
+    Bytes::from(buf)
+}
+
+/// vector StakeAssetVec <StakeAsset>, StakeAsset: sudt_script_hash | minimum_amount
+pub fn build_stake_asset_vec(assets: &[([u8; 32], u128)]) -> Bytes {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(assets.len() as u32).to_le_bytes());
+    for (sudt_script_hash, minimum_amount) in assets {
+        buf.extend_from_slice(sudt_script_hash);
+        buf.extend_from_slice(&minimum_amount.to_le_bytes());
+    }
+    Bytes::from(buf)
+}
@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use gw_common::{
+    error::Error, smt::SMT, sparse_merkle_tree::default_store::DefaultStore, state::State, H256,
+};
+use gw_types::{bytes::Bytes, packed::KVPairVec, prelude::*};
+
+/// A layer2 state on a sparse merkle tree, the touched keys are recorded
+/// to build the kv state and the merkle proof read by the contracts
+#[derive(Default)]
+pub struct SMTState {
+    tree: SMT<DefaultStore<H256>>,
+    account_count: u32,
+    touched_keys: RefCell<HashSet<H256>>,
+}
+
+impl SMTState {
+    pub fn clear_touched_keys(&self) {
+        self.touched_keys.borrow_mut().clear();
+    }
+
+    /// Returns the touched kv pairs and the merkle proof of them
+    pub fn touched_kv_state(&self) -> (KVPairVec, Bytes) {
+        let keys: Vec<H256> = self.touched_keys.borrow().iter().cloned().collect();
+        let kv_state: Vec<(H256, H256)> = keys
+            .iter()
+            .map(|k| (*k, self.tree.get(k).expect("get")))
+            .collect();
+        let kv_state_proof: Bytes = if keys.is_empty() {
+            Bytes::default()
+        } else {
+            self.tree
+                .merkle_proof(keys)
+                .expect("merkle proof")
+                .compile(kv_state.clone())
+                .expect("compile proof")
+                .0
+                .into()
+        };
+        (kv_state.pack(), kv_state_proof)
+    }
+}
+
+impl State for SMTState {
+    fn get_raw(&self, key: &H256) -> Result<H256, Error> {
+        self.touched_keys.borrow_mut().insert(*key);
+        self.tree.get(key).map_err(Error::SMT)
+    }
+    fn update_raw(&mut self, key: H256, value: H256) -> Result<(), Error> {
+        self.touched_keys.borrow_mut().insert(key);
+        self.tree.update(key, value).map_err(Error::SMT)?;
+        Ok(())
+    }
+    fn get_account_count(&self) -> Result<u32, Error> {
+        Ok(self.account_count)
+    }
+    fn set_account_count(&mut self, count: u32) -> Result<(), Error> {
+        self.account_count = count;
+        Ok(())
+    }
+    fn calculate_root(&self) -> Result<H256, Error> {
+        Ok(*self.tree.root())
+    }
+}
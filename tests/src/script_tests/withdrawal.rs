@@ -23,6 +23,7 @@ use gw_types::prelude::Pack;
 use secp256k1::rand::rngs::OsRng;
 use secp256k1::{Message, Secp256k1, SecretKey};
 
+const INVALID_OUTPUT_ERROR: i8 = 7;
 const OWNER_CELL_NOT_FOUND_ERROR: i8 = 8;
 
 #[test]
@@ -284,10 +285,14 @@ fn test_unlock_withdrawal_via_finalize_by_switch_indexed_output_to_owner_lock()
     assert_error_eq!(err, expected_err);
 
     // Fill incorrect output
+    let err_output = CellOutput::new_builder()
+        .capacity((DEFAULT_CAPACITY - 1).pack()) // ERROR: change output capacity
+        .lock(output_cell.0.to_gw().lock())
+        .build();
     let err_tx = tx
         .as_advanced_builder()
-        .output(output_cell.0.clone())
-        .output_data(1u128.pack().as_bytes().to_ckb()) // ERROR: change output data
+        .output(err_output.to_ckb())
+        .output_data(output_cell.1.to_ckb())
         .build();
 
     let err = verify_ctx.verify_tx(err_tx).unwrap_err();
@@ -482,6 +487,325 @@ fn test_unlock_withdrawal_via_finalize_fallback_to_input_owner_cell() {
     verify_ctx.verify_tx(sign_tx).expect("success");
 }
 
+#[test]
+fn test_unlock_withdrawal_via_finalize_by_split_and_merge() {
+    init_env_log();
+
+    const DEFAULT_CAPACITY: u64 = 1000 * 10u64.pow(8);
+
+    let rollup_type_script = random_always_success_script();
+    let rollup_type_hash = rollup_type_script.hash();
+    let (mut verify_ctx, script_ctx) = build_verify_context();
+
+    let last_finalized_block_number = rand::random::<u64>() + 100;
+    let rollup_cell = {
+        let global_state = GlobalState::new_builder()
+            .last_finalized_block_number(last_finalized_block_number.pack())
+            .build();
+
+        let output = CellOutput::new_builder()
+            .lock(random_always_success_script())
+            .type_(Some(rollup_type_script).pack())
+            .capacity(DEFAULT_CAPACITY.pack())
+            .build();
+
+        (output, global_state.as_bytes())
+    };
+    let rollup_dep = {
+        let out_point = verify_ctx.insert_cell(rollup_cell.0.to_ckb(), rollup_cell.1);
+        CellDep::new_builder().out_point(out_point.to_gw()).build()
+    };
+
+    let owner_lock = random_always_success_script();
+    let sudt_type = random_always_success_script();
+    let build_finalized_withdrawal_cell = |amount: u128| {
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .account_script_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_number(last_finalized_block_number.saturating_sub(1).pack())
+            .owner_lock_hash(owner_lock.hash().pack())
+            .build();
+
+        let mut args = Vec::new();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        let output = build_rollup_locked_cell(
+            &rollup_type_hash,
+            &script_ctx.withdrawal.script.hash(),
+            DEFAULT_CAPACITY,
+            Bytes::from(args),
+        )
+        .to_gw()
+        .as_builder()
+        .type_(Some(sudt_type.clone()).pack())
+        .build();
+
+        (output.to_ckb(), amount.pack().as_bytes())
+    };
+    let build_owner_cell = |capacity: u64, amount: u128| {
+        let output = CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(owner_lock.clone())
+            .type_(Some(sudt_type.clone()).pack())
+            .build();
+
+        (output.to_ckb(), amount.pack().as_bytes())
+    };
+    let unlock_via_finalize_witness = {
+        let unlock_args = UnlockWithdrawalViaFinalize::new_builder().build();
+        let unlock_witness = UnlockWithdrawalWitness::new_builder()
+            .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(
+                unlock_args,
+            ))
+            .build();
+        WitnessArgs::new_builder()
+            .lock(Some(unlock_witness.as_bytes()).pack())
+            .build()
+    };
+
+    // Split a withdrawal cell into two owner cells
+    let withdrawal_cell = build_finalized_withdrawal_cell(100);
+    let withdrawal_input = {
+        let out_point =
+            verify_ctx.insert_cell(withdrawal_cell.0.clone(), withdrawal_cell.1.clone());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let (output_cell_1, output_cell_2) = (
+        build_owner_cell(DEFAULT_CAPACITY / 2, 60),
+        build_owner_cell(DEFAULT_CAPACITY / 2, 40),
+    );
+    let tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        withdrawal_cell,
+        withdrawal_input.to_ckb().previous_output(),
+        output_cell_1.clone(),
+    )
+    .as_advanced_builder()
+    .output(output_cell_2.0)
+    .output_data(output_cell_2.1.to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .cell_dep(verify_ctx.always_success_dep.clone())
+    .cell_dep(rollup_dep.to_ckb())
+    .build();
+
+    verify_ctx.verify_tx(tx.clone()).expect("success");
+
+    // Split with insufficient sUDT amount
+    let err_output_cell = build_owner_cell(DEFAULT_CAPACITY / 2, 39);
+    let err_tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output_cell_1.0, err_output_cell.0])
+        .set_outputs_data(vec![output_cell_1.1.to_ckb(), err_output_cell.1.to_ckb()])
+        .build();
+
+    let err = verify_ctx.verify_tx(err_tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        OWNER_CELL_NOT_FOUND_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // Merge two withdrawal cells into one owner cell
+    let withdrawal_cell_1 = build_finalized_withdrawal_cell(100);
+    let withdrawal_input_1 = {
+        let out_point =
+            verify_ctx.insert_cell(withdrawal_cell_1.0.clone(), withdrawal_cell_1.1.clone());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let withdrawal_cell_2 = build_finalized_withdrawal_cell(50);
+    let withdrawal_input_2 = {
+        let out_point =
+            verify_ctx.insert_cell(withdrawal_cell_2.0.clone(), withdrawal_cell_2.1.clone());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        withdrawal_cell_1,
+        withdrawal_input_1.to_ckb().previous_output(),
+        build_owner_cell(DEFAULT_CAPACITY * 2, 150),
+    )
+    .as_advanced_builder()
+    .input(withdrawal_input_2.to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .cell_dep(verify_ctx.always_success_dep.clone())
+    .cell_dep(rollup_dep.to_ckb())
+    .build();
+
+    verify_ctx.verify_tx(tx).expect("success");
+}
+
+#[test]
+fn test_unlock_withdrawal_via_trade() {
+    init_env_log();
+
+    const DEFAULT_CAPACITY: u64 = 1000 * 10u64.pow(8);
+
+    let rollup_type_script = random_always_success_script();
+    let rollup_type_hash = rollup_type_script.hash();
+    let (mut verify_ctx, script_ctx) = build_verify_context();
+
+    let (sk, pk) = {
+        let secp = Secp256k1::new();
+        let mut rng = OsRng::new().unwrap();
+        secp.generate_keypair(&mut rng)
+    };
+    let owner_lock = {
+        let args = {
+            let mut buf = [0u8; 32];
+            let mut hasher = new_blake2b();
+            hasher.update(&pk.serialize());
+            hasher.finalize(&mut buf);
+
+            Bytes::copy_from_slice(&buf[..20])
+        };
+
+        Script::new_builder()
+            .code_hash(script_ctx.acp.script.hash().pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(args.pack())
+            .build()
+    };
+    let buyer_lock = random_always_success_script();
+
+    // withdrawal block isn't finalized yet
+    let lock_args = WithdrawalLockArgs::new_builder()
+        .account_script_hash(random_always_success_script().hash().pack())
+        .withdrawal_block_hash(random_always_success_script().hash().pack())
+        .withdrawal_block_number((rand::random::<u32>() as u64 + 100).pack())
+        .owner_lock_hash(owner_lock.hash().pack())
+        .build();
+    let build_withdrawal_cell = |owner_lock: &Script| {
+        let lock_args = lock_args
+            .clone()
+            .as_builder()
+            .owner_lock_hash(owner_lock.hash().pack())
+            .build();
+
+        let mut args = Vec::new();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        build_rollup_locked_cell(
+            &rollup_type_hash,
+            &script_ctx.withdrawal.script.hash(),
+            DEFAULT_CAPACITY,
+            args.into(),
+        )
+    };
+    let withdrawal_cell = (build_withdrawal_cell(&owner_lock), 0u128.pack().as_bytes());
+    let withdrawal_input = {
+        let out_point = verify_ctx.insert_cell(withdrawal_cell.0.clone(), 0u128.pack().as_bytes());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let owner_input = {
+        let output = CellOutput::new_builder()
+            .capacity(DEFAULT_CAPACITY.pack())
+            .lock(owner_lock.clone())
+            .build();
+
+        let out_point = verify_ctx.insert_cell(output.to_ckb(), 0u128.pack().as_bytes());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let traded_withdrawal_cell = (build_withdrawal_cell(&buyer_lock), 0u128.pack().as_bytes());
+    // buyer pays the owner
+    let payment_cell = {
+        let output = CellOutput::new_builder()
+            .capacity((DEFAULT_CAPACITY * 2).pack())
+            .lock(owner_lock.clone())
+            .build();
+
+        (output.to_ckb(), 0u128.pack().as_bytes())
+    };
+    let unlock_via_trade_witness = {
+        // union UnlockWithdrawalWitness item id | table UnlockWithdrawalViaTrade
+        let owner_lock = buyer_lock.as_bytes();
+        let mut unlock_witness = Vec::new();
+        unlock_witness.extend_from_slice(&2u32.to_le_bytes());
+        unlock_witness.extend_from_slice(&(8 + owner_lock.len() as u32).to_le_bytes());
+        unlock_witness.extend_from_slice(&8u32.to_le_bytes());
+        unlock_witness.extend_from_slice(&owner_lock);
+
+        WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(unlock_witness)).pack())
+            .build()
+    };
+
+    // Try trade without owner cell
+    let tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        withdrawal_cell,
+        withdrawal_input.to_ckb().previous_output(),
+        traded_withdrawal_cell.clone(),
+    )
+    .as_advanced_builder()
+    .witness(unlock_via_trade_witness.as_bytes().to_ckb())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .build();
+
+    let err = verify_ctx.verify_tx(tx.clone()).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        OWNER_CELL_NOT_FOUND_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // Owner authorizes the trade
+    let tx = tx
+        .as_advanced_builder()
+        .input(owner_input.to_ckb())
+        .witness(Default::default())
+        .output(payment_cell.0.clone())
+        .output_data(payment_cell.1.to_ckb())
+        .cell_dep(script_ctx.acp.dep.to_ckb())
+        .cell_dep(script_ctx.secp256k1_data.dep.to_ckb())
+        .build();
+
+    // Transfer to another lock than the one in witness
+    let err_withdrawal_cell = build_withdrawal_cell(&random_always_success_script());
+    let err_tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![err_withdrawal_cell, payment_cell.0])
+        .build();
+
+    let err = verify_ctx.verify_tx(sign_tx(err_tx, 1, &sk)).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        INVALID_OUTPUT_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    let sign_tx = sign_tx(tx, 1, &sk);
+    verify_ctx.verify_tx(sign_tx).expect("success");
+}
+
 struct ScriptDep {
     script: Script,
     dep: CellDep,
@@ -7,6 +7,8 @@ use std::{fs, io::Read, path::PathBuf};
 const SCRIPT_DIR: &str = "../build/debug";
 const CHALLENGE_LOCK_PATH: &str = "challenge-lock";
 const WITHDRAWAL_LOCK_PATH: &str = "withdrawal-lock";
+const CUSTODIAN_LOCK_PATH: &str = "custodian-lock";
+const STAKE_LOCK_PATH: &str = "stake-lock";
 const STATE_VALIDATOR: &str = "state-validator";
 const ALWAYS_SUCCESS_PATH: &str = "always-success";
 const SECP256K1_DATA_PATH: &str = "../c/deps/ckb-production-scripts/build/secp256k1_data";
@@ -14,9 +16,14 @@ const ANYONE_CAN_PAY_LOCK_PATH: &str = "../c/deps/ckb-production-scripts/build/a
 const C_SCRIPTS_DIR: &str = "../../godwoken-scripts/c/build";
 const META_CONTRACT_BIN_NAME: &str = "meta-contract-validator";
 const ETH_ADDR_REG_BIN_NAME: &str = "eth-addr-reg-generator";
+const SUDT_BIN_NAME: &str = "sudt-validator";
 // account locks
 const ETH_LOCK_PATH: &str = "eth-account-lock";
 const TRON_LOCK_PATH: &str = "tron-account-lock";
+const BITCOIN_LOCK_PATH: &str = "bitcoin-account-lock";
+const ED25519_LOCK_PATH: &str = "ed25519-account-lock";
+const WEBAUTHN_LOCK_PATH: &str = "webauthn-account-lock";
+const MULTISIG_LOCK_PATH: &str = "multisig-account-lock";
 
 lazy_static! {
     pub static ref ALWAYS_SUCCESS_PROGRAM: Bytes = {
@@ -99,6 +106,70 @@ lazy_static! {
         hasher.finalize(&mut buf);
         buf
     };
+    pub static ref BITCOIN_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&BITCOIN_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref BITCOIN_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&BITCOIN_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
+    pub static ref ED25519_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&ED25519_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref ED25519_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&ED25519_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
+    pub static ref WEBAUTHN_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&WEBAUTHN_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref WEBAUTHN_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&WEBAUTHN_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
+    pub static ref MULTISIG_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&MULTISIG_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref MULTISIG_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&MULTISIG_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
     pub static ref SECP256K1_DATA: Bytes = {
         let mut buf = Vec::new();
         let mut f = fs::File::open(&SECP256K1_DATA_PATH).expect("load secp256k1 data");
@@ -157,6 +228,15 @@ lazy_static! {
         hasher.finalize(&mut buf);
         buf
     };
+    pub static ref SUDT_VALIDATOR_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&C_SCRIPTS_DIR);
+        path.push(&SUDT_BIN_NAME);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
     pub static ref WITHDRAWAL_LOCK_PROGRAM: Bytes = {
         let mut buf = Vec::new();
         let mut path = PathBuf::new();
@@ -174,4 +254,37 @@ lazy_static! {
         hasher.finalize(&mut buf);
         buf
     };
+    pub static ref CUSTODIAN_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&CUSTODIAN_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load custodian lock program");
+        f.read_to_end(&mut buf)
+            .expect("read custodian lock program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref CUSTODIAN_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&CUSTODIAN_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
+    pub static ref STAKE_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&STAKE_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load stake lock program");
+        f.read_to_end(&mut buf).expect("read stake lock program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref STAKE_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&STAKE_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
 }